@@ -1,25 +1,38 @@
 use drone_macros_core::parse_error;
 use if_chain::if_chain;
 use proc_macro::TokenStream;
-use proc_macro2::Span;
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 use syn::parse::{Parse, ParseStream, Result};
 use syn::{
-    parenthesized, parse_macro_input, Data, DeriveInput, Fields, Ident, LitInt, LitStr,
-    PathArguments, Token,
+    parenthesized, parse_macro_input, token, Attribute, Data, DataEnum, DeriveInput, Expr, ExprLit,
+    Fields, Ident, Lit, LitInt, LitStr, PathArguments, Token, Type, TypeArray, Variant,
 };
 
 #[derive(Default)]
 struct Input {
+    strict: bool,
+    debug: bool,
+    const_builder: bool,
     fields: Vec<Field>,
 }
 
+#[derive(Default)]
+struct Conv {
+    types: Vec<Type>,
+}
+
 struct Field {
     ident: Ident,
     mode: Mode,
+    signed: bool,
     offset: LitInt,
     width: Option<LitInt>,
+    count: Option<LitInt>,
+    stride: Option<LitInt>,
+    ty: Option<Type>,
     doc: Option<LitStr>,
+    default: Option<(bool, LitInt)>,
 }
 
 enum Mode {
@@ -32,13 +45,43 @@ impl Parse for Input {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let content;
         parenthesized!(content in input);
+        let mut strict = false;
+        let mut debug = false;
+        let mut const_builder = false;
         let mut fields = Vec::new();
         let mut last_comma = true;
         while last_comma && !content.is_empty() {
-            fields.push(content.parse()?);
+            let fork = content.fork();
+            let ident = fork.parse::<Ident>()?;
+            if ident == "strict" && !fork.peek(token::Paren) {
+                content.parse::<Ident>()?;
+                strict = true;
+            } else if ident == "debug" && !fork.peek(token::Paren) {
+                content.parse::<Ident>()?;
+                debug = true;
+            } else if ident == "const_builder" && !fork.peek(token::Paren) {
+                content.parse::<Ident>()?;
+                const_builder = true;
+            } else {
+                fields.push(content.parse()?);
+            }
             last_comma = content.parse::<Option<Token![,]>>()?.is_some();
         }
-        Ok(Self { fields: fields.into_iter().collect() })
+        Ok(Self { strict, debug, const_builder, fields: fields.into_iter().collect() })
+    }
+}
+
+impl Parse for Conv {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let content;
+        parenthesized!(content in input);
+        let mut types = Vec::new();
+        let mut last_comma = true;
+        while last_comma && !content.is_empty() {
+            types.push(content.parse()?);
+            last_comma = content.parse::<Option<Token![,]>>()?.is_some();
+        }
+        Ok(Self { types })
     }
 }
 
@@ -47,10 +90,36 @@ impl Parse for Field {
         let ident = input.parse()?;
         let content;
         parenthesized!(content in input);
-        let mode = content.parse()?;
+        let (mode, signed) = Mode::parse_signed(&content)?;
         content.parse::<Token![,]>()?;
-        let offset = content.parse()?;
-        let width = if content.peek(Token![,]) && content.peek2(LitInt) {
+        let offset = content.parse::<LitInt>()?;
+        let parse_int = || -> Result<Option<LitInt>> {
+            if content.peek(Token![,]) && content.peek2(LitInt) {
+                content.parse::<Token![,]>()?;
+                Ok(Some(content.parse()?))
+            } else {
+                Ok(None)
+            }
+        };
+        let (offset, width) = if content.peek(Token![..=]) {
+            content.parse::<Token![..=]>()?;
+            let lsb = content.parse::<LitInt>()?;
+            let msb_value = offset.base10_parse::<u32>()?;
+            let lsb_value = lsb.base10_parse::<u32>()?;
+            if msb_value < lsb_value {
+                return Err(syn::Error::new(
+                    offset.span(),
+                    "bit range must be written as `msb..=lsb`",
+                ));
+            }
+            let width = LitInt::new(&(msb_value - lsb_value + 1).to_string(), offset.span());
+            (lsb, Some(width))
+        } else {
+            (offset, parse_int()?)
+        };
+        let count = parse_int()?;
+        let stride = parse_int()?;
+        let ty = if content.peek(Token![,]) && !content.peek2(LitStr) {
             content.parse::<Token![,]>()?;
             Some(content.parse()?)
         } else {
@@ -62,26 +131,32 @@ impl Parse for Field {
         } else {
             None
         };
-        Ok(Self { ident, mode, offset, width, doc })
+        let default = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            let negative = input.parse::<Option<Token![-]>>()?.is_some();
+            Some((negative, input.parse()?))
+        } else {
+            None
+        };
+        Ok(Self { ident, mode, signed, offset, width, count, stride, ty, doc, default })
     }
 }
 
-impl Parse for Mode {
-    fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let ident = input.parse::<Ident>()?;
-        if ident == "r" {
-            Ok(Self::Read)
-        } else if ident == "rw" {
-            Ok(Self::ReadWrite)
-        } else if ident == "w" {
-            Ok(Self::Write)
-        } else {
-            Err(input.error("invalid mode"))
+impl Mode {
+    fn parse_signed(input: ParseStream<'_>) -> Result<(Self, bool)> {
+        let ident = input.parse::<Ident>()?.to_string();
+        let (mode, signed) = match ident.strip_suffix("_i") {
+            Some(mode) => (mode, true),
+            None => (ident.as_str(), false),
+        };
+        match mode {
+            "r" => Ok((Self::Read, signed)),
+            "rw" => Ok((Self::ReadWrite, signed)),
+            "w" => Ok((Self::Write, signed)),
+            _ => Err(input.error("invalid mode")),
         }
     }
-}
 
-impl Mode {
     fn is_read(&self) -> bool {
         match *self {
             Self::Read | Self::ReadWrite => true,
@@ -100,22 +175,23 @@ impl Mode {
 #[allow(clippy::too_many_lines)]
 pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
     let DeriveInput { attrs, ident, data, .. } = parse_macro_input!(input);
-    let bitfield = attrs.into_iter().find(|attr| {
-        if_chain! {
-            if attr.path.leading_colon.is_none();
-            if attr.path.segments.len() <= 1;
-            if let Some(x) = attr.path.segments.iter().next();
-            if let PathArguments::None = x.arguments;
-            then { x.ident == "bitfield" } else { false }
-        }
-    });
-    let Input { fields } = match bitfield {
+    if let Data::Enum(data) = data {
+        return derive_enum(&ident, data);
+    }
+    let Input { strict, debug, const_builder, fields } = match find_attr(&attrs, "bitfield") {
         Some(attr) => {
-            let input = attr.tokens.into();
+            let input = attr.tokens.clone().into();
             parse_macro_input!(input)
         }
         None => Input::default(),
     };
+    let Conv { types: conv_types } = match find_attr(&attrs, "bitfield_conv") {
+        Some(attr) => {
+            let input = attr.tokens.clone().into();
+            parse_macro_input!(input)
+        }
+        None => Conv::default(),
+    };
     let bits = if_chain! {
         if let Data::Struct(x) = data;
         if let Fields::Unnamed(x) = x.fields;
@@ -124,9 +200,54 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
         then {
             x
         } else {
-            parse_error!("Bitfield can be derived only from a tuple struct with one field");
+            parse_error!(
+                "Bitfield can be derived only from a tuple struct with one field or a fieldless \
+                 enum"
+            );
         }
     };
+    let (bits, words, total_width) = match bits.ty {
+        Type::Array(TypeArray { elem, len, .. }) => {
+            if !matches!(&*elem, Type::Path(path) if path.path.is_ident("u32")) {
+                parse_error!("Bitfield arrays must consist of `u32` words");
+            }
+            let total_width = match &len {
+                Expr::Lit(ExprLit { lit: Lit::Int(len), .. }) => {
+                    len.base10_parse::<usize>().ok().map(|len| len * 32)
+                }
+                _ => None,
+            };
+            (quote!(::drone_core::bitfield::Words<#len>), true, total_width)
+        }
+        ty => {
+            let bits = quote!(#ty);
+            let total_width = bits.to_string().strip_prefix('u').and_then(|w| w.parse().ok());
+            (bits, false, total_width)
+        }
+    };
+    if const_builder && words {
+        parse_error!("const builders require a primitive integer bitfield");
+    }
+    let default_tokens = match def_default(&ident, &fields, words, total_width) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let fields_tokens = match def_fields(&fields) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let debug_tokens = if debug {
+        match def_debug(&ident, &fields, &bits, words) {
+            Ok(tokens) => tokens,
+            Err(err) => return err.to_compile_error().into(),
+        }
+    } else {
+        quote!()
+    };
+    let signed_bits = ["u8", "u16", "u32", "u64", "u128"]
+        .into_iter()
+        .find(|name| bits.to_string() == *name)
+        .map(|name| format_ident!("i{}", &name[1..]));
 
     let field_tokens = fields
         .into_iter()
@@ -135,9 +256,14 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
             let Field {
                 ident,
                 mode,
+                signed,
                 offset,
                 width,
+                count,
+                stride,
+                ty,
                 doc,
+                default: _,
             } = field;
             let width = width.unwrap_or_else(|| LitInt::new("1", Span::call_site()));
             let mut attrs = vec![quote!(#[inline])];
@@ -145,16 +271,183 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
                 attrs.push(quote!(#[doc = #doc]));
             }
             let attrs = &attrs;
-            if width.base10_digits() == "1" {
+            let (index, index_arg, offset) = if let Some(count) = &count {
+                let stride = stride.as_ref().unwrap_or(&width);
+                (quote!(index: usize,), quote!(index,), quote! {{
+                    ::core::assert!(index < #count, "bitfield index out of bounds");
+                    #offset + index * #stride
+                }})
+            } else {
+                (quote!(), quote!(), quote!(#offset))
+            };
+            let (offset_bits, width_bits) = if words {
+                (
+                    quote!(<#bits as ::drone_core::bitfield::Bits>::from_usize(#offset)),
+                    quote!(<#bits as ::drone_core::bitfield::Bits>::from_usize(#width)),
+                )
+            } else {
+                (quote!(#offset as #bits), quote!(#width as #bits))
+            };
+            // Accessors of primitive integer bitfields operate on the value
+            // directly, so they can be `const fn`.
+            let (constness, read_bit_expr, read_bits_expr, mask) = if signed_bits.is_some() {
+                let mask = quote!((#bits::MAX >> (#bits::BITS - #width as u32)));
+                (
+                    quote!(const),
+                    quote!((self.0 >> (#offset) & 1 != 0)),
+                    quote!((self.0 >> (#offset) & #mask)),
+                    mask,
+                )
+            } else {
+                (
+                    quote!(),
+                    quote! {
+                        unsafe { ::drone_core::bitfield::Bitfield::read_bit(self, #offset_bits) }
+                    },
+                    quote! {
+                        unsafe {
+                            ::drone_core::bitfield::Bitfield::read_bits(
+                                self,
+                                #offset_bits,
+                                #width_bits,
+                            )
+                        }
+                    },
+                    quote!(),
+                )
+            };
+            let with = format_ident!("with_{}", ident);
+            let const_builder = const_builder && mode.is_write() && signed_bits.is_some();
+            if signed {
+                let error = if ty.is_some() {
+                    Some("signed fields can't have an enum type")
+                } else if width.base10_digits() == "1" {
+                    Some("signed fields must be multi-bit")
+                } else if signed_bits.is_none() {
+                    Some("signed fields are not supported for multi-word bitfields")
+                } else {
+                    None
+                };
+                if let Some(error) = error {
+                    fields.push(syn::Error::new(ident.span(), error).to_compile_error());
+                    return fields;
+                }
+            }
+            if const_builder {
+                let signed_bits = signed_bits.as_ref().filter(|_| signed);
+                let (arg, check, value) = if let Some(signed_bits) = signed_bits {
+                    (
+                        quote!(value: #signed_bits),
+                        quote! {
+                            let min = #signed_bits::MIN >> (#signed_bits::BITS - #width as u32);
+                            ::core::assert!(
+                                value >= min && value <= !min,
+                                "value is out of range of a signed field"
+                            );
+                        },
+                        quote!(value as #bits),
+                    )
+                } else if let Some(ty) = &ty {
+                    (quote!(value: #ty), quote!(), quote!(value as #bits))
+                } else if width.base10_digits() == "1" {
+                    (quote!(bit: bool), quote!(), quote!(bit as #bits))
+                } else {
+                    (quote!(bits: #bits), quote!(), quote!(bits))
+                };
+                fields.push(quote! {
+                    #[allow(clippy::unnecessary_cast)]
+                    #(#attrs)*
+                    #[must_use]
+                    pub const fn #with(self, #index #arg) -> Self {
+                        #check
+                        let offset = #offset;
+                        Self(self.0 & !(#mask << offset) | (#value & #mask) << offset)
+                    }
+                });
+            }
+            if let (true, Some(signed_bits)) = (signed, &signed_bits) {
                 if mode.is_read() {
-                    let read_bit = format_ident!("{}", ident);
+                    let read_signed = format_ident!("{}", ident);
+                    fields.push(quote! {
+                        #[allow(clippy::unnecessary_cast)]
+                        #(#attrs)*
+                        pub #constness fn #read_signed(&self, #index) -> #signed_bits {
+                            let shift = #signed_bits::BITS - #width as u32;
+                            let bits = #read_bits_expr;
+                            (bits as #signed_bits) << shift >> shift
+                        }
+                    });
+                }
+                if mode.is_write() {
+                    let write_signed = format_ident!("write_{}", ident);
+                    fields.push(quote! {
+                        #[allow(clippy::unnecessary_cast)]
+                        #(#attrs)*
+                        pub fn #write_signed(&mut self, #index value: #signed_bits) -> &mut Self {
+                            let min = #signed_bits::MIN >> (#signed_bits::BITS - #width as u32);
+                            ::core::assert!(
+                                value >= min && value <= !min,
+                                "value is out of range of a signed field"
+                            );
+                            unsafe {
+                                ::drone_core::bitfield::Bitfield::write_bits(
+                                    self,
+                                    #offset_bits,
+                                    #width_bits,
+                                    value as #bits,
+                                );
+                            }
+                            self
+                        }
+                    });
+                }
+            } else if let Some(ty) = ty {
+                if mode.is_read() {
+                    let read_enum = format_ident!("{}", ident);
                     fields.push(quote! {
                         #[allow(clippy::unnecessary_cast)]
                         #(#attrs)*
-                        pub fn #read_bit(&self) -> bool {
+                        pub fn #read_enum(
+                            &self,
+                            #index
+                        ) -> ::core::result::Result<#ty, ::drone_core::bitfield::InvalidBits<#bits>>
+                        {
+                            <#ty as ::drone_core::bitfield::BitfieldEnum>::from_bits(unsafe {
+                                ::drone_core::bitfield::Bitfield::read_bits(
+                                    self,
+                                    #offset_bits,
+                                    #width_bits,
+                                )
+                            })
+                        }
+                    });
+                }
+                if mode.is_write() {
+                    let write_enum = format_ident!("write_{}", ident);
+                    fields.push(quote! {
+                        #[allow(clippy::unnecessary_cast)]
+                        #(#attrs)*
+                        pub fn #write_enum(&mut self, #index value: #ty) -> &mut Self {
                             unsafe {
-                                ::drone_core::bitfield::Bitfield::read_bit(self, #offset as #bits)
+                                ::drone_core::bitfield::Bitfield::write_bits(
+                                    self,
+                                    #offset_bits,
+                                    #width_bits,
+                                    ::drone_core::bitfield::BitfieldEnum::into_bits(value),
+                                );
                             }
+                            self
+                        }
+                    });
+                }
+            } else if width.base10_digits() == "1" {
+                if mode.is_read() {
+                    let read_bit = format_ident!("{}", ident);
+                    fields.push(quote! {
+                        #[allow(clippy::unnecessary_cast)]
+                        #(#attrs)*
+                        pub #constness fn #read_bit(&self, #index) -> bool {
+                            #read_bit_expr
                         }
                     });
                 }
@@ -166,9 +459,9 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
                     fields.push(quote! {
                         #[allow(clippy::unnecessary_cast)]
                         #(#attrs)*
-                        pub fn #set_bit(&mut self) -> &mut Self {
+                        pub fn #set_bit(&mut self, #index) -> &mut Self {
                             unsafe {
-                                ::drone_core::bitfield::Bitfield::set_bit(self, #offset as #bits);
+                                ::drone_core::bitfield::Bitfield::set_bit(self, #offset_bits);
                             }
                             self
                         }
@@ -176,9 +469,9 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
                     fields.push(quote! {
                         #[allow(clippy::unnecessary_cast)]
                         #(#attrs)*
-                        pub fn #clear_bit(&mut self) -> &mut Self {
+                        pub fn #clear_bit(&mut self, #index) -> &mut Self {
                             unsafe {
-                                ::drone_core::bitfield::Bitfield::clear_bit(self, #offset as #bits);
+                                ::drone_core::bitfield::Bitfield::clear_bit(self, #offset_bits);
                             }
                             self
                         }
@@ -186,9 +479,9 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
                     fields.push(quote! {
                         #[allow(clippy::unnecessary_cast)]
                         #(#attrs)*
-                        pub fn #toggle_bit(&mut self) -> &mut Self {
+                        pub fn #toggle_bit(&mut self, #index) -> &mut Self {
                             unsafe {
-                                ::drone_core::bitfield::Bitfield::toggle_bit(self, #offset as #bits);
+                                ::drone_core::bitfield::Bitfield::toggle_bit(self, #offset_bits);
                             }
                             self
                         }
@@ -196,9 +489,9 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
                     fields.push(quote! {
                         #[allow(clippy::unnecessary_cast)]
                         #(#attrs)*
-                        pub fn #write_bit(&mut self, bit: bool) -> &mut Self {
+                        pub fn #write_bit(&mut self, #index bit: bool) -> &mut Self {
                             unsafe {
-                                ::drone_core::bitfield::Bitfield::write_bit(self, #offset as #bits, bit);
+                                ::drone_core::bitfield::Bitfield::write_bit(self, #offset_bits, bit);
                             }
                             self
                         }
@@ -210,28 +503,47 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
                     fields.push(quote! {
                         #[allow(clippy::unnecessary_cast)]
                         #(#attrs)*
-                        pub fn #read_bits(&self) -> #bits {
-                            unsafe {
-                                ::drone_core::bitfield::Bitfield::read_bits(
-                                    self,
-                                    #offset as #bits,
-                                    #width as #bits,
-                                )
-                            }
+                        pub #constness fn #read_bits(&self, #index) -> #bits {
+                            #read_bits_expr
                         }
                     });
                 }
                 if mode.is_write() {
                     let write_bits = format_ident!("write_{}", ident);
+                    let try_write_bits = format_ident!("try_write_{}", ident);
+                    let overflows = quote! {
+                        ::drone_core::bitfield::overflows(bits, #width_bits)
+                    };
+                    let strict_check = strict.then(|| {
+                        quote!(::core::debug_assert!(!#overflows, "bitfield field overflow");)
+                    });
+                    fields.push(quote! {
+                        #[allow(clippy::unnecessary_cast)]
+                        #(#attrs)*
+                        pub fn #try_write_bits(
+                            &mut self,
+                            #index
+                            bits: #bits,
+                        ) -> ::core::result::Result<(), ::drone_core::bitfield::FieldOverflow> {
+                            if #overflows {
+                                return ::core::result::Result::Err(
+                                    ::drone_core::bitfield::FieldOverflow,
+                                );
+                            }
+                            self.#write_bits(#index_arg bits);
+                            ::core::result::Result::Ok(())
+                        }
+                    });
                     fields.push(quote! {
                         #[allow(clippy::unnecessary_cast)]
                         #(#attrs)*
-                        pub fn #write_bits(&mut self, bits: #bits) -> &mut Self {
+                        pub fn #write_bits(&mut self, #index bits: #bits) -> &mut Self {
+                            #strict_check
                             unsafe {
                                 ::drone_core::bitfield::Bitfield::write_bits(
                                     self,
-                                    #offset as #bits,
-                                    #width as #bits,
+                                    #offset_bits,
+                                    #width_bits,
                                     bits,
                                 );
                             }
@@ -243,11 +555,50 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
             fields
         })
         .collect::<Vec<_>>();
+    let conv_tokens = conv_types
+        .iter()
+        .map(|ty| {
+            quote! {
+                impl ::core::convert::From<#ident> for #ty {
+                    #[inline]
+                    fn from(value: #ident) -> Self {
+                        ::drone_core::bitfield::Bitfield::from_bits(
+                            ::drone_core::bitfield::Bitfield::bits(&value),
+                        )
+                    }
+                }
 
-    quote! {
-        impl ::drone_core::bitfield::Bitfield for #ident {
-            type Bits = #bits;
+                impl ::core::convert::From<#ty> for #ident {
+                    #[inline]
+                    fn from(value: #ty) -> Self {
+                        ::drone_core::bitfield::Bitfield::from_bits(
+                            ::drone_core::bitfield::Bitfield::bits(&value),
+                        )
+                    }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
 
+    let bits_impl = if words {
+        quote! {
+            #[inline]
+            fn bits(&self) -> #bits {
+                ::drone_core::bitfield::Words(self.0)
+            }
+
+            #[inline]
+            fn bits_mut(&mut self) -> &mut #bits {
+                ::drone_core::bitfield::Words::from_mut(&mut self.0)
+            }
+
+            #[inline]
+            fn from_bits(bits: #bits) -> Self {
+                Self(bits.0)
+            }
+        }
+    } else {
+        quote! {
             #[inline]
             fn bits(&self) -> #bits {
                 self.0
@@ -257,11 +608,252 @@ pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
             fn bits_mut(&mut self) -> &mut #bits {
                 &mut self.0
             }
+
+            #[inline]
+            fn from_bits(bits: #bits) -> Self {
+                Self(bits)
+            }
+        }
+    };
+
+    quote! {
+        impl ::drone_core::bitfield::Bitfield for #ident {
+            type Bits = #bits;
+
+            #fields_tokens
+
+            #bits_impl
         }
 
         impl #ident {
             #(#field_tokens)*
         }
+
+        #default_tokens
+
+        #debug_tokens
+
+        #(#conv_tokens)*
     }
     .into()
 }
+
+/// Returns the name, the offset, and the width of each field, with repeated
+/// fields expanded into separate entries.
+fn expand_fields(fields: &[Field]) -> Result<Vec<(String, usize, usize)>> {
+    let mut expanded = Vec::new();
+    for field in fields {
+        let offset = field.offset.base10_parse::<usize>()?;
+        let width = field.width.as_ref().map_or(Ok(1), LitInt::base10_parse::<usize>)?;
+        let stride = field.stride.as_ref().map_or(Ok(width), LitInt::base10_parse::<usize>)?;
+        match field.count.as_ref().map(LitInt::base10_parse::<usize>).transpose()? {
+            Some(count) => expanded.extend(
+                (0..count).map(|i| (format!("{}[{i}]", field.ident), offset + i * stride, width)),
+            ),
+            None => expanded.push((field.ident.to_string(), offset, width)),
+        }
+    }
+    Ok(expanded)
+}
+
+fn def_fields(fields: &[Field]) -> Result<TokenStream2> {
+    let modes = fields.iter().flat_map(|field| {
+        let mode = match field.mode {
+            Mode::Read => quote!(Read),
+            Mode::ReadWrite => quote!(ReadWrite),
+            Mode::Write => quote!(Write),
+        };
+        let count = field.count.as_ref().map_or(Ok(1), LitInt::base10_parse::<usize>);
+        std::iter::repeat(mode).take(count.unwrap_or(1))
+    });
+    let entries = expand_fields(fields)?.into_iter().zip(modes).map(
+        |((name, offset, width), mode)| {
+            quote! {
+                ::drone_core::bitfield::FieldInfo {
+                    name: #name,
+                    offset: #offset,
+                    width: #width,
+                    mode: ::drone_core::bitfield::FieldMode::#mode,
+                }
+            }
+        },
+    );
+    Ok(quote! {
+        const FIELDS: &'static [::drone_core::bitfield::FieldInfo] = &[#(#entries),*];
+    })
+}
+
+fn def_debug(
+    ident: &Ident,
+    fields: &[Field],
+    bits: &TokenStream2,
+    words: bool,
+) -> Result<TokenStream2> {
+    let name = ident.to_string();
+    let mut names = Vec::new();
+    let mut values = Vec::new();
+    for (field_name, offset, width) in expand_fields(fields)? {
+        let single = width == 1;
+        let offset = Literal::usize_unsuffixed(offset);
+        let width = Literal::usize_unsuffixed(width);
+        let (offset, width) = if words {
+            (
+                quote!(<#bits as ::drone_core::bitfield::Bits>::from_usize(#offset)),
+                quote!(<#bits as ::drone_core::bitfield::Bits>::from_usize(#width)),
+            )
+        } else {
+            (quote!(#offset as #bits), quote!(#width as #bits))
+        };
+        names.push(field_name);
+        values.push(if single {
+            quote!(::drone_core::bitfield::Bitfield::read_bit(self, #offset))
+        } else {
+            quote!(::drone_core::bitfield::Bitfield::read_bits(self, #offset, #width))
+        });
+    }
+    let defmt_tokens = cfg!(feature = "defmt").then(|| {
+        let fields = names.iter().map(|name| format!("{name}: {{}}")).collect::<Vec<_>>();
+        let format = format!("{name} {{{{ {} }}}}", fields.join(", "));
+        let values = values.iter().map(|value| {
+            if words {
+                quote!(unsafe { #value }.0)
+            } else {
+                quote!(unsafe { #value })
+            }
+        });
+        quote! {
+            impl ::defmt::Format for #ident {
+                #[allow(clippy::unnecessary_cast)]
+                fn format(&self, f: ::defmt::Formatter<'_>) {
+                    ::defmt::write!(f, #format, #(#values),*);
+                }
+            }
+        }
+    });
+    Ok(quote! {
+        impl ::core::fmt::Debug for #ident {
+            #[allow(clippy::unnecessary_cast)]
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(#name)
+                    #(.field(#names, &unsafe { #values }))*
+                    .finish()
+            }
+        }
+
+        #defmt_tokens
+    })
+}
+
+fn def_default(
+    ident: &Ident,
+    fields: &[Field],
+    words: bool,
+    total_width: Option<usize>,
+) -> Result<TokenStream2> {
+    if fields.iter().all(|field| field.default.is_none()) {
+        return Ok(quote!());
+    }
+    let Some(total_width) = total_width else {
+        return Err(syn::Error::new(
+            ident.span(),
+            "default values require a primitive integer or an array of known length",
+        ));
+    };
+    let mut value = vec![0_u32; (total_width + 31) / 32];
+    for field in fields {
+        let Some((negative, default)) = &field.default else { continue };
+        let offset = field.offset.base10_parse::<usize>()?;
+        let width = field.width.as_ref().map_or(Ok(1), LitInt::base10_parse::<usize>)?;
+        let count = field.count.as_ref().map_or(Ok(1), LitInt::base10_parse::<usize>)?;
+        let stride = field.stride.as_ref().map_or(Ok(width), LitInt::base10_parse::<usize>)?;
+        let raw = default.base10_parse::<u128>()?;
+        let mask = if width >= 128 { u128::MAX } else { (1 << width) - 1 };
+        let (bits, fits) = if *negative {
+            (raw.wrapping_neg() & mask, field.signed && raw <= 1 << (width - 1))
+        } else if field.signed {
+            (raw, raw < 1 << (width - 1))
+        } else {
+            (raw, raw <= mask)
+        };
+        if !fits {
+            return Err(syn::Error::new(default.span(), "default value doesn't fit into the field"));
+        }
+        for i in 0..count {
+            let base = offset + i * stride;
+            for bit in (0..width.min(128)).filter(|bit| bits >> bit & 1 == 1) {
+                let position = base + bit;
+                if position >= total_width {
+                    return Err(syn::Error::new(field.ident.span(), "field is out of bounds"));
+                }
+                value[position / 32] |= 1 << (position % 32);
+            }
+        }
+    }
+    let value = if words {
+        let words = value.iter().map(|word| LitInt::new(&format!("{word:#X}"), Span::call_site()));
+        quote!([#(#words),*])
+    } else {
+        let value = value.iter().rev().fold(0_u128, |acc, &word| acc << 32 | u128::from(word));
+        let value = LitInt::new(&format!("{value:#X}"), Span::call_site());
+        quote!(#value)
+    };
+    Ok(quote! {
+        impl #ident {
+            /// The value assembled from the default values of the fields.
+            pub const DEFAULT: Self = Self(#value);
+        }
+
+        impl ::core::default::Default for #ident {
+            #[inline]
+            fn default() -> Self {
+                Self::DEFAULT
+            }
+        }
+    })
+}
+
+fn derive_enum(ident: &Ident, data: DataEnum) -> TokenStream {
+    let mut arms = Vec::new();
+    for Variant { ident: variant, fields, discriminant, .. } in data.variants {
+        if !matches!(fields, Fields::Unit) {
+            parse_error!("Bitfield can be derived only from a fieldless enum");
+        }
+        if discriminant.is_none() {
+            parse_error!("Bitfield enum variants must have explicit discriminants");
+        }
+        arms.push(quote! {
+            if bits == B::from_usize(Self::#variant as usize) {
+                return ::core::result::Result::Ok(Self::#variant);
+            }
+        });
+    }
+    quote! {
+        impl ::drone_core::bitfield::BitfieldEnum for #ident {
+            #[inline]
+            fn from_bits<B: ::drone_core::bitfield::Bits>(
+                bits: B,
+            ) -> ::core::result::Result<Self, ::drone_core::bitfield::InvalidBits<B>> {
+                #(#arms)*
+                ::core::result::Result::Err(::drone_core::bitfield::InvalidBits(bits))
+            }
+
+            #[inline]
+            fn into_bits<B: ::drone_core::bitfield::Bits>(self) -> B {
+                B::from_usize(self as usize)
+            }
+        }
+    }
+    .into()
+}
+
+fn find_attr<'a>(attrs: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+    attrs.iter().find(|attr| {
+        if_chain! {
+            if attr.path.leading_colon.is_none();
+            if attr.path.segments.len() <= 1;
+            if let Some(x) = attr.path.segments.iter().next();
+            if let PathArguments::None = x.arguments;
+            then { x.ident == name } else { false }
+        }
+    })
+}
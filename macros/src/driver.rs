@@ -0,0 +1,59 @@
+use drone_macros_core::parse_error;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Index, Member};
+
+pub fn proc_macro_derive(input: TokenStream) -> TokenStream {
+    let DeriveInput { ident, generics, data, .. } = parse_macro_input!(input);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let fields = match data {
+        Data::Struct(data) => data.fields.into_iter().collect::<Vec<_>>(),
+        _ => parse_error!("Driver can be derived only from a struct"),
+    };
+    let mut resources = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| field.attrs.iter().any(|attr| attr.path.is_ident("resource")))
+        .map(|(index, _)| index);
+    let resource = match (resources.next(), resources.next()) {
+        (Some(index), None) => index,
+        (Some(_), Some(_)) => parse_error!("multiple `#[resource]` fields"),
+        (None, _) if fields.len() == 1 => 0,
+        (None, _) => parse_error!(
+            "Driver can be derived only from a struct with one field or with a `#[resource]` \
+             field"
+        ),
+    };
+    let member = |index: usize| match &fields[index].ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(Index::from(index)),
+    };
+    let resource_ty = &fields[resource].ty;
+    let resource_member = member(resource);
+    let inits = (0..fields.len())
+        .map(|index| {
+            let member = member(index);
+            if index == resource {
+                quote!(#member: res)
+            } else {
+                quote!(#member: ::core::default::Default::default())
+            }
+        })
+        .collect::<Vec<_>>();
+    quote! {
+        impl #impl_generics ::drone_core::drv::Driver for #ident #ty_generics #where_clause {
+            type Resource = #resource_ty;
+
+            #[inline]
+            fn new(res: Self::Resource) -> Self {
+                Self { #(#inits),* }
+            }
+
+            #[inline]
+            fn free(self) -> Self::Resource {
+                self.#resource_member
+            }
+        }
+    }
+    .into()
+}
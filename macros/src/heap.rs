@@ -6,13 +6,27 @@ use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree as TokenTree2};
 use quote::{format_ident, quote};
 use std::iter;
 use syn::parse::{Parse, ParseStream, Result};
-use syn::{parse_macro_input, Attribute, Ident, LitInt, LitStr, Token, Visibility};
+use syn::{
+    braced, parse_macro_input, Attribute, Ident, LitBool, LitInt, LitStr, Path, Token, Visibility,
+};
 
 struct Input {
     layout: Ident,
     metadata: Metadata,
     instance: Instance,
     trace_stream: Option<LitInt>,
+    thread_cache: Option<ThreadCache>,
+    alloc_error_hook: Option<Path>,
+    dma: bool,
+    paint: Option<LitInt>,
+    fallback: Option<Path>,
+    zeroize: bool,
+}
+
+struct ThreadCache {
+    thread: Path,
+    levels: LitInt,
+    capacity: LitInt,
 }
 
 struct Metadata {
@@ -33,10 +47,20 @@ impl Parse for Input {
         let mut metadata = None;
         let mut instance = None;
         let mut trace_stream = None;
+        let mut thread_cache = None;
+        let mut alloc_error_hook = None;
+        let mut dma = None;
+        let mut paint = None;
+        let mut fallback = None;
+        let mut zeroize = None;
         while !input.is_empty() {
             let attrs = input.call(Attribute::parse_outer)?;
+            let unsafety = input.parse::<Option<Token![unsafe]>>()?;
             let ident = input.parse::<Ident>()?;
             input.parse::<Token![=>]>()?;
+            if unsafety.is_some() && ident != "dma" {
+                return Err(input.error(format!("`{ident}` can't be marked `unsafe`")));
+            }
             if attrs.is_empty() && ident == "layout" {
                 if layout.is_none() {
                     layout = Some(input.parse()?);
@@ -61,6 +85,51 @@ impl Parse for Input {
                 } else {
                     return Err(input.error("multiple `trace_stream` specifications"));
                 }
+            } else if attrs.is_empty() && ident == "thread_cache" {
+                if thread_cache.is_none() {
+                    thread_cache = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `thread_cache` specifications"));
+                }
+            } else if attrs.is_empty() && ident == "alloc_error_hook" {
+                if alloc_error_hook.is_none() {
+                    alloc_error_hook = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `alloc_error_hook` specifications"));
+                }
+            } else if attrs.is_empty() && ident == "dma" {
+                if dma.is_none() {
+                    let value = input.parse::<LitBool>()?.value;
+                    if value && unsafety.is_none() {
+                        return Err(input.error(
+                            "`dma` must be marked `unsafe`: the heap memory must be accessible \
+                             by DMA and not cached",
+                        ));
+                    }
+                    dma = Some(value);
+                } else {
+                    return Err(input.error("multiple `dma` specifications"));
+                }
+            } else if attrs.is_empty() && ident == "paint" {
+                if paint.is_none() {
+                    let value = input.parse::<LitInt>()?;
+                    value.base10_parse::<u8>()?;
+                    paint = Some(value);
+                } else {
+                    return Err(input.error("multiple `paint` specifications"));
+                }
+            } else if attrs.is_empty() && ident == "fallback" {
+                if fallback.is_none() {
+                    fallback = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `fallback` specifications"));
+                }
+            } else if attrs.is_empty() && ident == "zeroize" {
+                if zeroize.is_none() {
+                    zeroize = Some(input.parse::<LitBool>()?.value);
+                } else {
+                    return Err(input.error("multiple `zeroize` specifications"));
+                }
             } else {
                 return Err(input.error(format!("unknown key: `{ident}`")));
             }
@@ -68,11 +137,67 @@ impl Parse for Input {
                 input.parse::<Token![;]>()?;
             }
         }
+        if dma == Some(true) && fallback.is_some() {
+            return Err(input.error("`dma` heap can't have a `fallback`"));
+        }
         Ok(Self {
             layout: layout.ok_or_else(|| input.error("missing `layout` specification"))?,
             metadata: metadata.ok_or_else(|| input.error("missing `metadata` specification"))?,
             instance: instance.ok_or_else(|| input.error("missing `instance` specification"))?,
             trace_stream,
+            thread_cache,
+            alloc_error_hook,
+            dma: dma.unwrap_or(false),
+            paint,
+            fallback,
+            zeroize: zeroize.unwrap_or(false),
+        })
+    }
+}
+
+impl Parse for ThreadCache {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let input2;
+        braced!(input2 in input);
+        let mut thread = None;
+        let mut levels = None;
+        let mut capacity = None;
+        while !input2.is_empty() {
+            let ident = input2.parse::<Ident>()?;
+            input2.parse::<Token![=>]>()?;
+            if ident == "thread" {
+                if thread.is_none() {
+                    thread = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `thread` specifications"));
+                }
+            } else if ident == "levels" {
+                if levels.is_none() {
+                    levels = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `levels` specifications"));
+                }
+            } else if ident == "capacity" {
+                if capacity.is_none() {
+                    let value = input2.parse::<LitInt>()?;
+                    if value.base10_parse::<usize>()? == 0 {
+                        return Err(input2.error("`capacity` must be non-zero"));
+                    }
+                    capacity = Some(value);
+                } else {
+                    return Err(input2.error("multiple `capacity` specifications"));
+                }
+            } else {
+                return Err(input2.error(format!("unknown key: `{ident}`")));
+            }
+            if !input2.is_empty() {
+                input2.parse::<Token![;]>()?;
+            }
+        }
+        Ok(Self {
+            thread: thread.ok_or_else(|| input2.error("missing `thread` specification"))?,
+            levels: levels.ok_or_else(|| input2.error("missing `levels` specification"))?,
+            capacity: capacity.ok_or_else(|| input2.error("missing `capacity` specification"))?,
         })
     }
 }
@@ -95,7 +220,18 @@ impl Instance {
 
 #[allow(clippy::too_many_lines)]
 pub fn proc_macro(input: TokenStream) -> TokenStream {
-    let Input { layout: heap_layout, metadata, instance, trace_stream } = parse_macro_input!(input);
+    let Input {
+        layout: heap_layout,
+        metadata,
+        instance,
+        trace_stream,
+        thread_cache,
+        alloc_error_hook,
+        dma,
+        paint,
+        fallback,
+        zeroize,
+    } = parse_macro_input!(input);
     let Metadata { attrs: metadata_attrs, vis: metadata_vis, ident: metadata_ident } = &metadata;
     let Instance { attrs: instance_attrs, vis: instance_vis, ident: instance_ident } = &instance;
     let layout = match Layout::read_from_cargo() {
@@ -120,7 +256,122 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
     .take(pools_len)
     .collect::<Vec<_>>();
 
-    let core_alloc = def_core_alloc(&metadata, trace_stream.as_ref());
+    let check = cfg!(feature = "heap-check");
+    let capacity =
+        pools.iter().map(|pool| pool.block as usize * pool.count as usize).sum::<usize>();
+
+    let core_alloc = def_core_alloc(
+        &metadata,
+        trace_stream.as_ref(),
+        thread_cache.is_some(),
+        zeroize,
+        alloc_error_hook.as_ref(),
+        fallback.as_ref(),
+        check,
+    );
+    let block_bitmap = check.then(|| {
+        let mut words = 0;
+        let offsets = pools
+            .iter()
+            .map(|pool| {
+                let offset = words;
+                words += (pool.count as usize + 31) / 32;
+                offset
+            })
+            .collect::<Vec<_>>();
+        quote! {
+            impl #metadata_ident {
+                fn block_bitmap(
+                    &self,
+                ) -> &'static ::drone_core::heap::BlockBitmap<#words, #pools_len> {
+                    static BITMAP: ::drone_core::heap::BlockBitmap<#words, #pools_len> =
+                        ::drone_core::heap::BlockBitmap::new([#(#offsets),*]);
+                    &BITMAP
+                }
+            }
+        }
+    });
+    let flush_thread_cache = if thread_cache.is_some() {
+        quote!(|| unsafe { #instance_ident.thread_cache().flush_all(&#instance_ident.pools) })
+    } else {
+        quote!(|| {})
+    };
+    let max_counts = if check {
+        let counts = pools.iter().map(|pool| pool.count as usize);
+        quote!(::core::option::Option::Some(&[#(#counts),*]))
+    } else {
+        quote!(::core::option::Option::None)
+    };
+    let layout_blocks = pools.iter().map(|pool| pool.block as usize);
+    let layout_counts = pools.iter().map(|pool| pool.count as usize);
+    let thread_cache = thread_cache.map(|ThreadCache { thread, levels, capacity }| {
+        let cache_ty = quote! {
+            ::drone_core::heap::ThreadCache<#thread, #levels, #pools_len, #capacity>
+        };
+        quote! {
+            impl #metadata_ident {
+                /// Returns the per-thread caches of recently freed blocks.
+                pub fn thread_cache(&self) -> &'static #cache_ty {
+                    static CACHE: #cache_ty = ::drone_core::heap::ThreadCache::new();
+                    &CACHE
+                }
+
+                /// Returns all blocks cached by the current thread preemption
+                /// level back to the pools.
+                ///
+                /// See [`ThreadCache::flush`](::drone_core::heap::ThreadCache::flush).
+                #[inline]
+                pub fn flush_thread_cache(&self) {
+                    unsafe { self.thread_cache().flush(&self.pools) };
+                }
+
+                /// Returns all blocks cached by the preempted threads back to
+                /// the pools.
+                ///
+                /// Intended to be called from the `on_preempt` hook of the
+                /// thread pool.
+                #[inline]
+                pub fn flush_preempted_thread_cache(&self) {
+                    unsafe { self.thread_cache().flush_preempted(&self.pools) };
+                }
+            }
+        }
+    });
+    let dma = dma.then(|| {
+        quote! {
+            unsafe impl ::drone_core::heap::DmaAllocator for #metadata_ident {}
+
+            impl #metadata_ident {
+                /// Returns a DMA-capable allocator handle to this heap.
+                #[inline]
+                pub fn dma(&'static self) -> ::drone_core::heap::DmaHeap {
+                    ::drone_core::heap::DmaHeap::new(self)
+                }
+            }
+        }
+    });
+    let paint_init = paint.as_ref().map(|pattern| {
+        quote! {
+            unsafe {
+                ::drone_core::heap::paint(&#instance_ident.pools, #instance_ident.base(), #pattern);
+            }
+        }
+    });
+    let paint = paint.map(|pattern| {
+        quote! {
+            impl #metadata_ident {
+                /// Returns the highest numbers of blocks ever used in the
+                /// pools, by scanning the heap for bytes different from the
+                /// pattern painted by [`init`](Self::init).
+                ///
+                /// The result is approximate: a block, which happened to be
+                /// filled with the pattern, is counted as unused.
+                pub fn high_water(&self) -> [usize; #pools_len] {
+                    ::drone_core::heap::high_water(&self.pools, self.base(), #pattern)
+                }
+            }
+        }
+    });
     let global_alloc = instance_attrs
         .clone()
         .into_iter()
@@ -141,7 +392,7 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
         #(#metadata_attrs)*
         #[repr(C)]
         #metadata_vis struct #metadata_ident {
-            base: *mut u8,
+            base: ::core::cell::UnsafeCell<*mut u8>,
             pools: [::drone_core::heap::Pool; #pools_len],
         }
 
@@ -155,13 +406,44 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
             /// Creates a instance of this new heap metadata.
             pub const fn new() -> Self {
                 Self {
-                    base: ::core::ptr::null_mut(), // actual address will be set by drone-ld
+                    // Actual address will be set by drone-ld.
+                    base: ::core::cell::UnsafeCell::new(::core::ptr::null_mut()),
                     pools: [
                         #(#pools_tokens)*
                     ],
                 }
             }
 
+            #[inline]
+            fn base(&self) -> *mut u8 {
+                unsafe { *self.base.get() }
+            }
+
+            /// Returns a snapshot of per-pool allocation counts.
+            ///
+            /// The counts are read from the same counters as
+            /// [`stats`](Self::stats), so blocks held by a thread cache are
+            /// counted as deallocated. See
+            /// [`heap::diff`](::drone_core::heap::diff) for comparing snapshots.
+            pub fn checkpoint(&self) -> ::drone_core::heap::Checkpoint<#pools_len> {
+                ::drone_core::heap::checkpoint(&self.pools, self.counters())
+            }
+
+            /// Returns the allocation statistics of the pools.
+            ///
+            /// The counters are maintained on each allocation and
+            /// deallocation with relaxed atomic operations. Blocks held by
+            /// a thread cache are counted as deallocated.
+            pub fn stats(&self) -> ::drone_core::heap::HeapStats<#pools_len> {
+                ::drone_core::heap::stats(&self.pools, self.counters(), self.base())
+            }
+
+            fn counters(&self) -> &'static ::drone_core::heap::HeapCounters<#pools_len> {
+                static COUNTERS: ::drone_core::heap::HeapCounters<#pools_len> =
+                    ::drone_core::heap::HeapCounters::new();
+                &COUNTERS
+            }
+
             /// Initializes this heap metadata.
             ///
             /// This function **must** be called as early as possible.
@@ -182,9 +464,76 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                         (#heap_rt_end.get() as usize - #heap_rt_base.get() as usize) >> 2,
                     );
                 }
+                #paint_init
+            }
+
+            /// Replaces the pools layout from the `layout.toml` with `pools`.
+            ///
+            /// This allows tuning the pools without recompiling the firmware,
+            /// e.g. with values read from a flash configuration or patched by
+            /// a debug probe. The pools must fit into the memory reserved for
+            /// the heap in the `layout.toml`.
+            ///
+            /// This function should be called right after
+            /// [`init`](Self::init), before any threads are started. Blocks
+            /// held by the thread cache are returned to the pools before the
+            /// check for allocated blocks.
+            ///
+            /// # Errors
+            ///
+            /// If `pools` are invalid or don't fit into the heap, or the heap
+            /// has allocated blocks, an error is returned and the pools are
+            /// left unchanged.
+            ///
+            /// # Safety
+            ///
+            /// This function must not preempt a thread in the middle of an
+            /// allocation or deallocation from this heap.
+            pub unsafe fn init_runtime(
+                pools: &[::drone_core::heap::PoolConfig; #pools_len],
+            ) -> ::core::result::Result<(), ::drone_core::heap::PoolConfigError> {
+                let flush = #flush_thread_cache;
+                unsafe {
+                    ::drone_core::heap::init_runtime(
+                        &#instance_ident.pools,
+                        #instance_ident.base(),
+                        #capacity,
+                        #max_counts,
+                        #instance_ident.counters(),
+                        flush,
+                        pools,
+                    )
+                }
+            }
+
+            /// Places the heap at `base` and applies the pools from the
+            /// `layout.toml`, as drone-ld does for the firmware. Used on the
+            /// host.
+            #[doc(hidden)]
+            pub unsafe fn init_at(
+                base: *mut u8,
+            ) -> ::core::result::Result<(), ::drone_core::heap::PoolConfigError> {
+                unsafe {
+                    *#instance_ident.base.get() = base;
+                    Self::init_runtime(&[#(::drone_core::heap::PoolConfig {
+                        block: #layout_blocks,
+                        count: #layout_counts,
+                    }),*])
+                }
+            }
+        }
+
+        impl ::drone_core::heap::PoolFit for #metadata_ident {
+            #[inline]
+            fn fit_capacity<T>(&self, requested: usize) -> usize {
+                ::drone_core::heap::fit_capacity::<T>(&self.pools, requested)
             }
         }
 
+        #thread_cache
+        #dma
+        #paint
+        #block_bitmap
         #core_alloc
         #global_alloc
     }
@@ -192,16 +541,197 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
 }
 
 #[allow(clippy::too_many_lines)]
-fn def_core_alloc(metadata: &Metadata, trace_stream: Option<&LitInt>) -> TokenStream2 {
+fn def_core_alloc(
+    metadata: &Metadata,
+    trace_stream: Option<&LitInt>,
+    thread_cache: bool,
+    zeroize: bool,
+    alloc_error_hook: Option<&Path>,
+    fallback: Option<&Path>,
+    check: bool,
+) -> TokenStream2 {
     let Metadata { ident: metadata_ident, .. } = metadata;
-    let trace_allocate =
-        trace_stream.map(|stream| quote!(::drone_core::heap::trace::allocate(#stream, layout);));
-    let trace_deallocate =
-        trace_stream.map(|stream| quote!(::drone_core::heap::trace::deallocate(#stream, layout);));
-    let trace_grow = trace_stream
-        .map(|stream| quote!(::drone_core::heap::trace::grow(#stream, old_layout, new_layout);));
-    let trace_shrink = trace_stream
-        .map(|stream| quote!(::drone_core::heap::trace::shrink(#stream, old_layout, new_layout);));
+    let cache = if thread_cache {
+        quote!(self.thread_cache())
+    } else {
+        quote!(&::drone_core::heap::NoCache)
+    };
+    let trace_allocate = trace_stream.map(|stream| {
+        quote!(::drone_core::heap::trace::allocate(#stream, &self.pools, layout);)
+    });
+    let trace_deallocate = trace_stream.map(|stream| {
+        quote! {
+            ::drone_core::heap::trace::deallocate(#stream, &self.pools, self.base(), ptr, layout);
+        }
+    });
+    let trace_realloc = |function: TokenStream2| {
+        trace_stream.map(|stream| {
+            quote! {
+                ::drone_core::heap::trace::#function(
+                    #stream,
+                    &self.pools,
+                    self.base(),
+                    ptr,
+                    old_layout,
+                    new_layout,
+                );
+            }
+        })
+    };
+    let trace_grow = trace_realloc(quote!(grow));
+    let trace_shrink = trace_realloc(quote!(shrink));
+    let counters = quote!(self.counters().pools());
+    let retry = |layout: TokenStream2, call: TokenStream2| match alloc_error_hook {
+        Some(hook) => quote!(::drone_core::heap::retry_alloc(#hook, #layout, || #call)),
+        None => call,
+    };
+    let allocate = quote!(::drone_core::heap::allocate(&self.pools, #cache, #counters, layout));
+    let allocate_zeroed =
+        quote!(::drone_core::heap::allocate_zeroed(&self.pools, #cache, #counters, layout));
+    let realloc = |function: TokenStream2| {
+        quote! {
+            ::drone_core::heap::#function(
+                &self.pools,
+                #cache,
+                #counters,
+                #zeroize,
+                self.base(),
+                ptr,
+                old_layout,
+                new_layout,
+            )
+        }
+    };
+    let grow = realloc(quote!(grow));
+    let grow_zeroed = realloc(quote!(grow_zeroed));
+    let shrink = realloc(quote!(shrink));
+    let (allocate, allocate_zeroed, check_deallocate, grow, grow_zeroed, shrink) = if check {
+        let mark_block = quote! {
+            self.block_bitmap().mark_allocated(&self.pools, self.base(), block.as_non_null_ptr());
+        };
+        let mark_ptr = quote!(self.block_bitmap().mark_allocated(&self.pools, self.base(), ptr););
+        let free_ptr = quote!(self.block_bitmap().mark_free(&self.pools, self.base(), ptr););
+        let check_allocate = |call: TokenStream2| {
+            quote! {{
+                let result = #call;
+                if let ::core::result::Result::Ok(block) = &result {
+                    if layout.size() != 0 {
+                        #mark_block
+                    }
+                }
+                result
+            }}
+        };
+        let check_realloc = |call: TokenStream2| {
+            quote! {{
+                if old_layout.size() != 0 {
+                    #free_ptr
+                }
+                let result = #call;
+                match &result {
+                    ::core::result::Result::Ok(block) if new_layout.size() != 0 => {
+                        #mark_block
+                    }
+                    ::core::result::Result::Err(_) if old_layout.size() != 0 => {
+                        #mark_ptr
+                    }
+                    _ => {}
+                }
+                result
+            }}
+        };
+        (
+            check_allocate(allocate),
+            check_allocate(allocate_zeroed),
+            Some(quote! {
+                if layout.size() != 0 {
+                    #free_ptr
+                }
+            }),
+            check_realloc(grow),
+            check_realloc(grow_zeroed),
+            check_realloc(shrink),
+        )
+    } else {
+        (allocate, allocate_zeroed, None, grow, grow_zeroed, shrink)
+    };
+    let (allocate, allocate_zeroed, fallback_deallocate, grow, grow_zeroed, shrink) =
+        if let Some(fallback) = fallback {
+            let owns = quote!(::drone_core::heap::owns(&self.pools, self.base(), ptr));
+            let fallback_allocate = |call: TokenStream2, method: TokenStream2| {
+                quote! {
+                    match #call {
+                        ::core::result::Result::Err(_) => {
+                            ::core::alloc::Allocator::#method(&#fallback, layout)
+                        }
+                        result => result,
+                    }
+                }
+            };
+            let fallback_realloc = |call: TokenStream2,
+                                    method: TokenStream2,
+                                    allocate: TokenStream2,
+                                    copy: TokenStream2| {
+                quote! {
+                    if old_layout.size() != 0 && !#owns {
+                        ::core::alloc::Allocator::#method(&#fallback, ptr, old_layout, new_layout)
+                    } else {
+                        match #call {
+                            ::core::result::Result::Err(_) => {
+                                match ::core::alloc::Allocator::#allocate(&#fallback, new_layout) {
+                                    ::core::result::Result::Ok(block) => {
+                                        ::core::ptr::copy_nonoverlapping(
+                                            ptr.as_ptr(),
+                                            block.as_mut_ptr(),
+                                            #copy,
+                                        );
+                                        ::core::alloc::Allocator::deallocate(self, ptr, old_layout);
+                                        ::core::result::Result::Ok(block)
+                                    }
+                                    result => result,
+                                }
+                            }
+                            result => result,
+                        }
+                    }
+                }
+            };
+            (
+                fallback_allocate(allocate, quote!(allocate)),
+                fallback_allocate(allocate_zeroed, quote!(allocate_zeroed)),
+                Some(quote! {
+                    if layout.size() != 0 && !#owns {
+                        ::core::alloc::Allocator::deallocate(&#fallback, ptr, layout);
+                        return;
+                    }
+                }),
+                fallback_realloc(
+                    grow,
+                    quote!(grow),
+                    quote!(allocate),
+                    quote!(old_layout.size()),
+                ),
+                fallback_realloc(
+                    grow_zeroed,
+                    quote!(grow_zeroed),
+                    quote!(allocate_zeroed),
+                    quote!(old_layout.size()),
+                ),
+                fallback_realloc(
+                    shrink,
+                    quote!(shrink),
+                    quote!(allocate),
+                    quote!(new_layout.size()),
+                ),
+            )
+        } else {
+            (allocate, allocate_zeroed, None, grow, grow_zeroed, shrink)
+        };
+    let allocate = retry(quote!(layout), allocate);
+    let allocate_zeroed = retry(quote!(layout), allocate_zeroed);
+    let grow = retry(quote!(new_layout), grow);
+    let grow_zeroed = retry(quote!(new_layout), grow_zeroed);
+    let shrink = retry(quote!(new_layout), shrink);
     quote! {
         unsafe impl ::core::alloc::Allocator for #metadata_ident {
             #[inline]
@@ -213,10 +743,7 @@ fn def_core_alloc(metadata: &Metadata, trace_stream: Option<&LitInt>) -> TokenSt
                 ::core::alloc::AllocError,
             > {
                 #trace_allocate
-                ::drone_core::heap::allocate(
-                    &self.pools,
-                    layout,
-                )
+                #allocate
             }
 
             #[inline]
@@ -227,10 +754,7 @@ fn def_core_alloc(metadata: &Metadata, trace_stream: Option<&LitInt>) -> TokenSt
                 ::core::ptr::NonNull<[u8]>,
                 ::core::alloc::AllocError,
             > {
-                ::drone_core::heap::allocate_zeroed(
-                    &self.pools,
-                    layout,
-                )
+                #allocate_zeroed
             }
 
             #[inline]
@@ -240,9 +764,14 @@ fn def_core_alloc(metadata: &Metadata, trace_stream: Option<&LitInt>) -> TokenSt
                 layout: ::core::alloc::Layout,
             ) {
                 #trace_deallocate
+                #fallback_deallocate
+                #check_deallocate
                 ::drone_core::heap::deallocate(
                     &self.pools,
-                    self.base,
+                    #cache,
+                    #counters,
+                    #zeroize,
+                    self.base(),
                     ptr,
                     layout,
                 )
@@ -259,13 +788,7 @@ fn def_core_alloc(metadata: &Metadata, trace_stream: Option<&LitInt>) -> TokenSt
                 ::core::alloc::AllocError,
             > {
                 #trace_grow
-                ::drone_core::heap::grow(
-                    &self.pools,
-                    self.base,
-                    ptr,
-                    old_layout,
-                    new_layout,
-                )
+                #grow
             }
 
             #[inline]
@@ -278,13 +801,7 @@ fn def_core_alloc(metadata: &Metadata, trace_stream: Option<&LitInt>) -> TokenSt
                 ::core::ptr::NonNull<[u8]>,
                 ::core::alloc::AllocError,
             > {
-                ::drone_core::heap::grow_zeroed(
-                    &self.pools,
-                    self.base,
-                    ptr,
-                    old_layout,
-                    new_layout,
-                )
+                #grow_zeroed
             }
 
             #[inline]
@@ -298,13 +815,7 @@ fn def_core_alloc(metadata: &Metadata, trace_stream: Option<&LitInt>) -> TokenSt
                 ::core::alloc::AllocError,
             > {
                 #trace_shrink
-                ::drone_core::heap::shrink(
-                    &self.pools,
-                    self.base,
-                    ptr,
-                    old_layout,
-                    new_layout,
-                )
+                #shrink
             }
         }
     }
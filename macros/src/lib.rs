@@ -9,28 +9,42 @@
 extern crate proc_macro;
 
 mod bitfield;
+mod driver;
 mod heap;
 mod override_layout;
 mod periph;
 mod periph_map;
 mod periph_singular;
 mod reg;
+mod reg_from_svd;
 mod reg_tokens;
 mod reg_tokens_inner;
+mod resource;
 mod simple_token;
 mod simple_tokens;
 mod static_tokens;
 mod stream;
+mod sv;
 mod thr_pool;
 mod thr_soft;
 
 use proc_macro::TokenStream;
 
-#[proc_macro_derive(Bitfield, attributes(bitfield))]
+#[proc_macro_derive(Bitfield, attributes(bitfield, bitfield_conv))]
 pub fn derive_bitfield(input: TokenStream) -> TokenStream {
     bitfield::proc_macro_derive(input)
 }
 
+#[proc_macro_derive(Driver, attributes(resource))]
+pub fn derive_driver(input: TokenStream) -> TokenStream {
+    driver::proc_macro_derive(input)
+}
+
+#[proc_macro_derive(Resource)]
+pub fn derive_resource(input: TokenStream) -> TokenStream {
+    resource::proc_macro_derive(input)
+}
+
 #[proc_macro]
 pub fn override_layout(input: TokenStream) -> TokenStream {
     override_layout::proc_macro(input)
@@ -61,6 +75,11 @@ pub fn reg(input: TokenStream) -> TokenStream {
     reg::proc_macro(input)
 }
 
+#[proc_macro]
+pub fn reg_from_svd(input: TokenStream) -> TokenStream {
+    reg_from_svd::proc_macro(input)
+}
+
 #[proc_macro]
 pub fn reg_tokens(input: TokenStream) -> TokenStream {
     reg_tokens::proc_macro(input)
@@ -91,6 +110,11 @@ pub fn stream(input: TokenStream) -> TokenStream {
     stream::proc_macro(input)
 }
 
+#[proc_macro]
+pub fn sv(input: TokenStream) -> TokenStream {
+    sv::proc_macro(input)
+}
+
 #[proc_macro]
 pub fn thr_pool(input: TokenStream) -> TokenStream {
     thr_pool::proc_macro(input)
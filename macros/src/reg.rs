@@ -1,16 +1,23 @@
 use drone_macros_core::unkeywordize;
 use heck::{ToSnakeCase, ToUpperCamelCase};
 use proc_macro::TokenStream;
-use proc_macro2::{Span, TokenStream as TokenStream2};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream, Result};
-use syn::{braced, parse_macro_input, Attribute, Ident, LitInt, LitStr, Token, Visibility};
+use syn::{
+    braced, parse_macro_input, Attribute, Ident, LitBool, LitInt, LitStr, Path, Token, Type,
+    Visibility,
+};
 
 struct Input {
     variants: Vec<Variant>,
+    default: Option<Variant>,
 }
 
+#[derive(Clone)]
 struct Variant {
     attrs: Vec<Attribute>,
     vis: Visibility,
@@ -19,28 +26,113 @@ struct Variant {
     address: LitInt,
     size: u8,
     reset: LitInt,
+    reserved: Option<Reserved>,
+    array: Option<Array>,
+    instances: Vec<Instance>,
+    bit_band: bool,
+    debug: bool,
     traits: Vec<Ident>,
     fields: Vec<Field>,
+    groups: Vec<Group>,
+    views: Vec<View>,
 }
 
+#[derive(Clone)]
+struct View {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    fields: Vec<Field>,
+    groups: Vec<Group>,
+}
+
+#[derive(Clone)]
+struct Instance {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    base: LitInt,
+}
+
+#[derive(Clone)]
+struct Array {
+    count: usize,
+    stride: LitInt,
+}
+
+#[derive(Clone)]
+struct Reserved {
+    mask: LitInt,
+    preserve: Option<Ident>,
+}
+
+#[derive(Clone)]
 struct Field {
     attrs: Vec<Attribute>,
     ident: Ident,
     offset: LitInt,
     width: LitInt,
     traits: Vec<Ident>,
+    convert: Option<Convert>,
+    scale: Option<Scale>,
+    values: Option<Vec<Value>>,
+    accessor: Option<Ident>,
+    alias: Option<LitStr>,
+    overlapping: bool,
+}
+
+#[derive(Clone)]
+struct Value {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    bits: LitInt,
+}
+
+#[derive(Clone)]
+struct Group {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    fields: Vec<Ident>,
+}
+
+#[derive(Clone)]
+struct Scale {
+    ident: Ident,
+    factor: LitInt,
+    bias: LitInt,
+    unit: Option<LitStr>,
+}
+
+#[derive(Clone)]
+struct Convert {
+    ty: Type,
+    read: Option<Path>,
+    write: Option<Path>,
 }
 
 impl Parse for Input {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let mut variants = Vec::new();
         while !input.is_empty() {
-            variants.push(input.parse()?);
+            variants.push(input.parse::<Variant>()?);
             if !input.is_empty() {
                 input.parse::<Token![;]>()?;
             }
         }
-        Ok(Self { variants })
+        let mut default = None;
+        if variants.iter().any(|variant| !variant.views.is_empty()) {
+            if variants.len() > 1 {
+                return Err(input.error("registers with `variants` can't have top-level variants"));
+            }
+            let base = variants.remove(0);
+            variants = base.expand_views();
+            default = Some(base);
+        }
+        if variants.len() > 1 && variants.iter().any(|variant| variant.array.is_some()) {
+            return Err(input.error("`array` registers can't have variants"));
+        }
+        if variants.len() > 1 && variants.iter().any(|variant| !variant.instances.is_empty()) {
+            return Err(input.error("registers with `instances` can't have variants"));
+        }
+        Ok(Self { variants, default })
     }
 }
 
@@ -56,8 +148,15 @@ impl Parse for Variant {
         let mut address = None;
         let mut size = None;
         let mut reset = None;
+        let mut reserved = None;
+        let mut array = None;
+        let mut instances = Vec::new();
+        let mut bit_band = None;
+        let mut debug = None;
         let mut traits = Vec::new();
         let mut fields = Vec::new();
+        let mut groups = Vec::new();
+        let mut views = Vec::new();
         while !input2.is_empty() {
             let ident = input2.parse::<Ident>()?;
             input2.parse::<Token![=>]>()?;
@@ -79,10 +178,45 @@ impl Parse for Variant {
                 } else {
                     return Err(input2.error("multiple `reset` specifications"));
                 }
+            } else if ident == "reserved" {
+                if reserved.is_none() {
+                    reserved = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `reserved` specifications"));
+                }
+            } else if ident == "array" {
+                if array.is_none() {
+                    array = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `array` specifications"));
+                }
+            } else if ident == "cluster" {
+                return Err(input2.error(
+                    "register clusters are not supported, declare each register of the cluster \
+                     as an `array` with the same `count` and `stride`",
+                ));
+            } else if ident == "instances" {
+                instances.extend(Instance::parse_list(&input2)?);
+            } else if ident == "bitband" {
+                if bit_band.is_none() {
+                    bit_band = Some(input2.parse::<LitBool>()?.value);
+                } else {
+                    return Err(input2.error("multiple `bitband` specifications"));
+                }
+            } else if ident == "debug" {
+                if debug.is_none() {
+                    debug = Some(input2.parse::<LitBool>()?.value);
+                } else {
+                    return Err(input2.error("multiple `debug` specifications"));
+                }
             } else if ident == "traits" {
                 traits.extend(parse_traits(&input2)?);
             } else if ident == "fields" {
                 fields.extend(Field::parse_list(&input2)?);
+            } else if ident == "groups" {
+                groups.extend(Group::parse_list(&input2)?);
+            } else if ident == "variants" {
+                views.extend(View::parse_list(&input2)?);
             } else {
                 return Err(input2.error(format!("unknown key: `{ident}`")));
             }
@@ -90,20 +224,168 @@ impl Parse for Variant {
                 input2.parse::<Token![;]>()?;
             }
         }
+        let size = size.ok_or_else(|| input2.error("missing `size` specification"))?;
+        if ![8, 16, 32, 64].contains(&size) {
+            return Err(input2.error("`size` must be one of 8, 16, 32, or 64"));
+        }
+        if traits.iter().any(|name| name == "RRegSideEffect")
+            && traits.iter().any(|name| name == "RReg" || name == "RoReg")
+        {
+            return Err(input2.error("`RRegSideEffect` registers can't have `RReg` trait"));
+        }
+        let address = address.ok_or_else(|| input2.error("missing `address` specification"))?;
+        let bit_band = bit_band.unwrap_or(false);
+        if bit_band {
+            if !instances.is_empty() {
+                return Err(input2.error("`bitband` registers can't have `instances`"));
+            }
+            if bit_band_base(address.base10_parse()?).is_none() {
+                return Err(syn::Error::new(
+                    address.span(),
+                    "`bitband` register address isn't in a bit-band region",
+                ));
+            }
+        }
+        if traits.iter().any(|name| name == "Shadowed") {
+            if !traits.iter().any(|name| name == "WoReg") {
+                return Err(input2.error("`Shadowed` registers must have `WoReg` trait"));
+            }
+            if !instances.is_empty() {
+                return Err(input2.error("`Shadowed` registers can't have `instances`"));
+            }
+        }
+        if array.is_some() && !instances.is_empty() {
+            return Err(input2.error("`array` registers can't have `instances`"));
+        }
+        if views.is_empty() {
+            check_fields(size, &traits, &fields, &groups)?;
+        }
+        for view in &views {
+            let fields = fields.iter().chain(&view.fields).cloned().collect::<Vec<_>>();
+            let groups = groups.iter().chain(&view.groups).cloned().collect::<Vec<_>>();
+            check_fields(size, &traits, &fields, &groups)?;
+        }
         Ok(Self {
             attrs,
             vis,
             block,
             ident,
-            address: address.ok_or_else(|| input2.error("missing `address` specification"))?,
-            size: size.ok_or_else(|| input2.error("missing `size` specification"))?,
+            address,
+            size,
             reset: reset.ok_or_else(|| input2.error("missing `reset` specification"))?,
+            reserved,
+            array,
+            instances,
+            bit_band,
+            debug: debug.unwrap_or(false),
             traits,
             fields,
+            groups,
+            views,
         })
     }
 }
 
+impl Instance {
+    fn parse_list(input: ParseStream<'_>) -> Result<Vec<Self>> {
+        let mut instances = Vec::new();
+        let input2;
+        braced!(input2 in input);
+        while !input2.is_empty() {
+            let attrs = input2.call(Attribute::parse_outer)?;
+            let ident = input2.parse()?;
+            input2.parse::<Token![=>]>()?;
+            let base = input2.parse()?;
+            instances.push(Self { attrs, ident, base });
+            if !input2.is_empty() {
+                input2.parse::<Token![;]>()?;
+            }
+        }
+        if instances.is_empty() {
+            return Err(input2.error("empty `instances`"));
+        }
+        Ok(instances)
+    }
+}
+
+impl View {
+    fn parse_list(input: ParseStream<'_>) -> Result<Vec<Self>> {
+        let mut views = Vec::new();
+        let input2;
+        braced!(input2 in input);
+        while !input2.is_empty() {
+            views.push(input2.parse()?);
+            if !input2.is_empty() {
+                input2.parse::<Token![;]>()?;
+            }
+        }
+        if views.is_empty() {
+            return Err(input2.error("empty `variants`"));
+        }
+        Ok(views)
+    }
+}
+
+impl Parse for View {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let ident = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let input2;
+        braced!(input2 in input);
+        let mut fields = Vec::new();
+        let mut groups = Vec::new();
+        while !input2.is_empty() {
+            let ident = input2.parse::<Ident>()?;
+            input2.parse::<Token![=>]>()?;
+            if ident == "fields" {
+                fields.extend(Field::parse_list(&input2)?);
+            } else if ident == "groups" {
+                groups.extend(Group::parse_list(&input2)?);
+            } else {
+                return Err(input2.error(format!("unknown key: `{ident}`")));
+            }
+            if !input2.is_empty() {
+                input2.parse::<Token![;]>()?;
+            }
+        }
+        Ok(Self { attrs, ident, fields, groups })
+    }
+}
+
+impl Group {
+    fn parse_list(input: ParseStream<'_>) -> Result<Vec<Self>> {
+        let mut groups = Vec::new();
+        let input2;
+        braced!(input2 in input);
+        while !input2.is_empty() {
+            groups.push(input2.parse()?);
+            if !input2.is_empty() {
+                input2.parse::<Token![;]>()?;
+            }
+        }
+        Ok(groups)
+    }
+}
+
+impl Parse for Group {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let ident = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let input2;
+        braced!(input2 in input);
+        let mut fields = Vec::new();
+        while !input2.is_empty() {
+            fields.push(input2.parse()?);
+        }
+        if fields.is_empty() {
+            return Err(input2.error("empty group"));
+        }
+        Ok(Self { attrs, ident, fields })
+    }
+}
+
 impl Field {
     fn parse_list(input: ParseStream<'_>) -> Result<Vec<Self>> {
         let mut fields = Vec::new();
@@ -129,8 +411,14 @@ impl Parse for Field {
         let mut offset = None;
         let mut width = None;
         let mut traits = Vec::new();
+        let mut convert = None;
+        let mut scale = None;
+        let mut values = None;
+        let mut accessor = None;
+        let mut alias = None;
+        let mut overlapping = None;
         while !input2.is_empty() {
-            let ident = input2.parse::<Ident>()?;
+            let ident = input2.call(Ident::parse_any)?;
             input2.parse::<Token![=>]>()?;
             if ident == "offset" {
                 if offset.is_none() {
@@ -146,6 +434,42 @@ impl Parse for Field {
                 }
             } else if ident == "traits" {
                 traits.extend(parse_traits(&input2)?);
+            } else if ident == "convert" {
+                if convert.is_none() {
+                    convert = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `convert` specifications"));
+                }
+            } else if ident == "scale" {
+                if scale.is_none() {
+                    scale = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `scale` specifications"));
+                }
+            } else if ident == "values" {
+                if values.is_none() {
+                    values = Some(Value::parse_list(&input2)?);
+                } else {
+                    return Err(input2.error("multiple `values` specifications"));
+                }
+            } else if ident == "as" {
+                if accessor.is_none() {
+                    accessor = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `as` specifications"));
+                }
+            } else if ident == "alias" {
+                if alias.is_none() {
+                    alias = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `alias` specifications"));
+                }
+            } else if ident == "overlapping" {
+                if overlapping.is_none() {
+                    overlapping = Some(input2.parse::<LitBool>()?.value);
+                } else {
+                    return Err(input2.error("multiple `overlapping` specifications"));
+                }
             } else {
                 return Err(input2.error(format!("unknown key: `{ident}`")));
             }
@@ -153,32 +477,333 @@ impl Parse for Field {
                 input2.parse::<Token![;]>()?;
             }
         }
+        let offset = offset.ok_or_else(|| input2.error("missing `offset` specification"))?;
+        let width = width.ok_or_else(|| input2.error("missing `width` specification"))?;
+        if let Some(values) = &values {
+            let width = width.base10_parse::<u32>()?;
+            let mut seen = HashSet::new();
+            for Value { ident, bits, .. } in values {
+                let bits = bits.base10_parse::<u64>()?;
+                if width < 64 && bits >> width != 0 {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("value `{ident}` doesn't fit into {width}-bit field"),
+                    ));
+                }
+                if !seen.insert(bits) {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("value `{ident}` duplicates another value"),
+                    ));
+                }
+            }
+        }
+        if scale.is_some() {
+            if convert.is_some() || values.is_some() {
+                return Err(input2.error("`scale` can't be combined with `convert` or `values`"));
+            }
+            if width.base10_digits() == "1" {
+                return Err(input2.error("`scale` requires multi-bit field"));
+            }
+        }
         Ok(Self {
             attrs,
             ident,
-            offset: offset.ok_or_else(|| input2.error("missing `offset` specification"))?,
-            width: width.ok_or_else(|| input2.error("missing `width` specification"))?,
+            offset,
+            width,
             traits,
+            convert,
+            scale,
+            values,
+            accessor,
+            alias,
+            overlapping: overlapping.unwrap_or(false),
+        })
+    }
+}
+
+impl Value {
+    fn parse_list(input: ParseStream<'_>) -> Result<Vec<Self>> {
+        let mut values = Vec::new();
+        let input2;
+        braced!(input2 in input);
+        while !input2.is_empty() {
+            values.push(input2.parse()?);
+            if !input2.is_empty() {
+                input2.parse::<Token![,]>()?;
+            }
+        }
+        if values.is_empty() {
+            return Err(input2.error("empty `values`"));
+        }
+        Ok(values)
+    }
+}
+
+impl Parse for Value {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let ident = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let bits = input.parse()?;
+        Ok(Self { attrs, ident, bits })
+    }
+}
+
+impl Parse for Convert {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let input2;
+        braced!(input2 in input);
+        let mut ty = None;
+        let mut read = None;
+        let mut write = None;
+        while !input2.is_empty() {
+            let ident = input2.call(Ident::parse_any)?;
+            input2.parse::<Token![=>]>()?;
+            if ident == "type" {
+                if ty.is_none() {
+                    ty = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `type` specifications"));
+                }
+            } else if ident == "read" {
+                if read.is_none() {
+                    read = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `read` specifications"));
+                }
+            } else if ident == "write" {
+                if write.is_none() {
+                    write = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `write` specifications"));
+                }
+            } else {
+                return Err(input2.error(format!("unknown key: `{ident}`")));
+            }
+            if !input2.is_empty() {
+                input2.parse::<Token![;]>()?;
+            }
+        }
+        Ok(Self {
+            ty: ty.ok_or_else(|| input2.error("missing `type` specification"))?,
+            read,
+            write,
+        })
+    }
+}
+
+impl Parse for Scale {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let input2;
+        braced!(input2 in input);
+        let mut ident = None;
+        let mut factor = None;
+        let mut bias = None;
+        let mut unit = None;
+        while !input2.is_empty() {
+            let key = input2.call(Ident::parse_any)?;
+            input2.parse::<Token![=>]>()?;
+            if key == "name" {
+                if ident.is_none() {
+                    ident = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `name` specifications"));
+                }
+            } else if key == "factor" {
+                if factor.is_none() {
+                    factor = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `factor` specifications"));
+                }
+            } else if key == "bias" {
+                if bias.is_none() {
+                    bias = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `bias` specifications"));
+                }
+            } else if key == "unit" {
+                if unit.is_none() {
+                    unit = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `unit` specifications"));
+                }
+            } else {
+                return Err(input2.error(format!("unknown key: `{key}`")));
+            }
+            if !input2.is_empty() {
+                input2.parse::<Token![;]>()?;
+            }
+        }
+        if let Some(factor) = &factor {
+            if factor.base10_parse::<u64>()? == 0 {
+                return Err(input2.error("`factor` must be non-zero"));
+            }
+        }
+        Ok(Self {
+            ident: ident.ok_or_else(|| input2.error("missing `name` specification"))?,
+            factor: factor.unwrap_or_else(|| LitInt::new("1", input2.span())),
+            bias: bias.unwrap_or_else(|| LitInt::new("0", input2.span())),
+            unit,
+        })
+    }
+}
+
+impl Parse for Reserved {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let input2;
+        braced!(input2 in input);
+        let mut mask = None;
+        let mut preserve = None;
+        while !input2.is_empty() {
+            let ident = input2.parse::<Ident>()?;
+            input2.parse::<Token![=>]>()?;
+            if ident == "mask" {
+                if mask.is_none() {
+                    mask = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `mask` specifications"));
+                }
+            } else if ident == "preserve" {
+                if preserve.is_none() {
+                    let value = input2.parse::<Ident>()?;
+                    if value != "reset" && value != "read" {
+                        return Err(input2.error("`preserve` must be either `reset` or `read`"));
+                    }
+                    preserve = Some(value);
+                } else {
+                    return Err(input2.error("multiple `preserve` specifications"));
+                }
+            } else {
+                return Err(input2.error(format!("unknown key: `{ident}`")));
+            }
+            if !input2.is_empty() {
+                input2.parse::<Token![;]>()?;
+            }
+        }
+        Ok(Self {
+            mask: mask.ok_or_else(|| input2.error("missing `mask` specification"))?,
+            preserve,
+        })
+    }
+}
+
+impl Parse for Array {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let input2;
+        braced!(input2 in input);
+        let mut count = None;
+        let mut stride = None;
+        while !input2.is_empty() {
+            let ident = input2.parse::<Ident>()?;
+            input2.parse::<Token![=>]>()?;
+            if ident == "count" {
+                if count.is_none() {
+                    count = Some(input2.parse::<LitInt>()?.base10_parse()?);
+                } else {
+                    return Err(input2.error("multiple `count` specifications"));
+                }
+            } else if ident == "stride" {
+                if stride.is_none() {
+                    stride = Some(input2.parse()?);
+                } else {
+                    return Err(input2.error("multiple `stride` specifications"));
+                }
+            } else {
+                return Err(input2.error(format!("unknown key: `{ident}`")));
+            }
+            if !input2.is_empty() {
+                input2.parse::<Token![;]>()?;
+            }
+        }
+        let count = count.ok_or_else(|| input2.error("missing `count` specification"))?;
+        if count == 0 {
+            return Err(input2.error("`count` must be greater than zero"));
+        }
+        Ok(Self {
+            count,
+            stride: stride.ok_or_else(|| input2.error("missing `stride` specification"))?,
         })
     }
 }
 
 impl Variant {
+    fn expand_views(&self) -> Vec<Self> {
+        self.views
+            .iter()
+            .map(|View { attrs, ident, fields, groups }| {
+                let mut variant = self.clone();
+                variant.attrs.extend(attrs.iter().cloned());
+                variant.ident = format_ident!("{}_{}", self.ident, ident);
+                variant.fields.extend(fields.iter().cloned());
+                variant.groups.extend(groups.iter().cloned());
+                variant.views.clear();
+                variant
+            })
+            .collect()
+    }
+
     #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
     fn generate(&mut self) -> TokenStream2 {
+        if let Some(array) = self.array.take() {
+            return self.generate_array(&array);
+        }
         let t = format_ident!("_T");
         let val_ty = format_ident!("u{}", self.size);
+        let (base_generics, base_param, base_arg) = if self.instances.is_empty() {
+            (quote!(), quote!(), quote!())
+        } else {
+            (quote!(<const BASE: usize>), quote!(, const BASE: usize), quote!(, BASE))
+        };
         let mut imports = self.traits.iter().cloned().collect::<HashSet<_>>();
         let mut tokens = Vec::new();
         let mut struct_tokens = Vec::new();
         let mut ctor_tokens = Vec::new();
-        for Field { attrs, ident, offset, width, traits } in &mut self.fields {
+        let mut field_take_tokens = Vec::new();
+        let mut group_fields = HashMap::new();
+        let mut field_tys = Vec::new();
+        let mut field_idents = Vec::new();
+        let side_effect = self.traits.iter().any(|name| name == "RRegSideEffect");
+        let bit_band = if self.bit_band {
+            match self.address.base10_parse().map(bit_band_base) {
+                Ok(Some(bit_band)) => Some(bit_band),
+                Ok(None) => {
+                    return syn::Error::new(
+                        self.address.span(),
+                        "`bitband` register address isn't in a bit-band region",
+                    )
+                    .to_compile_error();
+                }
+                Err(err) => return err.to_compile_error(),
+            }
+        } else {
+            None
+        };
+        for Field {
+            attrs,
+            ident,
+            offset,
+            width,
+            traits,
+            convert,
+            scale,
+            values,
+            accessor,
+            alias,
+            ..
+        } in &mut self.fields
+        {
             let mut force_bits = false;
+            let mut readable = false;
             traits.retain(|t| {
                 if t == "ForceBits" {
                     force_bits = true;
                     return false;
                 }
+                if t == "RRRegField" {
+                    readable = true;
+                    return !side_effect;
+                }
                 true
             });
             let field_snk = ident.to_string().to_snake_case();
@@ -187,21 +812,36 @@ impl Variant {
                 field_cml.push('_');
             }
             let field_cml = format_ident!("{}", field_cml);
+            field_tys.push(field_cml.clone());
             let field_ident = format_ident!("{}", unkeywordize(&field_snk));
+            field_idents.push(field_ident.clone());
+            let accessor_snk =
+                accessor.as_ref().map_or_else(|| field_snk.clone(), Ident::to_string);
+            let accessor_ident = format_ident!("{}", unkeywordize(&accessor_snk));
+            let mut alias_attrs = Vec::new();
+            if accessor.is_some() {
+                alias_attrs.push(quote!(#[doc(alias = #field_snk)]));
+            }
+            if let Some(alias) = alias {
+                alias_attrs.push(quote!(#[doc(alias = #alias)]));
+            }
             imports.extend(traits.iter().cloned());
             struct_tokens.push(quote! {
                 #(#attrs)*
-                pub #field_ident: #field_cml<#t>
+                pub #field_ident: #field_cml<#t #base_arg>
             });
             ctor_tokens.push(quote! {
+                #field_ident: unsafe { #field_cml::take_const() }
+            });
+            field_take_tokens.push(quote! {
                 #field_ident: ::drone_core::token::Token::take()
             });
             tokens.push(quote! {
                 #(#attrs)*
                 #[derive(Clone, Copy)]
-                pub struct #field_cml<#t: ::drone_core::reg::tag::RegTag>(#t);
+                pub struct #field_cml<#t: ::drone_core::reg::tag::RegTag #base_param>(#t);
 
-                unsafe impl<#t> ::drone_core::token::Token for #field_cml<#t>
+                unsafe impl<#t #base_param> ::drone_core::token::Token for #field_cml<#t #base_arg>
                 where
                     #t: ::drone_core::reg::tag::RegTag,
                 {
@@ -211,14 +851,27 @@ impl Variant {
                     }
                 }
 
-                impl<#t> ::drone_core::reg::field::RegField<#t> for #field_cml<#t>
+                impl<#t: ::drone_core::reg::tag::RegTagConst #base_param> #field_cml<#t #base_arg> {
+                    /// Creates the register field token in a `const` context.
+                    ///
+                    /// # Safety
+                    ///
+                    /// Same as for [`::drone_core::token::Token::take`].
+                    #[inline]
+                    pub const unsafe fn take_const() -> Self {
+                        #field_cml(#t::TAG)
+                    }
+                }
+
+                impl<#t #base_param> ::drone_core::reg::field::RegField<#t>
+                    for #field_cml<#t #base_arg>
                 where
                     #t: ::drone_core::reg::tag::RegTag,
                 {
-                    type Reg = Reg<#t>;
-                    type URegField = #field_cml<::drone_core::reg::tag::Urt>;
-                    type SRegField = #field_cml<::drone_core::reg::tag::Srt>;
-                    type CRegField = #field_cml<::drone_core::reg::tag::Crt>;
+                    type Reg = Reg<#t #base_arg>;
+                    type URegField = #field_cml<::drone_core::reg::tag::Urt #base_arg>;
+                    type SRegField = #field_cml<::drone_core::reg::tag::Srt #base_arg>;
+                    type CRegField = #field_cml<::drone_core::reg::tag::Crt #base_arg>;
 
                     const OFFSET: usize = #offset;
                     const WIDTH: usize = #width;
@@ -226,41 +879,113 @@ impl Variant {
             });
             for ident in &*traits {
                 tokens.push(quote! {
-                    impl<#t: ::drone_core::reg::tag::RegTag> #ident<#t> for #field_cml<#t> {}
+                    impl<#t #base_param> #ident<#t> for #field_cml<#t #base_arg>
+                    where
+                        #t: ::drone_core::reg::tag::RegTag,
+                    {
+                    }
                 });
             }
-            if width.base10_digits() == "1" && !force_bits {
+            let is_bit = width.base10_digits() == "1" && !force_bits;
+            let value_ty = values.as_ref().map(|_| format_ident!("{}Value", field_cml));
+            let exhaustive = values.as_ref().map_or(false, |values| {
+                width.base10_parse::<u32>().map_or(false, |width| {
+                    width < 64 && values.len() as u64 == 1 << width
+                })
+            });
+            if let (Some(values), Some(value_ty)) = (values, &value_ty) {
+                tokens.push(def_values(values, attrs, value_ty, &val_ty));
+            }
+            group_fields.insert(ident.to_string(), (field_ident.clone(), is_bit, value_ty.clone()));
+            if let Some(convert) = convert {
+                tokens.push(def_convert(
+                    convert,
+                    attrs,
+                    &accessor_snk,
+                    &alias_attrs,
+                    &field_ident,
+                    &field_cml,
+                    traits,
+                    is_bit,
+                    &base_param,
+                    &base_arg,
+                ));
+            }
+            if is_bit {
                 tokens.push(quote! {
-                    impl<#t> ::drone_core::reg::field::RegFieldBit<#t> for #field_cml<#t>
+                    impl<#t #base_param> ::drone_core::reg::field::RegFieldBit<#t>
+                        for #field_cml<#t #base_arg>
                     where
                         #t: ::drone_core::reg::tag::RegTag,
                     {
                     }
                 });
-                if traits.iter().any(|name| name == "RRRegField") {
+                if let Some(bit_band) = bit_band {
+                    let address = match offset.base10_parse::<usize>() {
+                        Ok(offset) => bit_band + offset * 4,
+                        Err(err) => return err.to_compile_error(),
+                    };
+                    let address = LitInt::new(&format!("{address:#X}"), offset.span());
+                    tokens.push(quote! {
+                        impl<#t #base_param> ::drone_core::reg::field::RegFieldBitBand<#t>
+                            for #field_cml<#t #base_arg>
+                        where
+                            #t: ::drone_core::reg::tag::RegTag,
+                        {
+                            const BIT_BAND_ADDRESS: usize = #address;
+                        }
+                    });
+                }
+                if readable {
+                    let bit = if side_effect {
+                        quote!(unsafe { self.val.read_bit(#offset) })
+                    } else {
+                        quote! {
+                            ::drone_core::reg::field::RRRegFieldBit::read(
+                                &self.reg.#field_ident,
+                                &self.val,
+                            )
+                        }
+                    };
+                    let (read_ty, read) = match &value_ty {
+                        Some(value_ty) => {
+                            let bits = quote!(<#val_ty as ::core::convert::From<bool>>::from(#bit));
+                            value_read(value_ty, &val_ty, exhaustive, &bits)
+                        }
+                        None => (quote!(bool), bit),
+                    };
                     tokens.push(quote! {
                         #[allow(clippy::len_without_is_empty)]
-                        impl<'a, #t: ::drone_core::reg::tag::RegTag> Hold<'a, #t> {
+                        impl<'a, #t #base_param> Hold<'a, #t #base_arg>
+                        where
+                            #t: ::drone_core::reg::tag::RegTag,
+                        {
                             #(#attrs)*
+                            #(#alias_attrs)*
                             #[inline]
-                            pub fn #field_ident(&self) -> bool {
-                                ::drone_core::reg::field::RRRegFieldBit::read(
-                                    &self.reg.#field_ident,
-                                    &self.val,
-                                )
+                            pub fn #accessor_ident(&self) -> #read_ty {
+                                #read
                             }
                         }
                     });
                 }
                 if traits.iter().any(|name| name == "WWRegField") {
-                    let set_field = format_ident!("set_{}", field_snk);
-                    let clear_field = format_ident!("clear_{}", field_snk);
-                    let toggle_field = format_ident!("toggle_{}", field_snk);
-                    let write_field = format_ident!("write_{}", field_snk);
+                    let set_field = format_ident!("set_{}", accessor_snk);
+                    let clear_field = format_ident!("clear_{}", accessor_snk);
+                    let toggle_field = format_ident!("toggle_{}", accessor_snk);
+                    let write_field = format_ident!("write_{}", accessor_snk);
+                    let (write_ty, bit) = match &value_ty {
+                        Some(value_ty) => (quote!(#value_ty), quote!(bit as #val_ty != 0)),
+                        None => (quote!(bool), quote!(bit)),
+                    };
                     tokens.push(quote! {
                         #[allow(clippy::len_without_is_empty)]
-                        impl<'a, #t: ::drone_core::reg::tag::RegTag> Hold<'a, #t> {
+                        impl<'a, #t #base_param> Hold<'a, #t #base_arg>
+                        where
+                            #t: ::drone_core::reg::tag::RegTag,
+                        {
                             #(#attrs)*
+                            #(#alias_attrs)*
                             #[inline]
                             pub fn #set_field(&mut self) -> &mut Self {
                                 ::drone_core::reg::field::WWRegFieldBit::set(
@@ -271,6 +996,7 @@ impl Variant {
                             }
 
                             #(#attrs)*
+                            #(#alias_attrs)*
                             #[inline]
                             pub fn #clear_field(&mut self) -> &mut Self {
                                 ::drone_core::reg::field::WWRegFieldBit::clear(
@@ -281,6 +1007,7 @@ impl Variant {
                             }
 
                             #(#attrs)*
+                            #(#alias_attrs)*
                             #[inline]
                             pub fn #toggle_field(&mut self) -> &mut Self {
                                 ::drone_core::reg::field::WWRegFieldBit::toggle(
@@ -291,59 +1018,169 @@ impl Variant {
                             }
 
                             #(#attrs)*
+                            #(#alias_attrs)*
                             #[inline]
-                            pub fn #write_field(&mut self, bit: bool) -> &mut Self {
+                            pub fn #write_field(&mut self, bit: #write_ty) -> &mut Self {
                                 ::drone_core::reg::field::WWRegFieldBit::write(
                                     &self.reg.#field_ident,
                                     &mut self.val,
-                                    bit,
+                                    #bit,
                                 );
                                 self
                             }
                         }
                     });
                 }
+                if let Some(name) =
+                    traits.iter().find(|name| *name == "W1CRegField" || *name == "W0CRegField")
+                {
+                    let clear_field = format_ident!("clear_{}", accessor_snk);
+                    tokens.push(quote! {
+                        impl<#t: ::drone_core::reg::tag::RegTag #base_param> Reg<#t #base_arg> {
+                            #(#attrs)*
+                            #(#alias_attrs)*
+                            #[inline]
+                            pub fn #clear_field(&self) {
+                                ::drone_core::reg::field::#name::clear_flag(&self.#field_ident);
+                            }
+                        }
+                    });
+                }
             } else {
                 tokens.push(quote! {
-                    impl<#t> ::drone_core::reg::field::RegFieldBits<#t> for #field_cml<#t>
+                    impl<#t #base_param> ::drone_core::reg::field::RegFieldBits<#t>
+                        for #field_cml<#t #base_arg>
                     where
                         #t: ::drone_core::reg::tag::RegTag,
                     {
                     }
                 });
-                if traits.iter().any(|name| name == "RRRegField") {
+                let bits = if side_effect {
+                    quote!(unsafe { self.val.read_bits(#offset, #width) })
+                } else {
+                    quote! {
+                        ::drone_core::reg::field::RRRegFieldBits::read(
+                            &self.reg.#field_ident,
+                            &self.val,
+                        )
+                    }
+                };
+                if readable {
+                    let (read_ty, read) = match &value_ty {
+                        Some(value_ty) => value_read(value_ty, &val_ty, exhaustive, &bits),
+                        None => (quote!(#val_ty), bits.clone()),
+                    };
                     tokens.push(quote! {
                         #[allow(clippy::len_without_is_empty)]
-                        impl<'a, #t: ::drone_core::reg::tag::RegTag> Hold<'a, #t> {
+                        impl<'a, #t #base_param> Hold<'a, #t #base_arg>
+                        where
+                            #t: ::drone_core::reg::tag::RegTag,
+                        {
                             #(#attrs)*
+                            #(#alias_attrs)*
                             #[inline]
-                            pub fn #field_ident(&self) -> #val_ty {
-                                ::drone_core::reg::field::RRRegFieldBits::read(
-                                    &self.reg.#field_ident,
-                                    &self.val,
-                                )
+                            pub fn #accessor_ident(&self) -> #read_ty {
+                                #read
                             }
                         }
                     });
                 }
                 if traits.iter().any(|name| name == "WWRegField") {
-                    let write_field = format_ident!("write_{}", field_snk);
+                    let write_field = format_ident!("write_{}", accessor_snk);
+                    let (write_ty, bits) = match &value_ty {
+                        Some(value_ty) => (quote!(#value_ty), quote!(bits as #val_ty)),
+                        None => (quote!(#val_ty), quote!(bits)),
+                    };
                     tokens.push(quote! {
                         #[allow(clippy::len_without_is_empty)]
-                        impl<'a, #t: ::drone_core::reg::tag::RegTag> Hold<'a, #t> {
+                        impl<'a, #t #base_param> Hold<'a, #t #base_arg>
+                        where
+                            #t: ::drone_core::reg::tag::RegTag,
+                        {
                             #(#attrs)*
+                            #(#alias_attrs)*
                             #[inline]
-                            pub fn #write_field(&mut self, bits: #val_ty) -> &mut Self {
+                            pub fn #write_field(&mut self, bits: #write_ty) -> &mut Self {
                                 ::drone_core::reg::field::WWRegFieldBits::write(
                                     &self.reg.#field_ident,
                                     &mut self.val,
-                                    bits,
+                                    #bits,
                                 );
                                 self
                             }
                         }
                     });
                 }
+                if let Some(scale) = scale {
+                    let writable = traits.iter().any(|name| name == "WWRegField");
+                    tokens.push(def_scale(
+                        scale,
+                        &accessor_snk,
+                        &field_ident,
+                        &bits,
+                        width,
+                        readable,
+                        writable,
+                        &val_ty,
+                        &base_param,
+                        &base_arg,
+                    ));
+                }
+            }
+        }
+        let rw = self.traits.iter().any(|name| name == "RReg")
+            && self.traits.iter().any(|name| name == "WReg");
+        for Group { attrs, ident, fields } in &self.groups {
+            let group_snk = ident.to_string().to_snake_case();
+            let write_group = format_ident!("write_{}", group_snk);
+            let mut params = Vec::new();
+            let mut param_tys = Vec::new();
+            let mut writes = Vec::new();
+            for field in fields {
+                let (field_ident, is_bit, value_ty) = &group_fields[&field.to_string()];
+                let write_trait =
+                    if *is_bit { quote!(WWRegFieldBit) } else { quote!(WWRegFieldBits) };
+                let arg = match value_ty {
+                    Some(_) if *is_bit => quote!(#field_ident as #val_ty != 0),
+                    Some(_) => quote!(#field_ident as #val_ty),
+                    None => quote!(#field_ident),
+                };
+                params.push(field_ident);
+                param_tys.push(match value_ty {
+                    Some(value_ty) => quote!(#value_ty),
+                    None if *is_bit => quote!(bool),
+                    None => quote!(#val_ty),
+                });
+                writes.push(quote! {
+                    ::drone_core::reg::field::#write_trait::write(
+                        &self.reg.#field_ident,
+                        &mut self.val,
+                        #arg,
+                    );
+                });
+            }
+            tokens.push(quote! {
+                impl<'a, #t: ::drone_core::reg::tag::RegTag #base_param> Hold<'a, #t #base_arg> {
+                    #(#attrs)*
+                    #[inline]
+                    pub fn #write_group(&mut self, #(#params: #param_tys),*) -> &mut Self {
+                        #(#writes)*
+                        self
+                    }
+                }
+            });
+            if rw {
+                tokens.push(quote! {
+                    impl #base_generics Reg<::drone_core::reg::tag::Urt #base_arg> {
+                        #(#attrs)*
+                        #[inline]
+                        pub fn #write_group(&mut self, #(#params: #param_tys),*) {
+                            ::drone_core::reg::RwRegUnsync::modify(self, |r| {
+                                r.#write_group(#(#params),*)
+                            });
+                        }
+                    }
+                });
             }
         }
         if self.fields.is_empty() {
@@ -352,22 +1189,172 @@ impl Variant {
                 pub _marker: ::core::marker::PhantomData<#t>
             });
             ctor_tokens.push(quote!(_marker: ::core::marker::PhantomData));
+            field_take_tokens.push(quote!(_marker: ::core::marker::PhantomData));
+        } else {
+            tokens.push(quote! {
+                impl<#t #base_param> ::drone_core::reg::RegFields<#t> for Reg<#t #base_arg>
+                where
+                    #t: ::drone_core::reg::tag::RegTag,
+                {
+                    type Fields = (#(#field_tys<#t #base_arg>,)*);
+
+                    #[inline]
+                    fn into_fields(self) -> Self::Fields {
+                        (#(self.#field_idents,)*)
+                    }
+
+                    #[inline]
+                    fn from_fields((#(#field_idents,)*): Self::Fields) -> Self {
+                        Self { #(#field_idents),* }
+                    }
+                }
+            });
         }
         for ident in &self.traits {
+            if ident == "Shadowed" {
+                let reset = &self.reset;
+                tokens.push(quote! {
+                    static SHADOW: ::drone_core::reg::Shadow<#val_ty> =
+                        ::drone_core::reg::Shadow::new(#reset);
+
+                    impl<#t> #ident<#t> for Reg<#t>
+                    where
+                        #t: ::drone_core::reg::tag::RegTag,
+                    {
+                        #[inline]
+                        fn shadow(&self) -> &'static ::drone_core::reg::Shadow<#val_ty> {
+                            &SHADOW
+                        }
+                    }
+                });
+                continue;
+            }
             tokens.push(quote! {
-                impl<#t: ::drone_core::reg::tag::RegTag> #ident<#t> for Reg<#t> {}
+                impl<#t #base_param> #ident<#t> for Reg<#t #base_arg>
+                where
+                    #t: ::drone_core::reg::tag::RegTag,
+                {
+                }
             });
         }
-        let imports = if imports.is_empty() {
-            quote!()
-        } else {
-            let imports = imports.iter();
-            quote!(use super::{#(#imports),*};)
-        };
-        let Variant { attrs, vis, address, reset, .. } = &self;
+        let shadowed = self.traits.iter().any(|name| name == "Shadowed");
+        if rw || shadowed {
+            let clear_mask = |name: &str| {
+                let mask = self
+                    .fields
+                    .iter()
+                    .filter(|field| field.traits.iter().any(|ident| ident == name))
+                    .filter_map(|field| field_range(field).ok())
+                    .fold(0_u128, |mask, range| {
+                        range.fold(mask, |mask, bit| mask | 1 << bit)
+                    });
+                Literal::u128_unsuffixed(mask)
+            };
+            let w1c = clear_mask("W1CRegField");
+            let w0c = clear_mask("W0CRegField");
+            let (save, restore) = if shadowed {
+                (
+                    quote!(::drone_core::reg::RegHold::val(
+                        &::drone_core::reg::Shadowed::load_shadow(self)
+                    )),
+                    quote!(::drone_core::reg::snapshot_restore_shadowed(self, saved, #w1c, #w0c)),
+                )
+            } else {
+                (
+                    quote!(::drone_core::reg::RReg::load_val(self)),
+                    quote!(::drone_core::reg::snapshot_restore(self, saved, #w1c, #w0c)),
+                )
+            };
+            tokens.push(quote! {
+                impl<#t #base_param> ::drone_core::reg::RegSnapshot<#t> for Reg<#t #base_arg>
+                where
+                    #t: ::drone_core::reg::tag::RegTag,
+                {
+                    #[inline]
+                    fn save(&self) -> Self::Val {
+                        #save
+                    }
+
+                    #[inline]
+                    fn restore(&self, saved: Self::Val) {
+                        #restore
+                    }
+                }
+            });
+        }
+        let c_fields = self.fields.iter().map(|Field { ident, offset, width, .. }| {
+            let field_name = ident.to_string();
+            quote!((#field_name, #offset, #width))
+        });
+        tokens.push(quote! {
+            impl<#t #base_param> ::drone_core::reg::RegCDefines for Reg<#t #base_arg>
+            where
+                #t: ::drone_core::reg::tag::RegTag,
+            {
+                fn write_c_defines(
+                    w: &mut dyn ::core::fmt::Write,
+                    name: ::core::fmt::Arguments<'_>,
+                ) -> ::core::fmt::Result {
+                    ::drone_core::reg::write_c_defines::<#t, Self>(w, name, &[#(#c_fields),*])
+                }
+            }
+        });
+        // The traits, which are not in the original `reg::prelude`, are
+        // imported by their absolute paths, so they don't require the `v2`
+        // prelude at the call site.
+        let imports = imports.iter().map(|ident| match ident.to_string().as_str() {
+            "W1CRegField" | "W0CRegField" => quote!(use ::drone_core::reg::field::#ident;),
+            "RRegSideEffect" | "Shadowed" => quote!(use ::drone_core::reg::#ident;),
+            _ => quote!(use super::#ident;),
+        });
+        let imports = quote!(#(#imports)*);
+        if self.debug {
+            tokens.push(self.def_debug(&base_param, &base_arg));
+        }
+        let Variant { attrs, vis, address, reset, reserved, .. } = &self;
+        let reserved = reserved.as_ref().map(|Reserved { mask, preserve }| {
+            let preserve = match preserve {
+                Some(preserve) if preserve == "read" => format_ident!("Read"),
+                _ => format_ident!("Reset"),
+            };
+            quote! {
+                const RESERVED: ::core::option::Option<(#val_ty, ::drone_core::reg::Reserved)> =
+                    ::core::option::Option::Some((#mask, ::drone_core::reg::Reserved::#preserve));
+            }
+        });
         let reg_full = self.reg_full();
+        let address_expr =
+            if self.instances.is_empty() { quote!(#address) } else { quote!(BASE + #address) };
+        let instances = self.instances.iter().map(|instance| {
+            let Instance { attrs: instance_attrs, ident, base } = instance;
+            let instance_full = format_ident!(
+                "{}_{}",
+                ident.to_string().to_snake_case(),
+                self.ident.to_string().to_snake_case()
+            );
+            quote! {
+                #(#attrs)*
+                #(#instance_attrs)*
+                #vis mod #instance_full {
+                    pub use super::#reg_full::*;
+
+                    #(#attrs)*
+                    pub type Reg<#t> = super::#reg_full::Reg<#t, #base>;
+
+                    #(#attrs)*
+                    pub type Hold<'a, #t> = super::#reg_full::Hold<'a, #t, #base>;
+
+                    #(
+                        #[allow(missing_docs)]
+                        pub type #field_tys<#t> = super::#reg_full::#field_tys<#t, #base>;
+                    )*
+                }
+            }
+        });
 
         quote! {
+            #(#instances)*
+
             #(#attrs)*
             #vis mod #reg_full {
                 #imports
@@ -379,26 +1366,45 @@ impl Variant {
 
                 #(#attrs)*
                 #[derive(Clone, Copy)]
-                pub struct Reg<#t: ::drone_core::reg::tag::RegTag> {
+                pub struct Reg<#t: ::drone_core::reg::tag::RegTag #base_param> {
                     #(#struct_tokens),*
                 }
 
-                unsafe impl<#t: ::drone_core::reg::tag::RegTag> ::drone_core::token::Token for Reg<#t> {
+                unsafe impl<#t #base_param> ::drone_core::token::Token for Reg<#t #base_arg>
+                where
+                    #t: ::drone_core::reg::tag::RegTag,
+                {
                     #[inline]
                     unsafe fn take() -> Self {
+                        Self { #(#field_take_tokens,)* }
+                    }
+                }
+
+                impl<#t: ::drone_core::reg::tag::RegTagConst #base_param> Reg<#t #base_arg> {
+                    /// Creates the register token in a `const` context.
+                    ///
+                    /// # Safety
+                    ///
+                    /// Same as for [`::drone_core::token::Token::take`].
+                    #[inline]
+                    pub const unsafe fn take_const() -> Self {
                         Self { #(#ctor_tokens,)* }
                     }
                 }
 
-                impl<#t: ::drone_core::reg::tag::RegTag> ::drone_core::reg::Reg<#t> for Reg<#t> {
+                impl<#t #base_param> ::drone_core::reg::Reg<#t> for Reg<#t #base_arg>
+                where
+                    #t: ::drone_core::reg::tag::RegTag,
+                {
                     type Val = Val;
-                    type Hold<'a> = Hold<'a, #t>;
-                    type UReg = Reg<::drone_core::reg::tag::Urt>;
-                    type SReg = Reg<::drone_core::reg::tag::Srt>;
-                    type CReg = Reg<::drone_core::reg::tag::Crt>;
+                    type Hold<'a> = Hold<'a, #t #base_arg>;
+                    type UReg = Reg<::drone_core::reg::tag::Urt #base_arg>;
+                    type SReg = Reg<::drone_core::reg::tag::Srt #base_arg>;
+                    type CReg = Reg<::drone_core::reg::tag::Crt #base_arg>;
 
-                    const ADDRESS: usize = #address;
+                    const ADDRESS: usize = #address_expr;
                     const RESET: #val_ty = #reset;
+                    #reserved
 
                     #[inline]
                     unsafe fn val_from(bits: #val_ty) -> Val {
@@ -412,12 +1418,13 @@ impl Variant {
                 }
 
                 #(#attrs)*
-                pub struct Hold<'a, #t: ::drone_core::reg::tag::RegTag> {
-                    reg: &'a Reg<#t>,
+                pub struct Hold<'a, #t: ::drone_core::reg::tag::RegTag #base_param> {
+                    reg: &'a Reg<#t #base_arg>,
                     val: Val,
                 }
 
-                impl<'a, #t> ::drone_core::reg::RegHold<'a, #t, Reg<#t>> for Hold<'a, #t>
+                impl<'a, #t #base_param> ::drone_core::reg::RegHold<'a, #t, Reg<#t #base_arg>>
+                    for Hold<'a, #t #base_arg>
                 where
                     #t: ::drone_core::reg::tag::RegTag,
                 {
@@ -437,6 +1444,136 @@ impl Variant {
         }
     }
 
+    fn generate_array(&self, array: &Array) -> TokenStream2 {
+        let t = format_ident!("_T");
+        let Array { count, stride } = array;
+        let (address, stride) =
+            match (self.address.base10_parse::<usize>(), stride.base10_parse::<usize>()) {
+                (Ok(address), Ok(stride)) => (address, stride),
+                (Err(err), _) | (_, Err(err)) => return err.to_compile_error(),
+            };
+        let mut tokens = Vec::new();
+        let mut elements = Vec::new();
+        for i in 0..*count {
+            let mut element = self.clone();
+            element.ident = format_ident!("{}{}", self.ident, i);
+            element.address =
+                LitInt::new(&format!("{:#X}", address + i * stride), self.address.span());
+            let element_full = element.reg_full();
+            elements.push(quote!(super::#element_full::Reg<#t>));
+            tokens.push(element.generate());
+        }
+        let take_const = elements.iter().map(|element| quote!(unsafe { <#element>::take_const() }));
+        let take = elements.iter().map(|_| quote!(::drone_core::token::Token::take()));
+        let indices = 0..*count;
+        let index_lits = (0..*count).map(Literal::usize_unsuffixed);
+        let index_fields = (0..*count).map(syn::Index::from);
+        let Variant { attrs, vis, .. } = self;
+        let reg_full = self.reg_full();
+        quote! {
+            #(#tokens)*
+
+            #(#attrs)*
+            #vis mod #reg_full {
+                /// The number of registers in the array.
+                pub const LEN: usize = #count;
+
+                #(#attrs)*
+                #[derive(Clone, Copy)]
+                pub struct Reg<#t: ::drone_core::reg::tag::RegTag>(#(pub #elements),*);
+
+                unsafe impl<#t> ::drone_core::token::Token for Reg<#t>
+                where
+                    #t: ::drone_core::reg::tag::RegTag,
+                {
+                    #[inline]
+                    unsafe fn take() -> Self {
+                        Self(#(#take),*)
+                    }
+                }
+
+                impl<#t> ::drone_core::reg::RegCDefines for Reg<#t>
+                where
+                    #t: ::drone_core::reg::tag::RegTag,
+                {
+                    fn write_c_defines(
+                        w: &mut dyn ::core::fmt::Write,
+                        name: ::core::fmt::Arguments<'_>,
+                    ) -> ::core::fmt::Result {
+                        #(
+                            <#elements as ::drone_core::reg::RegCDefines>::write_c_defines(
+                                w,
+                                ::core::format_args!("{}{}", name, #indices),
+                            )?;
+                        )*
+                        ::core::result::Result::Ok(())
+                    }
+                }
+
+                #(
+                    impl<#t> ::drone_core::reg::RegArray<#index_lits> for Reg<#t>
+                    where
+                        #t: ::drone_core::reg::tag::RegTag,
+                    {
+                        type Element = #elements;
+
+                        #[inline]
+                        fn element(&self) -> &Self::Element {
+                            &self.#index_fields
+                        }
+                    }
+                )*
+
+                impl<#t: ::drone_core::reg::tag::RegTag> Reg<#t> {
+                    /// Returns a reference to the register token at the index
+                    /// `I`. An out of bounds index fails at compile-time.
+                    #[inline]
+                    pub fn reg<const I: usize>(
+                        &self,
+                    ) -> &<Self as ::drone_core::reg::RegArray<I>>::Element
+                    where
+                        Self: ::drone_core::reg::RegArray<I>,
+                    {
+                        ::drone_core::reg::RegArray::<I>::element(self)
+                    }
+                }
+
+                impl<#t: ::drone_core::reg::tag::RegTagConst> Reg<#t> {
+                    /// Creates the register array token in a `const` context.
+                    ///
+                    /// # Safety
+                    ///
+                    /// Same as for [`::drone_core::token::Token::take`].
+                    #[inline]
+                    pub const unsafe fn take_const() -> Self {
+                        Self(#(#take_const),*)
+                    }
+                }
+            }
+        }
+    }
+
+    fn def_debug(&self, base_param: &TokenStream2, base_arg: &TokenStream2) -> TokenStream2 {
+        let t = format_ident!("_T");
+        let name = format!("{}_{}", self.block, self.ident);
+        let fields = self.fields.iter().map(|Field { ident, offset, width, .. }| {
+            let field_name = ident.to_string();
+            quote! {
+                .field(#field_name, &unsafe { self.val.read_bits(#offset, #width) })
+            }
+        });
+        quote! {
+            impl<'a, #t #base_param> ::core::fmt::Debug for Hold<'a, #t #base_arg>
+            where
+                #t: ::drone_core::reg::tag::RegTag,
+            {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.debug_struct(#name)#(#fields)*.finish()
+                }
+            }
+        }
+    }
+
     fn reg_full(&self) -> Ident {
         format_ident!(
             "{}_{}",
@@ -446,6 +1583,297 @@ impl Variant {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn def_scale(
+    scale: &Scale,
+    accessor_snk: &str,
+    field_ident: &Ident,
+    bits: &TokenStream2,
+    width: &LitInt,
+    readable: bool,
+    writable: bool,
+    val_ty: &Ident,
+    base_param: &TokenStream2,
+    base_arg: &TokenStream2,
+) -> TokenStream2 {
+    let t = format_ident!("_T");
+    let Scale { ident, factor, bias, unit } = scale;
+    let unit = unit.as_ref().map_or_else(String::new, |unit| format!(" in {}", unit.value()));
+    let mut methods = Vec::new();
+    if readable {
+        let read_scaled = format_ident!("read_{}", ident);
+        let doc = format!(
+            "Returns the `{accessor_snk}` field value{unit}, computed as `(bits + {bias}) * \
+             {factor}`.\n\nThe result saturates at the maximum value of the type."
+        );
+        methods.push(quote! {
+            #[doc = #doc]
+            #[inline]
+            pub fn #read_scaled(&self) -> #val_ty {
+                (#bits).saturating_add(#bias).saturating_mul(#factor)
+            }
+        });
+    }
+    if writable {
+        let write_scaled = format_ident!("write_{}", ident);
+        let doc = format!(
+            "Writes the `{accessor_snk}` field from `value`{unit}, computed as `value / \
+             {factor} - {bias}`.\n\nThe division rounds down.\n\n# Errors\n\nIf the \
+             computed bits are negative or don't fit into the field, the field is left \
+             unchanged, and [`ScaleError`](::drone_core::reg::field::ScaleError) is returned."
+        );
+        methods.push(quote! {
+            #[doc = #doc]
+            #[inline]
+            pub fn #write_scaled(
+                &mut self,
+                value: #val_ty,
+            ) -> ::core::result::Result<&mut Self, ::drone_core::reg::field::ScaleError> {
+                let bits = (value / #factor)
+                    .checked_sub(#bias)
+                    .filter(|bits| bits.checked_shr(#width).map_or(true, |rest| rest == 0))
+                    .ok_or(::drone_core::reg::field::ScaleError)?;
+                ::drone_core::reg::field::WWRegFieldBits::write(
+                    &self.reg.#field_ident,
+                    &mut self.val,
+                    bits,
+                );
+                ::core::result::Result::Ok(self)
+            }
+        });
+    }
+    quote! {
+        impl<'a, #t #base_param> Hold<'a, #t #base_arg>
+        where
+            #t: ::drone_core::reg::tag::RegTag,
+        {
+            #(#methods)*
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn def_convert(
+    convert: &Convert,
+    attrs: &[Attribute],
+    accessor_snk: &str,
+    alias_attrs: &[TokenStream2],
+    field_ident: &Ident,
+    field_cml: &Ident,
+    traits: &[Ident],
+    is_bit: bool,
+    base_param: &TokenStream2,
+    base_arg: &TokenStream2,
+) -> TokenStream2 {
+    let t = format_ident!("_T");
+    let Convert { ty, read, write } = convert;
+    let (read_trait, write_trait) = if is_bit {
+        (quote!(RRRegFieldBit), quote!(WWRegFieldBit))
+    } else {
+        (quote!(RRRegFieldBits), quote!(WWRegFieldBits))
+    };
+    let mut field_tokens = Vec::new();
+    let mut hold_tokens = Vec::new();
+    if let Some(read) = read.as_ref().filter(|_| traits.iter().any(|name| name == "RRRegField")) {
+        let field_converted = format_ident!("{}_converted", accessor_snk);
+        field_tokens.push(quote! {
+            /// Reads the field from `val`, and converts it to the high-level
+            /// value.
+            #[inline]
+            pub fn read_converted(&self, val: &Val) -> #ty {
+                #read(::drone_core::reg::field::#read_trait::read(self, val))
+            }
+        });
+        hold_tokens.push(quote! {
+            #(#attrs)*
+            #(#alias_attrs)*
+            #[inline]
+            pub fn #field_converted(&self) -> #ty {
+                self.reg.#field_ident.read_converted(&self.val)
+            }
+        });
+    }
+    if let Some(write) = write.as_ref().filter(|_| traits.iter().any(|name| name == "WWRegField"))
+    {
+        let write_field_converted = format_ident!("write_{}_converted", accessor_snk);
+        field_tokens.push(quote! {
+            /// Converts the high-level `value` to the field bits, and writes
+            /// them to `val`.
+            #[inline]
+            pub fn write_converted(&self, val: &mut Val, value: #ty) {
+                ::drone_core::reg::field::#write_trait::write(self, val, #write(value));
+            }
+        });
+        hold_tokens.push(quote! {
+            #(#attrs)*
+            #(#alias_attrs)*
+            #[inline]
+            pub fn #write_field_converted(&mut self, value: #ty) -> &mut Self {
+                self.reg.#field_ident.write_converted(&mut self.val, value);
+                self
+            }
+        });
+    }
+    quote! {
+        impl<#t: ::drone_core::reg::tag::RegTag #base_param> #field_cml<#t #base_arg> {
+            #(#field_tokens)*
+        }
+
+        impl<'a, #t: ::drone_core::reg::tag::RegTag #base_param> Hold<'a, #t #base_arg> {
+            #(#hold_tokens)*
+        }
+    }
+}
+
+fn def_values(
+    values: &[Value],
+    attrs: &[Attribute],
+    value_ty: &Ident,
+    val_ty: &Ident,
+) -> TokenStream2 {
+    let variants =
+        values.iter().map(|Value { attrs, ident, bits }| quote!(#(#attrs)* #ident = #bits));
+    let arms = values
+        .iter()
+        .map(|Value { ident, bits, .. }| quote!(#bits => ::core::result::Result::Ok(Self::#ident)));
+    quote! {
+        #(#attrs)*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[repr(#val_ty)]
+        pub enum #value_ty {
+            #(#variants,)*
+        }
+
+        impl ::core::convert::TryFrom<#val_ty> for #value_ty {
+            type Error = #val_ty;
+
+            #[inline]
+            fn try_from(bits: #val_ty) -> ::core::result::Result<Self, #val_ty> {
+                match bits {
+                    #(#arms,)*
+                    _ => ::core::result::Result::Err(bits),
+                }
+            }
+        }
+
+        impl ::core::convert::From<#value_ty> for #val_ty {
+            #[inline]
+            fn from(value: #value_ty) -> Self {
+                value as #val_ty
+            }
+        }
+    }
+}
+
+fn value_read(
+    value_ty: &Ident,
+    val_ty: &Ident,
+    exhaustive: bool,
+    bits: &TokenStream2,
+) -> (TokenStream2, TokenStream2) {
+    let try_from = quote!(<#value_ty as ::core::convert::TryFrom<#val_ty>>::try_from(#bits));
+    if exhaustive {
+        (quote!(#value_ty), quote! {
+            match #try_from {
+                ::core::result::Result::Ok(value) => value,
+                ::core::result::Result::Err(_) => ::core::unreachable!(),
+            }
+        })
+    } else {
+        (quote!(::core::result::Result<#value_ty, #val_ty>), try_from)
+    }
+}
+
+/// Returns the bit-band alias address of the first bit at `address`, if the
+/// address is in one of the Cortex-M bit-band regions.
+fn bit_band_base(address: usize) -> Option<usize> {
+    [(0x2000_0000, 0x2200_0000), (0x4000_0000, 0x4200_0000)]
+        .into_iter()
+        .find(|&(region, _)| (region..region + 0x10_0000).contains(&address))
+        .map(|(region, alias)| alias + (address - region) * 32)
+}
+
+fn check_fields(size: u8, traits: &[Ident], fields: &[Field], groups: &[Group]) -> Result<()> {
+    for field in fields {
+        if let Some(name) =
+            field.traits.iter().find(|name| *name == "W1CRegField" || *name == "W0CRegField")
+        {
+            if !traits.iter().any(|name| name == "WReg") {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("`{name}` requires `WReg` register trait"),
+                ));
+            }
+            if field.width.base10_digits() != "1" {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("`{name}` requires single-bit field"),
+                ));
+            }
+        }
+        let offset = field.offset.base10_parse::<u8>()?;
+        let width = field.width.base10_parse::<u8>()?;
+        if width == 0 || u16::from(offset) + u16::from(width) > u16::from(size) {
+            return Err(syn::Error::new(
+                field.ident.span(),
+                format!("field `{}` doesn't fit into {size}-bit register", field.ident),
+            ));
+        }
+    }
+    for (i, field) in fields.iter().enumerate() {
+        if field.overlapping {
+            continue;
+        }
+        let range = field_range(field)?;
+        for prev in fields[..i].iter().filter(|prev| !prev.overlapping) {
+            let prev_range = field_range(prev)?;
+            if range.start < prev_range.end && prev_range.start < range.end {
+                return Err(syn::Error::new(
+                    field.ident.span(),
+                    format!(
+                        "field `{}` overlaps field `{}`; mark one of them with `overlapping => \
+                         true`",
+                        field.ident, prev.ident
+                    ),
+                ));
+            }
+        }
+    }
+    if let Some(group) = groups.first() {
+        if !traits.iter().any(|name| name == "WReg") {
+            return Err(syn::Error::new(
+                group.ident.span(),
+                "`groups` require `WReg` register trait",
+            ));
+        }
+    }
+    for group in groups {
+        for ident in &group.fields {
+            match fields.iter().find(|field| field.ident == *ident) {
+                Some(field) if field.traits.iter().any(|name| name == "WWRegField") => {}
+                Some(_) => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("field `{ident}` of group `{}` is not writable", group.ident),
+                    ));
+                }
+                None => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("unknown field `{ident}` in group `{}`", group.ident),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn field_range(field: &Field) -> Result<Range<u16>> {
+    let offset = field.offset.base10_parse::<u16>()?;
+    Ok(offset..offset + field.width.base10_parse::<u16>()?)
+}
+
 fn parse_traits(input: ParseStream<'_>) -> Result<Vec<Ident>> {
     let mut traits = Vec::new();
     let input2;
@@ -457,7 +1885,18 @@ fn parse_traits(input: ParseStream<'_>) -> Result<Vec<Ident>> {
 }
 
 pub fn proc_macro(input: TokenStream) -> TokenStream {
-    let Input { mut variants } = parse_macro_input!(input);
+    let Input { mut variants, default } = parse_macro_input!(input);
+    let default_tokens = default.map(|base| {
+        let Variant { attrs, vis, .. } = &base;
+        let reg_full = base.reg_full();
+        let view_full = variants[0].reg_full();
+        quote! {
+            #(#attrs)*
+            #vis mod #reg_full {
+                pub use super::#view_full::*;
+            }
+        }
+    });
     let reg_tokens = variants.iter_mut().map(Variant::generate).collect::<Vec<_>>();
     let mut variant_tokens = Vec::new();
     for (i, reg_src) in variants.iter().enumerate() {
@@ -493,6 +1932,7 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
     quote! {
         #(#reg_tokens)*
         #(#variant_tokens)*
+        #default_tokens
     }
     .into()
 }
@@ -0,0 +1,475 @@
+use heck::ToUpperCamelCase;
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use roxmltree::{Document, Node};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::{env, fs};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{braced, parse_macro_input, Ident, LitInt, LitStr, Token};
+
+const WRITE_ONCE_DOC: &str = "Write-once: only the first write after reset takes effect.";
+
+struct Input {
+    path: LitStr,
+    peripherals: Option<Vec<Ident>>,
+}
+
+#[derive(Clone, Copy)]
+struct Props {
+    size: u64,
+    reset: u64,
+    access: Access,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+    /// Only the first write after reset takes effect. Mapped to read-write
+    /// with a doc marker, because such registers are usually readable.
+    WriteOnce,
+}
+
+struct Register {
+    doc: Option<String>,
+    name: String,
+    address: u64,
+    props: Props,
+    fields: Vec<Field>,
+}
+
+#[derive(Clone)]
+struct Field {
+    doc: Option<String>,
+    name: String,
+    offset: u64,
+    width: u64,
+    access: Access,
+    clear: Option<bool>,
+    values: Vec<Value>,
+}
+
+#[derive(Clone)]
+struct Value {
+    doc: Option<String>,
+    name: String,
+    bits: u64,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let mut path = None;
+        let mut peripherals = None;
+        while !input.is_empty() {
+            let ident = input.call(Ident::parse_any)?;
+            input.parse::<Token![=>]>()?;
+            if ident == "path" {
+                if path.is_none() {
+                    path = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `path` specifications"));
+                }
+            } else if ident == "peripherals" {
+                if peripherals.is_none() {
+                    let mut idents = Vec::new();
+                    let input2;
+                    braced!(input2 in input);
+                    while !input2.is_empty() {
+                        idents.push(input2.parse()?);
+                    }
+                    peripherals = Some(idents);
+                } else {
+                    return Err(input.error("multiple `peripherals` specifications"));
+                }
+            } else {
+                return Err(input.error(format!("unknown key: `{ident}`")));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![;]>()?;
+            }
+        }
+        let path = path.ok_or_else(|| input.error("missing `path` specification"))?;
+        Ok(Self { path, peripherals })
+    }
+}
+
+impl Props {
+    fn inherit(self, node: Node<'_, '_>) -> std::result::Result<Self, String> {
+        let mut props = self;
+        if let Some(size) = child_text(node, "size") {
+            props.size = parse_int(size).ok_or_else(|| format!("invalid `size`: `{size}`"))?;
+        }
+        if let Some(reset) = child_text(node, "resetValue") {
+            props.reset =
+                parse_int(reset).ok_or_else(|| format!("invalid `resetValue`: `{reset}`"))?;
+        }
+        if let Some(access) = child_text(node, "access") {
+            props.access = Access::parse(access);
+        }
+        Ok(props)
+    }
+}
+
+impl Access {
+    fn parse(text: &str) -> Self {
+        match text {
+            "read-only" => Self::ReadOnly,
+            "write-only" => Self::WriteOnly,
+            "writeOnce" | "read-writeOnce" => Self::WriteOnce,
+            _ => Self::ReadWrite,
+        }
+    }
+}
+
+pub fn proc_macro(input: TokenStream) -> TokenStream {
+    let Input { path, peripherals } = parse_macro_input!(input);
+    let root = env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap_or_default();
+    let file = root.join(path.value());
+    let text = match fs::read_to_string(&file) {
+        Ok(text) => text,
+        Err(err) => {
+            let message = format!("failed to read `{}`: {err}", file.display());
+            return syn::Error::new(path.span(), message).to_compile_error().into();
+        }
+    };
+    let registers = match Document::parse(&text)
+        .map_err(|err| format!("failed to parse SVD: {err}"))
+        .and_then(|document| collect_peripherals(&document, peripherals.as_deref()))
+    {
+        Ok(registers) => registers,
+        Err(message) => return syn::Error::new(path.span(), message).to_compile_error().into(),
+    };
+    let file = LitStr::new(&file.to_string_lossy(), path.span());
+    let registers = registers.iter().map(|(block, register)| expand_register(block, register));
+    quote! {
+        const _: &[u8] = ::core::include_bytes!(#file);
+
+        #(#registers)*
+    }
+    .into()
+}
+
+fn collect_peripherals(
+    document: &Document<'_>,
+    filter: Option<&[Ident]>,
+) -> std::result::Result<Vec<(Ident, Register)>, String> {
+    let device = document.root_element();
+    let props = Props { size: 32, reset: 0, access: Access::ReadWrite }.inherit(device)?;
+    let peripherals = device
+        .children()
+        .filter(|node| node.has_tag_name("peripherals"))
+        .flat_map(|node| node.children())
+        .filter(|node| node.has_tag_name("peripheral"))
+        .collect::<Vec<_>>();
+    if let Some(filter) = filter {
+        for ident in filter {
+            let name = ident.to_string();
+            if !peripherals.iter().any(|node| child_text(*node, "name") == Some(name.as_str())) {
+                return Err(format!("unknown peripheral `{ident}`"));
+            }
+        }
+    }
+    let mut registers = Vec::new();
+    for peripheral in &peripherals {
+        let name = child_text(*peripheral, "name").ok_or("peripheral without `name`")?;
+        if filter.map_or(false, |filter| !filter.iter().any(|ident| ident == name)) {
+            continue;
+        }
+        let base = child_text(*peripheral, "baseAddress")
+            .and_then(parse_int)
+            .ok_or_else(|| format!("peripheral `{name}` has no valid `baseAddress`"))?;
+        let mut source = *peripheral;
+        if let Some(derived) = peripheral.attribute("derivedFrom") {
+            if !peripheral.children().any(|node| node.has_tag_name("registers")) {
+                source = *peripherals
+                    .iter()
+                    .find(|node| child_text(**node, "name") == Some(derived))
+                    .ok_or_else(|| format!("`{name}` is derived from unknown `{derived}`"))?;
+            }
+        }
+        let props = props.inherit(source)?.inherit(*peripheral)?;
+        let mut block_registers = Vec::new();
+        for node in source.children().filter(|node| node.has_tag_name("registers")) {
+            collect_registers(node, base, "", props, &mut block_registers)?;
+        }
+        let block = sanitize(name);
+        registers.extend(block_registers.into_iter().map(|register| (block.clone(), register)));
+    }
+    Ok(registers)
+}
+
+fn collect_registers(
+    parent: Node<'_, '_>,
+    base: u64,
+    prefix: &str,
+    props: Props,
+    registers: &mut Vec<Register>,
+) -> std::result::Result<(), String> {
+    for node in parent.children().filter(Node::is_element) {
+        let is_cluster = node.has_tag_name("cluster");
+        if !is_cluster && !node.has_tag_name("register") {
+            continue;
+        }
+        let name = child_text(node, "name").ok_or("register without `name`")?;
+        let offset = child_text(node, "addressOffset")
+            .and_then(parse_int)
+            .ok_or_else(|| format!("register `{name}` has no valid `addressOffset`"))?;
+        let props = props.inherit(node)?;
+        for (name, increment) in expand_dim(node, name)? {
+            let name = format!("{prefix}{name}");
+            let address = base + offset + increment;
+            if is_cluster {
+                collect_registers(node, address, &format!("{name}_"), props, registers)?;
+                continue;
+            }
+            let mut fields = Vec::new();
+            for field in node
+                .children()
+                .filter(|node| node.has_tag_name("fields"))
+                .flat_map(|node| node.children())
+                .filter(|node| node.has_tag_name("field"))
+            {
+                fields.extend(parse_field(field, props.access)?);
+            }
+            registers.push(Register { doc: description(node), name, address, props, fields });
+        }
+    }
+    Ok(())
+}
+
+fn parse_field(node: Node<'_, '_>, access: Access) -> std::result::Result<Vec<Field>, String> {
+    let name = child_text(node, "name").ok_or("field without `name`")?;
+    let invalid = || format!("field `{name}` has no valid bit range");
+    let (offset, width) = if let Some(offset) = child_text(node, "bitOffset") {
+        let width = child_text(node, "bitWidth").unwrap_or("1");
+        (parse_int(offset).ok_or_else(invalid)?, parse_int(width).ok_or_else(invalid)?)
+    } else if let Some(lsb) = child_text(node, "lsb") {
+        let lsb = parse_int(lsb).ok_or_else(invalid)?;
+        let msb = child_text(node, "msb").and_then(parse_int).ok_or_else(invalid)?;
+        (lsb, msb.checked_sub(lsb).ok_or_else(invalid)? + 1)
+    } else {
+        let range = child_text(node, "bitRange").ok_or_else(invalid)?;
+        let (msb, lsb) = range
+            .strip_prefix('[')
+            .and_then(|range| range.strip_suffix(']'))
+            .and_then(|range| range.split_once(':'))
+            .ok_or_else(invalid)?;
+        let (msb, lsb) = (parse_int(msb).ok_or_else(invalid)?, parse_int(lsb).ok_or_else(invalid)?);
+        (lsb, msb.checked_sub(lsb).ok_or_else(invalid)? + 1)
+    };
+    let access = child_text(node, "access").map_or(access, Access::parse);
+    let clear = match child_text(node, "modifiedWriteValues") {
+        Some("oneToClear") if width == 1 => Some(true),
+        Some("zeroToClear") if width == 1 => Some(false),
+        _ => None,
+    };
+    let values = parse_values(node, name)?;
+    let doc = description(node);
+    Ok(expand_dim(node, name)?
+        .into_iter()
+        .map(|(name, increment)| Field {
+            doc: doc.clone(),
+            name,
+            offset: offset + increment,
+            width,
+            access,
+            clear,
+            values: values.clone(),
+        })
+        .collect())
+}
+
+fn parse_values(node: Node<'_, '_>, field: &str) -> std::result::Result<Vec<Value>, String> {
+    let mut names = HashSet::new();
+    let mut bits_seen = HashSet::new();
+    let Some(values) = node.children().find(|node| {
+        node.has_tag_name("enumeratedValues") && child_text(*node, "usage") != Some("write")
+    }) else {
+        return Ok(Vec::new());
+    };
+    let mut result = Vec::new();
+    for node in values.children().filter(|node| node.has_tag_name("enumeratedValue")) {
+        let (Some(name), Some(bits)) =
+            (child_text(node, "name"), child_text(node, "value").and_then(parse_int))
+        else {
+            continue;
+        };
+        let name = name.to_upper_camel_case();
+        let name = if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            name
+        } else {
+            format!("V{name}")
+        };
+        if !names.insert(name.clone()) {
+            return Err(format!("field `{field}` has duplicate enumerated value name `{name}`"));
+        }
+        if !bits_seen.insert(bits) {
+            return Err(format!("field `{field}` has duplicate enumerated value `{bits}`"));
+        }
+        result.push(Value { doc: description(node), name, bits });
+    }
+    Ok(result)
+}
+
+fn expand_dim(node: Node<'_, '_>, name: &str) -> std::result::Result<Vec<(String, u64)>, String> {
+    let Some(dim) = child_text(node, "dim") else {
+        return Ok(vec![(name.to_string(), 0)]);
+    };
+    let dim = parse_int(dim).ok_or_else(|| format!("`{name}` has invalid `dim`"))?;
+    let increment = child_text(node, "dimIncrement")
+        .and_then(parse_int)
+        .ok_or_else(|| format!("`{name}` has no valid `dimIncrement`"))?;
+    let indices = match child_text(node, "dimIndex") {
+        Some(index) => match index.split_once('-') {
+            Some((start, end)) if !index.contains(',') => {
+                match (parse_int(start), parse_int(end)) {
+                    (Some(start), Some(end)) => (start..=end).map(|i| i.to_string()).collect(),
+                    _ => return Err(format!("`{name}` has invalid `dimIndex`")),
+                }
+            }
+            _ => index.split(',').map(|index| index.trim().to_string()).collect(),
+        },
+        None => (0..dim).map(|i| i.to_string()).collect::<Vec<_>>(),
+    };
+    if indices.len() as u64 != dim {
+        return Err(format!(
+            "`{name}` has {} `dimIndex` entries, but `dim` is {dim}",
+            indices.len()
+        ));
+    }
+    Ok(indices
+        .iter()
+        .zip(0..)
+        .map(|(index, i)| {
+            (name.replace("[%s]", index).replace("%s", index), i * increment)
+        })
+        .collect())
+}
+
+fn expand_register(block: &Ident, register: &Register) -> TokenStream2 {
+    let Register { doc, name, address, props, fields } = register;
+    let ident = sanitize(name);
+    let doc = doc.iter();
+    let address = hex(*address);
+    let size = LitInt::new(&props.size.to_string(), Span::call_site());
+    let reset = hex(props.reset);
+    let traits = match props.access {
+        Access::ReadOnly => quote!(RReg RoReg),
+        Access::WriteOnly => quote!(WReg WoReg),
+        Access::ReadWrite | Access::WriteOnce => quote!(RReg WReg),
+    };
+    let write_once = (props.access == Access::WriteOnce).then_some(WRITE_ONCE_DOC);
+    // SVD files often describe alternative interpretations of the same bits
+    // with overlapping fields, which `reg!` rejects unless they are marked.
+    let fields = fields.iter().enumerate().filter_map(|(i, field)| {
+        let overlapping = fields.iter().enumerate().any(|(j, other)| {
+            i != j
+                && field.offset < other.offset + other.width
+                && other.offset < field.offset + field.width
+        });
+        expand_field(field, props.access, overlapping)
+    });
+    quote! {
+        ::drone_core::reg! {
+            #(#[doc = #doc])*
+            #(#[doc = ""] #[doc = #write_once])*
+            pub #block #ident => {
+                address => #address;
+                size => #size;
+                reset => #reset;
+                traits => { #traits };
+                fields => { #(#fields)* };
+            };
+        }
+    }
+}
+
+fn expand_field(field: &Field, access: Access, overlapping: bool) -> Option<TokenStream2> {
+    let Field { doc, name, offset, width, access: field_access, clear, values } = field;
+    let readable = access != Access::WriteOnly && *field_access != Access::WriteOnly;
+    let writable = access != Access::ReadOnly && *field_access != Access::ReadOnly;
+    let mut traits = Vec::new();
+    if readable {
+        traits.push(format_ident!("RRRegField"));
+    }
+    if writable {
+        match clear {
+            Some(true) => traits.push(format_ident!("W1CRegField")),
+            Some(false) => traits.push(format_ident!("W0CRegField")),
+            None => traits.push(format_ident!("WWRegField")),
+        }
+    }
+    match (readable, writable) {
+        (false, false) => return None,
+        (true, false) => traits.push(format_ident!("RoRRegField")),
+        (false, true) if clear.is_none() => traits.push(format_ident!("WoWRegField")),
+        _ => {}
+    }
+    let write_once = (*field_access == Access::WriteOnce && access != Access::WriteOnce)
+        .then_some(WRITE_ONCE_DOC);
+    let ident = sanitize(name);
+    let doc = doc.iter();
+    let offset = LitInt::new(&offset.to_string(), Span::call_site());
+    let width = LitInt::new(&width.to_string(), Span::call_site());
+    let values = (!values.is_empty()).then(|| {
+        let values = values.iter().map(|Value { doc, name, bits }| {
+            let doc = doc.iter();
+            let ident = format_ident!("{}", name);
+            let bits = LitInt::new(&bits.to_string(), Span::call_site());
+            quote!(#(#[doc = #doc])* #ident => #bits)
+        });
+        quote!(values => { #(#values),* };)
+    });
+    let overlapping = overlapping.then(|| quote!(overlapping => true;));
+    Some(quote! {
+        #(#[doc = #doc])*
+        #(#[doc = ""] #[doc = #write_once])*
+        #ident => {
+            offset => #offset;
+            width => #width;
+            traits => { #(#traits)* };
+            #overlapping
+            #values
+        };
+    })
+}
+
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    node.children().find(|node| node.has_tag_name(tag)).and_then(|node| node.text()).map(str::trim)
+}
+
+fn description(node: Node<'_, '_>) -> Option<String> {
+    child_text(node, "description")
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty())
+}
+
+fn parse_int(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if let Some(bin) = text.strip_prefix('#').or_else(|| text.strip_prefix("0b")) {
+        u64::from_str_radix(bin, 2).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+fn sanitize(name: &str) -> Ident {
+    let mut name = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    format_ident!("{}", name)
+}
+
+fn hex(value: u64) -> LitInt {
+    LitInt::new(&format!("{value:#X}"), Span::call_site())
+}
@@ -178,8 +178,12 @@ fn make_macro(
             let reg_long = format_ident!("{}_{}", block_snk, reg_snk);
             let reg_short = format_ident!("{}", unkeywordize(&reg_snk));
             if !block_skip {
+                let reg_cfg_attrs = reg_attrs.iter().filter(|attr| is_cfg_attr(attr));
+                let reg_cfg_attrs2 = reg_cfg_attrs.clone();
                 block_tokens.push(quote! {
+                    #(#reg_cfg_attrs)*
                     pub use #root_path::#reg_long as #reg_short;
+                    #(#reg_cfg_attrs2)*
                     pub use #root_path::#reg_long::Reg as #reg_cml;
                 });
             }
@@ -65,6 +65,7 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
     let Input { attrs, vis, ident, defs, undefs } = &parse_macro_input!(input);
     let mut def_tokens = BTreeMap::new();
     let mut ctor_tokens = BTreeMap::new();
+    let mut c_header_tokens = BTreeMap::new();
     for Def { attrs, ident, path } in defs {
         let string = ident.to_string();
         def_tokens.insert(string.clone(), quote! {
@@ -74,24 +75,58 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
         });
         ctor_tokens.insert(string.clone(), quote! {
             #(#attrs)*
-            #ident: ::drone_core::token::Token::take(),
+            #ident: unsafe { <#path<::drone_core::reg::tag::Srt>>::take_const() },
+        });
+        let name = string.to_uppercase();
+        c_header_tokens.insert(string.clone(), quote! {
+            #(#attrs)*
+            <#path<::drone_core::reg::tag::Srt> as ::drone_core::reg::RegCDefines>::write_c_defines(
+                w,
+                ::core::format_args!(#name),
+            )?;
         });
     }
     for Undef { ident } in undefs {
         let ident = ident.to_string();
         def_tokens.remove(&ident);
         ctor_tokens.remove(&ident);
+        c_header_tokens.remove(&ident);
     }
     let def_tokens = def_tokens.values();
     let ctor_tokens = ctor_tokens.values();
+    let c_header_tokens = c_header_tokens.values();
     quote! {
         #(#attrs)* #vis struct #ident {
             #(#def_tokens)*
         }
+        impl #ident {
+            /// Creates the register tokens in a `const` context.
+            ///
+            /// # Safety
+            ///
+            /// Same as for [`::drone_core::token::Token::take`].
+            #[inline]
+            pub const unsafe fn take_const() -> Self {
+                Self { #(#ctor_tokens)* }
+            }
+
+            /// Writes a C header with `#define`s of the addresses, the reset
+            /// values, and the field masks of the indexed registers.
+            ///
+            /// See [`::drone_core::reg::write_c_defines`] for the format.
+            pub fn write_c_header(w: &mut dyn ::core::fmt::Write) -> ::core::fmt::Result {
+                ::core::fmt::Write::write_str(
+                    w,
+                    "/* Register definitions generated by drone-core. */\n",
+                )?;
+                #(#c_header_tokens)*
+                ::core::result::Result::Ok(())
+            }
+        }
         unsafe impl ::drone_core::token::Token for #ident {
             #[inline]
             unsafe fn take() -> Self {
-                Self { #(#ctor_tokens)* }
+                unsafe { Self::take_const() }
             }
         }
     }
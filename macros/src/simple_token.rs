@@ -33,14 +33,26 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                 __priv: (),
             }
 
-            unsafe impl ::drone_core::token::Token for #ident {
+            impl #ident {
+                /// Creates the token in a `const` context.
+                ///
+                /// # Safety
+                ///
+                /// Same as for [`::drone_core::token::Token::take`].
                 #[inline]
-                unsafe fn take() -> Self {
+                pub const unsafe fn take_const() -> Self {
                     Self {
                         __priv: (),
                     }
                 }
             }
+
+            unsafe impl ::drone_core::token::Token for #ident {
+                #[inline]
+                unsafe fn take() -> Self {
+                    unsafe { Self::take_const() }
+                }
+            }
         }
 
         #vis use #wrapper::#ident;
@@ -59,10 +59,22 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                 #(#attrs)*
                 pub struct #struct_ident(());
 
+                impl #struct_ident {
+                    /// Creates the token in a `const` context.
+                    ///
+                    /// # Safety
+                    ///
+                    /// Same as for [`::drone_core::token::Token::take`].
+                    #[inline]
+                    pub const unsafe fn take_const() -> Self {
+                        #struct_ident(())
+                    }
+                }
+
                 unsafe impl ::drone_core::token::Token for #struct_ident {
                     #[inline]
                     unsafe fn take() -> Self {
-                        #struct_ident(())
+                        unsafe { Self::take_const() }
                     }
                 }
             }
@@ -88,7 +100,7 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
             pub #field_ident: #struct_ident,
         });
         ctor_tokens.push(quote! {
-            #field_ident: ::drone_core::token::Token::take(),
+            #field_ident: unsafe { #struct_ident::take_const() },
         });
     }
     quote! {
@@ -101,15 +113,27 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                 __priv: (),
             }
 
-            unsafe impl ::drone_core::token::Token for #ident {
+            impl #ident {
+                /// Creates the token in a `const` context.
+                ///
+                /// # Safety
+                ///
+                /// Same as for [`::drone_core::token::Token::take`].
                 #[inline]
-                unsafe fn take() -> Self {
+                pub const unsafe fn take_const() -> Self {
                     Self {
                         #(#ctor_tokens)*
                         __priv: (),
                     }
                 }
             }
+
+            unsafe impl ::drone_core::token::Token for #ident {
+                #[inline]
+                unsafe fn take() -> Self {
+                    unsafe { Self::take_const() }
+                }
+            }
         }
 
         #vis use #wrapper::#ident;
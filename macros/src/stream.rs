@@ -4,13 +4,14 @@ use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 use syn::parse::{Parse, ParseStream, Result};
-use syn::{parse_macro_input, Attribute, Ident, LitBool, LitStr, Token, Visibility};
+use syn::{braced, parse_macro_input, Attribute, Ident, LitBool, LitInt, LitStr, Token, Visibility};
 
 struct Input {
     layout: Ident,
     metadata: Metadata,
     instance: Instance,
     global: bool,
+    streams: Vec<Dedicated>,
 }
 
 struct Metadata {
@@ -25,12 +26,18 @@ struct Instance {
     ident: Ident,
 }
 
+struct Dedicated {
+    stream: u8,
+    size: u32,
+}
+
 impl Parse for Input {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let mut layout = None;
         let mut metadata = None;
         let mut instance = None;
         let mut global = None;
+        let mut streams = None;
         while !input.is_empty() {
             let attrs = input.call(Attribute::parse_outer)?;
             let ident = input.parse::<Ident>()?;
@@ -59,6 +66,12 @@ impl Parse for Input {
                 } else {
                     return Err(input.error("multiple `global` specifications"));
                 }
+            } else if attrs.is_empty() && ident == "streams" {
+                if streams.is_none() {
+                    streams = Some(parse_streams(input)?);
+                } else {
+                    return Err(input.error("multiple `streams` specifications"));
+                }
             } else {
                 return Err(input.error(format!("unknown key: `{ident}`")));
             }
@@ -66,15 +79,45 @@ impl Parse for Input {
                 input.parse::<Token![;]>()?;
             }
         }
+        let global = global.unwrap_or(false);
+        let streams = streams.unwrap_or_default();
+        if !global && !streams.is_empty() {
+            return Err(input.error("`streams` requires `global => true`"));
+        }
         Ok(Self {
             layout: layout.ok_or_else(|| input.error("missing `layout` specification"))?,
             metadata: metadata.ok_or_else(|| input.error("missing `metadata` specification"))?,
             instance: instance.ok_or_else(|| input.error("missing `instance` specification"))?,
-            global: global.unwrap_or(false),
+            global,
+            streams,
         })
     }
 }
 
+fn parse_streams(input: ParseStream<'_>) -> Result<Vec<Dedicated>> {
+    let content;
+    braced!(content in input);
+    let mut streams = Vec::<Dedicated>::new();
+    while !content.is_empty() {
+        let stream = content.parse::<LitInt>()?;
+        content.parse::<Token![=>]>()?;
+        let size = content.parse::<LitInt>()?;
+        let stream = stream.base10_parse()?;
+        if streams.iter().any(|dedicated| dedicated.stream == stream) {
+            return Err(content.error(format!("multiple stream {stream} specifications")));
+        }
+        let size = size.base10_parse()?;
+        if size == 0 {
+            return Err(content.error(format!("buffer size of stream {stream} must be non-zero")));
+        }
+        streams.push(Dedicated { stream, size });
+        if !content.is_empty() {
+            content.parse::<Token![;]>()?;
+        }
+    }
+    Ok(streams)
+}
+
 impl Metadata {
     fn parse(input: ParseStream<'_>, attrs: Vec<Attribute>) -> Result<Self> {
         let vis = input.parse()?;
@@ -93,7 +136,8 @@ impl Instance {
 
 #[allow(clippy::too_many_lines)]
 pub fn proc_macro(input: TokenStream) -> TokenStream {
-    let Input { layout: stream_layout, metadata, instance, global } = parse_macro_input!(input);
+    let Input { layout: stream_layout, metadata, instance, global, streams } =
+        parse_macro_input!(input);
     let Metadata { attrs: metadata_attrs, vis: metadata_vis, ident: metadata_ident } = &metadata;
     let Instance { attrs: instance_attrs, vis: instance_vis, ident: instance_ident } = &instance;
     let layout = match Layout::read_from_cargo() {
@@ -115,7 +159,18 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
     let init_ident =
         if init_primary { format_ident!("init_primary") } else { format_ident!("init") };
     let section = LitStr::new(&format!(".stream_{stream_layout}_rt"), Span::call_site());
-    let global = global.then(|| def_global(&instance));
+    let global = global.then(|| def_global(&instance, &streams));
+    let dedicated_tokens = def_dedicated(&stream_layout, &instance, &streams);
+    let dedicated_init = streams.iter().map(|Dedicated { stream, size }| {
+        let dedicated_ident = dedicated_ident(instance_ident, *stream);
+        quote! {
+            ::drone_core::stream::init(
+                ::core::ptr::addr_of_mut!((*#dedicated_ident.get()).runtime),
+                #size,
+                false,
+            );
+        }
+    });
 
     quote! {
         #(#metadata_attrs)*
@@ -151,21 +206,90 @@ pub fn proc_macro(input: TokenStream) -> TokenStream {
                         #buffer_size,
                         #init_primary,
                     );
+                    #(#dedicated_init)*
                 }
             }
         }
 
+        #dedicated_tokens
         #global
     }
     .into()
 }
 
-fn def_global(instance: &Instance) -> TokenStream2 {
+fn dedicated_ident(instance_ident: &Ident, stream: u8) -> Ident {
+    format_ident!("{}_STREAM_{}", instance_ident, stream)
+}
+
+fn def_dedicated(
+    stream_layout: &Ident,
+    instance: &Instance,
+    streams: &[Dedicated],
+) -> TokenStream2 {
+    let Instance { vis: instance_vis, ident: instance_ident, .. } = instance;
+    let mut tokens = Vec::new();
+    for Dedicated { stream, size } in streams {
+        let dedicated_ident = dedicated_ident(instance_ident, *stream);
+        let buffer_size = *size as usize;
+        let section =
+            LitStr::new(&format!(".stream_{stream_layout}_{stream}_rt"), Span::call_site());
+        let message = LitStr::new(
+            &format!("stream {stream} is out of range of `STREAM_COUNT`"),
+            Span::call_site(),
+        );
+        tokens.push(quote! {
+            const _: () = ::core::assert!(#stream < ::drone_core::stream::STREAM_COUNT, #message);
+
+            #[doc(hidden)]
+            #[link_section = #section]
+            #instance_vis static #dedicated_ident: ::core::cell::SyncUnsafeCell<
+                ::drone_core::stream::DedicatedRuntime<#buffer_size>,
+            > = ::core::cell::SyncUnsafeCell::new(
+                ::drone_core::stream::DedicatedRuntime::zeroed(),
+            );
+        });
+    }
+    quote!(#(#tokens)*)
+}
+
+fn def_global(instance: &Instance, streams: &[Dedicated]) -> TokenStream2 {
     let Instance { ident: instance_ident, .. } = instance;
+    let arms = streams.iter().map(|Dedicated { stream, .. }| {
+        let dedicated_ident = dedicated_ident(instance_ident, *stream);
+        quote! {
+            #stream => unsafe { ::core::ptr::addr_of_mut!((*#dedicated_ident.get()).runtime) },
+        }
+    });
+    let descriptors = streams.iter().map(|Dedicated { stream, size }| {
+        let dedicated_ident = dedicated_ident(instance_ident, *stream);
+        quote! {
+            ::drone_core::stream::DedicatedDescriptor {
+                stream: #stream,
+                buffer_size: #size,
+                runtime: unsafe { ::core::ptr::addr_of_mut!((*#dedicated_ident.get()).runtime) },
+            }
+        }
+    });
+    let count = streams.len();
     quote! {
+        #[used]
+        #[no_mangle]
+        static DRONE_STREAM_DEDICATED: ::drone_core::stream::DedicatedTable<#count> =
+            ::drone_core::stream::DedicatedTable::new([#(#descriptors),*]);
+
         #[no_mangle]
         extern "C" fn drone_stream_runtime() -> *mut ::drone_core::_rt::drone_stream::Runtime {
             unsafe { ::core::ptr::addr_of_mut!((*#instance_ident.get()).runtime) }
         }
+
+        #[no_mangle]
+        extern "C" fn drone_stream_dedicated_runtime(
+            stream: u8,
+        ) -> *mut ::drone_core::_rt::drone_stream::Runtime {
+            match stream {
+                #(#arms)*
+                _ => ::core::ptr::null_mut(),
+            }
+        }
     }
 }
@@ -0,0 +1,118 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{braced, parse_macro_input, Attribute, Ident, LitInt, Path, Token, Visibility};
+
+struct Input {
+    supervisor: Supervisor,
+    services: Vec<Path>,
+}
+
+struct Supervisor {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let mut supervisor = None;
+        let mut services = None;
+        while !input.is_empty() {
+            let attrs = input.call(Attribute::parse_outer)?;
+            let ident = input.parse::<Ident>()?;
+            input.parse::<Token![=>]>()?;
+            if ident == "supervisor" {
+                if supervisor.is_none() {
+                    supervisor = Some(Supervisor::parse(input, attrs)?);
+                } else {
+                    return Err(input.error("multiple `supervisor` specifications"));
+                }
+            } else if attrs.is_empty() && ident == "services" {
+                if services.is_none() {
+                    services = Some(parse_services(input)?);
+                } else {
+                    return Err(input.error("multiple `services` specifications"));
+                }
+            } else {
+                return Err(input.error(format!("unknown key: `{ident}`")));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![;]>()?;
+            }
+        }
+        Ok(Self {
+            supervisor: supervisor
+                .ok_or_else(|| input.error("missing `supervisor` specification"))?,
+            services: services.ok_or_else(|| input.error("missing `services` specification"))?,
+        })
+    }
+}
+
+impl Supervisor {
+    fn parse(input: ParseStream<'_>, attrs: Vec<Attribute>) -> Result<Self> {
+        let vis = input.parse()?;
+        let ident = input.parse()?;
+        Ok(Self { attrs, vis, ident })
+    }
+}
+
+fn parse_services(input: ParseStream<'_>) -> Result<Vec<Path>> {
+    let input2;
+    braced!(input2 in input);
+    let mut services = Vec::new();
+    while !input2.is_empty() {
+        services.push(input2.parse()?);
+        if !input2.is_empty() {
+            input2.parse::<Token![;]>()?;
+        }
+    }
+    if services.len() > usize::from(u8::MAX) + 1 {
+        return Err(input.error("too many services"));
+    }
+    Ok(services)
+}
+
+pub fn proc_macro(input: TokenStream) -> TokenStream {
+    let Input { supervisor: Supervisor { attrs, vis, ident }, services } =
+        parse_macro_input!(input);
+    let numbers =
+        (0..services.len()).map(|number| LitInt::new(&number.to_string(), ident.span()));
+    let numbers = numbers.collect::<Vec<_>>();
+    // A full table of 256 services already covers every `u8` number.
+    let unknown = (services.len() <= usize::from(u8::MAX))
+        .then(|| quote!(_ => ::core::unreachable!("unknown supervisor service number"),));
+    quote! {
+        #(#attrs)*
+        #vis struct #ident;
+
+        impl ::drone_core::sv::Supervisor for #ident {
+            #[inline]
+            unsafe fn handle(number: u8, service: *mut u8) {
+                match number {
+                    #(
+                        #numbers => unsafe {
+                            ::drone_core::sv::SvService::handler(&mut *service.cast::<#services>());
+                        },
+                    )*
+                    #unknown
+                }
+            }
+        }
+
+        #(
+            impl ::drone_core::sv::SvCall<#services> for #ident {
+                #[inline]
+                unsafe fn call(service: &mut #services) {
+                    unsafe { ::drone_core::sv::sv_call(service, #numbers) };
+                }
+            }
+        )*
+
+        #[no_mangle]
+        unsafe extern "C" fn drone_sv_handler(number: u8, service: *mut u8) {
+            unsafe { <#ident as ::drone_core::sv::Supervisor>::handle(number, service) };
+        }
+    }
+    .into()
+}
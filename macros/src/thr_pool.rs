@@ -13,6 +13,8 @@ struct Input {
     index: Index,
     threads: Threads,
     resume: Option<ExprPath>,
+    on_preempt: Option<ExprPath>,
+    trace_stream: Option<LitInt>,
 }
 
 struct Thr {
@@ -60,6 +62,8 @@ impl Parse for Input {
         let mut index = None;
         let mut threads = None;
         let mut resume = None;
+        let mut on_preempt = None;
+        let mut trace_stream = None;
         while !input.is_empty() {
             let attrs = input.call(Attribute::parse_outer)?;
             let ident = input.parse::<Ident>()?;
@@ -94,6 +98,18 @@ impl Parse for Input {
                 } else {
                     return Err(input.error("multiple `resume` specifications"));
                 }
+            } else if attrs.is_empty() && ident == "on_preempt" {
+                if on_preempt.is_none() {
+                    on_preempt = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `on_preempt` specifications"));
+                }
+            } else if attrs.is_empty() && ident == "enable_trace_stream" {
+                if trace_stream.is_none() {
+                    trace_stream = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `enable_trace_stream` specifications"));
+                }
             } else {
                 return Err(input.error(format!("unknown key: `{ident}`")));
             }
@@ -107,6 +123,8 @@ impl Parse for Input {
             index: index.ok_or_else(|| input.error("missing `index` specification"))?,
             threads: threads.ok_or_else(|| input.error("missing `threads` specification"))?,
             resume,
+            on_preempt,
+            trace_stream,
         })
     }
 }
@@ -185,9 +203,17 @@ impl Parse for Threads {
 }
 
 pub fn proc_macro(input: TokenStream) -> TokenStream {
-    let Input { thr, local, index, threads, resume } = parse_macro_input!(input);
+    let Input { thr, local, index, threads, resume, on_preempt, trace_stream } =
+        parse_macro_input!(input);
     let Threads { threads } = threads;
-    let def_thr = def_thr(&thr, &threads, &local, resume.as_ref());
+    let def_thr = def_thr(
+        &thr,
+        &threads,
+        &local,
+        resume.as_ref(),
+        on_preempt.as_ref(),
+        trace_stream.as_ref(),
+    );
     let def_local = def_local(&local);
     let def_index = def_index(&thr, &index, &threads);
     quote! {
@@ -203,6 +229,8 @@ fn def_thr(
     threads: &[Thread],
     local: &Local,
     resume: Option<&ExprPath>,
+    on_preempt: Option<&ExprPath>,
+    trace_stream: Option<&LitInt>,
 ) -> TokenStream2 {
     let Thr { vis: thr_vis, attrs: thr_attrs, ident: thr_ident, fields: thr_fields } = thr;
     let Local { ident: local_ident, .. } = local;
@@ -214,6 +242,19 @@ fn def_thr(
             }
         }
     });
+    let on_preempt = on_preempt.map(|on_preempt| {
+        quote! {
+            #[inline]
+            fn on_preempt() {
+                #on_preempt();
+            }
+        }
+    });
+    let trace_stream = trace_stream.map(|stream| {
+        quote! {
+            const TRACE_STREAM: ::core::option::Option<u8> = ::core::option::Option::Some(#stream);
+        }
+    });
     let count = LitInt::new(&format!("{}_u16", threads.len()), Span::call_site());
     let mut threads_tokens = Vec::new();
     for idx in 0..threads.len() {
@@ -252,6 +293,8 @@ fn def_thr(
 
             const COUNT: u16 = #count;
 
+            #trace_stream
+
             #[inline]
             fn pool() -> *const Self {
                 static THREADS: [#thr_ident; #count as usize] = [#(#threads_tokens),*];
@@ -265,6 +308,13 @@ fn def_thr(
                 &CURRENT
             }
 
+            #[inline]
+            fn depth() -> *const ::drone_core::thr::CurrentState {
+                static DEPTH: ::drone_core::thr::CurrentState =
+                    ::drone_core::thr::CurrentState::new(0);
+                &DEPTH
+            }
+
             #[inline]
             fn fib_chain(&self) -> &::drone_core::fib::Chain {
                 &self.fib_chain
@@ -276,6 +326,8 @@ fn def_thr(
             }
 
             #resume
+
+            #on_preempt
         }
     }
 }
@@ -307,6 +359,7 @@ fn def_local(local: &Local) -> TokenStream2 {
 
 fn def_index(thr: &Thr, index: &Index, threads: &[Thread]) -> TokenStream2 {
     let Index { attrs: index_attrs, vis: index_vis, ident: index_ident } = index;
+    let index_name = index_ident.to_string();
     let mut tokens = Vec::new();
     let mut index_tokens = Vec::new();
     let mut index_ctor_tokens = Vec::new();
@@ -325,10 +378,45 @@ fn def_index(thr: &Thr, index: &Index, threads: &[Thread]) -> TokenStream2 {
         unsafe impl ::drone_core::token::Token for #index_ident {
             #[inline]
             unsafe fn take() -> Self {
+                unsafe { Self::take_const() }
+            }
+        }
+
+        impl #index_ident {
+            /// Creates the thread token set in a `const` context.
+            ///
+            /// # Safety
+            ///
+            /// Same as for `Token::take`.
+            #[inline]
+            pub const unsafe fn take_const() -> Self {
                 Self {
                     #(#index_ctor_tokens),*
                 }
             }
+
+            /// Takes the thread token set, checking the initialization order.
+            ///
+            /// This function only checks the preconditions of `Token::take`,
+            /// the threads are initialized the same way as by `take`.
+            ///
+            /// In debug builds, panics if `drone_core::mem::init` hasn't been
+            /// called yet, or if this function is called more than once.
+            ///
+            /// # Safety
+            ///
+            /// Same as for `Token::take`.
+            #[inline]
+            #[track_caller]
+            pub unsafe fn take_checked() -> Self {
+                #[cfg(debug_assertions)]
+                {
+                    static TAKEN: ::drone_core::thr::TakenState =
+                        ::drone_core::thr::TakenState::new(false);
+                    ::drone_core::thr::check_take(&TAKEN, #index_name);
+                }
+                unsafe { <Self as ::drone_core::token::Token>::take() }
+            }
         }
 
         #(#tokens)*
@@ -353,15 +441,27 @@ fn def_thr_token(
             __priv: (),
         }
 
-        unsafe impl ::drone_core::token::Token for #struct_ident {
+        impl #struct_ident {
+            /// Creates the thread token in a `const` context.
+            ///
+            /// # Safety
+            ///
+            /// Same as for `Token::take`.
             #[inline]
-            unsafe fn take() -> Self {
+            pub const unsafe fn take_const() -> Self {
                 #struct_ident {
                     __priv: (),
                 }
             }
         }
 
+        unsafe impl ::drone_core::token::Token for #struct_ident {
+            #[inline]
+            unsafe fn take() -> Self {
+                unsafe { Self::take_const() }
+            }
+        }
+
         unsafe impl ::drone_core::thr::ThrToken for #struct_ident {
             type Thread = #thr_ident;
 
@@ -375,7 +475,7 @@ fn def_thr_token(
             #vis #field_ident: #struct_ident
         },
         quote! {
-            #field_ident: ::drone_core::token::Token::take()
+            #field_ident: unsafe { #struct_ident::take_const() }
         },
     )
 }
@@ -2,7 +2,7 @@ use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::parse::{Parse, ParseStream, Result};
-use syn::{braced, parse_macro_input, Attribute, ExprPath, Ident, Token, Visibility};
+use syn::{braced, parse_macro_input, Attribute, ExprPath, Ident, LitInt, Token, Visibility};
 
 struct Input {
     thr: Thr,
@@ -10,7 +10,9 @@ struct Input {
     index: Index,
     threads: Threads,
     resume: Option<ExprPath>,
+    on_preempt: Option<ExprPath>,
     set_pending: Option<ExprPath>,
+    critical_priority: Option<LitInt>,
 }
 
 struct Thr {
@@ -44,7 +46,9 @@ impl Parse for Input {
         let mut index = None;
         let mut threads = None;
         let mut resume = None;
+        let mut on_preempt = None;
         let mut set_pending = None;
+        let mut critical_priority = None;
         while !input.is_empty() {
             let attrs = input.call(Attribute::parse_outer)?;
             let ident = input.parse::<Ident>()?;
@@ -79,12 +83,24 @@ impl Parse for Input {
                 } else {
                     return Err(input.error("multiple `resume` specifications"));
                 }
+            } else if attrs.is_empty() && ident == "on_preempt" {
+                if on_preempt.is_none() {
+                    on_preempt = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `on_preempt` specifications"));
+                }
             } else if attrs.is_empty() && ident == "set_pending" {
                 if set_pending.is_none() {
                     set_pending = Some(input.parse()?);
                 } else {
                     return Err(input.error("multiple `set_pending` specifications"));
                 }
+            } else if attrs.is_empty() && ident == "critical_priority" {
+                if critical_priority.is_none() {
+                    critical_priority = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `critical_priority` specifications"));
+                }
             } else {
                 return Err(input.error(format!("unknown key: `{ident}`")));
             }
@@ -98,7 +114,9 @@ impl Parse for Input {
             index: index.ok_or_else(|| input.error("missing `index` specification"))?,
             threads: threads.ok_or_else(|| input.error("missing `threads` specification"))?,
             resume,
+            on_preempt,
             set_pending,
+            critical_priority,
         })
     }
 }
@@ -143,9 +161,10 @@ impl Parse for Threads {
 }
 
 pub fn proc_macro(input: TokenStream) -> TokenStream {
-    let Input { thr, local, index, threads, resume, set_pending } = parse_macro_input!(input);
-    let def_pool = def_pool(&thr, &local, &index, &threads, resume.as_ref());
-    let def_soft = def_soft(&thr, set_pending.as_ref());
+    let Input { thr, local, index, threads, resume, on_preempt, set_pending, critical_priority } =
+        parse_macro_input!(input);
+    let def_pool = def_pool(&thr, &local, &index, &threads, resume.as_ref(), on_preempt.as_ref());
+    let def_soft = def_soft(&thr, set_pending.as_ref(), critical_priority.as_ref());
 
     quote! {
         #def_pool
@@ -160,6 +179,7 @@ fn def_pool(
     index: &Index,
     threads: &Threads,
     resume: Option<&ExprPath>,
+    on_preempt: Option<&ExprPath>,
 ) -> TokenStream2 {
     let Thr { attrs: thr_attrs, vis: thr_vis, ident: thr_ident, tokens: thr_tokens } = thr;
     let Local { attrs: local_attrs, vis: local_vis, ident: local_ident, tokens: local_tokens } =
@@ -167,6 +187,7 @@ fn def_pool(
     let Index { attrs: index_attrs, vis: index_vis, ident: index_ident } = index;
     let Threads { tokens: threads_tokens } = threads;
     let resume = resume.into_iter();
+    let on_preempt = on_preempt.into_iter();
 
     quote! {
         ::drone_core::thr::pool! {
@@ -190,11 +211,16 @@ fn def_pool(
             };
 
             #(resume => #resume;)*
+            #(on_preempt => #on_preempt;)*
         }
     }
 }
 
-fn def_soft(thr: &Thr, set_pending: Option<&ExprPath>) -> TokenStream2 {
+fn def_soft(
+    thr: &Thr,
+    set_pending: Option<&ExprPath>,
+    critical_priority: Option<&LitInt>,
+) -> TokenStream2 {
     let Thr { ident: thr_ident, .. } = thr;
     let set_pending = set_pending.map(|set_pending| {
         quote! {
@@ -204,9 +230,22 @@ fn def_soft(thr: &Thr, set_pending: Option<&ExprPath>) -> TokenStream2 {
             }
         }
     });
+    let critical_priority = critical_priority.map(|priority| {
+        quote! {
+            const CRITICAL_PRIORITY: ::core::option::Option<u8> =
+                ::core::option::Option::Some(#priority);
+
+            #[inline]
+            fn critical_section<R, F: ::core::ops::FnOnce() -> R>(f: F) -> R {
+                ::drone_core::platform::Interrupts::masked_below(#priority, f)
+            }
+        }
+    });
 
     quote! {
         unsafe impl ::drone_core::thr::SoftThread for #thr_ident {
+            #critical_priority
+
             #[inline]
             fn pending() -> *const ::drone_core::thr::PendingState {
                 #[allow(clippy::declare_interior_mutable_const)]
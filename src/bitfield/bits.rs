@@ -27,6 +27,16 @@ where
 
     /// Returns `true` if all bits of the value are cleared.
     fn is_zero(self) -> bool;
+
+    /// Returns the number of ones in the binary representation of the value.
+    fn count_ones(self) -> u32;
+
+    /// Returns the number of zeros in the binary representation of the value.
+    fn count_zeros(self) -> u32;
+
+    /// Returns the number of trailing zeros in the binary representation of
+    /// the value.
+    fn trailing_zeros(self) -> u32;
 }
 
 macro_rules! bits {
@@ -46,6 +56,21 @@ macro_rules! bits {
             fn is_zero(self) -> bool {
                 self == 0
             }
+
+            #[inline]
+            fn count_ones(self) -> u32 {
+                <$type>::count_ones(self)
+            }
+
+            #[inline]
+            fn count_zeros(self) -> u32 {
+                <$type>::count_zeros(self)
+            }
+
+            #[inline]
+            fn trailing_zeros(self) -> u32 {
+                <$type>::trailing_zeros(self)
+            }
         }
     };
 }
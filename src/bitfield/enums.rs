@@ -0,0 +1,29 @@
+use super::Bits;
+use core::fmt;
+
+/// A fieldless enum, which can be stored in a [`Bitfield`](super::Bitfield)
+/// field.
+///
+/// See [the module level documentation](super) for details.
+pub trait BitfieldEnum: Sized + Copy {
+    /// Returns the variant with the discriminant equal to `bits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidBits`] if no variant matches `bits`.
+    fn from_bits<B: Bits>(bits: B) -> Result<Self, InvalidBits<B>>;
+
+    /// Returns the discriminant of the variant.
+    fn into_bits<B: Bits>(self) -> B;
+}
+
+/// The error returned when the bits of a field don't match any variant of a
+/// [`BitfieldEnum`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidBits<B>(pub B);
+
+impl<B: fmt::Debug> fmt::Display for InvalidBits<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid field bits: {:?}", self.0)
+    }
+}
@@ -0,0 +1,25 @@
+/// Description of a [`Bitfield`](super::Bitfield) field.
+///
+/// See [`Bitfield::FIELDS`](super::Bitfield::FIELDS).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldInfo {
+    /// The field name. Repeated fields are listed as `name[index]`.
+    pub name: &'static str,
+    /// The offset of the first bit of the field.
+    pub offset: usize,
+    /// The bit-width of the field.
+    pub width: usize,
+    /// The access mode of the field.
+    pub mode: FieldMode,
+}
+
+/// Access mode of a [`Bitfield`](super::Bitfield) field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldMode {
+    /// Read-only field.
+    Read,
+    /// Read-write field.
+    ReadWrite,
+    /// Write-only field.
+    Write,
+}
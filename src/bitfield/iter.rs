@@ -0,0 +1,43 @@
+use super::Bits;
+use core::iter::FusedIterator;
+
+/// An iterator over the offsets of the set bits of an integer, in ascending
+/// order.
+///
+/// This struct is created by [`Bitfield::iter_ones`](super::Bitfield::iter_ones)
+/// and [`Bitfield::iter_zeros`](super::Bitfield::iter_zeros) methods.
+#[derive(Clone, Copy, Debug)]
+pub struct Offsets<T: Bits> {
+    bits: T,
+}
+
+impl<T: Bits> Offsets<T> {
+    #[inline]
+    pub(super) fn new(bits: T) -> Self {
+        Self { bits }
+    }
+}
+
+impl<T: Bits> Iterator for Offsets<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.bits.is_zero() {
+            return None;
+        }
+        let offset = self.bits.trailing_zeros();
+        self.bits = self.bits & (self.bits - T::from_usize(1));
+        Some(T::from_usize(offset as usize))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.bits.count_ones() as usize;
+        (count, Some(count))
+    }
+}
+
+impl<T: Bits> ExactSizeIterator for Offsets<T> {}
+
+impl<T: Bits> FusedIterator for Offsets<T> {}
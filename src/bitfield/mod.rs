@@ -9,15 +9,19 @@
 //! #[derive(Clone, Copy, Bitfield)]
 //! #[bitfield(
 //!     // The syntax of the field definitions is the following:
-//!     //     field_name(mode, offset[, width[, doc_string]])
+//!     //     field_name(mode, offset[, width[, count[, stride]]][, type][, doc_string])
 //!     // `width` is default to 1 when omitted.
+//!     // `offset, width` can also be given as an inclusive bit range
+//!     // `msb..=lsb`, as found in datasheets.
 //!     // `mode` is one of `r` (for read-only), `rw` (for read-write),
-//!     //                  `w` (for write-only).
+//!     //                  `w` (for write-only), and optionally ends with `_i`
+//!     //                  for signed multi-bit fields.
 //!     foo(rw, 1, 4, "4-bits field"),
 //!     bar(rw, 5, 1, "1-bit field"),
+//!     baz(rw, 7..=6, "2-bits field"),
 //! )]
 //! // The choice of the underlying integer determines the total number of bits.
-//! // Available sizes: `u8`, `u16`, `u32`, `u64`, `u128`.
+//! // Available sizes: `u8`, `u16`, `u32`, `u64`, `u128`, and `[u32; N]` arrays.
 //! struct MyValue(u8);
 //!
 //! //                          * foo bit
@@ -49,10 +53,234 @@
 //!
 //! assert_eq!(value.0, 0b0001_0100);
 //! ```
+//!
+//! # Conversions
+//!
+//! The optional `#[bitfield_conv(...)]` attribute generates [`From`]
+//! conversions in both directions between the defined type and each of the
+//! listed [`Bitfield`] types. The underlying integers must be of the same type.
+//! This way a typed configuration struct can be stored directly into a
+//! register:
+//!
+//! ```
+//! # #![feature(proc_macro_hygiene)]
+//! use drone_core::{bitfield::Bitfield, reg::prelude::*, token::Token};
+//!
+//! drone_core::reg! {
+//!     pub TIM1 CR1 => {
+//!         address => 0x4001_0000; size => 0x20; reset => 0; traits => { RReg WReg };
+//!         fields => { CEN => { offset => 0; width => 1; traits => { RRRegField WWRegField } } };
+//!     };
+//! }
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(enable(rw, 0), prescaler(rw, 4, 4))]
+//! #[bitfield_conv(tim1_cr1::Val)]
+//! struct TimerConfig(u32);
+//!
+//! let mut config = TimerConfig(0);
+//! config.set_enable().write_prescaler(0b1010);
+//! let val: tim1_cr1::Val = config.into();
+//! assert_eq!(val.bits(), 0b1010_0001);
+//! assert_eq!(TimerConfig::from(val).prescaler(), 0b1010);
+//! # fn store(mut tim1_cr1: tim1_cr1::Reg<Urt>, config: TimerConfig) {
+//! tim1_cr1.store_val(config.into());
+//! # }
+//! ```
+//!
+//! A conversion should be declared on only one of the two types, otherwise the
+//! [`From`] implementations will conflict.
+//!
+//! # Multi-Word Bitfields
+//!
+//! Bitfields wider than 128 bits are backed by arrays of `u32` words, the
+//! least significant word first. Their [`Bits`] type is [`Words`], so
+//! multi-bit fields are read and written as [`Words`] too:
+//!
+//! ```
+//! use drone_core::bitfield::{Bitfield, Bits, Words};
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(irq33(rw, 33), irq240(rw, 240), prio(rw, 60, 8))]
+//! struct EnableSet([u32; 8]);
+//!
+//! let mut set = EnableSet([0; 8]);
+//! set.set_irq33().set_irq240();
+//! set.write_prio(Words::from_usize(0xAB));
+//! assert_eq!(set.0, [0, 0xB000_0002, 0xA, 0, 0, 0, 0, 0x0001_0000]);
+//! assert_eq!(set.prio().low_word(), 0xAB);
+//! assert_eq!(set.iter_ones().count(), 7);
+//! ```
+//!
+//! # Debug Formatting
+//!
+//! With the `debug` flag the macro implements [`Debug`](core::fmt::Debug),
+//! which lists every field with its raw value. Single-bit fields are shown as
+//! booleans, and each repetition of a repeated field is listed separately.
+//! With the `defmt` feature of this crate enabled, the flag also implements
+//! `defmt::Format`, which requires the `defmt` crate to be a dependency of
+//! the crate deriving [`Bitfield`]:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(debug, en(rw, 0), mode(rw, 1, 2, 2))]
+//! struct Control(u8);
+//!
+//! assert_eq!(
+//!     format!("{:?}", Control(0b1101)),
+//!     "Control { en: true, mode[0]: 2, mode[1]: 1 }",
+//! );
+//! ```
+//!
+//! # Default Values
+//!
+//! A field definition can be followed by `= value`. Then the macro assembles
+//! the default values of all fields into the `DEFAULT` constant, and
+//! implements [`Default`] with it. The fields without a default value are
+//! zeroed:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(version(rw, 6, 2) = 0b10, length(rw, 0, 6), ack(rw, 15) = 1)]
+//! struct Header(u16);
+//!
+//! assert_eq!(Header::DEFAULT.0, 0b1000_0000_1000_0000);
+//! assert_eq!(Header::default().version(), 0b10);
+//! ```
+//!
+//! # Checked Setters
+//!
+//! Plain setters of multi-bit fields silently truncate the value to the field
+//! width. Each of them is accompanied by a `try_write_` setter, which returns
+//! [`FieldOverflow`] instead. With the `strict` flag the plain setters panic
+//! on overflow in debug builds:
+//!
+//! ```
+//! use drone_core::bitfield::{Bitfield, FieldOverflow};
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(strict, prescaler(rw, 0, 4))]
+//! struct Divider(u8);
+//!
+//! let mut divider = Divider(0);
+//! assert_eq!(divider.try_write_prescaler(0b1_0000), Err(FieldOverflow));
+//! assert_eq!(divider.try_write_prescaler(0b1010), Ok(()));
+//! assert_eq!(divider.prescaler(), 0b1010);
+//! ```
+//!
+//! # Const Builders
+//!
+//! For bitfields backed by primitive integers, the getters are `const fn`.
+//! With the `const_builder` flag the macro also defines a by-value
+//! `with_` setter for each writable field, which is `const fn` too. This
+//! way complete values can be computed at compile time, for example to
+//! store configuration tables in `static`s:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(const_builder, en(rw, 0), speed(rw, 1, 2), pull(rw, 4, 2))]
+//! struct Pin(u8);
+//!
+//! static PINS: [Pin; 2] = [
+//!     Pin(0).with_en(true).with_speed(0b11),
+//!     Pin(0).with_en(true).with_pull(0b01),
+//! ];
+//!
+//! assert_eq!(PINS[0].speed(), 0b11);
+//! assert_eq!(PINS[1].0, 0b0001_0001);
+//! ```
+//!
+//! # Repeated Fields
+//!
+//! A field with a `count` after the `width` is repeated `count` times, every
+//! `stride` bits (defaults to the `width`). The accessors of such field take the
+//! index of the repetition as the first argument, and panic if it's out of
+//! bounds:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(mode(rw, 0, 2, 8, "Port mode"), lock(rw, 16, 1, 4, 2))]
+//! struct Moder(u32);
+//!
+//! let mut moder = Moder(0);
+//! moder.write_mode(3, 0b10).set_lock(1);
+//! assert_eq!(moder.0, 0b0100_0000_0000_1000_0000);
+//! assert_eq!(moder.mode(3), 0b10);
+//! assert!(moder.lock(1));
+//! ```
+//!
+//! # Signed Fields
+//!
+//! Multi-bit fields with the `_i` mode suffix hold two's-complement numbers.
+//! They are read with sign extension into the signed counterpart of the
+//! underlying integer, and writes panic if the value doesn't fit into the
+//! field:
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(trim(rw_i, 2, 5, "Oscillator trimming"))]
+//! struct Calibration(u16);
+//!
+//! let mut calibration = Calibration(0);
+//! calibration.write_trim(-3);
+//! assert_eq!(calibration.0, 0b111_0100);
+//! assert_eq!(calibration.trim(), -3);
+//! calibration.write_trim(15);
+//! assert_eq!(calibration.trim(), 15);
+//! ```
+//!
+//! # Enum Fields
+//!
+//! A field can be declared with a type of a fieldless enum, which derives
+//! [`Bitfield`] too. All variants of the enum must have explicit
+//! discriminants. The getter of such field returns the enum variant or
+//! [`InvalidBits`] for the bits, which don't match any variant, and the setter
+//! takes the enum:
+//!
+//! ```
+//! use drone_core::bitfield::{Bitfield, InvalidBits};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq, Eq, Bitfield)]
+//! enum Parity {
+//!     None = 0b00,
+//!     Even = 0b10,
+//!     Odd = 0b11,
+//! }
+//!
+//! #[derive(Clone, Copy, Bitfield)]
+//! #[bitfield(parity(rw, 2, 2, Parity, "Parity control"))]
+//! struct Control(u8);
+//!
+//! let mut control = Control(0);
+//! assert_eq!(control.parity(), Ok(Parity::None));
+//! control.write_parity(Parity::Odd);
+//! assert_eq!(control.0, 0b1100);
+//! control.0 = 0b0100;
+//! assert_eq!(control.parity(), Err(InvalidBits(0b01)));
+//! ```
 
 mod bits;
+mod enums;
+mod info;
+mod iter;
+mod words;
 
 pub use self::bits::Bits;
+pub use self::enums::{BitfieldEnum, InvalidBits};
+pub use self::info::{FieldInfo, FieldMode};
+pub use self::iter::Offsets;
+pub use self::words::Words;
+use core::fmt;
 /// Defines a new [`Bitfield`].
 ///
 /// See [the module level documentation](self) for details.
@@ -67,12 +295,109 @@ pub trait Bitfield: Sized + Send + Sync + Clone + Copy + 'static {
     /// The type of the integer. Determines the total number of bits.
     type Bits: Bits;
 
+    /// The fields of the bitfield, in the order of declaration.
+    ///
+    /// ```
+    /// use drone_core::bitfield::{Bitfield, FieldInfo, FieldMode};
+    ///
+    /// #[derive(Clone, Copy, Bitfield)]
+    /// #[bitfield(en(rw, 0), level(r, 4, 3))]
+    /// struct Control(u8);
+    ///
+    /// assert_eq!(Control::FIELDS, [
+    ///     FieldInfo { name: "en", offset: 0, width: 1, mode: FieldMode::ReadWrite },
+    ///     FieldInfo { name: "level", offset: 4, width: 3, mode: FieldMode::Read },
+    /// ]);
+    /// ```
+    const FIELDS: &'static [FieldInfo] = &[];
+
     /// Returns a copy of the underlying integer.
     fn bits(&self) -> Self::Bits;
 
     /// Returns a mutable reference to the underlying integer.
     fn bits_mut(&mut self) -> &mut Self::Bits;
 
+    /// Creates a new value from the underlying integer `bits`.
+    fn from_bits(bits: Self::Bits) -> Self;
+
+    /// Returns an iterator over the offsets of the set bits, in ascending
+    /// order.
+    ///
+    /// ```
+    /// # use drone_core::bitfield::Bitfield;
+    /// # #[derive(Clone, Copy, Bitfield)]
+    /// # struct Status(u8);
+    /// let status = Status(0b0010_0101);
+    /// let mut pending = [false; 8];
+    /// for bit in status.iter_ones() {
+    ///     pending[usize::from(bit)] = true;
+    /// }
+    /// assert_eq!(pending, [true, false, true, false, false, true, false, false]);
+    /// ```
+    #[inline]
+    fn iter_ones(&self) -> Offsets<Self::Bits> {
+        Offsets::new(self.bits())
+    }
+
+    /// Returns an iterator over the offsets of the cleared bits, in ascending
+    /// order.
+    #[inline]
+    fn iter_zeros(&self) -> Offsets<Self::Bits> {
+        Offsets::new(!self.bits())
+    }
+
+    /// Returns the number of set bits.
+    #[inline]
+    fn count_ones(&self) -> u32 {
+        self.bits().count_ones()
+    }
+
+    /// Returns the number of cleared bits.
+    #[inline]
+    fn count_zeros(&self) -> u32 {
+        self.bits().count_zeros()
+    }
+
+    /// Copies the fields of `other` into the fields of `self` with the same
+    /// names and widths, according to [`FIELDS`](Bitfield::FIELDS). The other
+    /// fields of `self` are left intact.
+    ///
+    /// This is useful for pairs of registers with the same layout, like
+    /// separate "set" and "clear" registers, or input and output registers.
+    ///
+    /// ```
+    /// use drone_core::bitfield::Bitfield;
+    ///
+    /// #[derive(Clone, Copy, Bitfield)]
+    /// #[bitfield(tx(r, 0), rx(r, 1), err(r, 7))]
+    /// struct Input(u8);
+    ///
+    /// #[derive(Clone, Copy, Bitfield)]
+    /// #[bitfield(rx(rw, 4), tx(rw, 5), led(rw, 6))]
+    /// struct Output(u8);
+    ///
+    /// let mut output = Output(0b0100_0000);
+    /// output.copy_fields_from(&Input(0b1000_0001));
+    /// assert_eq!(output.0, 0b0110_0000);
+    /// ```
+    #[inline]
+    fn copy_fields_from<U: Bitfield>(&mut self, other: &U) -> &mut Self {
+        for field in Self::FIELDS {
+            let source = U::FIELDS
+                .iter()
+                .find(|source| source.name == field.name && source.width == field.width);
+            if let Some(source) = source {
+                for i in 0..field.width {
+                    unsafe {
+                        let bit = other.read_bit(U::Bits::from_usize(source.offset + i));
+                        self.write_bit(Self::Bits::from_usize(field.offset + i), bit);
+                    }
+                }
+            }
+        }
+        self
+    }
+
     /// Returns `true` if the bit at `offset` is set.
     ///
     /// # Safety
@@ -152,6 +477,24 @@ pub trait Bitfield: Sized + Send + Sync + Clone + Copy + 'static {
     }
 }
 
+/// The error returned by the checked setters of [`Bitfield`] fields, when the
+/// value doesn't fit into the field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldOverflow;
+
+impl fmt::Display for FieldOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("value overflows bitfield field")
+    }
+}
+
+/// Returns `true` if `bits` don't fit into `width` bits.
+#[doc(hidden)]
+#[inline]
+pub fn overflows<T: Bits>(bits: T, width: T) -> bool {
+    width < T::width() && !(bits >> width).is_zero()
+}
+
 fn maybe_bit_at<T: Bits>(bit: bool, offset: T) -> T {
     T::from_usize(bit.into()) << offset
 }
@@ -0,0 +1,223 @@
+use super::Bits;
+use core::cmp::Ordering;
+use core::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr, Sub};
+
+/// A multi-word integer of `N` 32-bit words, the least significant word first.
+///
+/// This is the [`Bits`] type of bitfields backed by `[u32; N]` arrays. `N`
+/// must be non-zero. See [the module level documentation](super) for details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Words<const N: usize>(pub [u32; N]);
+
+impl<const N: usize> Words<N> {
+    /// Converts a mutable reference to the array into a mutable reference to
+    /// the multi-word integer.
+    #[inline]
+    pub fn from_mut(words: &mut [u32; N]) -> &mut Self {
+        unsafe { &mut *(words as *mut [u32; N]).cast::<Self>() }
+    }
+
+    /// Returns the least significant word.
+    #[inline]
+    pub fn low_word(self) -> u32 {
+        self.0[0]
+    }
+
+    fn shift_amount(self) -> usize {
+        if self.0[1..].iter().any(|&word| word != 0) {
+            usize::MAX
+        } else {
+            self.0[0] as usize
+        }
+    }
+
+    fn map(self, f: impl Fn(u32) -> u32) -> Self {
+        Self(self.0.map(f))
+    }
+
+    fn zip(self, rhs: Self, f: impl Fn(u32, u32) -> u32) -> Self {
+        let mut words = self.0;
+        for (word, rhs) in words.iter_mut().zip(rhs.0) {
+            *word = f(*word, rhs);
+        }
+        Self(words)
+    }
+}
+
+impl<const N: usize> PartialOrd for Words<N> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for Words<N> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl<const N: usize> Not for Words<N> {
+    type Output = Self;
+
+    #[inline]
+    fn not(self) -> Self {
+        self.map(|word| !word)
+    }
+}
+
+impl<const N: usize> BitAnd for Words<N> {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self {
+        self.zip(rhs, |a, b| a & b)
+    }
+}
+
+impl<const N: usize> BitOr for Words<N> {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        self.zip(rhs, |a, b| a | b)
+    }
+}
+
+impl<const N: usize> BitXor for Words<N> {
+    type Output = Self;
+
+    #[inline]
+    fn bitxor(self, rhs: Self) -> Self {
+        self.zip(rhs, |a, b| a ^ b)
+    }
+}
+
+impl<const N: usize> Sub for Words<N> {
+    type Output = Self;
+
+    /// Wrapping subtraction.
+    fn sub(self, rhs: Self) -> Self {
+        let mut words = self.0;
+        let mut borrow = false;
+        for (word, rhs) in words.iter_mut().zip(rhs.0) {
+            let (value, overflow1) = word.overflowing_sub(rhs);
+            let (value, overflow2) = value.overflowing_sub(borrow.into());
+            *word = value;
+            borrow = overflow1 || overflow2;
+        }
+        Self(words)
+    }
+}
+
+impl<const N: usize> Shl for Words<N> {
+    type Output = Self;
+
+    fn shl(self, rhs: Self) -> Self {
+        let amount = rhs.shift_amount();
+        let mut words = [0; N];
+        if amount < N * 32 {
+            let (skip, shift) = (amount / 32, amount % 32);
+            for (i, word) in words.iter_mut().enumerate().skip(skip) {
+                *word = self.0[i - skip] << shift;
+                if shift > 0 && i > skip {
+                    *word |= self.0[i - skip - 1] >> (32 - shift);
+                }
+            }
+        }
+        Self(words)
+    }
+}
+
+impl<const N: usize> Shr for Words<N> {
+    type Output = Self;
+
+    fn shr(self, rhs: Self) -> Self {
+        let amount = rhs.shift_amount();
+        let mut words = [0; N];
+        if amount < N * 32 {
+            let (skip, shift) = (amount / 32, amount % 32);
+            for (i, word) in words.iter_mut().take(N - skip).enumerate() {
+                *word = self.0[i + skip] >> shift;
+                if shift > 0 && i + skip + 1 < N {
+                    *word |= self.0[i + skip + 1] << (32 - shift);
+                }
+            }
+        }
+        Self(words)
+    }
+}
+
+impl<const N: usize> Bits for Words<N> {
+    #[inline]
+    fn from_usize(bits: usize) -> Self {
+        let bits = bits as u64;
+        let mut words = [0; N];
+        words[0] = bits as u32;
+        if N > 1 {
+            words[1] = (bits >> 32) as u32;
+        }
+        Self(words)
+    }
+
+    #[inline]
+    fn width() -> Self {
+        Self::from_usize(N * 32)
+    }
+
+    #[inline]
+    fn is_zero(self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    #[inline]
+    fn count_ones(self) -> u32 {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+
+    #[inline]
+    fn count_zeros(self) -> u32 {
+        self.0.iter().map(|word| word.count_zeros()).sum()
+    }
+
+    #[inline]
+    fn trailing_zeros(self) -> u32 {
+        let mut count = 0;
+        for word in self.0 {
+            count += word.trailing_zeros();
+            if word != 0 {
+                break;
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shifts() {
+        let one = Words::<3>::from_usize(1);
+        assert_eq!(one << Words::from_usize(40), Words([0, 1 << 8, 0]));
+        assert_eq!(one << Words::from_usize(95), Words([0, 0, 1 << 31]));
+        assert_eq!(one << Words::from_usize(96), Words([0, 0, 0]));
+        let x = Words([0x8000_0001, 0x1234_5678, 0]);
+        assert_eq!(x << Words::from_usize(4), Words([0x0000_0010, 0x2345_6788, 0x1]));
+        assert_eq!(x >> Words::from_usize(36), Words([0x0123_4567, 0, 0]));
+        assert_eq!(x >> Words::from_usize(1), Words([0x4000_0000, 0x091A_2B3C, 0]));
+    }
+
+    #[test]
+    fn sub_and_cmp() {
+        let x = Words([0, 1, 0]) - Words::from_usize(1);
+        assert_eq!(x, Words([0xFFFF_FFFF, 0, 0]));
+        assert_eq!(Words::<2>::from_usize(0) - Words::from_usize(1), Words([u32::MAX; 2]));
+        assert!(Words([0, 1]) > Words([0xFFFF_FFFF, 0]));
+        assert_eq!(Words([0, 0, 0b100]).trailing_zeros(), 66);
+        assert_eq!(Words::<2>::from_usize(0).trailing_zeros(), 64);
+    }
+}
@@ -0,0 +1,99 @@
+//! Driver and resource pattern.
+//!
+//! A driver is a type, which exclusively owns a set of tokens - peripheral
+//! structs, thread tokens, one-time initializers - and exposes a high-level
+//! interface on top of them. The owned tokens are grouped into a *resource*
+//! struct. A driver is constructed from its resource with [`Driver::new`], and
+//! can be torn down with [`Driver::free`], which gives the resource back. This
+//! way the tokens can be reused, for example, to re-initialize the peripheral
+//! with a different driver.
+//!
+//! Both traits can be derived:
+//!
+//! ```
+//! use drone_core::drv::{Driver, Resource};
+//! use drone_core::token::{simple_token, Token};
+//!
+//! simple_token! {
+//!     /// A token of the LED pin.
+//!     pub struct LedPin;
+//! }
+//!
+//! simple_token! {
+//!     /// A token of the blink timer.
+//!     pub struct BlinkTimer;
+//! }
+//!
+//! /// The tokens owned by the LED driver.
+//! #[derive(Resource)]
+//! pub struct LedRes {
+//!     pub pin: LedPin,
+//!     pub timer: BlinkTimer,
+//! }
+//!
+//! /// The LED driver.
+//! #[derive(Driver)]
+//! pub struct Led {
+//!     // The field holding the resource.
+//!     #[resource]
+//!     res: LedRes,
+//!     // Other fields are initialized with `Default::default()`.
+//!     blinks: u32,
+//! }
+//!
+//! impl Led {
+//!     pub fn blink(&mut self) {
+//!         self.blinks += 1;
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let res = unsafe { LedRes { pin: LedPin::take(), timer: BlinkTimer::take() } };
+//!     let mut led = Led::new(res);
+//!     led.blink();
+//!     // Tear the driver down and get the tokens back.
+//!     let LedRes { pin, timer } = led.free();
+//! }
+//! ```
+//!
+//! A tuple struct with a single field can derive [`Driver`](trait@Driver)
+//! without the `#[resource]` attribute:
+//!
+//! ```
+//! # use drone_core::drv::{Driver, Resource};
+//! # #[derive(Resource)]
+//! # pub struct LedRes;
+//! #[derive(Driver)]
+//! pub struct Led(LedRes);
+//! ```
+
+/// Derives the [`Driver`](trait@Driver) trait.
+///
+/// See [the module level documentation](self) for details.
+#[doc(inline)]
+pub use drone_core_macros::Driver;
+/// Derives the [`Resource`](trait@Resource) trait.
+///
+/// See [the module level documentation](self) for details.
+#[doc(inline)]
+pub use drone_core_macros::Resource;
+
+/// A set of tokens exclusively owned by a [`Driver`](trait@Driver).
+///
+/// See [the module level documentation](self) for more.
+pub trait Resource: Sized + Send + 'static {}
+
+/// A driver, which can be constructed from and released back into its
+/// [`Resource`](trait@Resource).
+///
+/// See [the module level documentation](self) for more.
+pub trait Driver: Sized + Send + 'static {
+    /// The resource type of the driver.
+    type Resource: Resource;
+
+    /// Creates a new driver from the resource `res`.
+    fn new(res: Self::Resource) -> Self;
+
+    /// Releases the driver, returning the resource back.
+    fn free(self) -> Self::Resource;
+}
@@ -0,0 +1,88 @@
+use crate::platform;
+
+#[cfg(any(feature = "atomics", loom))]
+type AtomicU32 = core::sync::atomic::AtomicU32;
+#[cfg(not(any(feature = "atomics", loom)))]
+type AtomicU32 = crate::sync::soft_atomic::Atomic<u32>;
+
+/// A snapshot of the execution budget of a fiber.
+///
+/// Returned by [`Chain::budgets`](crate::fib::Chain::budgets). The counters
+/// wrap around, so long-running measurements should compare two snapshots
+/// with `wrapping_sub`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FiberBudget {
+    /// The priority of the fiber.
+    pub priority: u8,
+    /// The number of resumptions of the fiber.
+    pub resumes: u32,
+    /// The total number of cycles spent in the fiber.
+    pub cycles: u32,
+    /// The largest number of cycles spent in a single resumption.
+    pub max_cycles: u32,
+}
+
+/// Execution budget counters of a fiber.
+///
+/// The counters are updated only by the fiber chain drain, so they don't
+/// need read-modify-write operations.
+pub(crate) struct Budget {
+    resumes: AtomicU32,
+    cycles: AtomicU32,
+    max_cycles: AtomicU32,
+}
+
+impl Budget {
+    pub(crate) fn new() -> Self {
+        Self {
+            resumes: AtomicU32::new(0),
+            cycles: AtomicU32::new(0),
+            max_cycles: AtomicU32::new(0),
+        }
+    }
+
+    /// Runs `f` and accounts the cycles it took.
+    #[inline]
+    pub(crate) fn measure<R>(&self, f: impl FnOnce() -> R) -> R {
+        let start = platform::cycles();
+        let result = f();
+        self.record(platform::cycles().wrapping_sub(start));
+        result
+    }
+
+    fn record(&self, cycles: u32) {
+        let resumes = load_atomic!(self.resumes, Relaxed).wrapping_add(1);
+        let total = load_atomic!(self.cycles, Relaxed).wrapping_add(cycles);
+        store_atomic!(self.resumes, resumes, Relaxed);
+        store_atomic!(self.cycles, total, Relaxed);
+        if cycles > load_atomic!(self.max_cycles, Relaxed) {
+            store_atomic!(self.max_cycles, cycles, Relaxed);
+        }
+    }
+
+    pub(crate) fn snapshot(&self, priority: u8) -> FiberBudget {
+        FiberBudget {
+            priority,
+            resumes: load_atomic!(self.resumes, Relaxed),
+            cycles: load_atomic!(self.cycles, Relaxed),
+            max_cycles: load_atomic!(self.max_cycles, Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record() {
+        let budget = Budget::new();
+        budget.record(10);
+        budget.record(30);
+        budget.record(20);
+        assert_eq!(
+            budget.snapshot(1),
+            FiberBudget { priority: 1, resumes: 3, cycles: 60, max_cycles: 30 }
+        );
+    }
+}
@@ -1,17 +1,28 @@
+#[cfg(feature = "fib-budget")]
+use crate::fib::budget::Budget;
+#[cfg(feature = "fib-budget")]
+use crate::fib::FiberBudget;
 use crate::fib::RootFiber;
 use crate::sync::linked_list::{DrainFilterRaw, LinkedList, Node as ListNode};
 use core::iter::FusedIterator;
 use core::pin::Pin;
 
 /// A lock-free list of fibers.
+///
+/// Fibers are resumed in LIFO order. With the `fib-priority` feature, fibers
+/// are resumed in the order of their priorities, from the highest to the
+/// lowest, and fibers with equal priorities are resumed in LIFO order.
 pub struct Chain {
-    list: LinkedList<Node<()>>,
+    /// A list of fibers for each priority.
+    lists: [LinkedList<Node<()>>; Chain::PRIORITIES as usize],
 }
 
 #[repr(C)]
 pub struct Node<F> {
     advance: unsafe fn(*mut ListNode<Node<()>>) -> bool,
     deallocate: unsafe fn(*mut ListNode<Node<()>>),
+    #[cfg(feature = "fib-budget")]
+    budget: Budget,
     fib: F,
 }
 
@@ -20,28 +31,70 @@ pub struct Drain<'a, F>
 where
     F: FnMut(*const ListNode<Node<()>>) -> bool,
 {
+    chain: &'a Chain,
+    /// The priority of the list being drained.
+    priority: u8,
     inner: DrainFilterRaw<'a, Node<()>, F>,
+    restart: unsafe fn(&'a LinkedList<Node<()>>) -> DrainFilterRaw<'a, Node<()>, F>,
 }
 
 impl Chain {
+    /// The number of fiber priorities. Valid priorities are in the range
+    /// `0..PRIORITIES`.
+    ///
+    /// Without the `fib-priority` feature, there is only one priority, so the
+    /// chain keeps a single list.
+    #[cfg(feature = "fib-priority")]
+    pub const PRIORITIES: u8 = 4;
+
+    /// The number of fiber priorities. Valid priorities are in the range
+    /// `0..PRIORITIES`.
+    ///
+    /// Without the `fib-priority` feature, there is only one priority, so the
+    /// chain keeps a single list.
+    #[cfg(not(feature = "fib-priority"))]
+    pub const PRIORITIES: u8 = 1;
+
     maybe_const_fn! {
         /// Creates an empty fiber chain.
         #[inline]
         pub const fn new() -> Self {
-            Self { list: LinkedList::new() }
+            Self {
+                #[cfg(feature = "fib-priority")]
+                lists: [LinkedList::new(), LinkedList::new(), LinkedList::new(), LinkedList::new()],
+                #[cfg(not(feature = "fib-priority"))]
+                lists: [LinkedList::new()],
+            }
         }
     }
 
     /// Adds a fiber first in the chain.
+    ///
+    /// The fiber has the lowest priority `0`.
     #[inline]
     pub fn add<F: RootFiber>(&self, fib: F) {
-        unsafe { self.list.push_raw(Node::allocate(fib)) };
+        unsafe { self.lists[0].push_raw(Node::allocate(fib)) };
+    }
+
+    /// Adds a fiber with the given `priority` to the chain.
+    ///
+    /// The fiber is resumed before all fibers with lower priorities, and
+    /// first among the fibers with the same priority.
+    ///
+    /// # Panics
+    ///
+    /// If `priority` is not less than [`Chain::PRIORITIES`].
+    #[cfg(feature = "fib-priority")]
+    #[inline]
+    pub fn add_with_priority<F: RootFiber>(&self, fib: F, priority: u8) {
+        assert!(priority < Self::PRIORITIES, "fiber priority out of range");
+        unsafe { self.lists[usize::from(priority)].push_raw(Node::allocate(fib)) };
     }
 
     /// Returns `true` if the chain is empty.
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.list.is_empty()
+        self.lists.iter().all(LinkedList::is_empty)
     }
 
     /// Returns an iterator that advances each fiber in the chain, returning
@@ -80,22 +133,82 @@ impl Chain {
     /// alive.
     #[inline]
     pub unsafe fn drain(&self) -> Drain<'_, impl FnMut(*const ListNode<Node<()>>) -> bool> {
+        unsafe fn pass<'a>(
+            list: &'a LinkedList<Node<()>>,
+        ) -> DrainFilterRaw<'a, Node<()>, impl FnMut(*const ListNode<Node<()>>) -> bool> {
+            unsafe { list.drain_filter_raw(Node::filter) }
+        }
+        let priority = Self::PRIORITIES - 1;
         // This is the only place where nodes are getting removed. This cannot
         // run concurrently because of the safety invariant of this function.
-        unsafe { Drain { inner: self.list.drain_filter_raw(Node::filter) } }
+        unsafe {
+            Drain {
+                chain: self,
+                priority,
+                inner: pass(&self.lists[usize::from(priority)]),
+                restart: pass,
+            }
+        }
+    }
+
+    /// Returns an iterator over the execution budgets of the fibers in the
+    /// chain.
+    ///
+    /// The cycles are measured with the
+    /// [`platform::cycles`](crate::platform::cycles) counter. The cycles spent
+    /// in higher-priority threads preempting a fiber are accounted to that
+    /// fiber. Completed fibers are removed from the chain together with their
+    /// budgets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use drone_core::fib::{self, Chain};
+    /// # #[no_mangle]
+    /// # extern "C" fn drone_cycle_counter() -> u32 {
+    /// #     0
+    /// # }
+    ///
+    /// let chain = Chain::new();
+    /// chain.add(fib::new_fn(|| fib::Yielded::<(), ()>(())));
+    /// unsafe {
+    ///     chain.drain().for_each(drop);
+    ///     chain.drain().for_each(drop);
+    ///     let budget = chain.budgets().next().unwrap();
+    ///     assert_eq!(budget.resumes, 2);
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// This method must not be called while a drain of the chain is in
+    /// progress in another context. It can be called from a fiber of the
+    /// chain, or when the thread of the chain can't be activated.
+    #[cfg(feature = "fib-budget")]
+    #[inline]
+    pub unsafe fn budgets(&self) -> impl Iterator<Item = FiberBudget> + '_ {
+        (0..Self::PRIORITIES).rev().flat_map(move |priority| {
+            unsafe { self.lists[usize::from(priority)].iter_raw() }
+                .map(move |node| unsafe { (*node).budget.snapshot(priority) })
+        })
     }
 }
 
 impl Drop for Chain {
     #[inline]
     fn drop(&mut self) {
-        unsafe { self.list.drain_filter_raw(|_| true).for_each(Node::delete) };
+        for list in &self.lists {
+            unsafe { list.drain_filter_raw(|_| true).for_each(Node::delete) };
+        }
     }
 }
 
 impl Node<()> {
     fn filter(node: *const ListNode<Self>) -> bool {
-        unsafe { ((*node).advance)(node.cast_mut()) }
+        #[cfg(feature = "fib-budget")]
+        return unsafe { (*node).budget.measure(|| ((*node).advance)(node.cast_mut())) };
+        #[cfg(not(feature = "fib-budget"))]
+        return unsafe { ((*node).advance)(node.cast_mut()) };
     }
 
     fn delete(node: *const ListNode<Self>) {
@@ -105,7 +218,13 @@ impl Node<()> {
 
 impl<F: RootFiber> Node<F> {
     fn allocate(fib: F) -> *mut ListNode<Node<()>> {
-        let node = Node { advance: Self::advance, deallocate: Self::deallocate, fib };
+        let node = Node {
+            advance: Self::advance,
+            deallocate: Self::deallocate,
+            #[cfg(feature = "fib-budget")]
+            budget: Budget::new(),
+            fib,
+        };
         unsafe { Self::upcast(Box::into_raw(Box::new(ListNode::from(node)))) }
     }
 
@@ -134,6 +253,7 @@ where
     #[inline]
     pub fn is_end(&self) -> bool {
         self.inner.is_end()
+            && self.chain.lists[..usize::from(self.priority)].iter().all(LinkedList::is_empty)
     }
 }
 
@@ -145,7 +265,15 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(Node::delete)
+        loop {
+            if let Some(node) = self.inner.next() {
+                Node::delete(node);
+                return Some(());
+            }
+            // Proceed to the fibers with the next lower priority.
+            self.priority = self.priority.checked_sub(1)?;
+            self.inner = unsafe { (self.restart)(&self.chain.lists[usize::from(self.priority)]) };
+        }
     }
 }
 
@@ -1,4 +1,6 @@
-use crate::fib::{Fiber, FiberState, RootFiber};
+use crate::fib::interval::Ticked;
+use crate::fib::{self, Fiber, FiberHandle, FiberState, IntervalPolicy, RootFiber};
+use crate::sync::spsc::oneshot::Receiver;
 use crate::thr::prelude::*;
 use core::marker::Unpin;
 use core::pin::Pin;
@@ -118,7 +120,11 @@ where
 }
 
 /// Extends [`ThrToken`](crate::thr::ThrToken) types with `add_fn`,
-/// `add_fn_factory`, and `add_once` methods.
+/// `add_fn_factory`, `add_every`, `add_every_ticks`, `add_once`,
+/// `add_try_fn`, and `add_try_once` methods.
+///
+/// The `*_cancelable` variants return a [`FiberHandle`], which can cancel the
+/// added fiber. The fallible methods return a [`Receiver`] for the fiber error.
 pub trait ThrFiberClosure: ThrToken {
     /// Adds a fiber that runs the closure `f` until [`FiberState::Complete`] is
     /// returned.
@@ -132,6 +138,20 @@ pub trait ThrFiberClosure: ThrToken {
         self.add_fib(new_fn(f));
     }
 
+    /// Adds a fiber that runs the closure `f` until [`FiberState::Complete`] is
+    /// returned, and returns a handle to cancel the fiber.
+    #[inline]
+    fn add_fn_cancelable<F, R>(self, f: F) -> FiberHandle
+    where
+        F: FnMut() -> FiberState<(), R>,
+        F: Send + 'static,
+        R: ReturnNone,
+    {
+        let (fib, handle) = fib::cancelable(new_fn(f));
+        self.add_fib(fib);
+        handle
+    }
+
     /// Adds a fiber that runs the closure returned by `factory` until
     /// [`FiberState::Complete`] is returned.
     ///
@@ -147,6 +167,66 @@ pub trait ThrFiberClosure: ThrToken {
         self.add_fib_factory(|| new_fn(factory()));
     }
 
+    /// Adds a fiber that runs the closure returned by `factory` until
+    /// [`FiberState::Complete`] is returned, and returns a handle to cancel
+    /// the fiber.
+    ///
+    /// This method is useful for non-`Send` fibers.
+    #[inline]
+    fn add_fn_factory_cancelable<C, F, R>(self, factory: C) -> FiberHandle
+    where
+        C: FnOnce() -> F + Send + 'static,
+        F: FnMut() -> FiberState<(), R>,
+        F: 'static,
+        R: ReturnNone,
+    {
+        let (factory, handle) = fib::cancelable_factory(|| new_fn(factory()));
+        self.add_fib_factory(factory);
+        handle
+    }
+
+    /// Adds a fiber that runs the closure `f` on every `n`-th thread
+    /// activation until [`FiberState::Complete`] is returned.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    #[inline]
+    fn add_every<F, R>(self, n: u32, f: F)
+    where
+        F: FnMut() -> FiberState<(), R>,
+        F: Send + 'static,
+        R: ReturnNone,
+    {
+        self.add_fib(fib::new_interval(n, f));
+    }
+
+    /// Adds a fiber that runs the closure `f` every `n` ticks of the
+    /// free-running counter `ticks` until [`FiberState::Complete`] is
+    /// returned.
+    ///
+    /// On each thread activation the fiber reads `ticks` and resumes the
+    /// closure once per elapsed period. When several periods elapsed since the
+    /// previous activation, e.g. because the activations were delayed or
+    /// coalesced, `policy` decides whether the closure is called for each of
+    /// them or only once. The counter may wrap around. See
+    /// [`fib::new_interval`](crate::fib::new_interval) for details.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    #[inline]
+    fn add_every_ticks<T, F, R>(self, n: u32, policy: IntervalPolicy, ticks: T, f: F)
+    where
+        T: FnMut() -> u32,
+        T: Send + 'static,
+        F: FnMut() -> FiberState<(), R>,
+        F: Send + 'static,
+        R: ReturnNone,
+    {
+        self.add_fib(Ticked::new(ticks, fib::new_interval(n, f).with_policy(policy)));
+    }
+
     /// Adds a fiber that calls the closure `f` once.
     #[inline]
     fn add_once<F>(self, f: F)
@@ -156,6 +236,56 @@ pub trait ThrFiberClosure: ThrToken {
     {
         self.add_fib(new_once(f));
     }
+
+    /// Adds a fiber that calls the closure `f` once, and returns a handle to
+    /// cancel the fiber before it runs.
+    #[inline]
+    fn add_once_cancelable<F>(self, f: F) -> FiberHandle
+    where
+        F: FnOnce(),
+        F: Unpin + Send + 'static,
+    {
+        let (fib, handle) = fib::cancelable(new_once(f));
+        self.add_fib(fib);
+        handle
+    }
+
+    /// Adds a fiber that runs the fallible closure `f` until
+    /// [`FiberState::Complete`] or an error is returned.
+    ///
+    /// The returned receiver resolves to the error of the closure. If the
+    /// fiber completes successfully, or is dropped, the receiver resolves to
+    /// [`Canceled`](crate::sync::spsc::oneshot::Canceled). Dropping the
+    /// receiver doesn't affect the fiber.
+    #[inline]
+    fn add_try_fn<F, R, E>(self, f: F) -> Receiver<E>
+    where
+        F: FnMut() -> Result<FiberState<(), R>, E>,
+        F: Send + 'static,
+        R: ReturnNone,
+        E: Send + 'static,
+    {
+        let (fib, rx) = fib::send_error(fib::new_try_fn(f));
+        self.add_fib(fib);
+        rx
+    }
+
+    /// Adds a fiber that calls the fallible closure `f` once.
+    ///
+    /// The returned receiver resolves to the error of the closure. If the
+    /// closure succeeds, or the fiber is dropped, the receiver resolves to
+    /// [`Canceled`](crate::sync::spsc::oneshot::Canceled).
+    #[inline]
+    fn add_try_once<F, E>(self, f: F) -> Receiver<E>
+    where
+        F: FnOnce() -> Result<(), E>,
+        F: Unpin + Send + 'static,
+        E: Send + 'static,
+    {
+        let (fib, rx) = fib::send_error(new_once(f));
+        self.add_fib(fib);
+        rx
+    }
 }
 
 impl<T: ThrToken> ThrFiberClosure for T {}
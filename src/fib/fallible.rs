@@ -0,0 +1,99 @@
+use crate::fib::{Fiber, FiberState, RootFiber};
+use crate::sync::spsc::oneshot::{channel, Receiver, Sender};
+use core::pin::Pin;
+
+/// Fiber for a fallible [`FnMut`] closure.
+///
+/// Can be created with [`fib::new_try_fn`](crate::fib::new_try_fn).
+pub struct FiberTryFn<F, Y, T, E>(Option<F>)
+where
+    F: FnMut() -> Result<FiberState<Y, T>, E>;
+
+pub(crate) struct SendError<F, E> {
+    fib: F,
+    tx: Option<Sender<E>>,
+}
+
+/// Creates a fiber that runs the fallible closure `f` until
+/// [`FiberState::Complete`] or an error is returned.
+///
+/// The fiber returns `Ok` with the completion value, or `Err` with the error
+/// returned by the closure. This allows using the `?` operator inside the
+/// closure.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::fib::{self, Fiber};
+/// use std::pin::Pin;
+///
+/// let mut fib = fib::new_try_fn(|| {
+///     let value = "12x".parse::<u32>()?;
+///     Ok(fib::Complete(value))
+/// });
+/// let state = Pin::new(&mut fib).resume(());
+/// assert!(matches!(state, fib::Complete(Err(_))));
+/// ```
+#[inline]
+pub fn new_try_fn<F, Y, T, E>(f: F) -> FiberTryFn<F, Y, T, E>
+where
+    F: FnMut() -> Result<FiberState<Y, T>, E>,
+{
+    FiberTryFn(Some(f))
+}
+
+impl<F, Y, T, E> Fiber for FiberTryFn<F, Y, T, E>
+where
+    F: FnMut() -> Result<FiberState<Y, T>, E>,
+{
+    type Input = ();
+    type Return = Result<T, E>;
+    type Yield = Y;
+
+    fn resume(self: Pin<&mut Self>, (): ()) -> FiberState<Y, Result<T, E>> {
+        let option = unsafe { &mut self.get_unchecked_mut().0 };
+        match option {
+            Some(f) => match f() {
+                Ok(FiberState::Yielded(value)) => FiberState::Yielded(value),
+                Ok(FiberState::Complete(value)) => {
+                    *option = None;
+                    FiberState::Complete(Ok(value))
+                }
+                Err(err) => {
+                    *option = None;
+                    FiberState::Complete(Err(err))
+                }
+            },
+            None => panic!("fiber resumed after completion"),
+        }
+    }
+}
+
+impl<F, Y, T, E> RootFiber for SendError<F, E>
+where
+    F: Fiber<Input = (), Yield = Y, Return = Result<T, E>>,
+    F: 'static,
+    E: 'static,
+{
+    #[inline]
+    fn advance(self: Pin<&mut Self>) -> bool {
+        let this = unsafe { self.get_unchecked_mut() };
+        match unsafe { Pin::new_unchecked(&mut this.fib) }.resume(()) {
+            FiberState::Yielded(_) => false,
+            FiberState::Complete(Ok(_)) => true,
+            FiberState::Complete(Err(err)) => {
+                if let Some(tx) = this.tx.take() {
+                    drop(tx.send(err));
+                }
+                true
+            }
+        }
+    }
+}
+
+/// Wraps the fallible fiber `fib` into a root fiber, which sends the error of
+/// `fib` to the returned receiver.
+pub(crate) fn send_error<F, E>(fib: F) -> (SendError<F, E>, Receiver<E>) {
+    let (tx, rx) = channel();
+    (SendError { fib, tx: Some(tx) }, rx)
+}
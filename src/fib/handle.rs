@@ -0,0 +1,95 @@
+use crate::fib::RootFiber;
+use crate::sync::spsc::oneshot::{channel, Receiver, Sender};
+use core::fmt;
+use core::pin::Pin;
+
+/// A handle to a fiber attached to a thread, which can cancel the fiber.
+///
+/// The handle is returned by the `*_cancelable` methods of
+/// [`ThrFiberClosure`](crate::fib::ThrFiberClosure), like
+/// [`add_fn_cancelable`](crate::fib::ThrFiberClosure::add_fn_cancelable).
+/// Dropping the handle doesn't affect the fiber.
+///
+/// # Examples
+///
+/// ```
+/// # #![feature(never_type)]
+/// # use drone_core::token::Token;
+/// # drone_core::thr::pool! {
+/// #     thread => Thr {};
+/// #     local => ThrLocal {};
+/// #     index => Thrs;
+/// #     threads => { sys_tick };
+/// # }
+/// # fn main() {
+/// #     let thr = unsafe { Thrs::take() };
+/// use drone_core::fib;
+/// use drone_core::thr::prelude::*;
+///
+/// let handle = thr.sys_tick.add_fn_cancelable(|| fib::Yielded::<(), !>(()));
+/// assert!(!handle.is_complete());
+/// // The fiber will be dropped on the next SYS_TICK activation.
+/// handle.cancel();
+/// # }
+/// ```
+pub struct FiberHandle {
+    tx: Sender<()>,
+}
+
+pub(crate) struct Cancelable<F> {
+    fib: F,
+    rx: Receiver<()>,
+}
+
+impl FiberHandle {
+    /// Returns `true` if the fiber has been dropped.
+    ///
+    /// A fiber is dropped by the thread once it returned or was canceled, or
+    /// when the fiber chain is dropped.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.tx.is_canceled()
+    }
+
+    /// Cancels the fiber.
+    ///
+    /// The fiber is not resumed anymore, and is dropped on the next thread
+    /// activation.
+    #[inline]
+    pub fn cancel(self) {
+        drop(self.tx.send(()));
+    }
+}
+
+impl fmt::Debug for FiberHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FiberHandle").field("is_complete", &self.is_complete()).finish()
+    }
+}
+
+impl<F: RootFiber> RootFiber for Cancelable<F> {
+    #[inline]
+    fn advance(self: Pin<&mut Self>) -> bool {
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Ok(Some(())) = this.rx.try_recv() {
+            return true;
+        }
+        unsafe { Pin::new_unchecked(&mut this.fib) }.advance()
+    }
+}
+
+pub(crate) fn cancelable<F: RootFiber>(fib: F) -> (Cancelable<F>, FiberHandle) {
+    let (tx, rx) = channel();
+    (Cancelable { fib, rx }, FiberHandle { tx })
+}
+
+pub(crate) fn cancelable_factory<C, F>(
+    factory: C,
+) -> (impl FnOnce() -> Cancelable<F> + Send + 'static, FiberHandle)
+where
+    C: FnOnce() -> F + Send + 'static,
+    F: RootFiber,
+{
+    let (tx, rx) = channel();
+    (move || Cancelable { fib: factory(), rx }, FiberHandle { tx })
+}
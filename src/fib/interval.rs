@@ -0,0 +1,198 @@
+use crate::fib::{Fiber, FiberState, ReturnNone, RootFiber};
+use core::pin::Pin;
+
+/// What an interval fiber does when several periods elapsed since its
+/// previous resumption.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IntervalPolicy {
+    /// Call the closure once for each elapsed period.
+    CatchUp,
+    /// Call the closure once, and skip the rest of the elapsed periods.
+    Skip,
+}
+
+/// Fiber calling a [`FnMut`] closure every `n` ticks.
+///
+/// Can be created with [`fib::new_interval`](crate::fib::new_interval).
+pub struct FiberInterval<F, R>
+where
+    F: FnMut() -> FiberState<(), R>,
+{
+    f: Option<F>,
+    n: u32,
+    ticks: u32,
+    policy: IntervalPolicy,
+}
+
+/// Creates a fiber that calls the closure `f` every `n` ticks, until
+/// [`FiberState::Complete`] is returned.
+///
+/// The fiber input is the number of ticks elapsed since the previous
+/// resumption. When attached to a thread directly, each thread activation
+/// counts as one tick. [`ThrFiberClosure::add_every_ticks`] reads the elapsed
+/// ticks from a free-running counter instead. A resumption spanning several
+/// periods follows the [`IntervalPolicy`] set with
+/// [`FiberInterval::with_policy`], which is [`IntervalPolicy::Skip`] by
+/// default.
+///
+/// [`ThrFiberClosure::add_every_ticks`]: crate::fib::ThrFiberClosure::add_every_ticks
+///
+/// # Panics
+///
+/// If `n` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::fib::{self, Fiber, IntervalPolicy};
+/// use std::pin::Pin;
+///
+/// let mut calls = 0;
+/// let mut fib = fib::new_interval(4, || {
+///     calls += 1;
+///     fib::Yielded::<(), ()>(())
+/// })
+/// .with_policy(IntervalPolicy::CatchUp);
+/// // Three periods elapsed at once.
+/// assert!(Pin::new(&mut fib).resume(12).is_yielded());
+/// drop(fib);
+/// assert_eq!(calls, 3);
+/// ```
+#[inline]
+pub fn new_interval<F, R>(n: u32, f: F) -> FiberInterval<F, R>
+where
+    F: FnMut() -> FiberState<(), R>,
+{
+    assert!(n > 0, "activation divider must be non-zero");
+    FiberInterval { f: Some(f), n, ticks: 0, policy: IntervalPolicy::Skip }
+}
+
+impl<F, R> FiberInterval<F, R>
+where
+    F: FnMut() -> FiberState<(), R>,
+{
+    /// Sets the policy for resumptions spanning several periods.
+    #[must_use]
+    #[inline]
+    pub fn with_policy(mut self, policy: IntervalPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+impl<F, R> Fiber for FiberInterval<F, R>
+where
+    F: FnMut() -> FiberState<(), R>,
+{
+    type Input = u32;
+    type Return = R;
+    type Yield = ();
+
+    fn resume(self: Pin<&mut Self>, elapsed: u32) -> FiberState<(), R> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let Some(f) = &mut this.f else {
+            panic!("fiber resumed after completion");
+        };
+        this.ticks = this.ticks.saturating_add(elapsed);
+        while this.ticks >= this.n {
+            this.ticks = match this.policy {
+                IntervalPolicy::CatchUp => this.ticks - this.n,
+                IntervalPolicy::Skip => this.ticks % this.n,
+            };
+            if let FiberState::Complete(complete) = f() {
+                this.f = None;
+                return FiberState::Complete(complete);
+            }
+        }
+        FiberState::Yielded(())
+    }
+}
+
+#[allow(clippy::mismatching_type_param_order)]
+impl<F, R> RootFiber for FiberInterval<F, R>
+where
+    F: FnMut() -> FiberState<(), R>,
+    F: 'static,
+    R: ReturnNone,
+{
+    #[inline]
+    fn advance(self: Pin<&mut Self>) -> bool {
+        match self.resume(1) {
+            FiberState::Yielded(()) => false,
+            FiberState::Complete(_) => true,
+        }
+    }
+}
+
+/// Root fiber resuming an interval fiber with the ticks elapsed on a
+/// free-running counter.
+pub(crate) struct Ticked<T, F, R>
+where
+    T: FnMut() -> u32,
+    F: FnMut() -> FiberState<(), R>,
+{
+    ticks: T,
+    last: u32,
+    fib: FiberInterval<F, R>,
+}
+
+impl<T, F, R> Ticked<T, F, R>
+where
+    T: FnMut() -> u32,
+    F: FnMut() -> FiberState<(), R>,
+{
+    /// Wraps `fib`, counting the elapsed ticks from the current value of
+    /// `ticks`.
+    pub(crate) fn new(mut ticks: T, fib: FiberInterval<F, R>) -> Self {
+        let last = ticks();
+        Self { ticks, last, fib }
+    }
+}
+
+impl<T, F, R> RootFiber for Ticked<T, F, R>
+where
+    T: FnMut() -> u32,
+    T: 'static,
+    F: FnMut() -> FiberState<(), R>,
+    F: 'static,
+    R: ReturnNone,
+{
+    #[inline]
+    fn advance(self: Pin<&mut Self>) -> bool {
+        let this = unsafe { self.get_unchecked_mut() };
+        let now = (this.ticks)();
+        let elapsed = now.wrapping_sub(this.last);
+        this.last = now;
+        match unsafe { Pin::new_unchecked(&mut this.fib) }.resume(elapsed) {
+            FiberState::Yielded(()) => false,
+            FiberState::Complete(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(policy: IntervalPolicy, elapsed: &[u32]) -> usize {
+        let mut calls = 0;
+        let mut fib = new_interval(3, || {
+            calls += 1;
+            FiberState::Yielded::<(), ()>(())
+        })
+        .with_policy(policy);
+        for &ticks in elapsed {
+            assert!(unsafe { Pin::new_unchecked(&mut fib) }.resume(ticks).is_yielded());
+        }
+        drop(fib);
+        calls
+    }
+
+    #[test]
+    fn policies() {
+        assert_eq!(count(IntervalPolicy::Skip, &[1, 1, 1, 1, 1, 1]), 2);
+        assert_eq!(count(IntervalPolicy::CatchUp, &[1, 1, 1, 1, 1, 1]), 2);
+        assert_eq!(count(IntervalPolicy::Skip, &[7, 2]), 2);
+        assert_eq!(count(IntervalPolicy::CatchUp, &[7, 2]), 3);
+    }
+}
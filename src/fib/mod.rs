@@ -11,6 +11,22 @@
 //! and proceeds to the next fiber. When there are no fibers left, the thread
 //! suspends.
 //!
+//! With the `fib-priority` feature enabled, a fiber can be added with a
+//! priority through the token returned by
+//! [`token.with_priority::<P>()`](crate::thr::ThrToken::with_priority), which
+//! works with all fiber extension methods. The thread executes fibers with
+//! higher priorities first, and falls back to LIFO order among fibers with
+//! equal priorities. This allows ordering fibers within a single thread,
+//! instead of splitting them into several threads. There are
+//! [`Chain::PRIORITIES`] priority levels, each with its own list, so a thread
+//! activation still visits each fiber once.
+//!
+//! With the `fib-budget` feature enabled, the chain measures the cycles spent
+//! by each fiber resumption with the `drone_cycle_counter` platform hook, see
+//! [`platform::cycles`](crate::platform::cycles). The accumulated budgets are
+//! available through [`Chain::budgets`], which helps to find the fiber
+//! starving its thread.
+//!
 //! # Basic Fibers
 //!
 //! A basic fiber can be created with [`fib::new`](new),
@@ -99,6 +115,29 @@
 //! # }
 //! ```
 //!
+//! A fallible fiber can be created with [`fib::new_try_fn`](new_try_fn), whose
+//! closure returns a [`Result`] and can use the `?` operator. The
+//! [`token.add_try_fn(...)`](ThrFiberClosure::add_try_fn) and
+//! [`token.add_try_once(...)`](ThrFiberClosure::add_try_once) methods return a
+//! oneshot receiver, which resolves to the fiber error. This way an error can
+//! be propagated out of an interrupt handler without a dedicated channel.
+//!
+//! A periodic fiber can be created with [`fib::new_interval`](new_interval),
+//! or added with [`token.add_every(...)`](ThrFiberClosure::add_every), which
+//! calls its closure every `n`-th thread activation. This is useful for slow
+//! periodic work driven by a fast periodic interrupt. The
+//! [`token.add_every_ticks(...)`](ThrFiberClosure::add_every_ticks) method
+//! measures the periods on a free-running tick counter instead, so delayed or
+//! coalesced activations are caught up or skipped according to an
+//! [`IntervalPolicy`].
+//!
+//! The [`token.add_fn_cancelable(...)`](ThrFiberClosure::add_fn_cancelable)
+//! family of methods return a [`FiberHandle`], which can cancel the fiber
+//! later, e.g. when a driver is torn down. The plain methods don't have this
+//! overhead. A future returned by
+//! [`token.add_future(...)`](ThrFiberFuture::add_future) cancels its fiber
+//! when dropped.
+//!
 //! # Compound Fibers
 //!
 //! There is a number of useful compound fibers implemented in this module:
@@ -155,19 +194,34 @@
 //! # }
 //! ```
 
+#[cfg(feature = "fib-budget")]
+mod budget;
 mod chain;
 mod closure;
+mod fallible;
 mod future;
 mod generator;
+mod handle;
+mod interval;
 mod stream_pulse;
 mod stream_ring;
+mod weak;
 
+#[cfg(feature = "fib-budget")]
+pub use self::budget::FiberBudget;
 pub use self::chain::Chain;
 pub use self::closure::{new_fn, new_once, FiberFn, FiberOnce, ThrFiberClosure};
+pub(crate) use self::fallible::send_error;
+pub use self::fallible::{new_try_fn, FiberTryFn};
 pub use self::future::{FiberFuture, ThrFiberFuture};
 pub use self::generator::{new, FiberGen, ThrFiberGen};
+pub(crate) use self::handle::{cancelable, cancelable_factory};
+pub use self::handle::FiberHandle;
+pub use self::interval::{new_interval, FiberInterval, IntervalPolicy};
 pub use self::stream_pulse::{FiberStreamPulse, ThrFiberStreamPulse, TryFiberStreamPulse};
 pub use self::stream_ring::{FiberStreamRing, ThrFiberStreamRing, TryFiberStreamRing};
+pub(crate) use self::weak::track;
+pub use self::weak::FiberWeak;
 pub use self::FiberState::*;
 use core::pin::Pin;
 
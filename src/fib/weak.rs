@@ -0,0 +1,48 @@
+use crate::fib::RootFiber;
+use crate::sync::spsc::oneshot::{channel, Receiver, Sender};
+use core::fmt;
+use core::pin::Pin;
+
+/// A weak handle to a fiber attached to a thread.
+///
+/// The handle doesn't keep the fiber alive and doesn't affect its execution.
+/// It is only able to tell whether the fiber is still in the fiber chain.
+///
+/// See [`ThrToken::add_fib_weak`](crate::thr::ThrToken::add_fib_weak).
+pub struct FiberWeak {
+    rx: Receiver<!>,
+}
+
+pub(crate) struct Tracked<F> {
+    fib: F,
+    _tx: Sender<!>,
+}
+
+impl FiberWeak {
+    /// Returns `true` if the fiber hasn't completed yet.
+    ///
+    /// A fiber is considered completed once it returned and the thread dropped
+    /// it, or the fiber chain was dropped.
+    #[inline]
+    pub fn is_alive(&mut self) -> bool {
+        matches!(self.rx.try_recv(), Ok(None))
+    }
+}
+
+impl fmt::Debug for FiberWeak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FiberWeak").finish_non_exhaustive()
+    }
+}
+
+impl<F: RootFiber> RootFiber for Tracked<F> {
+    #[inline]
+    fn advance(self: Pin<&mut Self>) -> bool {
+        unsafe { self.map_unchecked_mut(|x| &mut x.fib) }.advance()
+    }
+}
+
+pub(crate) fn track<F: RootFiber>(fib: F) -> (Tracked<F>, FiberWeak) {
+    let (tx, rx) = channel();
+    (Tracked { fib, _tx: tx }, FiberWeak { rx })
+}
@@ -0,0 +1,176 @@
+use core::alloc::{AllocError, Allocator, Layout};
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ptr::NonNull;
+
+#[cfg(all(feature = "atomics", not(loom)))]
+type AtomicUsize = core::sync::atomic::AtomicUsize;
+#[cfg(all(feature = "atomics", loom))]
+type AtomicUsize = loom::sync::atomic::AtomicUsize;
+#[cfg(not(feature = "atomics"))]
+type AtomicUsize = crate::sync::soft_atomic::Atomic<usize>;
+
+/// A bump allocator over `N` bytes of its own storage.
+///
+/// An allocation only moves the top of the arena, which makes it cheaper than
+/// a pool heap, and doesn't waste memory on rounding up to a block size.
+/// Individual deallocations don't reclaim memory, except for the most recent
+/// allocation. Instead, the memory allocated within [`Arena::scope`] is freed
+/// all at once when the scope ends, which suits transient per-frame or
+/// per-request data.
+///
+/// The storage is a part of the arena value, so [`Allocator`] is implemented
+/// for `&Arena` only. The borrow keeps the arena in place while its blocks are
+/// alive.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(allocator_api)]
+///
+/// use drone_core::heap::Arena;
+///
+/// let mut arena = Arena::<256>::new();
+/// let sum = arena.scope(|arena| {
+///     let mut numbers = Vec::with_capacity_in(16, arena);
+///     numbers.extend(1..=16_u32);
+///     numbers.iter().sum::<u32>()
+/// });
+/// assert_eq!(sum, 136);
+/// assert_eq!(arena.used(), 0);
+/// ```
+pub struct Arena<const N: usize> {
+    storage: UnsafeCell<MaybeUninit<[u8; N]>>,
+    top: AtomicUsize,
+}
+
+unsafe impl<const N: usize> Sync for Arena<N> {}
+
+impl<const N: usize> Arena<N> {
+    maybe_const_fn! {
+        /// Creates a new empty arena.
+        #[inline]
+        pub const fn new() -> Self {
+            Self { storage: UnsafeCell::new(MaybeUninit::uninit()), top: AtomicUsize::new(0) }
+        }
+    }
+
+    /// Returns the size of the arena storage in bytes.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of allocated bytes, including alignment padding.
+    #[inline]
+    pub fn used(&self) -> usize {
+        load_atomic!(self.top, Relaxed)
+    }
+
+    /// Runs `f`, and frees everything it allocated from the arena.
+    ///
+    /// Allocations made before the scope are kept. The exclusive borrow
+    /// guarantees that no allocations outlive the scope.
+    pub fn scope<R>(&mut self, f: impl FnOnce(&Self) -> R) -> R {
+        let mark = self.used();
+        let result = f(self);
+        store_atomic!(self.top, mark, Relaxed);
+        result
+    }
+
+    /// Frees all allocations of the arena.
+    #[inline]
+    pub fn reset(&mut self) {
+        store_atomic!(self.top, 0, Relaxed);
+    }
+
+    fn start(&self) -> usize {
+        self.storage.get() as usize
+    }
+}
+
+/// Returns the offset of a block with `layout` placed above `top`, and the
+/// new top.
+fn bump(start: usize, top: usize, layout: Layout, capacity: usize) -> Option<(usize, usize)> {
+    let address = start.checked_add(top)?.checked_add(layout.align() - 1)? & !(layout.align() - 1);
+    let offset = address - start;
+    let end = offset.checked_add(layout.size())?;
+    (end <= capacity).then_some((offset, end))
+}
+
+impl<const N: usize> Default for Arena<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for Arena<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arena").field("capacity", &N).field("used", &self.used()).finish()
+    }
+}
+
+unsafe impl<const N: usize> Allocator for &Arena<N> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let start = self.start();
+        let top = load_try_modify_atomic!(self.top, Relaxed, Relaxed, |top| {
+            bump(start, top, layout, N).map(|(_, end)| end)
+        })
+        .map_err(|_| AllocError)?;
+        let (offset, _) = bump(start, top, layout, N).ok_or(AllocError)?;
+        let ptr = unsafe { NonNull::new_unchecked((start + offset) as *mut u8) };
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Only the most recent allocation can be reclaimed.
+        let offset = ptr.as_ptr() as usize - self.start();
+        let _ = load_try_modify_atomic!(self.top, Relaxed, Relaxed, |top| {
+            (top == offset + layout.size()).then_some(offset)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bump_and_align() {
+        let arena = &Arena::<64>::new();
+        let a = arena.allocate(Layout::from_size_align(3, 1).unwrap()).unwrap();
+        let b = arena.allocate(Layout::from_size_align(8, 8).unwrap()).unwrap();
+        assert_eq!(b.as_mut_ptr() as usize % 8, 0);
+        assert!(b.as_mut_ptr() as usize >= a.as_mut_ptr() as usize + 3);
+        assert!(arena.allocate(Layout::from_size_align(64, 1).unwrap()).is_err());
+    }
+
+    #[test]
+    fn reclaim_last() {
+        let arena = &Arena::<64>::new();
+        let layout = Layout::from_size_align(16, 1).unwrap();
+        let a = arena.allocate(layout).unwrap();
+        let b = arena.allocate(layout).unwrap();
+        unsafe { arena.deallocate(a.as_non_null_ptr(), layout) };
+        assert_eq!(arena.used(), 32);
+        unsafe { arena.deallocate(b.as_non_null_ptr(), layout) };
+        assert_eq!(arena.used(), 16);
+    }
+
+    #[test]
+    fn scope() {
+        let mut arena = Arena::<64>::new();
+        let layout = Layout::from_size_align(16, 1).unwrap();
+        (&arena).allocate(layout).unwrap();
+        arena.scope(|arena| {
+            arena.allocate(layout).unwrap();
+            arena.allocate(layout).unwrap();
+            assert_eq!(arena.used(), 48);
+        });
+        assert_eq!(arena.used(), 16);
+        arena.reset();
+        assert_eq!(arena.used(), 0);
+    }
+}
@@ -0,0 +1,332 @@
+use super::Pool;
+use crate::thr::{preemption_depth, Thread};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::ptr;
+use core::sync::atomic::{compiler_fence, Ordering};
+use core::ptr::NonNull;
+
+/// A cache of free blocks in front of the shared pool free lists.
+///
+/// Normally this trait is implemented by [`ThreadCache`], or by [`NoCache`]
+/// for heaps without a cache.
+pub trait BlockCache: Sync {
+    /// Takes a cached block of the pool number `pool_idx`.
+    fn pop(&self, pool_idx: usize) -> Option<NonNull<u8>>;
+
+    /// Puts the block `ptr` of the pool number `pool_idx` into the cache.
+    /// Returns `false` if the block wasn't cached and must be returned to the
+    /// pool by the caller.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must point to a block of `pools[pool_idx]`, which is no longer
+    ///   used.
+    /// * `pools` must be the pools of the heap owning the cache.
+    unsafe fn push(&self, pools: &[Pool], pool_idx: usize, ptr: NonNull<u8>) -> bool;
+}
+
+/// A [`BlockCache`], which never caches blocks.
+///
+/// Used by heaps without a [`ThreadCache`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoCache;
+
+/// Per-thread caches of recently freed blocks.
+///
+/// The cache keeps a magazine of up to `N` blocks for each of `POOLS` pools
+/// and each of `LEVELS` preemption levels of the thread pool `T` (see
+/// [`thr::preemption_depth`](crate::thr::preemption_depth)). A thread running
+/// at a particular level can't be interleaved with another thread of `T`
+/// running at the same level, therefore a magazine is accessed without atomic
+/// operations. When a magazine is full, a half of it is flushed back to the
+/// pool.
+///
+/// Code running outside of the thread pool `T`, or at a level deeper than
+/// `LEVELS`, uses the pools directly.
+///
+/// Blocks cached by a preempted thread are unavailable to the preempting one.
+/// [`ThreadCache::flush_preempted`] returns them to the pools, and is intended
+/// to be called from the `on_preempt` hook of the thread pool (see
+/// [`Thread::on_preempt`](crate::thr::Thread::on_preempt)).
+///
+/// # Safety Notes
+///
+/// The cache relies on the preemption depth of `T` maintained by
+/// [`Thread::call`](crate::thr::Thread::call). An interrupt handler, which
+/// allocates without entering a thread of `T`, including the threads of other
+/// pools running on the same core, must not preempt the threads of `T`. The
+/// depth of `T` is not visible to other cores, therefore the heap owning the
+/// cache must not be used by a core other than the one running `T`.
+pub struct ThreadCache<T: Thread, const LEVELS: usize, const POOLS: usize, const N: usize> {
+    levels: [Level<POOLS, N>; LEVELS],
+    _thr: PhantomData<T>,
+}
+
+struct Level<const POOLS: usize, const N: usize> {
+    /// Set while a thread of this level is in the middle of a cache operation.
+    busy: Cell<bool>,
+    magazines: [Magazine<N>; POOLS],
+}
+
+struct Magazine<const N: usize> {
+    len: Cell<usize>,
+    blocks: [Cell<*mut u8>; N],
+}
+
+unsafe impl<T: Thread, const LEVELS: usize, const POOLS: usize, const N: usize> Sync
+    for ThreadCache<T, LEVELS, POOLS, N>
+{
+}
+
+impl<T: Thread, const LEVELS: usize, const POOLS: usize, const N: usize>
+    ThreadCache<T, LEVELS, POOLS, N>
+{
+    #[allow(clippy::declare_interior_mutable_const)]
+    const LEVEL: Level<POOLS, N> = Level::EMPTY;
+
+    /// Creates an empty cache.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { levels: [Self::LEVEL; LEVELS], _thr: PhantomData }
+    }
+
+    /// Returns all blocks cached by the current thread preemption level back
+    /// to the pools.
+    ///
+    /// # Safety
+    ///
+    /// `pools` must be the pools of the heap owning the cache.
+    pub unsafe fn flush(&self, pools: &[Pool]) {
+        if let Some(level) = self.level() {
+            level.lock(|magazines| {
+                for (pool, magazine) in pools.iter().zip(magazines) {
+                    unsafe { magazine.drain_to(pool, 0) };
+                }
+            });
+        }
+    }
+
+    /// Returns all blocks cached by the thread preemption levels below the
+    /// current one back to the pools.
+    ///
+    /// A level, which was preempted in the middle of a cache operation, is
+    /// skipped.
+    ///
+    /// # Safety
+    ///
+    /// `pools` must be the pools of the heap owning the cache.
+    pub unsafe fn flush_preempted(&self, pools: &[Pool]) {
+        let depth = usize::from(preemption_depth::<T>());
+        unsafe { self.flush_below(pools, depth.saturating_sub(1)) };
+    }
+
+    /// Returns all blocks cached by all thread preemption levels back to the
+    /// pools.
+    ///
+    /// # Safety
+    ///
+    /// * `pools` must be the pools of the heap owning the cache.
+    /// * The cache must not be accessed concurrently, neither by a thread
+    ///   preempting the caller nor by a thread preempted in the middle of a
+    ///   cache operation.
+    pub unsafe fn flush_all(&self, pools: &[Pool]) {
+        for level in &self.levels {
+            for (pool, magazine) in pools.iter().zip(&level.magazines) {
+                unsafe { magazine.drain_to(pool, 0) };
+            }
+        }
+    }
+
+    unsafe fn flush_below(&self, pools: &[Pool], level: usize) {
+        for level in self.levels.iter().take(level) {
+            if level.busy.get() {
+                continue;
+            }
+            for (pool, magazine) in pools.iter().zip(&level.magazines) {
+                unsafe { magazine.drain_to(pool, 0) };
+            }
+        }
+    }
+
+    fn level(&self) -> Option<&Level<POOLS, N>> {
+        usize::from(preemption_depth::<T>()).checked_sub(1).and_then(|level| self.levels.get(level))
+    }
+}
+
+impl<T: Thread, const LEVELS: usize, const POOLS: usize, const N: usize> Default
+    for ThreadCache<T, LEVELS, POOLS, N>
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Thread, const LEVELS: usize, const POOLS: usize, const N: usize> BlockCache
+    for ThreadCache<T, LEVELS, POOLS, N>
+{
+    #[inline]
+    fn pop(&self, pool_idx: usize) -> Option<NonNull<u8>> {
+        self.level()?.lock(|magazines| magazines.get(pool_idx)?.pop())
+    }
+
+    #[inline]
+    unsafe fn push(&self, pools: &[Pool], pool_idx: usize, ptr: NonNull<u8>) -> bool {
+        let Some(level) = self.level() else {
+            return false;
+        };
+        level.lock(|magazines| {
+            let Some(magazine) = magazines.get(pool_idx) else {
+                return false;
+            };
+            if magazine.len.get() == N {
+                unsafe { magazine.drain_to(&pools[pool_idx], N / 2) };
+            }
+            magazine.push(ptr)
+        })
+    }
+}
+
+impl BlockCache for NoCache {
+    #[inline]
+    fn pop(&self, _pool_idx: usize) -> Option<NonNull<u8>> {
+        None
+    }
+
+    #[inline]
+    unsafe fn push(&self, _pools: &[Pool], _pool_idx: usize, _ptr: NonNull<u8>) -> bool {
+        false
+    }
+}
+
+impl<const POOLS: usize, const N: usize> Level<POOLS, N> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY: Self = Self { busy: Cell::new(false), magazines: [Magazine::EMPTY; POOLS] };
+
+    /// Runs `f` with the level marked busy, so a preempting thread doesn't
+    /// flush the magazines in the middle of the operation.
+    #[inline]
+    fn lock<R>(&self, f: impl FnOnce(&[Magazine<N>; POOLS]) -> R) -> R {
+        self.busy.set(true);
+        compiler_fence(Ordering::SeqCst);
+        let result = f(&self.magazines);
+        compiler_fence(Ordering::SeqCst);
+        self.busy.set(false);
+        result
+    }
+}
+
+impl<const N: usize> Magazine<N> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const BLOCK: Cell<*mut u8> = Cell::new(ptr::null_mut());
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY: Self = Self { len: Cell::new(0), blocks: [Self::BLOCK; N] };
+
+    fn pop(&self) -> Option<NonNull<u8>> {
+        let len = self.len.get().checked_sub(1)?;
+        self.len.set(len);
+        NonNull::new(self.blocks[len].get())
+    }
+
+    fn push(&self, ptr: NonNull<u8>) -> bool {
+        let len = self.len.get();
+        if len == N {
+            return false;
+        }
+        self.blocks[len].set(ptr.as_ptr());
+        self.len.set(len + 1);
+        true
+    }
+
+    unsafe fn drain_to(&self, pool: &Pool, len: usize) {
+        while self.len.get() > len {
+            if let Some(ptr) = self.pop() {
+                unsafe { pool.deallocate(ptr) };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fib::Chain;
+    use crate::thr::{CurrentState, LocalOpaque};
+    use core::mem::size_of;
+
+    struct Thr {
+        fib_chain: Chain,
+        local: LocalOpaque<Self>,
+    }
+
+    unsafe impl Thread for Thr {
+        type Local = ();
+
+        const COUNT: u16 = 1;
+
+        fn pool() -> *const Self {
+            static THREADS: [Thr; 1] =
+                [Thr { fib_chain: Chain::new(), local: LocalOpaque::new(()) }];
+            THREADS.as_ptr()
+        }
+
+        fn current() -> *const CurrentState {
+            static CURRENT: CurrentState = CurrentState::new(0);
+            &CURRENT
+        }
+
+        fn depth() -> *const CurrentState {
+            static DEPTH: CurrentState = CurrentState::new(0);
+            &DEPTH
+        }
+
+        fn fib_chain(&self) -> &Chain {
+            &self.fib_chain
+        }
+
+        fn local_opaque(&self) -> &LocalOpaque<Self> {
+            &self.local
+        }
+    }
+
+    #[test]
+    fn magazine() {
+        let magazine = Magazine::<2>::EMPTY;
+        let mut blocks = [0_u8; 3];
+        let [a, b, c] = blocks.each_mut().map(NonNull::from);
+        assert!(magazine.pop().is_none());
+        assert!(magazine.push(a));
+        assert!(magazine.push(b));
+        assert!(!magazine.push(c));
+        assert_eq!(magazine.pop(), Some(b));
+        assert_eq!(magazine.pop(), Some(a));
+        assert!(magazine.pop().is_none());
+    }
+
+    #[test]
+    fn flush_below() {
+        let cache = ThreadCache::<Thr, 3, 1, 4>::new();
+        let mut memory = [0_usize; 3];
+        let pools = [Pool::new(memory.as_mut_ptr() as usize, size_of::<usize>(), 3)];
+        let [a, b, c] = memory.each_mut().map(|block| NonNull::from(block).cast());
+        assert!(cache.levels[0].magazines[0].push(a));
+        assert!(cache.levels[1].magazines[0].push(b));
+        assert!(cache.levels[2].magazines[0].push(c));
+        cache.levels[1].busy.set(true);
+        unsafe { cache.flush_below(&pools, 2) };
+        assert_eq!(cache.levels[0].magazines[0].len.get(), 0);
+        assert_eq!(cache.levels[1].magazines[0].len.get(), 1);
+        assert_eq!(cache.levels[2].magazines[0].len.get(), 1);
+        assert_eq!(pools[0].allocate(), Some(a));
+    }
+
+    #[test]
+    fn outside_of_threads() {
+        let cache = ThreadCache::<Thr, 2, 1, 4>::new();
+        let mut block = [0_u8; 4];
+        let pools = [Pool::new(block.as_mut_ptr() as usize, 4, 1)];
+        assert!(!unsafe { cache.push(&pools, 0, NonNull::from(&mut block[0])) });
+        assert!(cache.pop(0).is_none());
+    }
+}
@@ -0,0 +1,109 @@
+use super::pool::{pool_by_ptr, Pool};
+use core::ptr::NonNull;
+
+#[cfg(any(feature = "atomics", loom))]
+type AtomicU32 = core::sync::atomic::AtomicU32;
+#[cfg(not(any(feature = "atomics", loom)))]
+type AtomicU32 = crate::sync::soft_atomic::Atomic<u32>;
+
+/// A bitmap of allocated blocks of a heap with `POOLS` pools, used to detect
+/// double and invalid frees.
+///
+/// The bitmap is maintained by a heap defined with [`heap!`](crate::heap!),
+/// when the `heap-check` feature is enabled.
+pub struct BlockBitmap<const WORDS: usize, const POOLS: usize> {
+    offsets: [usize; POOLS],
+    words: [AtomicU32; WORDS],
+}
+
+impl<const WORDS: usize, const POOLS: usize> BlockBitmap<WORDS, POOLS> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const WORD: AtomicU32 = AtomicU32::new(0);
+
+    /// Creates an empty bitmap. `offsets` are the indices of the first words
+    /// of the pools.
+    #[inline]
+    pub const fn new(offsets: [usize; POOLS]) -> Self {
+        Self { offsets, words: [Self::WORD; WORDS] }
+    }
+
+    /// Marks the block `ptr` as allocated.
+    ///
+    /// # Panics
+    ///
+    /// If the block is already marked as allocated, which means the heap is
+    /// corrupted.
+    pub fn mark_allocated(&self, pools: &[Pool; POOLS], base: *mut u8, ptr: NonNull<u8>) {
+        let (word, mask) = self.locate(pools, base, ptr);
+        let prev = fetch_or_atomic!(self.words[word], mask, Relaxed);
+        assert!(prev & mask == 0, "heap block {ptr:p} is allocated twice");
+    }
+
+    /// Marks the block `ptr` as free.
+    ///
+    /// # Panics
+    ///
+    /// If `ptr` doesn't point to a block of the heap, or the block is not
+    /// allocated.
+    pub fn mark_free(&self, pools: &[Pool; POOLS], base: *mut u8, ptr: NonNull<u8>) {
+        let (word, mask) = self.locate(pools, base, ptr);
+        let prev = fetch_and_atomic!(self.words[word], !mask, Relaxed);
+        assert!(prev & mask != 0, "double free of heap block {ptr:p}");
+    }
+
+    fn locate(&self, pools: &[Pool; POOLS], base: *mut u8, ptr: NonNull<u8>) -> (usize, u32) {
+        let Some(i) = pool_by_ptr(pools, base, ptr) else {
+            panic!("invalid free of {ptr:p}: not a heap block");
+        };
+        let start = if i == 0 { base } else { pools[i - 1].edge() };
+        let offset = ptr.as_ptr() as usize - start as usize;
+        assert!(offset % pools[i].size() == 0, "invalid free of {ptr:p}: not a block boundary");
+        let index = offset / pools[i].size();
+        (self.offsets[i] + index / 32, 1 << (index % 32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> ([u8; 96], BlockBitmap<2, 2>) {
+        ([0; 96], BlockBitmap::new([0, 1]))
+    }
+
+    #[test]
+    fn allocate_and_free() {
+        let (mut m, bitmap) = setup();
+        let o = m.as_mut_ptr() as usize;
+        let pools = [Pool::new(o, 4, 16), Pool::new(o + 64, 16, 2)];
+        let block = |address| NonNull::new(address as *mut u8).unwrap();
+        bitmap.mark_allocated(&pools, o as *mut u8, block(o + 8));
+        bitmap.mark_allocated(&pools, o as *mut u8, block(o + 80));
+        assert_eq!(load_atomic!(bitmap.words[0], Relaxed), 0b100);
+        assert_eq!(load_atomic!(bitmap.words[1], Relaxed), 0b10);
+        bitmap.mark_free(&pools, o as *mut u8, block(o + 8));
+        bitmap.mark_free(&pools, o as *mut u8, block(o + 80));
+        assert_eq!(load_atomic!(bitmap.words[0], Relaxed), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "double free")]
+    fn double_free() {
+        let (mut m, bitmap) = setup();
+        let o = m.as_mut_ptr() as usize;
+        let pools = [Pool::new(o, 4, 16), Pool::new(o + 64, 16, 2)];
+        let ptr = NonNull::new((o + 4) as *mut u8).unwrap();
+        bitmap.mark_allocated(&pools, o as *mut u8, ptr);
+        bitmap.mark_free(&pools, o as *mut u8, ptr);
+        bitmap.mark_free(&pools, o as *mut u8, ptr);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a block boundary")]
+    fn misaligned_free() {
+        let (mut m, bitmap) = setup();
+        let o = m.as_mut_ptr() as usize;
+        let pools = [Pool::new(o, 4, 16), Pool::new(o + 64, 16, 2)];
+        bitmap.mark_free(&pools, o as *mut u8, NonNull::new((o + 70) as *mut u8).unwrap());
+    }
+}
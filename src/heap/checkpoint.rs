@@ -0,0 +1,154 @@
+use super::pool::Pool;
+use super::stats::HeapCounters;
+use crate::stream::Stream;
+use core::fmt;
+use core::fmt::Write;
+
+/// A snapshot of per-pool allocation counts.
+///
+/// Created by the `checkpoint` method of a heap defined with
+/// [`heap!`](crate::heap!). Two checkpoints of the same heap can be compared
+/// with [`diff`] to detect memory leaks.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Checkpoint<const N: usize> {
+    sizes: [usize; N],
+    counts: [usize; N],
+}
+
+/// Net growth of per-pool allocation counts between two [`Checkpoint`]s.
+///
+/// See [`diff`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Diff<const N: usize> {
+    sizes: [usize; N],
+    growth: [isize; N],
+}
+
+#[doc(hidden)]
+pub fn checkpoint<const N: usize>(pools: &[Pool; N], counters: &HeapCounters<N>) -> Checkpoint<N> {
+    let mut sizes = [0; N];
+    let mut counts = [0; N];
+    for (i, (pool, counters)) in pools.iter().zip(counters.pools()).enumerate() {
+        sizes[i] = pool.size();
+        counts[i] = counters.allocated();
+    }
+    Checkpoint { sizes, counts }
+}
+
+/// Returns net growth of allocation counts from checkpoint `a` to checkpoint
+/// `b`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #![feature(allocator_api)]
+/// # #![feature(slice_ptr_get)]
+/// # drone_core::override_layout! { r#"
+/// # [ram]
+/// # main = { origin = 0x20000000, size = "20K" }
+/// # [data]
+/// # ram = "main"
+/// # [heap.main]
+/// # ram = "main"
+/// # size = "10K"
+/// # pools = [
+/// #     { block = "4", count = "896" },
+/// #     { block = "32", count = "80" },
+/// #     { block = "256", count = "16" },
+/// # ]
+/// # "# }
+/// use drone_core::heap;
+///
+/// heap! {
+///     layout => main;
+///     metadata => pub Heap;
+///     instance => pub HEAP;
+/// }
+///
+/// fn main() {
+///     let before = HEAP.checkpoint();
+///     // Do some work, which is expected to free all its memory.
+///     let after = HEAP.checkpoint();
+///     let diff = heap::diff(&before, &after);
+///     if !diff.is_empty() {
+///         diff.write_to(11);
+///     }
+/// }
+/// ```
+pub fn diff<const N: usize>(a: &Checkpoint<N>, b: &Checkpoint<N>) -> Diff<N> {
+    let mut growth = [0; N];
+    for (i, growth) in growth.iter_mut().enumerate() {
+        *growth = b.counts[i] as isize - a.counts[i] as isize;
+    }
+    Diff { sizes: b.sizes, growth }
+}
+
+impl<const N: usize> Checkpoint<N> {
+    /// Returns block sizes of the pools.
+    #[inline]
+    pub fn sizes(&self) -> &[usize; N] {
+        &self.sizes
+    }
+
+    /// Returns numbers of allocated blocks in the pools.
+    #[inline]
+    pub fn counts(&self) -> &[usize; N] {
+        &self.counts
+    }
+}
+
+impl<const N: usize> Diff<N> {
+    /// Returns block sizes of the pools.
+    #[inline]
+    pub fn sizes(&self) -> &[usize; N] {
+        &self.sizes
+    }
+
+    /// Returns net growth of allocated blocks in the pools.
+    #[inline]
+    pub fn growth(&self) -> &[isize; N] {
+        &self.growth
+    }
+
+    /// Returns `true` if no pool has changed its allocation count.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.growth.iter().all(|&growth| growth == 0)
+    }
+
+    /// Writes the report into the stream number `stream`, if the stream is
+    /// enabled.
+    pub fn write_to(&self, stream: u8) {
+        let mut stream = Stream::new(stream);
+        if stream.is_enabled() {
+            let _ = write!(stream, "{self}");
+        }
+    }
+}
+
+impl<const N: usize> fmt::Display for Diff<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (size, growth) in self.sizes.iter().zip(&self.growth) {
+            if *growth != 0 {
+                writeln!(f, "pool {size}: {growth:+} blocks")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_growth() {
+        let a = Checkpoint { sizes: [4, 32, 256], counts: [10, 2, 1] };
+        let b = Checkpoint { sizes: [4, 32, 256], counts: [12, 2, 0] };
+        let diff = diff(&a, &b);
+        assert_eq!(diff.growth(), &[2, 0, -1]);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.to_string(), "pool 4: +2 blocks\npool 256: -1 blocks\n");
+        assert!(super::diff(&a, &a).is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+use super::pool::{Pool, MAX_BLOCKS};
+use super::stats::HeapCounters;
+use crate::platform::Interrupts;
+use core::fmt;
+use core::mem::size_of;
+
+/// Run-time parameters of a heap pool.
+///
+/// See the `init_runtime` method of a heap defined with
+/// [`heap!`](crate::heap!).
+// This structure can be patched by a debug probe or read from flash.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PoolConfig {
+    /// Block size in bytes.
+    pub block: usize,
+    /// Number of blocks.
+    pub count: usize,
+}
+
+/// An error returned by the `init_runtime` method of a heap.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoolConfigError {
+    /// The block size of the pool is zero, or is not a multiple of the word
+    /// size.
+    InvalidBlock(usize),
+    /// The block size of the pool is not larger than the block size of the
+    /// previous pool.
+    Unordered(usize),
+    /// The pools don't fit into the heap memory.
+    Overflow,
+    /// The heap has allocated blocks.
+    InUse,
+    /// The pool has more blocks than a pool can address, which is `2^20 - 1`
+    /// on 32-bit targets, or than the block bitmap of the `heap-check` feature
+    /// can track. The bitmap is sized by the counts from `layout.toml`.
+    TooManyBlocks(usize),
+}
+
+/// Replaces the geometry of `pools` with `config`.
+///
+/// `flush` must return all blocks cached in front of the pools, because a
+/// cached block is counted as deallocated, but would be handed out again
+/// after the pools are reshaped. `flush` is called with interrupts paused.
+#[doc(hidden)]
+pub unsafe fn init_runtime<const N: usize>(
+    pools: &[Pool; N],
+    base: *mut u8,
+    capacity: usize,
+    max_counts: Option<&[usize; N]>,
+    counters: &HeapCounters<N>,
+    flush: impl FnOnce(),
+    config: &[PoolConfig; N],
+) -> Result<(), PoolConfigError> {
+    validate(config, capacity, max_counts.map(|counts| &counts[..]))?;
+    Interrupts::paused(|| {
+        flush();
+        if !counters.is_idle() {
+            return Err(PoolConfigError::InUse);
+        }
+        let mut address = base as usize;
+        for (pool, config) in pools.iter().zip(config) {
+            unsafe { pool.reshape(address, config.block, config.count) };
+            address += config.block * config.count;
+        }
+        Ok(())
+    })
+}
+
+fn validate(
+    config: &[PoolConfig],
+    capacity: usize,
+    max_counts: Option<&[usize]>,
+) -> Result<(), PoolConfigError> {
+    let mut size = 0_usize;
+    for (i, pool) in config.iter().enumerate() {
+        if pool.block == 0 || pool.block % size_of::<usize>() != 0 {
+            return Err(PoolConfigError::InvalidBlock(i));
+        }
+        if pool.count > MAX_BLOCKS || max_counts.map_or(false, |counts| pool.count > counts[i]) {
+            return Err(PoolConfigError::TooManyBlocks(i));
+        }
+        if i > 0 && pool.block <= config[i - 1].block {
+            return Err(PoolConfigError::Unordered(i));
+        }
+        size = pool
+            .block
+            .checked_mul(pool.count)
+            .and_then(|pool_size| size.checked_add(pool_size))
+            .ok_or(PoolConfigError::Overflow)?;
+    }
+    if size > capacity {
+        return Err(PoolConfigError::Overflow);
+    }
+    Ok(())
+}
+
+impl fmt::Display for PoolConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBlock(i) => write!(f, "invalid block size of pool {i}"),
+            Self::Unordered(i) => write!(f, "block size of pool {i} is out of order"),
+            Self::Overflow => write!(f, "pools don't fit into the heap"),
+            Self::InUse => write!(f, "heap has allocated blocks"),
+            Self::TooManyBlocks(i) => write!(f, "pool {i} has too many blocks"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: usize = size_of::<usize>();
+
+    fn pool(block: usize, count: usize) -> PoolConfig {
+        PoolConfig { block, count }
+    }
+
+    #[test]
+    fn validate_config() {
+        use PoolConfigError::*;
+        assert_eq!(validate(&[pool(W, 4), pool(4 * W, 2)], 12 * W, None), Ok(()));
+        assert_eq!(validate(&[pool(W, 4), pool(4 * W, 2)], 11 * W, None), Err(Overflow));
+        assert_eq!(validate(&[pool(W, 4), pool(W, 2)], 100 * W, None), Err(Unordered(1)));
+        assert_eq!(validate(&[pool(W + 1, 4)], 100 * W, None), Err(InvalidBlock(0)));
+        assert_eq!(validate(&[pool(W, MAX_BLOCKS)], usize::MAX, None), Ok(()));
+        assert_eq!(
+            validate(&[pool(W, MAX_BLOCKS + 1)], usize::MAX, None),
+            Err(TooManyBlocks(0))
+        );
+        assert_eq!(validate(&[pool(usize::MAX & !(W - 1), 2)], usize::MAX, None), Err(Overflow));
+        assert_eq!(validate(&[pool(W, 4), pool(4 * W, 2)], 12 * W, Some(&[4, 2])), Ok(()));
+        assert_eq!(
+            validate(&[pool(W, 4), pool(4 * W, 3)], 100 * W, Some(&[4, 2])),
+            Err(TooManyBlocks(1))
+        );
+    }
+
+    #[test]
+    fn init_pools() {
+        let mut m = [0usize; 12];
+        let o = m.as_mut_ptr() as usize;
+        let pools = [Pool::new(0, 0, 0), Pool::new(0, 0, 0)];
+        let counters = HeapCounters::new();
+        let config = [pool(W, 4), pool(4 * W, 2)];
+        let mut flushed = false;
+        let flush = || flushed = true;
+        unsafe {
+            init_runtime(&pools, o as *mut u8, 12 * W, None, &counters, flush, &config).unwrap();
+        }
+        assert!(flushed);
+        assert_eq!(pools[0].size(), W);
+        assert_eq!(pools[0].edge() as usize, o + 4 * W);
+        assert_eq!(pools[1].size(), 4 * W);
+        assert_eq!(pools[1].edge() as usize, o + 12 * W);
+        assert!(pools[1].allocate().is_some());
+    }
+}
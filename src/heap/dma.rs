@@ -0,0 +1,83 @@
+use core::alloc::{AllocError, Allocator, Layout};
+use core::fmt;
+use core::ptr::NonNull;
+
+/// Heap residing in memory, which is accessible by DMA and is not cached.
+///
+/// This trait is implemented by [`heap!`](crate::heap!) for heaps defined
+/// with `unsafe dma => true`.
+///
+/// # Safety
+///
+/// All blocks returned by the allocator must be accessible by the DMA
+/// controllers of the target, and must not be subject to data caching.
+pub unsafe trait DmaAllocator: Allocator + Sync {}
+
+/// A handle to a DMA-capable heap.
+///
+/// Drivers can require this type as the allocator of their buffers, for
+/// example `Box<[u8], DmaHeap>`, to make sure the buffers are placed in the
+/// right memory.
+#[derive(Clone, Copy)]
+pub struct DmaHeap(&'static dyn DmaAllocator);
+
+impl DmaHeap {
+    /// Creates a new handle to the `heap`.
+    #[inline]
+    pub const fn new(heap: &'static dyn DmaAllocator) -> Self {
+        Self(heap)
+    }
+}
+
+impl fmt::Debug for DmaHeap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DmaHeap").finish_non_exhaustive()
+    }
+}
+
+unsafe impl Allocator for DmaHeap {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.allocate(layout)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.allocate_zeroed(layout)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { self.0.deallocate(ptr, layout) }
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        unsafe { self.0.grow(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        unsafe { self.0.grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        unsafe { self.0.shrink(ptr, old_layout, new_layout) }
+    }
+}
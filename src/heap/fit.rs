@@ -0,0 +1,119 @@
+use super::pool::{pool_range_by_layout, Pool};
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+use core::alloc::{Allocator, Layout};
+use core::mem::size_of;
+
+/// A heap, which can tell how many elements fit into its pool blocks.
+///
+/// Implemented for heaps defined with [`heap!`](crate::heap!).
+pub trait PoolFit {
+    /// Returns the largest capacity of `T` elements, which fits the pool a
+    /// `requested` capacity would land in.
+    ///
+    /// The result is never less than `requested`. If `requested` doesn't fit
+    /// any pool, it is returned unchanged.
+    fn fit_capacity<T>(&self, requested: usize) -> usize;
+}
+
+/// Extends [`Vec`] with pool-aware growth.
+pub trait VecExt {
+    /// Reserves capacity for at least `additional` more elements, rounding the
+    /// new capacity up to fill the whole block of the pool it lands in.
+    ///
+    /// Like [`Vec::reserve`], the capacity is at least doubled on growth, so
+    /// that repeated calls have amortized *O*(1) cost.
+    ///
+    /// # Panics
+    ///
+    /// If the new capacity overflows `usize`.
+    fn reserve_pool_fit<H: PoolFit>(&mut self, heap: &H, additional: usize);
+
+    /// Tries to reserve capacity for at least `additional` more elements,
+    /// rounding the new capacity up to fill the whole block of the pool it
+    /// lands in.
+    ///
+    /// This is a fallible version of [`VecExt::reserve_pool_fit`]. Using the
+    /// whole block avoids a grow-copy cycle on each of the following pushes,
+    /// which would fit the block anyway.
+    ///
+    /// # Errors
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an
+    /// error is returned.
+    fn try_reserve_pool_aligned<H: PoolFit>(
+        &mut self,
+        heap: &H,
+        additional: usize,
+    ) -> Result<(), TryReserveError>;
+}
+
+#[doc(hidden)]
+pub fn fit_capacity<T>(pools: &[Pool], requested: usize) -> usize {
+    let Ok(layout) = Layout::array::<T>(requested) else { return requested };
+    if layout.size() == 0 {
+        return requested;
+    }
+    pools
+        .get(pool_range_by_layout(pools, &layout).start)
+        .map_or(requested, |pool| pool.size() / size_of::<T>())
+}
+
+impl<T, A: Allocator> VecExt for Vec<T, A> {
+    fn reserve_pool_fit<H: PoolFit>(&mut self, heap: &H, additional: usize) {
+        let required = self.len().checked_add(additional).expect("capacity overflow");
+        if required <= self.capacity() {
+            return;
+        }
+        let capacity = heap.fit_capacity::<T>(required.max(self.capacity().saturating_mul(2)));
+        self.reserve_exact(capacity - self.len());
+    }
+
+    fn try_reserve_pool_aligned<H: PoolFit>(
+        &mut self,
+        heap: &H,
+        additional: usize,
+    ) -> Result<(), TryReserveError> {
+        let Some(required) = self.len().checked_add(additional) else {
+            // Let `Vec` report the capacity overflow.
+            return self.try_reserve(additional);
+        };
+        if required <= self.capacity() {
+            return Ok(());
+        }
+        let capacity = heap.fit_capacity::<T>(required.max(self.capacity().saturating_mul(2)));
+        self.try_reserve_exact(capacity - self.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit() {
+        let pools = [Pool::new(0, 4, 10), Pool::new(40, 32, 10), Pool::new(360, 256, 10)];
+        assert_eq!(fit_capacity::<u8>(&pools, 3), 4);
+        assert_eq!(fit_capacity::<u8>(&pools, 5), 32);
+        assert_eq!(fit_capacity::<u32>(&pools, 2), 8);
+        assert_eq!(fit_capacity::<[u8; 3]>(&pools, 2), 10);
+        assert_eq!(fit_capacity::<u32>(&pools, 65), 65);
+        assert_eq!(fit_capacity::<()>(&pools, 7), 7);
+    }
+
+    #[test]
+    fn try_reserve() {
+        struct Heap([Pool; 2]);
+        impl PoolFit for Heap {
+            fn fit_capacity<T>(&self, requested: usize) -> usize {
+                fit_capacity::<T>(&self.0, requested)
+            }
+        }
+        let heap = Heap([Pool::new(0, 4, 10), Pool::new(40, 32, 10)]);
+        let mut vec = Vec::<u16>::new();
+        vec.try_reserve_pool_aligned(&heap, 3).unwrap();
+        assert_eq!(vec.capacity(), 16);
+        vec.extend([1, 2, 3]);
+        assert!(vec.try_reserve_pool_aligned(&heap, usize::MAX).is_err());
+    }
+}
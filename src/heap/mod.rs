@@ -63,6 +63,98 @@
 //! }
 //! ```
 //!
+//! # Multiple Heaps
+//!
+//! Each `[heap.<name>]` section of the `layout.toml` can be placed into a
+//! different RAM region, for example a small heap in the fast tightly-coupled
+//! memory, and a big one in the main SRAM:
+//!
+//! ```toml
+//! [ram]
+//! main = { origin = 0x20000000, size = "128K" }
+//! dtcm = { origin = 0x10000000, size = "16K" }
+//!
+//! [heap.main]
+//! ram = "main"
+//! size = "64K"
+//! pools = [...]
+//!
+//! [heap.fast]
+//! ram = "dtcm"
+//! size = "8K"
+//! pools = [...]
+//! ```
+//!
+//! Every heap is defined with its own [`heap!`](crate::heap!) invocation, and
+//! has its own trace stream, statistics, and checkpoints. At most one of them
+//! can be the global allocator. The others are chosen per allocation by
+//! passing a reference to the heap as the [`Allocator`](core::alloc::Allocator)
+//! handle:
+//!
+//! ```ignore
+//! heap! {
+//!     layout => main;
+//!     metadata => pub Heap;
+//!     #[global_allocator]
+//!     instance => pub HEAP;
+//! }
+//!
+//! heap! {
+//!     layout => fast;
+//!     metadata => pub FastHeap;
+//!     instance => pub FAST_HEAP;
+//!     enable_trace_stream => 30;
+//! }
+//!
+//! // Allocated from the global `HEAP`.
+//! let samples = Vec::<u32>::with_capacity(1024);
+//! // Allocated from the `FAST_HEAP` in DTCM.
+//! let coeffs = Box::new_in([0_i16; 64], &FAST_HEAP);
+//! ```
+//!
+//! # DMA Heaps
+//!
+//! A heap placed into memory, which is accessible by DMA and is not cached,
+//! can be marked with the `dma` key of [`heap!`](crate::heap!). The macro then
+//! implements [`DmaAllocator`] for the heap, and adds a `dma` method returning
+//! a [`DmaHeap`] handle. Drivers requiring [`DmaHeap`] as the allocator of
+//! their buffers can't be given memory from a wrong region. Since
+//! [`DmaAllocator`] is an unsafe trait, the key must be marked `unsafe`, and a
+//! DMA heap can't have a `fallback` heap:
+//!
+//! ```ignore
+//! heap! {
+//!     layout => dma;
+//!     metadata => pub DmaPool;
+//!     instance => pub DMA_POOL;
+//!     // The `dma` heap is placed into a non-cacheable region.
+//!     unsafe dma => true;
+//! }
+//!
+//! fn start_transfer(buf: Box<[u8], DmaHeap>) { /* ... */ }
+//!
+//! let mut buf = Vec::with_capacity_in(64, DMA_POOL.dma());
+//! buf.resize(64, 0);
+//! start_transfer(buf.into_boxed_slice());
+//! ```
+//!
+//! It is the responsibility of the application to configure the memory region
+//! accordingly, e.g. with the MPU.
+//!
+//! # Object Pools
+//!
+//! Fixed-size objects, like messages passed from interrupt handlers, can be
+//! allocated from a dedicated [`ObjectPool`] instead of the heap. The pool is
+//! usually a `static`, which reserves memory for a fixed number of values, and
+//! returns [`PoolBox`] pointers to them.
+//!
+//! # Arenas
+//!
+//! Transient data, which is freed all at once, e.g. at the end of a frame or
+//! a request, can be allocated from an [`Arena`] instead of the heap. The
+//! arena is a bump allocator over a fixed-size buffer, and its
+//! [`scope`](Arena::scope) method frees everything allocated within it.
+//!
 //! # Tuning
 //!
 //! Using empiric values for the memory pools layout may lead to undesired
@@ -73,27 +165,301 @@
 //!
 //! The actual steps are platform-specific. Refer to the platform crate
 //! documentation for instructions.
+//!
+//! A cheaper estimate, which doesn't require tracing, is given by painting.
+//! With the `paint` key of [`heap!`](crate::heap!), the `init` function fills
+//! the heap with the given byte pattern, and the `high_water` method returns
+//! the highest number of blocks ever used in each pool:
+//!
+//! ```ignore
+//! heap! {
+//!     layout => main;
+//!     metadata => pub Heap;
+//!     instance => pub HEAP;
+//!     paint => 0xA5;
+//! }
+//!
+//! // After running the application for a while.
+//! let used_blocks = HEAP.high_water();
+//! ```
+//!
+//! On busy systems the trace can be narrowed down at the run-time with a
+//! [`TraceFilter`], which is returned by [`trace_filter`] for the trace stream
+//! number. The filter can skip blocks smaller than a minimum size, blocks of
+//! unselected pools, or trace only every N-th operation. The filters are
+//! stored in the [stream](crate::stream) runtime, so a debug probe can
+//! configure them too:
+//!
+//! ```ignore
+//! // Trace only allocations from the third pool and above.
+//! heap::trace_filter(31).set_pools(!0b11);
+//! ```
+//!
+//! Sampling breaks the pairing of allocations and deallocations, therefore a
+//! sampled trace is only suitable for estimating the allocation rates.
+//!
+//! With the `host` feature enabled, captured trace streams can be decoded
+//! programmatically with `TraceDecoder`, and the decoded packets can be fed
+//! into `TraceAggregator`, which recommends an optimized pools layout.
+//!
+//! The pools can also be tuned without recompiling the firmware. The
+//! `init_runtime` function of a heap replaces the pools from the
+//! `layout.toml` with a table of [`PoolConfig`], e.g. read from a flash
+//! configuration sector or patched by a debug probe. The new pools must fit
+//! into the memory reserved for the heap, and the heap must have no allocated
+//! blocks. Blocks held by the thread cache are flushed back to the pools
+//! first:
+//!
+//! ```ignore
+//! unsafe { Heap::init() };
+//! if let Some(pools) = read_pools_config() {
+//!     unsafe { Heap::init_runtime(&pools) }.expect("invalid pools configuration");
+//! }
+//! ```
+//!
+//! A pool can have at most `2^20 - 1` blocks on 32-bit targets. With the
+//! `heap-check` feature, the block bitmap is sized by the `layout.toml`,
+//! therefore a pool with more blocks than there is also rejected with
+//! [`PoolConfigError::TooManyBlocks`].
+//!
+//! # Leak Detection
+//!
+//! A heap defined with [`heap!`](crate::heap!) has a `checkpoint` method,
+//! which returns a [`Checkpoint`] with per-pool allocation counts. The counts
+//! are taken from the same counters as the [statistics](#statistics), so a
+//! checkpoint doesn't walk the free lists. The net growth between two
+//! checkpoints is computed with [`diff`].
+//!
+//! # Statistics
+//!
+//! A heap defined with [`heap!`](crate::heap!) counts allocated blocks of
+//! each pool, along with the high-water mark. The `stats` method returns the
+//! counters as [`HeapStats`], which is cheap enough to be polled in the field
+//! to monitor fragmentation and detect leaks:
+//!
+//! ```ignore
+//! let stats = HEAP.stats();
+//! for pool in stats.pools() {
+//!     if pool.peak() == pool.total() {
+//!         // The pool has been exhausted at least once.
+//!     }
+//! }
+//! let usage = stats.allocated_bytes() * 100 / stats.total_bytes();
+//! ```
+//!
+//! # Double-Free Detection
+//!
+//! With the `heap-check` feature of this crate enabled, every heap keeps a
+//! [`BlockBitmap`] of its allocated blocks. Deallocation of a pointer, which
+//! is not a block of the heap, or of a block, which is not allocated, panics
+//! with the offending address, instead of silently corrupting the free lists.
+//!
+//! # Thread Caches
+//!
+//! On heavily interrupt-driven workloads the shared free lists of the pools can
+//! become a point of contention. The `thread_cache` key of
+//! [`heap!`](crate::heap!) adds a [`ThreadCache`], which keeps a small number
+//! of recently freed blocks per pool for each preemption level of a thread
+//! pool:
+//!
+//! ```ignore
+//! heap! {
+//!     layout => main;
+//!     metadata => pub Heap;
+//!     instance => pub HEAP;
+//!     // Up to 8 blocks per pool for each of 4 preemption levels of `Thr`.
+//!     thread_cache => { thread => Thr; levels => 4; capacity => 8; };
+//! }
+//! ```
+//!
+//! The cache is available through the `thread_cache` method of the heap. The
+//! levels are keyed on the preemption depth of the `thread` pool, so code
+//! running outside of this pool allocates from the pools directly. On a
+//! multi-core chip, a heap with a cache must be used only by the core running
+//! the `thread` pool. Each preemption level has its own magazines, so a
+//! preempting thread never allocates from the cache of the preempted one.
+//! Blocks cached by a level stay there until a thread of the same level reuses
+//! them, or the `flush_thread_cache` method of the heap returns them to the
+//! pools. Cached blocks are counted as deallocated by the statistics and
+//! [`Checkpoint`]s.
+//!
+//! To keep the blocks of a preempted thread available to the preempting one,
+//! the `flush_preempted_thread_cache` method of the heap can be called from
+//! the `on_preempt` hook of the thread pool:
+//!
+//! ```ignore
+//! thr::pool! {
+//!     // ...
+//!     on_preempt => flush_heap_cache;
+//! }
+//!
+//! fn flush_heap_cache() {
+//!     HEAP.flush_preempted_thread_cache();
+//! }
+//! ```
+//!
+//! # Allocation Failures
+//!
+//! By default an allocation, which can't be satisfied by any of the pools,
+//! returns [`AllocError`], which usually ends up in the alloc error handler.
+//! The `alloc_error_hook` key of [`heap!`](crate::heap!) registers a function,
+//! which is called with the requested [`Layout`] before returning the error.
+//! The hook can log the failing size, or free some memory and return `true`
+//! to retry the allocation. Returning `false` gives up:
+//!
+//! ```ignore
+//! use core::alloc::Layout;
+//!
+//! heap! {
+//!     layout => main;
+//!     metadata => pub Heap;
+//!     instance => pub HEAP;
+//!     alloc_error_hook => on_alloc_error;
+//! }
+//!
+//! fn on_alloc_error(layout: Layout) -> bool {
+//!     log_oom(layout.size());
+//!     // Retry if some of the cached buffers were released.
+//!     release_buffers()
+//! }
+//! ```
+//!
+//! The hook is called again after each failed retry, so it must eventually
+//! return `false` if no more memory can be freed.
+//!
+//! # Fallback Allocators
+//!
+//! A small and fast heap, e.g. in a tightly-coupled memory, can be backed by a
+//! larger one. The `fallback` key of [`heap!`](crate::heap!) names a static
+//! implementing [`Allocator`](core::alloc::Allocator), which serves the
+//! allocations not fitting any of the heap pools:
+//!
+//! ```ignore
+//! heap! {
+//!     layout => fast;
+//!     metadata => pub FastHeap;
+//!     instance => pub FAST_HEAP;
+//!     fallback => HEAP;
+//! }
+//! ```
+//!
+//! Deallocation and resizing of the blocks served by the fallback are
+//! delegated back to it, as the heap recognizes its own blocks by address. A
+//! block, which can't be grown in the heap, is moved to the fallback. The
+//! `alloc_error_hook` of the heap is called only if the fallback fails too.
+//!
+//! # Pool-Aware Growth
+//!
+//! A [`Vec`] growing by doubling its capacity can waste most of each pool
+//! block, because the growth policy knows nothing about the pool sizes. A heap
+//! defined with [`heap!`](crate::heap!) implements [`PoolFit`], which returns
+//! the largest capacity fitting the pool block. [`VecExt::reserve_pool_fit`]
+//! uses it to grow a vector up to the whole block:
+//!
+//! ```ignore
+//! use drone_core::heap::VecExt;
+//!
+//! let mut vec = Vec::<u16>::new();
+//! vec.reserve_pool_fit(&HEAP, 5);
+//! // With pools of 4, 32, and 256 bytes, the vector fills the 32-byte block.
+//! assert_eq!(vec.capacity(), 16);
+//! ```
+//!
+//! [`VecExt::try_reserve_pool_aligned`] is the fallible counterpart, which
+//! returns an error instead of aborting on allocation failure. The trait is
+//! included in the [`prelude`](crate::prelude), so the import is usually not
+//! needed.
+//!
+//! # Zeroization
+//!
+//! A heap, which is dedicated to sensitive data, like cryptographic keys, can
+//! wipe its blocks with the `zeroize` key of [`heap!`](crate::heap!). Blocks
+//! of such a heap are wiped with zeros when deallocated, before they are
+//! returned to the free list or the thread cache:
+//!
+//! ```ignore
+//! heap! {
+//!     layout => secure;
+//!     metadata => pub SecureHeap;
+//!     instance => pub SECURE_HEAP;
+//!     zeroize => true;
+//! }
+//! ```
+//!
+//! For individual values allocated from a general-purpose heap there is
+//! [`ZeroizingBox`], which wipes its memory when dropped.
+//!
+//! The wipe uses volatile writes, therefore it is not optimized out by the
+//! compiler.
 
+mod arena;
+mod cache;
+mod check;
+mod checkpoint;
+mod config;
+mod dma;
+mod fit;
+mod object_pool;
+mod paint;
 mod pool;
+mod stats;
 #[doc(hidden)]
 pub mod trace;
+#[cfg(feature = "host")]
+mod trace_decode;
+mod zeroizing;
 
+pub use self::arena::Arena;
+pub use self::cache::{BlockCache, NoCache, ThreadCache};
+pub use self::check::BlockBitmap;
+#[doc(hidden)]
+pub use self::checkpoint::checkpoint;
+pub use self::checkpoint::{diff, Checkpoint, Diff};
+#[doc(hidden)]
+pub use self::config::init_runtime;
+pub use self::config::{PoolConfig, PoolConfigError};
+pub use self::dma::{DmaAllocator, DmaHeap};
+#[doc(hidden)]
+pub use self::fit::fit_capacity;
+pub use self::fit::{PoolFit, VecExt};
+pub use self::object_pool::{ObjectPool, PoolBox};
+#[doc(hidden)]
+pub use self::paint::{high_water, paint};
 pub use self::pool::Pool;
+#[doc(hidden)]
+pub use self::stats::stats;
+pub use self::stats::{HeapCounters, HeapStats, PoolCounters, PoolStats};
+pub use self::trace::{trace_filter, TraceFilter};
+#[cfg(feature = "host")]
+pub use self::trace_decode::{
+    PoolRecommendation, TraceAggregator, TraceDecodeError, TraceDecoder, TracePacket,
+};
+pub use self::zeroizing::ZeroizingBox;
 use self::pool::{pool_by_ptr, pool_range_by_layout};
 use core::alloc::{AllocError, Layout};
+use core::intrinsics;
 use core::ptr;
 use core::ptr::NonNull;
+use core::sync::atomic;
+use core::sync::atomic::Ordering;
 
 #[doc(hidden)]
 #[inline(never)]
-#[export_name = "heap_allocate"]
-pub fn allocate(pools: &[Pool], layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+pub fn allocate<C: BlockCache>(
+    pools: &[Pool],
+    cache: &C,
+    counters: &[PoolCounters],
+    layout: Layout,
+) -> Result<NonNull<[u8]>, AllocError> {
     if layout.size() == 0 {
         return Ok(NonNull::slice_from_raw_parts(layout.dangling(), 0));
     }
     for i in pool_range_by_layout(pools, &layout) {
         let pool = unsafe { pools.get_unchecked(i) };
-        if let Some(ptr) = pool.allocate() {
+        if let Some(ptr) = cache.pop(i).or_else(|| pool.allocate()) {
+            if let Some(counters) = counters.get(i) {
+                counters.allocate();
+            }
             return Ok(NonNull::slice_from_raw_parts(ptr, pool.size()));
         }
     }
@@ -102,79 +468,166 @@ pub fn allocate(pools: &[Pool], layout: Layout) -> Result<NonNull<[u8]>, AllocEr
 
 #[doc(hidden)]
 #[inline(never)]
-#[export_name = "heap_allocate_zeroed"]
-pub fn allocate_zeroed(pools: &[Pool], layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-    let ptr = allocate(pools, layout)?;
+pub fn allocate_zeroed<C: BlockCache>(
+    pools: &[Pool],
+    cache: &C,
+    counters: &[PoolCounters],
+    layout: Layout,
+) -> Result<NonNull<[u8]>, AllocError> {
+    let ptr = allocate(pools, cache, counters, layout)?;
     unsafe { ptr.as_non_null_ptr().as_ptr().write_bytes(0, ptr.len()) }
     Ok(ptr)
 }
 
 #[doc(hidden)]
 #[inline(never)]
-#[export_name = "heap_deallocate"]
-pub unsafe fn deallocate(pools: &[Pool], base: *mut u8, ptr: NonNull<u8>, layout: Layout) {
+pub unsafe fn deallocate<C: BlockCache>(
+    pools: &[Pool],
+    cache: &C,
+    counters: &[PoolCounters],
+    zeroize: bool,
+    base: *mut u8,
+    ptr: NonNull<u8>,
+    layout: Layout,
+) {
     if layout.size() == 0 {
         return;
     }
     if let Some(i) = pool_by_ptr(pools, base, ptr) {
+        if let Some(counters) = counters.get(i) {
+            counters.deallocate();
+        }
+        if zeroize {
+            unsafe { wipe(ptr, pools.get_unchecked(i).size()) };
+        }
+        if unsafe { cache.push(pools, i, ptr) } {
+            return;
+        }
         unsafe { pools.get_unchecked(i).deallocate(ptr) };
     }
 }
 
 #[doc(hidden)]
 #[inline(never)]
-#[export_name = "heap_grow"]
-pub unsafe fn grow(
+pub unsafe fn grow<C: BlockCache>(
     pools: &[Pool],
+    cache: &C,
+    counters: &[PoolCounters],
+    zeroize: bool,
     base: *mut u8,
     ptr: NonNull<u8>,
     old_layout: Layout,
     new_layout: Layout,
 ) -> Result<NonNull<[u8]>, AllocError> {
+    if let Some(block) = resize_in_place(pools, base, ptr, old_layout, new_layout) {
+        return Ok(block);
+    }
     unsafe {
-        let new_ptr = allocate(pools, new_layout)?;
+        let new_ptr = allocate(pools, cache, counters, new_layout)?;
         ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_mut_ptr(), old_layout.size());
-        deallocate(pools, base, ptr, old_layout);
+        deallocate(pools, cache, counters, zeroize, base, ptr, old_layout);
         Ok(new_ptr)
     }
 }
 
 #[doc(hidden)]
 #[inline(never)]
-#[export_name = "heap_grow_zeroed"]
-pub unsafe fn grow_zeroed(
+pub unsafe fn grow_zeroed<C: BlockCache>(
     pools: &[Pool],
+    cache: &C,
+    counters: &[PoolCounters],
+    zeroize: bool,
     base: *mut u8,
     ptr: NonNull<u8>,
     old_layout: Layout,
     new_layout: Layout,
 ) -> Result<NonNull<[u8]>, AllocError> {
+    if let Some(block) = resize_in_place(pools, base, ptr, old_layout, new_layout) {
+        unsafe {
+            ptr.as_ptr()
+                .add(old_layout.size())
+                .write_bytes(0, new_layout.size() - old_layout.size());
+        }
+        return Ok(block);
+    }
     unsafe {
-        let new_ptr = allocate_zeroed(pools, new_layout)?;
+        let new_ptr = allocate_zeroed(pools, cache, counters, new_layout)?;
         ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_mut_ptr(), old_layout.size());
-        deallocate(pools, base, ptr, old_layout);
+        deallocate(pools, cache, counters, zeroize, base, ptr, old_layout);
         Ok(new_ptr)
     }
 }
 
 #[doc(hidden)]
 #[inline(never)]
-#[export_name = "heap_shrink"]
-pub unsafe fn shrink(
+pub unsafe fn shrink<C: BlockCache>(
     pools: &[Pool],
+    cache: &C,
+    counters: &[PoolCounters],
+    zeroize: bool,
     base: *mut u8,
     ptr: NonNull<u8>,
     old_layout: Layout,
     new_layout: Layout,
 ) -> Result<NonNull<[u8]>, AllocError> {
+    if let Some(block) = resize_in_place(pools, base, ptr, old_layout, new_layout) {
+        return Ok(block);
+    }
     unsafe {
-        let new_ptr = allocate(pools, new_layout)?;
+        let new_ptr = allocate(pools, cache, counters, new_layout)?;
         ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_mut_ptr(), new_layout.size());
-        deallocate(pools, base, ptr, old_layout);
+        deallocate(pools, cache, counters, zeroize, base, ptr, old_layout);
         Ok(new_ptr)
     }
 }
 
+/// Returns the whole block `ptr`, if `new_layout` maps to the same pool as the
+/// block.
+fn resize_in_place(
+    pools: &[Pool],
+    base: *mut u8,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+) -> Option<NonNull<[u8]>> {
+    // Zero-sized allocations don't occupy blocks.
+    if old_layout.size() == 0 || new_layout.size() == 0 {
+        return None;
+    }
+    let i = pool_by_ptr(pools, base, ptr)?;
+    (pool_range_by_layout(pools, &new_layout).start == i)
+        .then(|| NonNull::slice_from_raw_parts(ptr, unsafe { pools.get_unchecked(i) }.size()))
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn retry_alloc<F: FnMut() -> Result<NonNull<[u8]>, AllocError>>(
+    hook: fn(Layout) -> bool,
+    layout: Layout,
+    mut f: F,
+) -> Result<NonNull<[u8]>, AllocError> {
+    loop {
+        match f() {
+            Ok(ptr) => break Ok(ptr),
+            Err(AllocError) if hook(layout) => {}
+            Err(AllocError) => break Err(AllocError),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn owns(pools: &[Pool], base: *mut u8, ptr: NonNull<u8>) -> bool {
+    pool_by_ptr(pools, base, ptr).is_some()
+}
+
+/// Overwrites the block with zeros in a way, which can't be optimized out.
+#[inline]
+unsafe fn wipe(ptr: NonNull<u8>, size: usize) {
+    unsafe { intrinsics::volatile_set_memory(ptr.as_ptr(), 0, size) };
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,7 +700,7 @@ mod tests {
         #[track_caller]
         unsafe fn allocate_and_set(heap: &TestHeap, layout: Layout, value: u8) {
             unsafe {
-                *allocate(&heap.pools, layout).unwrap().as_mut_ptr() = value;
+                *allocate(&heap.pools, &NoCache, &[], layout).unwrap().as_mut_ptr() = value;
             }
         }
         #[track_caller]
@@ -255,6 +708,9 @@ mod tests {
             unsafe {
                 deallocate(
                     &heap.pools,
+                    None,
+                    &[],
+                    &[],
                     heap.base,
                     NonNull::new_unchecked(address as *mut u8),
                     layout,
@@ -298,4 +754,61 @@ mod tests {
             assert_eq!(*(&m[736] as *const _ as *const usize), o + 698);
         }
     }
+
+    #[test]
+    fn zeroize() {
+        let mut m = [0u8; 64];
+        let o = &mut m as *mut _ as usize;
+        let pools = [Pool::new(o, 32, 2)];
+        let layout = Layout::from_size_align(32, 1).unwrap();
+        unsafe {
+            let ptr = allocate(&pools, &NoCache, &[], layout).unwrap().as_non_null_ptr();
+            ptr.as_ptr().write_bytes(0xAA, 32);
+            deallocate(&pools, &NoCache, &[], true, o as *mut u8, ptr, layout);
+        }
+        assert!(m[core::mem::size_of::<usize>()..32].iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn in_place_resize() {
+        let mut m = [0u8; 96];
+        let o = &mut m as *mut _ as usize;
+        let pools = [Pool::new(o, 16, 2), Pool::new(o + 32, 32, 2)];
+        let layout = |size| Layout::from_size_align(size, 1).unwrap();
+        unsafe {
+            let ptr = allocate(&pools, &NoCache, &[], layout(4)).unwrap().as_non_null_ptr();
+            ptr.as_ptr().write_bytes(0xAA, 16);
+            let grown =
+                grow_zeroed(&pools, &NoCache, &[], false, o as *mut u8, ptr, layout(4), layout(16))
+                    .unwrap();
+            assert_eq!(grown.as_non_null_ptr(), ptr);
+            assert_eq!(grown.len(), 16);
+            assert_eq!(m[3..6], [0xAA, 0, 0]);
+            let base = o as *mut u8;
+            let moved = grow(&pools, &NoCache, &[], false, base, ptr, layout(16), layout(17))
+                .unwrap()
+                .as_non_null_ptr();
+            assert_eq!(moved.as_ptr() as usize, o + 32);
+            let shrunk = shrink(&pools, &NoCache, &[], false, base, moved, layout(17), layout(8))
+                .unwrap()
+                .as_non_null_ptr();
+            assert_ne!(shrunk, moved);
+        }
+    }
+
+    #[test]
+    fn alloc_error_hook() {
+        static CALLS: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+        fn hook(layout: Layout) -> bool {
+            assert_eq!(layout.size(), 32);
+            CALLS.fetch_add(1, Ordering::Relaxed) == 0
+        }
+        let mut m = [0u8; 32];
+        let o = &mut m as *mut _ as usize;
+        let pools = [Pool::new(o, 32, 1)];
+        let layout = Layout::from_size_align(32, 1).unwrap();
+        assert!(retry_alloc(hook, layout, || allocate(&pools, &NoCache, &[], layout)).is_ok());
+        assert!(retry_alloc(hook, layout, || allocate(&pools, &NoCache, &[], layout)).is_err());
+        assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+    }
 }
@@ -0,0 +1,223 @@
+use super::pool::Pool;
+use crate::platform::Interrupts;
+use core::cell::UnsafeCell;
+use core::mem::{size_of, ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
+use core::{fmt, ptr};
+
+#[cfg(all(feature = "atomics", not(loom)))]
+type AtomicU8 = core::sync::atomic::AtomicU8;
+#[cfg(all(feature = "atomics", loom))]
+type AtomicU8 = loom::sync::atomic::AtomicU8;
+#[cfg(not(feature = "atomics"))]
+type AtomicU8 = crate::sync::soft_atomic::Atomic<u8>;
+
+const UNINIT: u8 = 0;
+const INIT: u8 = 1;
+const READY: u8 = 2;
+
+/// A fixed-capacity pool of objects of type `T`.
+///
+/// The pool reserves `N` slots for values of `T`, and hands out [`PoolBox`]
+/// smart pointers to them. Both allocation and deallocation are lock-free and
+/// have *O(1)* time complexity, as they are performed by a single [`Pool`].
+/// Unlike the heap, the pool doesn't look up a pool by a
+/// [`Layout`](core::alloc::Layout), which makes it suitable for allocating
+/// fixed message types in interrupt handlers.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::heap::ObjectPool;
+///
+/// struct Message {
+///     id: u16,
+///     payload: [u8; 8],
+/// }
+///
+/// static MESSAGES: ObjectPool<Message, 16> = ObjectPool::new();
+///
+/// let message = MESSAGES.allocate(Message { id: 1, payload: [0; 8] }).ok().unwrap();
+/// assert_eq!(message.id, 1);
+/// drop(message); // The slot is returned to the pool.
+/// ```
+pub struct ObjectPool<T, const N: usize> {
+    slots: UnsafeCell<MaybeUninit<[Slot<T>; N]>>,
+    pool: UnsafeCell<MaybeUninit<Pool>>,
+    state: AtomicU8,
+}
+
+/// An owning pointer to a value allocated from an [`ObjectPool`].
+///
+/// The value is dropped and its slot is returned to the pool when the pointer
+/// goes out of scope.
+pub struct PoolBox<T> {
+    ptr: NonNull<T>,
+    pool: &'static Pool,
+}
+
+/// A slot, which is large enough to hold either a value or a free list link.
+#[repr(C)]
+union Slot<T> {
+    _value: ManuallyDrop<T>,
+    _next: *mut u8,
+}
+
+unsafe impl<T: Send, const N: usize> Sync for ObjectPool<T, N> {}
+
+unsafe impl<T: Send> Send for PoolBox<T> {}
+
+unsafe impl<T: Sync> Sync for PoolBox<T> {}
+
+impl<T, const N: usize> ObjectPool<T, N> {
+    maybe_const_fn! {
+        /// Creates a new pool with `N` free slots.
+        #[inline]
+        pub const fn new() -> Self {
+            Self {
+                slots: UnsafeCell::new(MaybeUninit::uninit()),
+                pool: UnsafeCell::new(MaybeUninit::uninit()),
+                state: AtomicU8::new(UNINIT),
+            }
+        }
+    }
+
+    /// Returns the number of slots in the pool.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Moves `value` into a free slot of the pool.
+    ///
+    /// Returns the `value` back if the pool is exhausted.
+    pub fn allocate(&'static self, value: T) -> Result<PoolBox<T>, T> {
+        let pool = self.pool();
+        match pool.allocate() {
+            Some(ptr) => {
+                let ptr = ptr.cast::<T>();
+                unsafe { ptr.as_ptr().write(value) };
+                Ok(PoolBox { ptr, pool })
+            }
+            None => Err(value),
+        }
+    }
+
+    fn pool(&self) -> &Pool {
+        if load_atomic!(self.state, Acquire) != READY {
+            self.init();
+        }
+        unsafe { (*self.pool.get()).assume_init_ref() }
+    }
+
+    #[cold]
+    fn init(&self) {
+        Interrupts::paused(|| {
+            if load_try_modify_atomic!(self.state, Acquire, Acquire, |state| {
+                (state == UNINIT).then_some(INIT)
+            })
+            .is_ok()
+            {
+                let address = self.slots.get() as usize;
+                unsafe { (*self.pool.get()).write(Pool::new(address, size_of::<Slot<T>>(), N)) };
+                store_atomic!(self.state, READY, Release);
+            } else {
+                crate::spin_while!(load_atomic!(self.state, Acquire) != READY);
+            }
+        });
+    }
+}
+
+impl<T, const N: usize> Default for ObjectPool<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> PoolBox<T> {
+    /// Moves the value out of the pool, and returns the slot to the pool.
+    #[inline]
+    pub fn into_inner(this: Self) -> T {
+        let this = ManuallyDrop::new(this);
+        unsafe {
+            let value = this.ptr.as_ptr().read();
+            this.pool.deallocate(this.ptr.cast());
+            value
+        }
+    }
+}
+
+impl<T> Deref for PoolBox<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> DerefMut for PoolBox<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T> Drop for PoolBox<T> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.ptr.as_ptr());
+            self.pool.deallocate(self.ptr.cast());
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PoolBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for PoolBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn allocate_and_free() {
+        static POOL: ObjectPool<u64, 2> = ObjectPool::new();
+        let a = POOL.allocate(1).unwrap();
+        let mut b = POOL.allocate(2).unwrap();
+        assert_eq!(POOL.allocate(3).unwrap_err(), 3);
+        *b += 10;
+        assert_eq!((*a, *b), (1, 12));
+        assert_eq!(PoolBox::into_inner(b), 12);
+        let c = POOL.allocate(4).unwrap();
+        assert_eq!(*c, 4);
+        assert_eq!(POOL.capacity(), 2);
+    }
+
+    #[test]
+    fn drop_value() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        struct Counted;
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        static POOL: ObjectPool<Counted, 1> = ObjectPool::new();
+        drop(POOL.allocate(Counted).ok().unwrap());
+        drop(POOL.allocate(Counted).ok().unwrap());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    }
+}
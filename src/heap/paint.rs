@@ -0,0 +1,48 @@
+use super::pool::Pool;
+use core::ptr;
+
+#[doc(hidden)]
+pub unsafe fn paint(pools: &[Pool], base: *mut u8, pattern: u8) {
+    if let Some(last) = pools.last() {
+        unsafe { base.write_bytes(pattern, last.edge() as usize - base as usize) };
+    }
+}
+
+#[doc(hidden)]
+pub fn high_water<const N: usize>(pools: &[Pool; N], base: *mut u8, pattern: u8) -> [usize; N] {
+    let mut counts = [0; N];
+    let mut start = base;
+    for (pool, count) in pools.iter().zip(&mut counts) {
+        if pool.size() != 0 {
+            let len = pool.edge() as usize - start as usize;
+            // Scan backwards, because freed blocks in the middle of the pool
+            // can happen to contain the pattern.
+            if let Some(last) = (0..len)
+                .rev()
+                .find(|&offset| unsafe { ptr::read_volatile(start.add(offset)) } != pattern)
+            {
+                *count = last / pool.size() + 1;
+            }
+        }
+        start = pool.edge();
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_and_scan() {
+        let mut m = [0u8; 64];
+        let o = &mut m as *mut _ as usize;
+        let pools = [Pool::new(o, 8, 4), Pool::new(o + 32, 16, 2)];
+        unsafe { paint(&pools, o as *mut u8, 0xA5) };
+        assert!(m.iter().all(|&byte| byte == 0xA5));
+        assert_eq!(high_water(&pools, o as *mut u8, 0xA5), [0, 0]);
+        m[17] = 0;
+        m[40] = 0;
+        assert_eq!(high_water(&pools, o as *mut u8, 0xA5), [3, 1]);
+    }
+}
@@ -1,8 +1,16 @@
 use core::alloc::Layout;
+use core::cell::UnsafeCell;
 use core::ops::Range;
 use core::ptr;
 use core::ptr::NonNull;
 
+#[cfg(all(feature = "atomics", not(loom)))]
+type AtomicUsize = core::sync::atomic::AtomicUsize;
+#[cfg(all(feature = "atomics", loom))]
+type AtomicUsize = loom::sync::atomic::AtomicUsize;
+#[cfg(not(feature = "atomics"))]
+type AtomicUsize = crate::sync::soft_atomic::Atomic<usize>;
+
 #[cfg(all(feature = "atomics", not(loom)))]
 type AtomicPtr = core::sync::atomic::AtomicPtr<u8>;
 #[cfg(all(feature = "atomics", loom))]
@@ -10,20 +18,42 @@ type AtomicPtr = loom::sync::atomic::AtomicPtr<u8>;
 #[cfg(not(feature = "atomics"))]
 type AtomicPtr = crate::sync::soft_atomic::Atomic<*mut u8>;
 
+/// Number of the most significant bits of the free list head, which are used
+/// for the ABA tag.
+const TAG_BITS: u32 = usize::BITS * 3 / 8;
+
+/// Mask for the block index part of the free list head.
+const INDEX_MASK: usize = usize::MAX >> TAG_BITS;
+
+/// Maximum number of blocks in a pool, i.e. `2^20 - 1` on 32-bit targets.
+pub(crate) const MAX_BLOCKS: usize = INDEX_MASK;
+
 /// The set of free memory blocks.
 ///
 /// It operates by connecting unallocated regions of memory together in a linked
 /// list, using the first word of each unallocated region as a pointer to the
 /// next.
+///
+/// The head of the list is a tagged pointer: the least significant bits store
+/// the block index counted from the pool edge, and the most significant bits
+/// store a counter, which is incremented on each list modification. This
+/// prevents the ABA problem when a block is allocated and deallocated by a
+/// preempting thread between reading the head and updating it. Without the
+/// `atomics` feature, the head is updated inside a critical section, so the
+/// problem doesn't arise in the first place.
+///
+/// The index part limits a pool to `2^20 - 1` blocks of any size on 32-bit
+/// targets.
 // This structure should be kept in sync with drone-ld.
 #[repr(C)]
 pub struct Pool {
-    /// Block size. This field is immutable.
-    size: usize,
-    /// Address of the byte past the last element. This field is immutable.
-    edge: *mut u8,
-    /// Free List of previously allocated blocks.
-    free: AtomicPtr,
+    /// Block size. This field is changed only by `init_runtime` of the heap.
+    size: UnsafeCell<usize>,
+    /// Address of the byte past the last element. This field is changed only by
+    /// `init_runtime` of the heap.
+    edge: UnsafeCell<*mut u8>,
+    /// Tagged head of the free list of previously allocated blocks.
+    free: AtomicUsize,
     /// Pointer growing from the starting address until it reaches the `edge`.
     uninit: AtomicPtr,
 }
@@ -33,12 +63,18 @@ unsafe impl Sync for Pool {}
 impl Pool {
     maybe_const_fn! {
         /// Creates a new `Pool`.
+        ///
+        /// # Panics
+        ///
+        /// If `count` is greater than the maximum number of blocks, which fits
+        /// into the index part of the free list head.
         #[inline]
         pub const fn new(address: usize, size: usize, count: usize) -> Self {
+            assert!(count <= MAX_BLOCKS);
             Self {
-                size,
-                edge: (address + size * count) as *mut u8,
-                free: AtomicPtr::new(ptr::null_mut()),
+                size: UnsafeCell::new(size),
+                edge: UnsafeCell::new((address + size * count) as *mut u8),
+                free: AtomicUsize::new(0),
                 uninit: AtomicPtr::new(address as *mut u8),
             }
         }
@@ -47,7 +83,7 @@ impl Pool {
     /// Returns the block size.
     #[inline]
     pub const fn size(&self) -> usize {
-        self.size
+        unsafe { *self.size.get() }
     }
 
     /// Allocates one block of memory.
@@ -75,34 +111,76 @@ impl Pool {
     pub unsafe fn deallocate(&self, ptr: NonNull<u8>) {
         load_modify_atomic!(self.free, Acquire, AcqRel, |curr| unsafe {
             #[allow(clippy::cast_ptr_alignment)]
-            ptr.as_ptr().cast::<*mut u8>().write(curr);
-            ptr.as_ptr().cast::<u8>()
+            ptr.as_ptr().cast::<*mut u8>().write(self.unpack(curr));
+            self.pack(ptr.as_ptr(), curr)
         });
     }
 
+    /// Returns the address of the byte past the last block.
+    #[inline]
+    pub(crate) fn edge(&self) -> *mut u8 {
+        unsafe { *self.edge.get() }
+    }
+
+    /// Replaces the geometry of the pool with `count` blocks of `size` bytes
+    /// starting at `address`, and makes all blocks free.
+    ///
+    /// # Safety
+    ///
+    /// The pool must have no allocated blocks, and must not be accessed
+    /// concurrently.
+    pub(crate) unsafe fn reshape(&self, address: usize, size: usize, count: usize) {
+        assert!(count <= MAX_BLOCKS);
+        unsafe {
+            *self.size.get() = size;
+            *self.edge.get() = (address + size * count) as *mut u8;
+        }
+        store_atomic!(self.free, 0, Relaxed);
+        store_atomic!(self.uninit, address as *mut u8, Relaxed);
+    }
+
     fn allocate_free(&self) -> Option<*mut u8> {
         load_try_modify_atomic!(self.free, Acquire, AcqRel, |curr| unsafe {
+            let head = self.unpack(curr);
             #[allow(clippy::cast_ptr_alignment)]
-            (!curr.is_null()).then(|| (curr as *const *mut u8).read())
+            (!head.is_null()).then(|| self.pack((head as *const *mut u8).read(), curr))
         })
         .ok()
+        .map(|curr| self.unpack(curr))
+    }
+
+    /// Makes a new free list head pointing to `ptr`, with the tag incremented
+    /// from the tag of `prev` head.
+    fn pack(&self, ptr: *mut u8, prev: usize) -> usize {
+        let tag = (prev & !INDEX_MASK).wrapping_add(INDEX_MASK + 1);
+        if ptr.is_null() { tag } else { tag | (self.edge() as usize - ptr as usize) / self.size() }
+    }
+
+    /// Extracts the pointer from the free list `head`.
+    fn unpack(&self, head: usize) -> *mut u8 {
+        let index = head & INDEX_MASK;
+        if index == 0 {
+            ptr::null_mut()
+        } else {
+            (self.edge() as usize - index * self.size()) as *mut u8
+        }
     }
 
     fn allocate_uninit(&self) -> Option<*mut u8> {
         load_try_modify_atomic!(self.uninit, Relaxed, Relaxed, |curr| unsafe {
-            (curr != self.edge).then(|| curr.add(self.size))
+            (curr != self.edge()).then(|| curr.add(self.size()))
         })
         .ok()
     }
 }
 
 pub fn pool_range_by_layout(pools: &[Pool], layout: &Layout) -> Range<usize> {
-    let first = binary_search(pools, |pool| layout.size() <= pool.size);
+    let first = binary_search(pools, |pool| layout.size() <= pool.size());
     first..pools.len()
 }
 
 pub fn pool_by_ptr(pools: &[Pool], base: *mut u8, ptr: NonNull<u8>) -> Option<usize> {
-    let index = binary_search(pools, |pool| ptr.as_ptr() < pool.edge);
+    let index = binary_search(pools, |pool| ptr.as_ptr() < pool.edge());
     (index < pools.len() && (index > 0 || ptr.as_ptr() >= base)).then_some(index)
 }
 
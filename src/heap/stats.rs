@@ -0,0 +1,192 @@
+use super::pool::Pool;
+
+#[cfg(any(feature = "atomics", loom))]
+type AtomicUsize = core::sync::atomic::AtomicUsize;
+#[cfg(not(any(feature = "atomics", loom)))]
+type AtomicUsize = crate::sync::soft_atomic::Atomic<usize>;
+
+/// Allocation counters of a heap with `N` pools.
+///
+/// The counters are maintained by the allocation functions of a heap defined
+/// with [`heap!`](crate::heap!), and read with its `stats` method.
+pub struct HeapCounters<const N: usize> {
+    pools: [PoolCounters; N],
+}
+
+/// Allocation counters of a single pool.
+pub struct PoolCounters {
+    allocated: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+/// Allocation statistics of a single pool.
+///
+/// See [`HeapStats`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PoolStats {
+    block_size: usize,
+    total: usize,
+    allocated: usize,
+    peak: usize,
+}
+
+/// Allocation statistics of a heap with `N` pools.
+///
+/// Created by the `stats` method of a heap defined with
+/// [`heap!`](crate::heap!). The statistics are collected without walking the
+/// free lists, so they are cheap enough to be polled periodically.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HeapStats<const N: usize> {
+    pools: [PoolStats; N],
+}
+
+#[doc(hidden)]
+pub fn stats<const N: usize>(
+    pools: &[Pool; N],
+    counters: &HeapCounters<N>,
+    base: *mut u8,
+) -> HeapStats<N> {
+    let mut stats = [PoolStats { block_size: 0, total: 0, allocated: 0, peak: 0 }; N];
+    let mut start = base;
+    for ((stats, pool), counters) in stats.iter_mut().zip(pools).zip(&counters.pools) {
+        stats.block_size = pool.size();
+        if pool.size() != 0 {
+            stats.total = (pool.edge() as usize - start as usize) / pool.size();
+        }
+        stats.allocated = counters.allocated();
+        stats.peak = load_atomic!(counters.peak, Relaxed);
+        start = pool.edge();
+    }
+    HeapStats { pools: stats }
+}
+
+impl<const N: usize> HeapCounters<N> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const POOL: PoolCounters = PoolCounters::new();
+
+    /// Creates zeroed counters.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { pools: [Self::POOL; N] }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn pools(&self) -> &[PoolCounters; N] {
+        &self.pools
+    }
+
+    /// Returns `true` if no blocks are allocated.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.pools.iter().all(|pool| pool.allocated() == 0)
+    }
+}
+
+impl<const N: usize> Default for HeapCounters<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoolCounters {
+    const fn new() -> Self {
+        Self { allocated: AtomicUsize::new(0), peak: AtomicUsize::new(0) }
+    }
+
+    /// Counts a newly allocated block.
+    #[inline]
+    pub(crate) fn allocate(&self) {
+        let allocated = load_modify_atomic!(self.allocated, Relaxed, Relaxed, |old| old + 1) + 1;
+        let _ = load_try_modify_atomic!(self.peak, Relaxed, Relaxed, |peak| {
+            (peak < allocated).then_some(allocated)
+        });
+    }
+
+    /// Counts a deallocated block.
+    #[inline]
+    pub(crate) fn deallocate(&self) {
+        load_modify_atomic!(self.allocated, Relaxed, Relaxed, |old| old.saturating_sub(1));
+    }
+
+    /// Returns the number of currently allocated blocks.
+    #[inline]
+    pub(crate) fn allocated(&self) -> usize {
+        load_atomic!(self.allocated, Relaxed)
+    }
+}
+
+impl PoolStats {
+    /// Returns the block size of the pool.
+    #[inline]
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Returns the total number of blocks in the pool.
+    #[inline]
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns the number of currently allocated blocks.
+    #[inline]
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    /// Returns the highest number of simultaneously allocated blocks.
+    #[inline]
+    pub fn peak(&self) -> usize {
+        self.peak
+    }
+}
+
+impl<const N: usize> HeapStats<N> {
+    /// Returns the statistics of the pools.
+    #[inline]
+    pub fn pools(&self) -> &[PoolStats; N] {
+        &self.pools
+    }
+
+    /// Returns the total size of all pools in bytes.
+    pub fn total_bytes(&self) -> usize {
+        self.pools.iter().map(|pool| pool.block_size * pool.total).sum()
+    }
+
+    /// Returns the size of currently allocated blocks in bytes.
+    pub fn allocated_bytes(&self) -> usize {
+        self.pools.iter().map(|pool| pool.block_size * pool.allocated).sum()
+    }
+
+    /// Returns the sum of the pool high-water marks in bytes.
+    ///
+    /// The pools may reach their peaks at different times, therefore this is
+    /// an upper bound of the actual heap high-water mark.
+    pub fn peak_bytes(&self) -> usize {
+        self.pools.iter().map(|pool| pool.block_size * pool.peak).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters() {
+        let mut m = [0u8; 48];
+        let o = m.as_mut_ptr() as usize;
+        let pools = [Pool::new(o, 4, 4), Pool::new(o + 16, 8, 4)];
+        let counters = HeapCounters::<2>::new();
+        counters.pools()[0].allocate();
+        counters.pools()[0].allocate();
+        counters.pools()[0].deallocate();
+        counters.pools()[1].allocate();
+        let stats = stats(&pools, &counters, o as *mut u8);
+        assert_eq!(stats.pools()[0], PoolStats { block_size: 4, total: 4, allocated: 1, peak: 2 });
+        assert_eq!(stats.pools()[1], PoolStats { block_size: 8, total: 4, allocated: 1, peak: 1 });
+        assert_eq!(stats.total_bytes(), 48);
+        assert_eq!(stats.allocated_bytes(), 12);
+        assert_eq!(stats.peak_bytes(), 16);
+    }
+}
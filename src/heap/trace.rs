@@ -1,55 +1,227 @@
-use crate::stream::Stream;
+use super::pool::{pool_by_ptr, pool_range_by_layout, Pool};
+use crate::stream::{Stream, HEAP_TRACE_FILTERS};
 use core::alloc::Layout;
 use core::mem;
+use core::ptr::NonNull;
+
+#[cfg(any(feature = "atomics", loom))]
+type AtomicU32 = core::sync::atomic::AtomicU32;
+#[cfg(not(any(feature = "atomics", loom)))]
+type AtomicU32 = crate::sync::soft_atomic::Atomic<u32>;
+
+/// Tag of a packet emitted on allocation.
+pub const ALLOCATE_TAG: u8 = 0;
+
+/// Tag of a packet emitted on deallocation.
+pub const DEALLOCATE_TAG: u8 = 1;
+
+/// Tag of a packet emitted on reallocation to a bigger size.
+pub const GROW_TAG: u8 = 2;
+
+/// Tag of a packet emitted on reallocation to a smaller size.
+pub const SHRINK_TAG: u8 = 3;
+
+/// Run-time filter of heap trace packets.
+///
+/// All fields are zero by default, which means no filtering. The filters are
+/// stored in the stream runtime, see [`stream`](crate::stream). The layout of
+/// the structure is a part of the debug probe protocol.
+#[repr(C)]
+pub struct TraceFilter {
+    min_size: AtomicU32,
+    pools: AtomicU32,
+    sample_rate: AtomicU32,
+    counter: AtomicU32,
+}
+
+/// Returns the filter of the heap trace stream number `stream`.
+///
+/// # Panics
+///
+/// If `stream` is more than or equal to
+/// [`STREAM_COUNT`](crate::stream::STREAM_COUNT).
+#[inline]
+pub fn trace_filter(stream: u8) -> &'static TraceFilter {
+    unsafe { &(*HEAP_TRACE_FILTERS.get())[usize::from(stream)] }
+}
+
+impl TraceFilter {
+    #[allow(clippy::declare_interior_mutable_const)]
+    pub(crate) const ZEROED: Self = Self {
+        min_size: AtomicU32::new(0),
+        pools: AtomicU32::new(0),
+        sample_rate: AtomicU32::new(0),
+        counter: AtomicU32::new(0),
+    };
+
+    /// Traces only operations on blocks of at least `size` bytes. Reallocations
+    /// are matched by the bigger of the old and new sizes.
+    #[inline]
+    pub fn set_min_size(&self, size: u32) {
+        store_atomic!(self.min_size, size, Relaxed);
+    }
+
+    /// Traces only operations on the pools, which indices are set in `mask`.
+    /// Zero `mask` traces all pools.
+    ///
+    /// Allocations are matched by the first pool fitting the requested size.
+    /// Deallocations and reallocations are matched by the pool holding the
+    /// existing block.
+    #[inline]
+    pub fn set_pools(&self, mask: u32) {
+        store_atomic!(self.pools, mask, Relaxed);
+    }
+
+    /// Traces only every `rate`-th operation, which passed the other filters.
+    #[inline]
+    pub fn set_sample_rate(&self, rate: u32) {
+        store_atomic!(self.sample_rate, rate, Relaxed);
+    }
+
+    /// Resets all filters.
+    #[inline]
+    pub fn reset(&self) {
+        self.set_min_size(0);
+        self.set_pools(0);
+        self.set_sample_rate(0);
+    }
+
+    fn passes(&self, pool: Option<usize>, size: usize) -> bool {
+        if size < load_atomic!(self.min_size, Relaxed) as usize {
+            return false;
+        }
+        let mask = load_atomic!(self.pools, Relaxed);
+        if mask != 0 && !pool.is_some_and(|pool| pool < 32 && mask & 1 << pool != 0) {
+            return false;
+        }
+        let rate = load_atomic!(self.sample_rate, Relaxed);
+        if rate > 1 {
+            let count =
+                load_modify_atomic!(self.counter, Relaxed, Relaxed, |old| old.wrapping_add(1));
+            return count % rate == 0;
+        }
+        true
+    }
+}
+
+#[inline(always)]
+fn is_enabled(trace_stream: u8, pool: Option<usize>, size: usize) -> bool {
+    Stream::new(trace_stream).is_enabled() && trace_filter(trace_stream).passes(pool, size)
+}
+
+#[inline(always)]
+fn block_pool(pools: &[Pool], base: *mut u8, ptr: NonNull<u8>, layout: &Layout) -> Option<usize> {
+    if layout.size() == 0 { None } else { pool_by_ptr(pools, base, ptr) }
+}
 
 #[inline(always)]
-pub fn allocate(trace_stream: u8, layout: Layout) {
+pub fn allocate(trace_stream: u8, pools: &[Pool], layout: Layout) {
     #[inline(never)]
     fn trace(trace_stream: u8, layout: Layout) {
-        let buffer: [usize; 2] = [0_usize.to_be(), layout.size()];
+        let buffer: [usize; 2] = [usize::from(ALLOCATE_TAG).to_be(), layout.size()];
         let buffer: [u8; mem::size_of::<[usize; 2]>()] = unsafe { mem::transmute(buffer) };
         Stream::new(trace_stream).write_transaction(&buffer[3..]);
     }
-    if Stream::new(trace_stream).is_enabled() {
+    let pool = pool_range_by_layout(pools, &layout).start;
+    if is_enabled(trace_stream, Some(pool), layout.size()) {
         trace(trace_stream, layout);
     }
 }
 
 #[inline(always)]
-pub fn deallocate(trace_stream: u8, layout: Layout) {
+pub fn deallocate(
+    trace_stream: u8,
+    pools: &[Pool],
+    base: *mut u8,
+    ptr: NonNull<u8>,
+    layout: Layout,
+) {
     #[inline(never)]
     fn trace(trace_stream: u8, layout: Layout) {
-        let buffer: [usize; 2] = [1_usize.to_be(), layout.size()];
+        let buffer: [usize; 2] = [usize::from(DEALLOCATE_TAG).to_be(), layout.size()];
         let buffer: [u8; mem::size_of::<[usize; 2]>()] = unsafe { mem::transmute(buffer) };
         Stream::new(trace_stream).write_transaction(&buffer[3..]);
     }
-    if Stream::new(trace_stream).is_enabled() {
+    let pool = block_pool(pools, base, ptr, &layout);
+    if is_enabled(trace_stream, pool, layout.size()) {
         trace(trace_stream, layout);
     }
 }
 
 #[inline(always)]
-pub fn grow(trace_stream: u8, old_layout: Layout, new_layout: Layout) {
+pub fn grow(
+    trace_stream: u8,
+    pools: &[Pool],
+    base: *mut u8,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+) {
     #[inline(never)]
     fn trace(trace_stream: u8, old_layout: Layout, new_layout: Layout) {
-        let buffer: [usize; 3] = [2_usize.to_be(), old_layout.size(), new_layout.size()];
+        let buffer: [usize; 3] =
+            [usize::from(GROW_TAG).to_be(), old_layout.size(), new_layout.size()];
         let buffer: [u8; mem::size_of::<[usize; 3]>()] = unsafe { mem::transmute(buffer) };
         Stream::new(trace_stream).write_transaction(&buffer[3..]);
     }
-    if Stream::new(trace_stream).is_enabled() {
+    let pool = block_pool(pools, base, ptr, &old_layout);
+    if is_enabled(trace_stream, pool, old_layout.size().max(new_layout.size())) {
         trace(trace_stream, old_layout, new_layout);
     }
 }
 
 #[inline(always)]
-pub fn shrink(trace_stream: u8, old_layout: Layout, new_layout: Layout) {
+pub fn shrink(
+    trace_stream: u8,
+    pools: &[Pool],
+    base: *mut u8,
+    ptr: NonNull<u8>,
+    old_layout: Layout,
+    new_layout: Layout,
+) {
     #[inline(never)]
     fn trace(trace_stream: u8, old_layout: Layout, new_layout: Layout) {
-        let buffer: [usize; 3] = [3_usize.to_be(), old_layout.size(), new_layout.size()];
+        let buffer: [usize; 3] =
+            [usize::from(SHRINK_TAG).to_be(), old_layout.size(), new_layout.size()];
         let buffer: [u8; mem::size_of::<[usize; 3]>()] = unsafe { mem::transmute(buffer) };
         Stream::new(trace_stream).write_transaction(&buffer[3..]);
     }
-    if Stream::new(trace_stream).is_enabled() {
+    let pool = block_pool(pools, base, ptr, &old_layout);
+    if is_enabled(trace_stream, pool, old_layout.size().max(new_layout.size())) {
         trace(trace_stream, old_layout, new_layout);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_packets() {
+        let filter = TraceFilter::ZEROED;
+        assert!(filter.passes(Some(0), 1));
+        filter.set_min_size(16);
+        assert!(!filter.passes(Some(1), 8));
+        assert!(filter.passes(Some(1), 16));
+        filter.set_pools(0b100);
+        assert!(!filter.passes(Some(1), 16));
+        assert!(!filter.passes(None, 100));
+        assert!(filter.passes(Some(2), 100));
+        filter.set_sample_rate(3);
+        let passed = (0..9).filter(|_| filter.passes(Some(2), 100)).count();
+        assert_eq!(passed, 3);
+        filter.reset();
+        assert!(filter.passes(None, 1));
+    }
+
+    #[test]
+    fn block_pools() {
+        let pools = [Pool::new(0x100, 4, 4), Pool::new(0x110, 32, 2), Pool::new(0x150, 256, 1)];
+        let base = 0x100 as *mut u8;
+        let ptr = |address| NonNull::new(address as *mut u8).unwrap();
+        let layout = |size| Layout::from_size_align(size, 1).unwrap();
+        assert_eq!(block_pool(&pools, base, ptr(0x110), &layout(4)), Some(1));
+        assert_eq!(block_pool(&pools, base, ptr(0x110), &layout(0)), None);
+        assert_eq!(block_pool(&pools, base, ptr(0x300), &layout(4)), None);
+    }
+}
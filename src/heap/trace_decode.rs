@@ -0,0 +1,320 @@
+use super::trace::{ALLOCATE_TAG, DEALLOCATE_TAG, GROW_TAG, SHRINK_TAG};
+use alloc::collections::BTreeMap;
+use core::fmt;
+
+/// Size of a target machine word in a trace packet.
+const WORD_SIZE: usize = 4;
+
+/// A decoded heap trace packet.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TracePacket {
+    /// A memory block of `size` bytes was allocated.
+    Allocate {
+        /// Requested size.
+        size: u32,
+    },
+    /// A memory block of `size` bytes was deallocated.
+    Deallocate {
+        /// Size of the deallocated block.
+        size: u32,
+    },
+    /// A memory block was reallocated to a bigger size.
+    Grow {
+        /// Size before reallocation.
+        old_size: u32,
+        /// Size after reallocation.
+        new_size: u32,
+    },
+    /// A memory block was reallocated to a smaller size.
+    Shrink {
+        /// Size before reallocation.
+        old_size: u32,
+        /// Size after reallocation.
+        new_size: u32,
+    },
+}
+
+/// An error returned by [`TraceDecoder`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TraceDecodeError {
+    /// The packet tag is not recognized.
+    UnknownTag(u8),
+    /// The input ended in the middle of a packet.
+    Truncated,
+}
+
+/// An iterator decoding heap trace packets from a captured stream.
+///
+/// The input is the sequence of bytes written to the stream configured by
+/// `enable_trace_stream` of [`heap!`](crate::heap!). Each packet is a
+/// one-byte tag followed by one or two 32-bit little-endian words.
+///
+/// The iterator stops after the first error.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::heap::{TraceDecoder, TracePacket};
+///
+/// let capture = [0, 16, 0, 0, 0, 1, 16, 0, 0, 0];
+/// let packets = TraceDecoder::new(&capture).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(packets, [TracePacket::Allocate { size: 16 }, TracePacket::Deallocate {
+///     size: 16
+/// }]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TraceDecoder<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> TraceDecoder<'a> {
+    /// Creates a new decoder for the captured `input`.
+    #[inline]
+    pub fn new(input: &'a [u8]) -> Self {
+        Self { input }
+    }
+
+    /// Returns the bytes not yet decoded.
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        self.input
+    }
+
+    fn word(&mut self) -> Result<u32, TraceDecodeError> {
+        if self.input.len() < WORD_SIZE {
+            return Err(TraceDecodeError::Truncated);
+        }
+        let (word, rest) = self.input.split_at(WORD_SIZE);
+        self.input = rest;
+        Ok(u32::from_le_bytes(word.try_into().unwrap()))
+    }
+
+    fn packet(&mut self, tag: u8) -> Result<TracePacket, TraceDecodeError> {
+        Ok(match tag {
+            ALLOCATE_TAG => TracePacket::Allocate { size: self.word()? },
+            DEALLOCATE_TAG => TracePacket::Deallocate { size: self.word()? },
+            GROW_TAG => TracePacket::Grow { old_size: self.word()?, new_size: self.word()? },
+            SHRINK_TAG => TracePacket::Shrink { old_size: self.word()?, new_size: self.word()? },
+            tag => return Err(TraceDecodeError::UnknownTag(tag)),
+        })
+    }
+}
+
+impl Iterator for TraceDecoder<'_> {
+    type Item = Result<TracePacket, TraceDecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&tag, rest) = self.input.split_first()?;
+        let input = self.input;
+        self.input = rest;
+        let packet = self.packet(tag);
+        if packet.is_err() {
+            self.input = &input[input.len()..];
+        }
+        Some(packet)
+    }
+}
+
+/// A pool of the layout recommended by [`TraceAggregator`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PoolRecommendation {
+    /// Block size in bytes.
+    pub block: u32,
+    /// Number of blocks.
+    pub count: u32,
+}
+
+/// Collects allocation counts from decoded heap trace packets, and recommends
+/// a pools layout for the `layout.toml`.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::heap::{PoolRecommendation, TraceAggregator, TraceDecoder};
+///
+/// # let capture = [0, 4, 0, 0, 0, 0, 12, 0, 0, 0, 0, 16, 0, 0, 0];
+/// let mut aggregator = TraceAggregator::new();
+/// for packet in TraceDecoder::new(&capture) {
+///     aggregator.push(packet?);
+/// }
+/// for PoolRecommendation { block, count } in aggregator.recommend(8) {
+///     println!("{{ block = \"{block}\", count = \"{count}\" }},");
+/// }
+/// # Ok::<(), drone_core::heap::TraceDecodeError>(())
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct TraceAggregator {
+    sizes: BTreeMap<u32, SizeCount>,
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+struct SizeCount {
+    current: u32,
+    peak: u32,
+}
+
+impl TraceAggregator {
+    /// Creates an empty aggregator.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accounts the `packet`.
+    pub fn push(&mut self, packet: TracePacket) {
+        match packet {
+            TracePacket::Allocate { size } => self.allocate(size),
+            TracePacket::Deallocate { size } => self.deallocate(size),
+            TracePacket::Grow { old_size, new_size }
+            | TracePacket::Shrink { old_size, new_size } => {
+                self.deallocate(old_size);
+                self.allocate(new_size);
+            }
+        }
+    }
+
+    /// Returns an iterator over the requested sizes, with the highest numbers
+    /// of simultaneous allocations of each size.
+    pub fn peaks(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.sizes.iter().map(|(&size, count)| (size, count.peak))
+    }
+
+    /// Recommends a layout of at most `max_pools` pools, which can hold the
+    /// peak numbers of allocations with the least wasted memory.
+    ///
+    /// The block counts assume that allocations of the sizes merged into one
+    /// pool reach their peaks simultaneously.
+    pub fn recommend(&self, max_pools: usize) -> Vec<PoolRecommendation> {
+        let sizes = self
+            .sizes
+            .iter()
+            .filter(|(_, count)| count.peak > 0)
+            .map(|(&size, count)| (u64::from(size), u64::from(count.peak)))
+            .collect::<Vec<_>>();
+        let pools = max_pools.min(sizes.len());
+        if pools == 0 {
+            return Vec::new();
+        }
+        let n = sizes.len();
+        let mut peak_sums = vec![0; n + 1];
+        let mut byte_sums = vec![0; n + 1];
+        for (i, &(size, peak)) in sizes.iter().enumerate() {
+            peak_sums[i + 1] = peak_sums[i] + peak;
+            byte_sums[i + 1] = byte_sums[i] + size * peak;
+        }
+        // Wasted memory of serving the sizes `i..=j` by blocks of `sizes[j]`.
+        let waste = |i: usize, j: usize| {
+            sizes[j].0 * (peak_sums[j + 1] - peak_sums[i]) - (byte_sums[j + 1] - byte_sums[i])
+        };
+        // `cost[g][j]` is the least waste of serving the sizes `0..=j` by `g + 1`
+        // pools, and `split[g][j]` is the first size of the last of the pools.
+        let mut cost = vec![vec![u64::MAX; n]; pools];
+        let mut split = vec![vec![0; n]; pools];
+        for (j, cost) in cost[0].iter_mut().enumerate() {
+            *cost = waste(0, j);
+        }
+        for g in 1..pools {
+            for j in g..n {
+                for i in g..=j {
+                    let candidate = cost[g - 1][i - 1] + waste(i, j);
+                    if candidate < cost[g][j] {
+                        cost[g][j] = candidate;
+                        split[g][j] = i;
+                    }
+                }
+            }
+        }
+        let mut layout = Vec::with_capacity(pools);
+        let mut j = n - 1;
+        for g in (0..pools).rev() {
+            let i = if g == 0 { 0 } else { split[g][j] };
+            layout.push(PoolRecommendation {
+                block: sizes[j].0 as u32,
+                count: (peak_sums[j + 1] - peak_sums[i]) as u32,
+            });
+            j = i.saturating_sub(1);
+        }
+        layout.reverse();
+        layout
+    }
+
+    fn allocate(&mut self, size: u32) {
+        if size == 0 {
+            return;
+        }
+        let count = self.sizes.entry(size).or_default();
+        count.current += 1;
+        count.peak = count.peak.max(count.current);
+    }
+
+    fn deallocate(&mut self, size: u32) {
+        if let Some(count) = self.sizes.get_mut(&size) {
+            count.current = count.current.saturating_sub(1);
+        }
+    }
+}
+
+impl Extend<TracePacket> for TraceAggregator {
+    fn extend<I: IntoIterator<Item = TracePacket>>(&mut self, iter: I) {
+        for packet in iter {
+            self.push(packet);
+        }
+    }
+}
+
+impl fmt::Display for TraceDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownTag(tag) => write!(f, "unknown heap trace packet tag: {tag}"),
+            Self::Truncated => write!(f, "heap trace packet is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for TraceDecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        let capture = [2, 4, 0, 0, 0, 0, 1, 0, 0, 3, 0, 1, 0, 0, 8, 0, 0, 0];
+        let packets = TraceDecoder::new(&capture).collect::<Vec<_>>();
+        assert_eq!(packets, [
+            Ok(TracePacket::Grow { old_size: 4, new_size: 256 }),
+            Ok(TracePacket::Shrink { old_size: 256, new_size: 8 }),
+        ]);
+    }
+
+    #[test]
+    fn recommend() {
+        let mut aggregator = TraceAggregator::new();
+        aggregator.extend([
+            TracePacket::Allocate { size: 4 },
+            TracePacket::Allocate { size: 4 },
+            TracePacket::Deallocate { size: 4 },
+            TracePacket::Allocate { size: 4 },
+            TracePacket::Allocate { size: 12 },
+            TracePacket::Grow { old_size: 12, new_size: 16 },
+            TracePacket::Allocate { size: 12 },
+            TracePacket::Allocate { size: 100 },
+        ]);
+        assert_eq!(aggregator.peaks().collect::<Vec<_>>(), [(4, 2), (12, 1), (16, 1), (100, 1)]);
+        let pool = |block, count| PoolRecommendation { block, count };
+        assert_eq!(aggregator.recommend(3), [pool(4, 2), pool(16, 2), pool(100, 1)]);
+        assert_eq!(aggregator.recommend(8), [pool(4, 2), pool(12, 1), pool(16, 1), pool(100, 1)]);
+        assert_eq!(aggregator.recommend(1), [pool(100, 5)]);
+        assert!(TraceAggregator::new().recommend(4).is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        let mut decoder = TraceDecoder::new(&[0, 1, 0]);
+        assert_eq!(decoder.next(), Some(Err(TraceDecodeError::Truncated)));
+        assert_eq!(decoder.next(), None);
+        let mut decoder = TraceDecoder::new(&[7, 0, 0, 0, 0]);
+        assert_eq!(decoder.next(), Some(Err(TraceDecodeError::UnknownTag(7))));
+        assert_eq!(decoder.next(), None);
+    }
+}
@@ -0,0 +1,119 @@
+use super::wipe;
+use alloc::alloc::Global;
+use core::alloc::{Allocator, Layout};
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::ptr::NonNull;
+
+/// A [`Box`] wiping its memory with zeros when dropped.
+///
+/// Unlike a heap defined with `zeroize => true`, the box wipes only its own
+/// memory, and works with any allocator. It suits secrets, like
+/// cryptographic keys, which are allocated from a general-purpose heap.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::heap::ZeroizingBox;
+///
+/// let key = ZeroizingBox::new([0x2B_u8; 16]);
+/// assert_eq!(key[0], 0x2B);
+/// drop(key); // The key is wiped before the memory is returned to the heap.
+/// ```
+pub struct ZeroizingBox<T: ?Sized, A: Allocator = Global>(ManuallyDrop<Box<T, A>>);
+
+impl<T> ZeroizingBox<T> {
+    /// Allocates memory on the global heap and places `value` into it.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self::from_box(Box::new(value))
+    }
+}
+
+impl<T, A: Allocator> ZeroizingBox<T, A> {
+    /// Allocates memory in the given allocator and places `value` into it.
+    #[inline]
+    pub fn new_in(value: T, alloc: A) -> Self {
+        Self::from_box(Box::new_in(value, alloc))
+    }
+}
+
+impl<T: ?Sized, A: Allocator> ZeroizingBox<T, A> {
+    /// Takes ownership of the memory of `boxed`.
+    #[inline]
+    pub fn from_box(boxed: Box<T, A>) -> Self {
+        Self(ManuallyDrop::new(boxed))
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Deref for ZeroizingBox<T, A> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized, A: Allocator> DerefMut for ZeroizingBox<T, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for ZeroizingBox<T, A> {
+    fn drop(&mut self) {
+        let layout = Layout::for_value::<T>(&self.0);
+        let boxed = unsafe { ManuallyDrop::take(&mut self.0) };
+        let (raw, alloc) = Box::into_raw_with_allocator(boxed);
+        unsafe {
+            ptr::drop_in_place(raw);
+            if layout.size() != 0 {
+                let ptr = NonNull::new_unchecked(raw.cast::<u8>());
+                wipe(ptr, layout.size());
+                alloc.deallocate(ptr, layout);
+            }
+        }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug, A: Allocator> fmt::Debug for ZeroizingBox<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::alloc::AllocError;
+    use core::cell::Cell;
+
+    struct Spy<'a>(&'a Cell<Option<u8>>);
+
+    unsafe impl Allocator for Spy<'_> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            unsafe {
+                self.0.set(Some(*ptr.as_ptr().add(layout.size() - 1)));
+                Global.deallocate(ptr, layout);
+            }
+        }
+    }
+
+    #[test]
+    fn wipe_on_drop() {
+        let last = Cell::new(None);
+        let mut secret = ZeroizingBox::new_in([0xAA_u8; 8], Spy(&last));
+        secret[7] = 0x55;
+        assert_eq!(*secret, [0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0x55]);
+        drop(secret);
+        assert_eq!(last.get(), Some(0));
+    }
+}
@@ -206,11 +206,57 @@
 //!     assert!(!DMA_EN.load(Ordering::Relaxed));
 //! }
 //! ```
+//!
+//! # Generations
+//!
+//! Tokens are zero-sized, so they can't detect a resource reset. If a driver
+//! is torn down and re-created while an old guard is still held somewhere, for
+//! example by a forgotten fiber, the old guard would silently operate on the
+//! new driver. To catch this class of bugs, an item can embed a [`Generation`]
+//! counter and return it from [`Item::generation`]. The counter is advanced
+//! each time the item is torn down or freed from its inventory. A [`Claim`]
+//! records the generation at the moment it was made, and becomes stale after
+//! the next reset:
+//!
+//! ```
+//! use drone_core::inventory::{self, Claim, Generation, Inventory};
+//! use typenum::U0;
+//!
+//! pub struct DmaEn {
+//!     generation: Generation,
+//! }
+//!
+//! impl inventory::Item for DmaEn {
+//!     fn teardown(&mut self, _token: &mut inventory::GuardToken<DmaEn>) {}
+//!
+//!     fn generation(&self) -> Option<&Generation> {
+//!         Some(&self.generation)
+//!     }
+//! }
+//!
+//! let mut dma: Inventory<DmaEn, U0> = Inventory::new(DmaEn { generation: Generation::new() });
+//! let claim = {
+//!     let guard = Inventory::guard(&mut dma);
+//!     let claim = Claim::new(&*guard);
+//!     assert!(claim.check(&*guard).is_ok());
+//!     claim
+//! };
+//! // The guard has been dropped, and the item has been torn down.
+//! assert!(claim.check(&*dma).is_err());
+//! ```
 
+use core::fmt;
 use core::marker::PhantomData;
 use core::ops::{Add, Deref, DerefMut, Sub};
 use typenum::{Diff, Sum, Unsigned, U0, U1, U2, U3, U4, U5, U6, U7, U8};
 
+#[cfg(all(feature = "atomics", not(loom)))]
+type AtomicUsize = core::sync::atomic::AtomicUsize;
+#[cfg(all(feature = "atomics", loom))]
+type AtomicUsize = loom::sync::atomic::AtomicUsize;
+#[cfg(not(feature = "atomics"))]
+type AtomicUsize = crate::sync::soft_atomic::Atomic<usize>;
+
 /// The inventory wrapper for `T`. Parameter `C` encodes the number of emitted
 /// tokens.
 ///
@@ -237,10 +283,42 @@ pub struct GuardToken<T: Item>(PhantomData<T>);
 /// guarantees that `T` is in its active state.
 pub struct Token<T: Item>(PhantomData<T>);
 
+/// A generation counter of an inventory item.
+///
+/// See [the module-level documentation](self#generations) for details.
+pub struct Generation(AtomicUsize);
+
+/// A claim on the inventory item `T`, tagged with the item generation.
+///
+/// See [the module-level documentation](self#generations) for details.
+pub struct Claim<T: Item> {
+    generation: usize,
+    _marker: PhantomData<T>,
+}
+
+/// The error returned when a [`Claim`] is used after the item has been reset.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StaleClaim {
+    /// The generation recorded by the claim.
+    pub claimed: usize,
+    /// The current generation of the item.
+    pub current: usize,
+}
+
 /// An inventory item interface.
 pub trait Item: Sized {
     /// Sets the inactive state. Called by [`Guard`] on `drop`.
     fn teardown(&mut self, _token: &mut GuardToken<Self>);
+
+    /// Returns the generation counter of the item, if the item tracks
+    /// generations.
+    ///
+    /// The default implementation returns `None`, in which case all claims on
+    /// the item stay valid forever.
+    #[inline]
+    fn generation(&self) -> Option<&Generation> {
+        None
+    }
 }
 
 impl<T: Item> Inventory<T, U0> {
@@ -254,8 +332,11 @@ impl<T: Item> Inventory<T, U0> {
     }
 
     /// Drops `inventory` and returns the stored item.
+    ///
+    /// Advances the item generation.
     #[inline]
     pub fn free(inventory: Self) -> T {
+        advance(&inventory.item);
         inventory.item
     }
 
@@ -273,9 +354,12 @@ impl<T: Item> Inventory<T, U0> {
     }
 
     /// Calls [`Item::teardown`] for the stored item.
+    ///
+    /// Advances the item generation.
     #[inline]
     pub fn teardown(inventory: &mut Self) {
         inventory.item.teardown(&mut GuardToken(PhantomData));
+        advance(&inventory.item);
     }
 }
 
@@ -405,5 +489,87 @@ impl<T: Item> Drop for Guard<'_, T> {
     #[inline]
     fn drop(&mut self) {
         self.borrow.teardown(&mut self.guard_token);
+        advance(self.borrow);
+    }
+}
+
+impl Generation {
+    maybe_const_fn! {
+        /// Creates a new generation counter starting from zero.
+        #[inline]
+        pub const fn new() -> Self {
+            Self(AtomicUsize::new(0))
+        }
+    }
+
+    /// Returns the current generation.
+    #[inline]
+    pub fn current(&self) -> usize {
+        load_atomic!(self.0, Relaxed)
+    }
+
+    /// Advances the generation, making all existing claims stale.
+    #[inline]
+    pub fn advance(&self) {
+        load_modify_atomic!(self.0, Relaxed, Relaxed, |generation| generation.wrapping_add(1));
+    }
+}
+
+impl Default for Generation {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Item> Claim<T> {
+    /// Creates a new claim on the current generation of `item`.
+    #[inline]
+    pub fn new(item: &T) -> Self {
+        Self { generation: generation(item), _marker: PhantomData }
+    }
+
+    /// Returns the generation recorded by the claim.
+    #[inline]
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Checks that `item` hasn't been reset since the claim was made.
+    #[inline]
+    pub fn check(&self, item: &T) -> Result<(), StaleClaim> {
+        let current = generation(item);
+        if self.generation == current {
+            Ok(())
+        } else {
+            Err(StaleClaim { claimed: self.generation, current })
+        }
+    }
+}
+
+impl<T: Item> Clone for Claim<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self { generation: self.generation, _marker: PhantomData }
+    }
+}
+
+impl fmt::Display for StaleClaim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "stale inventory claim: claimed generation {}, current generation {}",
+            self.claimed, self.current
+        )
+    }
+}
+
+fn generation<T: Item>(item: &T) -> usize {
+    item.generation().map_or(0, Generation::current)
+}
+
+fn advance<T: Item>(item: &T) {
+    if let Some(generation) = item.generation() {
+        generation.advance();
     }
 }
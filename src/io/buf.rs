@@ -0,0 +1,360 @@
+use super::{Read, Write};
+use core::cmp;
+
+/// An error returned by [`BufReader`] and [`BufWriter`] operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufError<E> {
+    /// The underlying reader or writer returned an error.
+    Io(E),
+    /// The line doesn't fit into the provided buffer.
+    Overflow,
+    /// The underlying writer accepted zero bytes.
+    WriteZero,
+}
+
+/// Adds buffering to a [`Read`]er.
+///
+/// The buffer is provided by the caller, therefore the reader doesn't
+/// allocate, and the buffer can be placed in a static.
+///
+/// # Examples
+///
+/// Parsing a line-oriented protocol:
+///
+/// ```no_run
+/// # async fn f<R: for<'sess> drone_core::io::Read<'sess, Error = ()>>(uart: R) {
+/// use drone_core::io::BufReader;
+///
+/// let mut buf = [0; 64];
+/// let mut line = [0; 82];
+/// let mut reader = BufReader::new(uart, &mut buf);
+/// loop {
+///     match reader.read_line(&mut line).await {
+///         Ok(0) => break,
+///         Ok(len) => handle_sentence(&line[..len]),
+///         Err(_) => continue,
+///     }
+/// }
+/// # }
+/// # fn handle_sentence(_: &[u8]) {}
+/// ```
+pub struct BufReader<'buf, R> {
+    inner: R,
+    buf: &'buf mut [u8],
+    pos: usize,
+    filled: usize,
+}
+
+/// Adds buffering to a [`Write`]r.
+///
+/// The buffer is provided by the caller, therefore the writer doesn't
+/// allocate. The buffered data is not flushed on drop, [`BufWriter::flush`]
+/// should be called explicitly.
+pub struct BufWriter<'buf, W> {
+    inner: W,
+    buf: &'buf mut [u8],
+    len: usize,
+}
+
+impl<'buf, R> BufReader<'buf, R> {
+    /// Creates a new `BufReader` for `inner` using `buf` as the buffer.
+    #[inline]
+    pub fn new(inner: R, buf: &'buf mut [u8]) -> Self {
+        Self { inner, buf, pos: 0, filled: 0 }
+    }
+
+    /// Returns a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the currently buffered data.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    /// Returns the size of the buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Marks `amt` bytes of the buffered data as consumed.
+    #[inline]
+    pub fn consume(&mut self, amt: usize) {
+        self.pos = cmp::min(self.pos + amt, self.filled);
+    }
+
+    /// Consumes this `BufReader`, returning the underlying reader.
+    ///
+    /// The buffered data is lost.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Returns the buffered data, reading more from the underlying reader if
+    /// the buffer is empty.
+    ///
+    /// An empty slice means the underlying reader reached the end.
+    pub async fn fill_buf<E>(&mut self) -> Result<&[u8], E>
+    where
+        R: for<'sess> Read<'sess, Error = E>,
+    {
+        if self.pos == self.filled {
+            self.pos = 0;
+            self.filled = 0;
+            self.filled = self.inner.read(&mut self.buf[..]).await?;
+        }
+        Ok(self.buffer())
+    }
+
+    /// Reads some bytes into `buf`, eventually returning how many bytes were
+    /// read.
+    ///
+    /// If the buffer is empty and `buf` is not smaller than the buffer, reads
+    /// directly from the underlying reader.
+    pub async fn read<E>(&mut self, buf: &mut [u8]) -> Result<usize, E>
+    where
+        R: for<'sess> Read<'sess, Error = E>,
+    {
+        if self.pos == self.filled && buf.len() >= self.buf.len() {
+            return self.inner.read(buf).await;
+        }
+        let available = self.fill_buf().await?;
+        let amt = cmp::min(available.len(), buf.len());
+        buf[..amt].copy_from_slice(&available[..amt]);
+        self.consume(amt);
+        Ok(amt)
+    }
+
+    /// Reads bytes into `line` until a newline (the `0xA` byte) is reached,
+    /// eventually returning how many bytes were read.
+    ///
+    /// The newline byte is included into the returned length. Returns less
+    /// bytes without the newline if the underlying reader reached the end, and
+    /// `Ok(0)` if there were no more bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BufError::Overflow`] if `line` filled up before a newline was
+    /// found. In this case `line` contains the beginning of the line, and the
+    /// next call will read the rest of the line.
+    pub async fn read_line<E>(&mut self, line: &mut [u8]) -> Result<usize, BufError<E>>
+    where
+        R: for<'sess> Read<'sess, Error = E>,
+    {
+        let mut len = 0;
+        loop {
+            let available = self.fill_buf().await.map_err(BufError::Io)?;
+            if available.is_empty() {
+                return Ok(len);
+            }
+            let (chunk, done) = match available.iter().position(|&byte| byte == b'\n') {
+                Some(idx) => (&available[..=idx], true),
+                None => (available, false),
+            };
+            let rest = line.len() - len;
+            if chunk.len() > rest {
+                line[len..].copy_from_slice(&chunk[..rest]);
+                self.consume(rest);
+                return Err(BufError::Overflow);
+            }
+            let amt = chunk.len();
+            line[len..len + amt].copy_from_slice(chunk);
+            self.consume(amt);
+            len += amt;
+            if done {
+                return Ok(len);
+            }
+        }
+    }
+}
+
+impl<'buf, W> BufWriter<'buf, W> {
+    /// Creates a new `BufWriter` for `inner` using `buf` as the buffer.
+    #[inline]
+    pub fn new(inner: W, buf: &'buf mut [u8]) -> Self {
+        Self { inner, buf, len: 0 }
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the currently buffered data.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Returns the size of the buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Consumes this `BufWriter`, returning the underlying writer.
+    ///
+    /// The buffered data is lost.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Writes `data` into the buffer, eventually returning how many bytes were
+    /// written.
+    ///
+    /// If `data` doesn't fit, flushes the buffer first. If `data` is not
+    /// smaller than the buffer, writes directly to the underlying writer.
+    pub async fn write<E>(&mut self, data: &[u8]) -> Result<usize, BufError<E>>
+    where
+        W: for<'sess> Write<'sess, Error = E>,
+    {
+        if self.len + data.len() > self.buf.len() {
+            self.flush().await?;
+        }
+        if data.len() >= self.buf.len() {
+            return self.inner.write(data).await.map_err(BufError::Io);
+        }
+        self.buf[self.len..self.len + data.len()].copy_from_slice(data);
+        self.len += data.len();
+        Ok(data.len())
+    }
+
+    /// Writes the whole `data`.
+    pub async fn write_all<E>(&mut self, mut data: &[u8]) -> Result<(), BufError<E>>
+    where
+        W: for<'sess> Write<'sess, Error = E>,
+    {
+        while !data.is_empty() {
+            match self.write(data).await? {
+                0 => return Err(BufError::WriteZero),
+                amt => data = &data[amt..],
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes all buffered data to the underlying writer.
+    ///
+    /// On error the data, which wasn't written, stays in the buffer.
+    pub async fn flush<E>(&mut self) -> Result<(), BufError<E>>
+    where
+        W: for<'sess> Write<'sess, Error = E>,
+    {
+        let mut written = 0;
+        let result = loop {
+            if written == self.len {
+                break Ok(());
+            }
+            match self.inner.write(&self.buf[written..self.len]).await {
+                Ok(0) => break Err(BufError::WriteZero),
+                Ok(amt) => written += amt,
+                Err(err) => break Err(BufError::Io(err)),
+            }
+        };
+        self.buf.copy_within(written..self.len, 0);
+        self.len -= written;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::future::Future;
+    use core::pin::Pin;
+    use futures::FutureExt;
+
+    struct Chunks {
+        data: &'static [u8],
+        chunk: usize,
+    }
+
+    struct Sink {
+        data: Vec<u8>,
+        chunk: usize,
+    }
+
+    impl<'sess> Read<'sess> for Chunks {
+        type Error = ();
+
+        fn read(
+            &'sess mut self,
+            buf: &'sess mut [u8],
+        ) -> Pin<Box<dyn Future<Output = Result<usize, ()>> + Send + 'sess>> {
+            let amt = cmp::min(cmp::min(self.chunk, buf.len()), self.data.len());
+            buf[..amt].copy_from_slice(&self.data[..amt]);
+            self.data = &self.data[amt..];
+            Box::pin(async move { Ok(amt) })
+        }
+    }
+
+    impl<'sess> Write<'sess> for Sink {
+        type Error = ();
+
+        fn write(
+            &'sess mut self,
+            buf: &'sess [u8],
+        ) -> Pin<Box<dyn Future<Output = Result<usize, ()>> + Send + 'sess>> {
+            let amt = cmp::min(self.chunk, buf.len());
+            self.data.extend_from_slice(&buf[..amt]);
+            Box::pin(async move { Ok(amt) })
+        }
+    }
+
+    #[test]
+    fn read_lines() {
+        let mut buf = [0; 8];
+        let mut line = [0; 16];
+        let chunks = Chunks { data: b"$GPGGA,1\r\n$GPRMC\nEND", chunk: 3 };
+        let mut reader = BufReader::new(chunks, &mut buf);
+        assert_eq!(reader.read_line(&mut line).now_or_never(), Some(Ok(10)));
+        assert_eq!(&line[..10], b"$GPGGA,1\r\n");
+        assert_eq!(reader.read_line(&mut line).now_or_never(), Some(Ok(7)));
+        assert_eq!(&line[..7], b"$GPRMC\n");
+        assert_eq!(reader.read_line(&mut line).now_or_never(), Some(Ok(3)));
+        assert_eq!(&line[..3], b"END");
+        assert_eq!(reader.read_line(&mut line).now_or_never(), Some(Ok(0)));
+    }
+
+    #[test]
+    fn read_line_overflow() {
+        let mut buf = [0; 8];
+        let mut line = [0; 4];
+        let mut reader = BufReader::new(Chunks { data: b"ABCDEF\n", chunk: 8 }, &mut buf);
+        assert_eq!(reader.read_line(&mut line).now_or_never(), Some(Err(BufError::Overflow)));
+        assert_eq!(&line, b"ABCD");
+        assert_eq!(reader.read_line(&mut line).now_or_never(), Some(Ok(3)));
+        assert_eq!(&line[..3], b"EF\n");
+    }
+
+    #[test]
+    fn write_flush() {
+        let mut buf = [0; 4];
+        let mut writer = BufWriter::new(Sink { data: Vec::new(), chunk: 3 }, &mut buf);
+        assert_eq!(writer.write_all(b"AT").now_or_never(), Some(Ok(())));
+        assert!(writer.get_ref().data.is_empty());
+        assert_eq!(writer.write_all(b"+CSQ\r\n").now_or_never(), Some(Ok(())));
+        assert_eq!(writer.flush().now_or_never(), Some(Ok(())));
+        assert!(writer.buffer().is_empty());
+        assert_eq!(writer.into_inner().data, b"AT+CSQ\r\n");
+    }
+}
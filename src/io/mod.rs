@@ -4,11 +4,17 @@
 //! and output. The most core part of this module is the [`Read`] and [`Write`]
 //! traits, which provide the most general interface for reading and writing
 //! input and output.
+//!
+//! [`BufReader`] and [`BufWriter`] add buffering on top of these traits using
+//! caller-provided buffers. [`BufReader::read_line`] is useful for parsing
+//! line-oriented text protocols, like NMEA sentences or AT commands.
 
+mod buf;
 mod read;
 mod seek;
 mod write;
 
+pub use self::buf::{BufError, BufReader, BufWriter};
 pub use self::read::Read;
 pub use self::seek::{Seek, SeekFrom};
 pub use self::write::Write;
@@ -1,15 +1,27 @@
-use crate::{eprintln, platform};
+#[cfg(not(feature = "compact-panic"))]
+use crate::eprintln;
+#[cfg(feature = "compact-panic")]
+use crate::panic::{CompactReport, ALLOC_ERROR_FILE_ID};
+use crate::platform;
 use core::alloc::Layout;
 use core::panic::PanicInfo;
 
 #[panic_handler]
 fn begin_panic(pi: &PanicInfo<'_>) -> ! {
+    #[cfg(not(feature = "compact-panic"))]
     eprintln!("{}", pi);
+    #[cfg(feature = "compact-panic")]
+    if let Some(location) = pi.location() {
+        CompactReport::from_location(location).write();
+    }
     platform::reset()
 }
 
 #[alloc_error_handler]
 fn alloc_error_handler(layout: Layout) -> ! {
+    #[cfg(not(feature = "compact-panic"))]
     eprintln!("memory allocation of {} bytes failed", layout.size());
+    #[cfg(feature = "compact-panic")]
+    CompactReport { file_id: ALLOC_ERROR_FILE_ID, line: layout.size() as u32, column: 0 }.write();
     platform::reset()
 }
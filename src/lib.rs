@@ -58,18 +58,24 @@ extern crate alloc;
 mod atomic_macros;
 
 pub mod bitfield;
+pub mod drv;
 pub mod fib;
 pub mod heap;
 pub mod inventory;
 pub mod io;
 pub mod mem;
+pub mod panic;
 pub mod periph;
 pub mod platform;
 pub mod prelude;
 pub mod proc_loop;
 pub mod reg;
+pub mod registry;
 pub mod stream;
+pub mod sv;
 pub mod sync;
+#[cfg(feature = "host")]
+pub mod testing;
 pub mod thr;
 pub mod token;
 
@@ -101,6 +107,11 @@ pub use drone_core_macros::reg;
 /// See [the module level documentation](mod@stream) for details.
 #[doc(inline)]
 pub use drone_core_macros::stream;
+/// Defines the supervisor.
+///
+/// See [the module level documentation](mod@sv) for details.
+#[doc(inline)]
+pub use drone_core_macros::sv;
 
 /// Re-exports for use inside macros.
 #[doc(hidden)]
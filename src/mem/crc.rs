@@ -0,0 +1,161 @@
+use core::cell::UnsafeCell;
+use core::slice;
+
+/// The reversed polynomial of CRC-32.
+const CRC32_POLY: u32 = 0xEDB8_8320;
+
+/// The polynomial of CRC-16/CCITT.
+const CRC16_POLY: u16 = 0x1021;
+
+/// An incremental CRC-32 (ISO-HDLC) computation.
+///
+/// This is the checksum used by Ethernet, zlib, and PNG: the reflected
+/// polynomial `0x04C11DB7`, the initial value `0xFFFFFFFF`, and the final XOR
+/// value `0xFFFFFFFF`.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::mem::{crc32, Crc32};
+///
+/// let mut crc = Crc32::new();
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+/// assert_eq!(crc.finish(), crc32(b"123456789"));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32(u32);
+
+/// An incremental CRC-16 (CCITT-FALSE) computation.
+///
+/// The polynomial `0x1021`, the initial value `0xFFFF`, no reflection, and no
+/// final XOR.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::mem::{crc16, Crc16};
+///
+/// let mut crc = Crc16::new();
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+/// assert_eq!(crc.finish(), crc16(b"123456789"));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Crc16(u16);
+
+impl Crc32 {
+    /// Starts a new computation.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(!0)
+    }
+
+    /// Feeds `data` into the computation.
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.0 ^= u32::from(byte);
+            for _ in 0..8 {
+                self.0 = self.0 >> 1 ^ CRC32_POLY & (self.0 & 1).wrapping_neg();
+            }
+        }
+    }
+
+    /// Returns the checksum of the data fed so far.
+    #[inline]
+    pub const fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+impl Default for Crc32 {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc16 {
+    /// Starts a new computation.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(!0)
+    }
+
+    /// Feeds `data` into the computation.
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.0 ^= u16::from(byte) << 8;
+            for _ in 0..8 {
+                self.0 = self.0 << 1 ^ CRC16_POLY & (self.0 >> 15).wrapping_neg();
+            }
+        }
+    }
+
+    /// Returns the checksum of the data fed so far.
+    #[inline]
+    pub const fn finish(&self) -> u16 {
+        self.0
+    }
+}
+
+impl Default for Crc16 {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Computes the CRC-32 checksum of `data`.
+///
+/// See [`Crc32`] for the parameters.
+#[inline]
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+/// Computes the CRC-16 checksum of `data`.
+///
+/// See [`Crc16`] for the parameters.
+#[inline]
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = Crc16::new();
+    crc.update(data);
+    crc.finish()
+}
+
+/// Computes the CRC-32 checksum of the memory region from `base` to `end`.
+///
+/// # Safety
+///
+/// `base` and `end` must be the bounds of a readable memory region, usually
+/// linker symbols, with `base` not greater than `end`.
+pub unsafe fn crc32_region(base: &UnsafeCell<usize>, end: &UnsafeCell<usize>) -> u32 {
+    let base = base.get().cast::<u8>().cast_const();
+    let len = end.get() as usize - base as usize;
+    crc32(unsafe { slice::from_raw_parts(base, len) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::mem::size_of;
+
+    #[test]
+    fn check_values() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc16(b""), 0xFFFF);
+    }
+
+    #[test]
+    fn region() {
+        let data = [UnsafeCell::new(0x0403_0201_usize), UnsafeCell::new(0)];
+        let base = data[0].get().cast::<u8>();
+        let bytes = unsafe { slice::from_raw_parts(base, size_of::<usize>()) };
+        assert_eq!(unsafe { crc32_region(&data[0], &data[1]) }, crc32(bytes));
+    }
+}
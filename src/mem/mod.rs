@@ -0,0 +1,69 @@
+//! Basic functions for dealing with memory.
+//!
+//! # Checksums
+//!
+//! [`crc32`] and [`crc16`] compute checksums without lookup tables, trading
+//! speed for code size. They are suitable for firmware image self-checks and
+//! protocol frame check sequences. [`Crc32`] and [`Crc16`] compute the same
+//! checksums incrementally. [`crc32_region`] computes the checksum of a
+//! memory region bounded by linker symbols:
+//!
+//! ```no_run
+//! use core::cell::UnsafeCell;
+//! use drone_core::mem;
+//!
+//! extern "C" {
+//!     static IMAGE_BASE: UnsafeCell<usize>;
+//!     static IMAGE_END: UnsafeCell<usize>;
+//!     static IMAGE_CRC: u32;
+//! }
+//!
+//! let valid = unsafe { mem::crc32_region(&IMAGE_BASE, &IMAGE_END) == IMAGE_CRC };
+//! ```
+
+mod crc;
+
+pub use self::crc::{crc16, crc32, crc32_region, Crc16, Crc32};
+use crate::platform::{data_mem_init, zeroed_mem_init};
+use core::cell::UnsafeCell;
+use core::ptr;
+
+/// The value of [`INIT_MARKER`] after [`init`] has been called.
+const INIT_MAGIC: u32 = 0xD0E1_C0DE;
+
+extern "C" {
+    static BSS_BASE: UnsafeCell<usize>;
+    static BSS_END: UnsafeCell<usize>;
+    static DATA_LOAD: UnsafeCell<usize>;
+    static DATA_BASE: UnsafeCell<usize>;
+    static DATA_END: UnsafeCell<usize>;
+}
+
+/// A mutable static placed into the data section, which is populated by
+/// [`init`].
+static mut INIT_MARKER: u32 = INIT_MAGIC;
+
+/// Initializes global mutable memory.
+///
+/// This function **must** be called as early as possible, because it
+/// initializes `static` variables.
+///
+/// # Safety
+///
+/// This function must be called only once and before using any global
+/// variables.
+pub unsafe fn init() {
+    unsafe {
+        zeroed_mem_init(&BSS_BASE, &BSS_END);
+        data_mem_init(&DATA_LOAD, &DATA_BASE, &DATA_END);
+    }
+}
+
+/// Returns `true` if [`init`] has been called.
+///
+/// This is a heuristic: before [`init`] the data section contains arbitrary
+/// bytes, which could happen to match the marker value.
+#[inline]
+pub fn is_initialized() -> bool {
+    unsafe { ptr::read_volatile(ptr::addr_of!(INIT_MARKER)) == INIT_MAGIC }
+}
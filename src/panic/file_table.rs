@@ -0,0 +1,101 @@
+use super::file_id;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// A table resolving file-ids back to source file paths.
+///
+/// See [the module level documentation](super) for details.
+#[derive(Clone, Default, Debug)]
+pub struct FileTable {
+    files: BTreeMap<u32, String>,
+}
+
+impl FileTable {
+    /// Creates an empty table.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a table of all `.rs` files found recursively in `root`.
+    ///
+    /// The paths are stored with `root` prepended, and use `/` as the
+    /// separator. Therefore `root` should be the path relative to the
+    /// directory, from which `cargo` compiles the firmware, e.g. `src`.
+    pub fn scan(root: &Path) -> io::Result<Self> {
+        let mut table = Self::new();
+        table.scan_dir(root)?;
+        Ok(table)
+    }
+
+    /// Inserts the source file `path`, and returns its file-id.
+    pub fn insert(&mut self, path: impl Into<String>) -> u32 {
+        let path = path.into();
+        let file_id = file_id(&path);
+        self.files.insert(file_id, path);
+        file_id
+    }
+
+    /// Returns the source file path for `file_id`.
+    #[inline]
+    pub fn get(&self, file_id: u32) -> Option<&str> {
+        self.files.get(&file_id).map(String::as_str)
+    }
+
+    /// Writes the table as lines of a hexadecimal file-id and a path.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        for (file_id, path) in &self.files {
+            writeln!(writer, "{file_id:08x} {path}")?;
+        }
+        Ok(())
+    }
+
+    /// Reads a table previously written with [`FileTable::write_to`].
+    pub fn read_from(reader: impl BufRead) -> io::Result<Self> {
+        let mut table = Self::new();
+        for line in reader.lines() {
+            let line = line?;
+            let (file_id, path) = line
+                .split_once(' ')
+                .and_then(|(file_id, path)| Some((u32::from_str_radix(file_id, 16).ok()?, path)))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, line.clone()))?;
+            table.files.insert(file_id, path.to_string());
+        }
+        Ok(table)
+    }
+
+    fn scan_dir(&mut self, dir: &Path) -> io::Result<()> {
+        let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(fs::DirEntry::path);
+        for entry in entries {
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                self.scan_dir(&path)?;
+            } else if path.extension().map_or(false, |extension| extension == "rs") {
+                let path = path.to_string_lossy().replace('\\', "/");
+                self.insert(path);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_read() {
+        let mut table = FileTable::new();
+        let main = table.insert("src/main.rs");
+        let lib = table.insert("src/lib.rs");
+        let mut output = Vec::new();
+        table.write_to(&mut output).unwrap();
+        let table = FileTable::read_from(output.as_slice()).unwrap();
+        assert_eq!(table.get(main), Some("src/main.rs"));
+        assert_eq!(table.get(lib), Some("src/lib.rs"));
+        assert_eq!(table.get(0), None);
+    }
+}
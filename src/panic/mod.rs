@@ -0,0 +1,144 @@
+//! Compact panic reports.
+//!
+//! Panic messages are formatted with `core::fmt`, which together with the
+//! message strings can dominate the flash usage of a small image. With
+//! `compact-panic` cargo feature enabled, the panic handler doesn't touch the
+//! panic message. Instead it writes a 12-byte [`CompactReport`] to the
+//! [standard error stream](crate::stream::STDERR_STREAM): a file-id, a line,
+//! and a column, each encoded as a 32-bit little-endian word.
+//!
+//! A file-id is a hash of the source file path computed with [`file_id`]. The
+//! host side resolves file-ids back to paths with a [`FileTable`], which can be
+//! generated at build time from the project sources:
+//!
+//! ```no_run
+//! // build.rs
+//! use drone_core::panic::FileTable;
+//! use std::env;
+//! use std::fs::File;
+//! use std::path::Path;
+//!
+//! fn main() -> std::io::Result<()> {
+//!     let table = FileTable::scan(Path::new("src"))?;
+//!     let out = Path::new(&env::var_os("OUT_DIR").unwrap()).join("panic_files.txt");
+//!     table.write_to(File::create(out)?)
+//! }
+//! ```
+//!
+//! The file-id `0` is reserved for memory allocation failures, in which case
+//! the line contains the requested allocation size.
+
+#[cfg(feature = "host")]
+mod file_table;
+
+#[cfg(feature = "host")]
+pub use self::file_table::FileTable;
+use crate::stream::{Stream, STDERR_STREAM};
+use core::fmt;
+use core::panic::Location;
+
+/// Size of an encoded [`CompactReport`].
+pub const REPORT_SIZE: usize = 12;
+
+/// The file-id reserved for memory allocation failures.
+pub const ALLOC_ERROR_FILE_ID: u32 = 0;
+
+/// A compact panic report.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CompactReport {
+    /// Hash of the source file path, see [`file_id`].
+    pub file_id: u32,
+    /// Line number.
+    pub line: u32,
+    /// Column number.
+    pub column: u32,
+}
+
+/// Returns the file-id for the source file `path`.
+///
+/// The file-id is a 32-bit FNV-1a hash of the path. It is never equal to
+/// [`ALLOC_ERROR_FILE_ID`].
+pub const fn file_id(path: &str) -> u32 {
+    let bytes = path.as_bytes();
+    let mut hash = 0x811C_9DC5_u32;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+        i += 1;
+    }
+    if hash == ALLOC_ERROR_FILE_ID { 1 } else { hash }
+}
+
+impl CompactReport {
+    /// Creates a report for the source code location `location`.
+    #[inline]
+    pub fn from_location(location: &Location<'_>) -> Self {
+        Self {
+            file_id: file_id(location.file()),
+            line: location.line(),
+            column: location.column(),
+        }
+    }
+
+    /// Encodes the report into bytes.
+    pub fn encode(&self) -> [u8; REPORT_SIZE] {
+        let mut bytes = [0; REPORT_SIZE];
+        bytes[0..4].copy_from_slice(&self.file_id.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.line.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.column.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a report from `bytes`.
+    pub fn decode(bytes: &[u8; REPORT_SIZE]) -> Self {
+        let word = |i: usize| {
+            u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+        };
+        Self { file_id: word(0), line: word(4), column: word(8) }
+    }
+
+    /// Returns `true` if the report is for a memory allocation failure.
+    #[inline]
+    pub fn is_alloc_error(&self) -> bool {
+        self.file_id == ALLOC_ERROR_FILE_ID
+    }
+
+    /// Writes the report to the standard error stream, if the stream is
+    /// enabled.
+    pub fn write(&self) {
+        let stream = Stream::new(STDERR_STREAM);
+        if stream.is_enabled() {
+            stream.write_transaction(&self.encode());
+        }
+    }
+}
+
+impl fmt::Display for CompactReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_alloc_error() {
+            write!(f, "memory allocation of {} bytes failed", self.line)
+        } else {
+            write!(f, "panicked at file #{:08x}:{}:{}", self.file_id, self.line, self.column)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_ids() {
+        assert_eq!(file_id(""), 0x811C_9DC5);
+        assert_eq!(file_id("a"), 0xE40C_292C);
+        assert_ne!(file_id("src/main.rs"), file_id("src/lib.rs"));
+    }
+
+    #[test]
+    fn encode_decode() {
+        let report = CompactReport { file_id: 0xDEAD_BEEF, line: 42, column: 7 };
+        assert_eq!(CompactReport::decode(&report.encode()), report);
+        assert_eq!(report.to_string(), "panicked at file #deadbeef:42:7");
+    }
+}
@@ -112,7 +112,7 @@
 //! #         address => 0; size => 32; reset => 0; traits => { RReg WReg };
 //! #         fields => {
 //! #             UART4EN => { offset => 0; width => 1; traits => { RRRegField WWRegField } };
-//! #             UARTRST => { offset => 0; width => 1; traits => { RRRegField WWRegField } };
+//! #             UARTRST => { offset => 1; width => 1; traits => { RRRegField WWRegField } };
 //! #         };
 //! #     };
 //! # }
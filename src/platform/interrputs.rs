@@ -1,6 +1,9 @@
 #![cfg_attr(feature = "host", allow(unused_imports, unused_variables))]
 
-use super::{drone_restore_interrupts, drone_save_and_disable_interrupts};
+use super::{
+    drone_restore_interrupt_mask, drone_restore_interrupts, drone_save_and_disable_interrupts,
+    drone_save_and_mask_interrupts,
+};
 
 /// Critical section.
 ///
@@ -36,6 +39,19 @@ pub struct Interrupts {
     save: u32,
 }
 
+/// Priority-masked critical section.
+///
+/// A variation of [`Interrupts`], which blocks only interrupts with priorities
+/// lower than a given priority, leaving higher-priority interrupts running.
+/// This type masks interrupts on creation and restores the previous mask on
+/// drop. Masked critical sections are allowed to be nested.
+///
+/// On ARM Cortex-M this is normally backed by the `BASEPRI` register. It is
+/// created with [`Interrupts::mask_below`] or [`Interrupts::masked_below`].
+pub struct InterruptsMask {
+    save: u32,
+}
+
 impl Interrupts {
     /// Creates a new critical section handle.
     ///
@@ -92,6 +108,66 @@ impl Interrupts {
         let _paused = Self::pause();
         f()
     }
+
+    /// Creates a new priority-masked critical section handle.
+    ///
+    /// This function blocks interrupts with priorities lower than `priority`
+    /// for the current CPU. Interrupts with `priority` and higher are still
+    /// able to preempt. The previous mask is restored when the returned
+    /// instance is dropped.
+    ///
+    /// The masked critical section protects only data, which is never
+    /// accessed from interrupts with `priority` and higher.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use drone_core::platform::Interrupts;
+    ///
+    /// let mut x = 0;
+    /// {
+    ///     // Block interrupts with priorities lower than 2.
+    ///     let _masked = Interrupts::mask_below(2);
+    ///     x += 1;
+    /// }
+    /// dbg!(x);
+    /// ```
+    #[inline]
+    pub fn mask_below(priority: u8) -> InterruptsMask {
+        InterruptsMask {
+            #[cfg(feature = "host")]
+            save: 0,
+            #[cfg(not(feature = "host"))]
+            save: unsafe { drone_save_and_mask_interrupts(priority) },
+        }
+    }
+
+    /// Runs a closure inside a priority-masked critical section.
+    ///
+    /// Interrupts with priorities lower than `priority` are blocked before
+    /// executing the closure and unblocked after the closure has executed
+    /// (unless it's nested into another masked critical section).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use drone_core::platform::Interrupts;
+    ///
+    /// let mut x = 0;
+    /// Interrupts::masked_below(2, || {
+    ///     // This block of code can be interrupted only by interrupts with
+    ///     // priorities 2 and higher.
+    ///     x += 1;
+    /// });
+    /// dbg!(x);
+    /// ```
+    #[cfg_attr(not(feature = "xip"), inline)]
+    #[cfg_attr(feature = "xip", inline(never))]
+    #[cfg_attr(feature = "xip", link_section = ".time_critical")]
+    pub fn masked_below<R, F: FnOnce() -> R>(priority: u8, f: F) -> R {
+        let _masked = Self::mask_below(priority);
+        f()
+    }
 }
 
 impl Drop for Interrupts {
@@ -103,3 +179,14 @@ impl Drop for Interrupts {
         }
     }
 }
+
+impl Drop for InterruptsMask {
+    #[inline]
+    fn drop(&mut self) {
+        let Self { save } = *self;
+        #[cfg(not(feature = "host"))]
+        unsafe {
+            drone_restore_interrupt_mask(save);
+        }
+    }
+}
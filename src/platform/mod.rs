@@ -4,17 +4,24 @@
 
 mod interrputs;
 
-pub use self::interrputs::Interrupts;
+pub use self::interrputs::{Interrupts, InterruptsMask};
 use core::cell::UnsafeCell;
+use core::mem;
 use drone_stream::Runtime;
 
 extern "C" {
     fn drone_reset() -> !;
+    fn drone_boot_jump(vector_table: usize) -> !;
     fn drone_save_and_disable_interrupts() -> u32;
     fn drone_restore_interrupts(status: u32);
+    fn drone_save_and_mask_interrupts(priority: u8) -> u32;
+    fn drone_restore_interrupt_mask(status: u32);
     fn drone_data_mem_init(load: *const usize, base: *mut usize, end: *const usize);
     fn drone_zeroed_mem_init(base: *mut usize, end: *const usize);
     fn drone_stream_runtime() -> *mut Runtime;
+    fn drone_stream_dedicated_runtime(stream: u8) -> *mut Runtime;
+    #[cfg(feature = "fib-budget")]
+    fn drone_cycle_counter() -> u32;
 }
 
 /// Runs a predicate in a tight loop. Stops when the predicate returns `false`.
@@ -80,6 +87,73 @@ pub fn reset() -> ! {
     }
 }
 
+/// Jumps to another firmware image, e.g. a bootloader or a second firmware
+/// slot, with the vector table at `vector_table`.
+///
+/// The platform crate loads the stack pointer and the reset handler from the
+/// vector table, and transfers control to the reset handler. Normally
+/// [`boot_handoff`] should be used instead, which quiesces Drone subsystems
+/// first.
+///
+/// # Safety
+///
+/// `vector_table` must point to a valid vector table. The state of the
+/// peripherals left by the current firmware must be acceptable for the target
+/// firmware.
+#[inline]
+pub unsafe fn boot_jump(vector_table: usize) -> ! {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        drone_boot_jump(vector_table)
+    }
+}
+
+/// Quiesces Drone subsystems and jumps to another firmware image with the
+/// vector table at `vector_table`.
+///
+/// The hand-off goes as follows:
+///
+/// 1. `pause` is called, which should pause the thread pools with
+///    [`thr::pause_pool`](crate::thr::pause_pool), and tear down the drivers.
+/// 2. The streams are given a chance to be read by a debug probe with
+///    [`stream::wait_drained`](crate::stream::wait_drained), which is limited
+///    by `drain_spins`.
+/// 3. All interrupts are disabled, and they stay disabled in the target
+///    firmware.
+/// 4. [`boot_jump`] is called.
+///
+/// # Examples
+///
+/// ```no_run
+/// # drone_core::thr::soft! {
+/// #     thread => Thr {};
+/// #     local => ThrLocal {};
+/// #     index => Thrs;
+/// #     threads => {};
+/// # }
+/// use drone_core::{platform, thr};
+///
+/// const BOOTLOADER_VECTOR_TABLE: usize = 0x0800_0000;
+///
+/// unsafe {
+///     platform::boot_handoff(BOOTLOADER_VECTOR_TABLE, 100_000, || {
+///         thr::pause_pool::<Thr>();
+///     });
+/// }
+/// ```
+///
+/// # Safety
+///
+/// See [`boot_jump`].
+pub unsafe fn boot_handoff<F: FnOnce()>(vector_table: usize, drain_spins: u32, pause: F) -> ! {
+    pause();
+    crate::stream::wait_drained(drain_spins);
+    mem::forget(Interrupts::pause());
+    unsafe { boot_jump(vector_table) }
+}
+
 /// Fills a memory region with zeros without using compiler built-ins.
 ///
 /// See also [`data_mem_init`].
@@ -166,3 +240,33 @@ pub fn stream_rt() -> *mut Runtime {
         drone_stream_runtime()
     }
 }
+
+/// Returns a mutable reference to the Drone Stream runtime serving the stream
+/// number `stream`.
+///
+/// If the stream has a dedicated buffer, its runtime is returned. Otherwise
+/// the shared runtime is returned, same as [`stream_rt`].
+#[inline]
+pub fn stream_rt_for(stream: u8) -> *mut Runtime {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        let runtime = drone_stream_dedicated_runtime(stream);
+        if runtime.is_null() { drone_stream_runtime() } else { runtime }
+    }
+}
+
+/// Reads the free-running cycle counter, e.g. the DWT `CYCCNT` register. The
+/// counter is allowed to wrap around.
+///
+/// The counter is provided by the `drone_cycle_counter` platform hook. It's
+/// used to measure the cycles spent by each fiber resumption, see
+/// [`Chain::budgets`](crate::fib::Chain::budgets). Unlike the other hooks, it
+/// is called on the host too, so a host binary with the `fib-budget` feature
+/// must define it.
+#[cfg(feature = "fib-budget")]
+#[inline]
+pub fn cycles() -> u32 {
+    unsafe { drone_cycle_counter() }
+}
@@ -11,19 +11,88 @@
 //! #[allow(unused_imports)]
 //! use drone_core::prelude::*;
 //! ```
+//!
+//! The prelude is composed of the following pieces:
+//!
+//! * [`base`] - the libcore prelude, common `alloc` types, and the
+//!   [`VecExt`](crate::heap::VecExt) extension trait. Always included.
+//! * `stream` - `print!`-family macros writing to Drone Stream. Included
+//!   with `prelude-stream` cargo feature, which is enabled by default.
+//! * `future` - extension traits for futures and streams. Included with
+//!   `prelude-future` cargo feature.
+//!
+//! A project, which doesn't use the stream macros, can disable default
+//! features of `drone-core` to keep the standard `print!` unshadowed, or
+//! import only the needed pieces:
+//!
+//! ```
+//! #![feature(prelude_import)]
+//!
+//! #[prelude_import]
+//! #[allow(unused_imports)]
+//! use drone_core::prelude::base::*;
+//! ```
+//!
+//! The [`extended`] prelude additionally includes the common vocabulary types
+//! from [`sync`](crate::sync) and [`io`](crate::io).
+
+/// The libcore prelude, common `alloc` types, and extension traits.
+pub mod base {
+    #[doc(no_inline)]
+    pub use alloc::{
+        borrow::ToOwned,
+        boxed::Box,
+        string::{String, ToString},
+        vec::Vec,
+    };
+    #[doc(no_inline)]
+    pub use core::prelude::rust_2021::*;
+    #[doc(no_inline)]
+    pub use crate::heap::VecExt as _;
+}
+
+/// `print!`-family macros writing to Drone Stream.
+#[cfg(feature = "prelude-stream")]
+pub mod stream {
+    #[cfg(not(feature = "host"))]
+    #[doc(no_inline)]
+    pub use crate::{dbg, eprint, eprintln, print, println};
+    #[cfg(feature = "host")]
+    #[doc(no_inline)]
+    pub use std::{dbg, eprint, eprintln, print, println};
+}
+
+/// Extension traits for futures and streams.
+#[cfg(feature = "prelude-future")]
+pub mod future {
+    #[doc(no_inline)]
+    pub use core::future::Future;
+    #[doc(no_inline)]
+    pub use futures::{FutureExt as _, StreamExt as _};
+}
+
+/// The extended prelude, which includes all enabled pieces and the common
+/// vocabulary types.
+pub mod extended {
+    #[doc(no_inline)]
+    pub use super::base::*;
+    #[cfg(feature = "prelude-future")]
+    #[doc(no_inline)]
+    pub use super::future::*;
+    #[cfg(feature = "prelude-stream")]
+    #[doc(no_inline)]
+    pub use super::stream::*;
+    #[doc(no_inline)]
+    pub use crate::io::{Read as _, Seek as _, SeekFrom, Write as _};
+    #[doc(no_inline)]
+    pub use crate::sync::{Mutex, RcuCell, SeqLock, Stack};
+}
 
-#[cfg(not(feature = "host"))]
-#[doc(no_inline)]
-pub use crate::{dbg, eprint, eprintln, print, println};
 #[doc(no_inline)]
-pub use alloc::{
-    borrow::ToOwned,
-    boxed::Box,
-    string::{String, ToString},
-    vec::Vec,
-};
+pub use self::base::*;
+#[cfg(feature = "prelude-future")]
 #[doc(no_inline)]
-pub use core::prelude::rust_2021::*;
-#[cfg(feature = "host")]
+pub use self::future::*;
+#[cfg(feature = "prelude-stream")]
 #[doc(no_inline)]
-pub use std::{dbg, eprint, eprintln, print, println};
+pub use self::stream::*;
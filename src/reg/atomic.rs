@@ -1,7 +1,15 @@
-use crate::bitfield::Bitfield;
+use crate::bitfield::{Bitfield, Bits};
+use crate::platform::Interrupts;
 use crate::reg::field::{RegFieldBit, RegFieldBits, WWRegField, WWRegFieldBit, WWRegFieldBits};
 use crate::reg::tag::RegAtomic;
+#[cfg(feature = "host")]
+use crate::reg::{load_raw, store_raw};
+#[cfg(all(feature = "reg-trace", not(feature = "host")))]
+use crate::reg::trace;
 use crate::reg::{RReg, Reg, RegHold, WReg, WRegAtomic};
+use core::ptr::{read_volatile, write_volatile};
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
 use core::sync::atomic::{AtomicU16, AtomicU32, AtomicU8, Ordering};
 
 /// Atomic operations for read-write register.
@@ -11,7 +19,9 @@ pub trait RwRegAtomic<T: RegAtomic>: RReg<T> + WRegAtomic<T> {
     /// memory.
     ///
     /// This operation is atomic, it repeats itself in case it was interrupted
-    /// in the middle. Thus the closure `f` may be called multiple times.
+    /// in the middle. Thus the closure `f` may be called multiple times. On
+    /// targets without 64-bit atomics, 64-bit registers are exchanged in a
+    /// critical section.
     ///
     /// See also [`modify_reg`](RwRegAtomic::modify_reg).
     fn modify<'a, F>(&'a self, f: F)
@@ -42,6 +52,19 @@ where
     fn modify<F>(&self, f: F)
     where
         F: Fn(&mut <Self::Reg as Reg<T>>::Val);
+
+    /// Reads the value from the register memory, then passes the field bits to
+    /// the closure `f`, then replaces the field bits with the result of the
+    /// closure and writes the value back to the register memory. Other bits of
+    /// the register are preserved.
+    ///
+    /// This operation is atomic, it repeats itself in case it was interrupted
+    /// in the middle. Thus the closure `f` may be called multiple times.
+    fn modify_field<F>(&self, f: F)
+    where
+        F: Fn(
+            <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+        ) -> <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits;
 }
 
 /// Atomic operations for writable single-bit field of read-write register.
@@ -146,6 +169,21 @@ where
             }
         }
     }
+
+    #[inline]
+    fn modify_field<F>(&self, f: F)
+    where
+        F: Fn(
+            <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+        ) -> <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+    {
+        let offset = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::OFFSET);
+        let width = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::WIDTH);
+        self.modify(|val| unsafe {
+            let bits = f(val.read_bits(offset, width));
+            val.write_bits(offset, width, bits);
+        });
+    }
 }
 
 impl<T, R> WRwRegFieldBitAtomic<T> for R
@@ -197,17 +235,22 @@ where
     }
 }
 
+#[cfg(not(feature = "host"))]
 unsafe fn atomic_load<T, R>() -> <R::Val as Bitfield>::Bits
 where
     T: RegAtomic,
     R: Reg<T>,
     <R::Val as Bitfield>::Bits: AtomicBits,
 {
-    <R::Val as Bitfield>::Bits::atomic_load(unsafe {
+    let bits = <R::Val as Bitfield>::Bits::atomic_load(unsafe {
         &mut *(R::ADDRESS as *mut <R::Val as Bitfield>::Bits)
-    })
+    });
+    #[cfg(feature = "reg-trace")]
+    trace::emit(R::ADDRESS, bits, trace::Access::Read);
+    bits
 }
 
+#[cfg(not(feature = "host"))]
 unsafe fn atomic_compare_exchange_weak<T, R>(
     current: <R::Val as Bitfield>::Bits,
     new: <R::Val as Bitfield>::Bits,
@@ -217,11 +260,48 @@ where
     R: Reg<T>,
     <R::Val as Bitfield>::Bits: AtomicBits,
 {
-    <R::Val as Bitfield>::Bits::atomic_compare_exchange_weak(
+    let result = <R::Val as Bitfield>::Bits::atomic_compare_exchange_weak(
         unsafe { &mut *(R::ADDRESS as *mut <R::Val as Bitfield>::Bits) },
         current,
         new,
-    )
+    );
+    // A successful exchange is traced as a write of the new value, and a
+    // failed one as a read of the actual value.
+    #[cfg(feature = "reg-trace")]
+    match result {
+        Ok(_) => trace::emit(R::ADDRESS, new, trace::Access::Write),
+        Err(actual) => trace::emit(R::ADDRESS, actual, trace::Access::Read),
+    }
+    result
+}
+
+#[cfg(feature = "host")]
+unsafe fn atomic_load<T, R>() -> <R::Val as Bitfield>::Bits
+where
+    T: RegAtomic,
+    R: Reg<T>,
+    <R::Val as Bitfield>::Bits: AtomicBits,
+{
+    unsafe { load_raw(R::ADDRESS) }
+}
+
+#[cfg(feature = "host")]
+unsafe fn atomic_compare_exchange_weak<T, R>(
+    current: <R::Val as Bitfield>::Bits,
+    new: <R::Val as Bitfield>::Bits,
+) -> Result<<R::Val as Bitfield>::Bits, <R::Val as Bitfield>::Bits>
+where
+    T: RegAtomic,
+    R: Reg<T>,
+    <R::Val as Bitfield>::Bits: AtomicBits,
+{
+    let actual = unsafe { load_raw(R::ADDRESS) };
+    if actual == current {
+        unsafe { store_raw(R::ADDRESS, new) };
+        Ok(actual)
+    } else {
+        Err(actual)
+    }
 }
 
 macro_rules! atomic_bits {
@@ -247,6 +327,53 @@ macro_rules! atomic_bits {
     };
 }
 
+#[cfg(target_has_atomic = "64")]
+atomic_bits!(u64, AtomicU64);
 atomic_bits!(u32, AtomicU32);
 atomic_bits!(u16, AtomicU16);
 atomic_bits!(u8, AtomicU8);
+
+// Targets without 64-bit atomics access 64-bit registers with two loads or
+// stores, so the exchange runs in a critical section. A torn load fails the
+// comparison and the operation repeats.
+#[cfg(not(target_has_atomic = "64"))]
+impl AtomicBits for u64 {
+    fn atomic_load(&mut self) -> Self {
+        unsafe { read_volatile(self) }
+    }
+
+    fn atomic_compare_exchange_weak(&mut self, current: Self, new: Self) -> Result<Self, Self> {
+        paused_compare_exchange(self, current, new)
+    }
+}
+
+// Compiled on all targets, so the host tests cover it.
+#[cfg_attr(target_has_atomic = "64", allow(dead_code))]
+fn paused_compare_exchange(bits: &mut u64, current: u64, new: u64) -> Result<u64, u64> {
+    Interrupts::paused(|| {
+        let actual = unsafe { read_volatile(bits) };
+        if actual == current {
+            unsafe { write_volatile(bits, new) };
+            Ok(actual)
+        } else {
+            Err(actual)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paused_compare_exchange_u64() {
+        let mut bits = 0x0000_0001_0000_0000_u64;
+        assert_eq!(paused_compare_exchange(&mut bits, 0, 5), Err(0x0000_0001_0000_0000));
+        assert_eq!(bits, 0x0000_0001_0000_0000);
+        assert_eq!(
+            paused_compare_exchange(&mut bits, 0x0000_0001_0000_0000, 0x0000_0001_0000_0005),
+            Ok(0x0000_0001_0000_0000)
+        );
+        assert_eq!(bits, 0x0000_0001_0000_0005);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::bitfield::Bitfield;
+use crate::platform::Interrupts;
+use crate::reg::tag::RegTag;
+use crate::reg::{store_raw, RReg, Reg, RegHold, WReg};
+use core::marker::PhantomData;
+
+/// A handle for register operations inside [`batch`].
+///
+/// The operations of the handle don't disable interrupts on their own, and
+/// don't repeat themselves like atomic operations, because the whole batch is
+/// already running in a critical section.
+pub struct Batch<'a> {
+    _marker: PhantomData<&'a mut ()>,
+}
+
+/// Runs the closure `f` with interrupts disabled, and passes it a handle for
+/// register operations.
+///
+/// This way several register stores are performed with interrupts masked
+/// exactly once. Use it for updates, which are dangerous to leave partially
+/// applied, e.g. configuring clock trees.
+///
+/// # Examples
+///
+/// ```ignore
+/// use drone_core::reg;
+///
+/// reg::batch(|b| {
+///     b.store(&rcc_cfgr, |r| r.write_sw(0b10));
+///     b.modify(&flash_acr, |r| r.write_latency(2));
+/// });
+/// ```
+#[inline]
+pub fn batch<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut Batch<'_>) -> R,
+{
+    Interrupts::paused(|| f(&mut Batch { _marker: PhantomData }))
+}
+
+impl Batch<'_> {
+    /// Passes the reset value to the closure `f`, then writes the result of the
+    /// closure into the register memory.
+    #[inline]
+    pub fn store<'a, T, R, F>(&mut self, reg: &'a R, f: F)
+    where
+        T: RegTag,
+        R: WReg<T>,
+        F: for<'b> FnOnce(&'b mut <R as Reg<T>>::Hold<'a>) -> &'b mut <R as Reg<T>>::Hold<'a>,
+    {
+        self.store_val::<T, R>(reg, f(&mut reg.default()).val());
+    }
+
+    /// Writes `val` into the register memory.
+    #[inline]
+    pub fn store_val<T, R>(&mut self, reg: &R, val: R::Val)
+    where
+        T: RegTag,
+        R: WReg<T>,
+    {
+        unsafe { store_raw(R::ADDRESS, reg.preserve_reserved(val.bits())) };
+    }
+
+    /// Reads the value from the register memory, then passes the value to the
+    /// closure `f`, then writes the result of the closure back to the register
+    /// memory.
+    #[inline]
+    pub fn modify<'a, T, R, F>(&mut self, reg: &'a R, f: F)
+    where
+        T: RegTag,
+        R: RReg<T> + WReg<T>,
+        F: for<'b> FnOnce(&'b mut <R as Reg<T>>::Hold<'a>) -> &'b mut <R as Reg<T>>::Hold<'a>,
+    {
+        unsafe { store_raw(R::ADDRESS, f(&mut reg.load()).val().bits()) };
+    }
+}
@@ -0,0 +1,44 @@
+use crate::bitfield::Bitfield;
+use crate::reg::tag::RegTag;
+use crate::reg::{bits_to_u64, Reg};
+use core::fmt::{self, Write};
+use core::mem::size_of;
+
+/// Register token, which can be exported as C `#define`s.
+///
+/// This trait is implemented by [`reg!`](crate::reg!) for all registers and
+/// register arrays. The index structure generated by
+/// [`tokens!`](crate::reg::tokens!) uses it in its `write_c_header` function.
+pub trait RegCDefines {
+    /// Writes the `#define`s of the register to `w`, prefixing the macro names
+    /// with `name`.
+    fn write_c_defines(w: &mut dyn Write, name: fmt::Arguments<'_>) -> fmt::Result;
+}
+
+/// Writes the address, the reset value, and the field positions and masks of
+/// the register `R` as C `#define`s.
+///
+/// The `fields` are `(name, offset, width)` triples. The output looks like the
+/// following:
+///
+/// ```text
+/// #define TIM2_SR_ADDR 0x40000010UL
+/// #define TIM2_SR_RESET 0x0UL
+/// #define TIM2_SR_UIF_Pos 0U
+/// #define TIM2_SR_UIF_Msk 0x1UL
+/// ```
+pub fn write_c_defines<T: RegTag, R: Reg<T>>(
+    w: &mut dyn Write,
+    name: fmt::Arguments<'_>,
+    fields: &[(&str, u32, u32)],
+) -> fmt::Result {
+    let suffix = if size_of::<<R::Val as Bitfield>::Bits>() > 4 { "ULL" } else { "UL" };
+    writeln!(w, "#define {name}_ADDR {:#X}UL", R::ADDRESS)?;
+    writeln!(w, "#define {name}_RESET {:#X}{suffix}", bits_to_u64(R::RESET))?;
+    for &(field, offset, width) in fields {
+        let mask = (u64::MAX >> (64 - width)) << offset;
+        writeln!(w, "#define {name}_{field}_Pos {offset}U")?;
+        writeln!(w, "#define {name}_{field}_Msk {mask:#X}{suffix}")?;
+    }
+    Ok(())
+}
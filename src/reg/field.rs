@@ -3,6 +3,7 @@
 //! See [the top-level module documentation](self) for details.
 
 use crate::bitfield::{Bitfield, Bits};
+use crate::fib::FiberFuture;
 #[cfg(feature = "atomics")]
 pub use crate::reg::atomic::{WRwRegFieldAtomic, WRwRegFieldBitAtomic, WRwRegFieldBitsAtomic};
 #[cfg(not(feature = "atomics"))]
@@ -10,9 +11,10 @@ pub use crate::reg::soft_atomic::{
     WRwRegFieldBitSoftAtomic, WRwRegFieldBitsSoftAtomic, WRwRegFieldSoftAtomic,
 };
 use crate::reg::tag::{Crt, RegAtomic, RegTag, Srt, Urt};
-use crate::reg::{RReg, Reg, WReg, WoReg};
+use crate::reg::{load_raw, poll_until, store_raw, RReg, Reg, WReg, WoReg};
+use crate::thr::ThrToken;
 use crate::token::Token;
-use core::ptr::{read_volatile, write_volatile};
+use core::fmt;
 
 /// The base trait for a field token of a memory-mapped register.
 pub trait RegField<T: RegTag>: Token + Sync {
@@ -89,11 +91,7 @@ where
     /// Reads the value from the register memory to the opaque value type.
     #[inline]
     fn load_val(&self) -> <Self::Reg as Reg<T>>::Val {
-        unsafe {
-            Self::Reg::val_from(read_volatile(
-                Self::Reg::ADDRESS as *const <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
-            ))
-        }
+        unsafe { Self::Reg::val_from(load_raw(Self::Reg::ADDRESS)) }
     }
 }
 
@@ -159,6 +157,32 @@ where
     /// Reads the value from the register memory and returns `true` if the bit
     /// is set.
     fn read_bit(&self) -> bool;
+
+    /// Returns a future, which resolves to the field token when the bit is
+    /// set.
+    ///
+    /// The bit is re-checked on each activation of the thread `thr`. See
+    /// [`poll_until`] for details.
+    #[inline]
+    fn wait_for_set<H: ThrToken>(self, thr: H) -> FiberFuture<Self>
+    where
+        Self: Sized + Send + 'static,
+    {
+        poll_until::<T, _, _, _>(self, thr, |field| field.read_bit())
+    }
+
+    /// Returns a future, which resolves to the field token when the bit is
+    /// cleared.
+    ///
+    /// The bit is re-checked on each activation of the thread `thr`. See
+    /// [`poll_until`] for details.
+    #[inline]
+    fn wait_for_clear<H: ThrToken>(self, thr: H) -> FiberFuture<Self>
+    where
+        Self: Sized + Send + 'static,
+    {
+        poll_until::<T, _, _, _>(self, thr, |field| !field.read_bit())
+    }
 }
 
 /// Writable single-bit field of writable register.
@@ -244,6 +268,136 @@ where
     fn write_bits(&self, bits: <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits);
 }
 
+/// Non-atomic operations for writable field of read-write register.
+pub trait WRwRegFieldUnsync
+where
+    Self: WWRegField<Urt>,
+    Self::Reg: RReg<Urt> + WReg<Urt>,
+{
+    /// Reads the value from the register memory, then passes the field bits to
+    /// the closure `f`, then replaces the field bits with the result of the
+    /// closure and writes the value back to the register memory. Other bits of
+    /// the register are preserved.
+    ///
+    /// This operation is non-atomic, thus it requires a mutable reference to
+    /// the token.
+    fn modify_field<F>(&mut self, f: F)
+    where
+        F: FnOnce(
+            <<Self::Reg as Reg<Urt>>::Val as Bitfield>::Bits,
+        ) -> <<Self::Reg as Reg<Urt>>::Val as Bitfield>::Bits;
+}
+
+/// Single-bit field of a register in a bit-band region.
+///
+/// Each bit of a bit-band region is mapped to a whole word in the bit-band
+/// alias region. Reading the word returns the bit, and writing `1` or `0` to
+/// the word sets or clears the bit. The bus performs the update as a single
+/// transaction, therefore the operations are atomic without compare-and-swap
+/// loops and critical sections.
+///
+/// The bus still reads and writes back the whole register, therefore the
+/// operations must not be used on registers with write-1-to-clear bits.
+pub trait RegFieldBitBand<T: RegTag>: RegFieldBit<T> {
+    /// The address of the bit in the bit-band alias region.
+    const BIT_BAND_ADDRESS: usize;
+
+    /// Reads the bit through the bit-band alias.
+    #[inline]
+    fn read_bit_band(&self) -> bool
+    where
+        Self::Reg: RReg<T>,
+    {
+        unsafe { load_raw::<u32>(Self::BIT_BAND_ADDRESS) != 0 }
+    }
+
+    /// Sets the bit through the bit-band alias.
+    #[inline]
+    fn set_bit_band(&self)
+    where
+        Self::Reg: WReg<T>,
+    {
+        unsafe { store_raw::<u32>(Self::BIT_BAND_ADDRESS, 1) };
+    }
+
+    /// Clears the bit through the bit-band alias.
+    #[inline]
+    fn clear_bit_band(&self)
+    where
+        Self::Reg: WReg<T>,
+    {
+        unsafe { store_raw::<u32>(Self::BIT_BAND_ADDRESS, 0) };
+    }
+
+    /// Returns a raw pointer to the bit-band alias word.
+    #[inline]
+    fn bit_band_ptr(&self) -> *const u32 {
+        Self::BIT_BAND_ADDRESS as *const u32
+    }
+
+    /// Returns a mutable raw pointer to the bit-band alias word.
+    #[inline]
+    fn bit_band_mut_ptr(&self) -> *mut u32 {
+        Self::BIT_BAND_ADDRESS as *mut u32
+    }
+}
+
+/// Write-1-to-clear single-bit field of writable register.
+///
+/// Writing `1` to the bit clears it, and writing `0` has no effect. Usually
+/// this is a status flag.
+pub trait W1CRegField<T: RegTag>
+where
+    Self: RegFieldBit<T>,
+    Self::Reg: WReg<T>,
+{
+    /// Clears the bit by writing `1` to it, and `0` to the other bits of the
+    /// register memory.
+    ///
+    /// This is a single store without reading the register, therefore other
+    /// write-1-to-clear bits, which became set in the meantime, are not lost.
+    #[inline]
+    fn clear_flag(&self) {
+        let bit = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(1)
+            << <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::OFFSET);
+        store_bits_reset_reserved::<T, Self::Reg>(bit);
+    }
+}
+
+/// Write-0-to-clear single-bit field of writable register.
+///
+/// Writing `0` to the bit clears it, and writing `1` has no effect. Usually
+/// this is a status flag.
+pub trait W0CRegField<T: RegTag>
+where
+    Self: RegFieldBit<T>,
+    Self::Reg: WReg<T>,
+{
+    /// Clears the bit by writing `0` to it, and `1` to the other bits of the
+    /// register memory.
+    ///
+    /// This is a single store without reading the register, therefore other
+    /// write-0-to-clear bits, which became set in the meantime, are not lost.
+    #[inline]
+    fn clear_flag(&self) {
+        let bit = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(1)
+            << <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::OFFSET);
+        store_bits_reset_reserved::<T, Self::Reg>(!bit);
+    }
+}
+
+/// An error returned by the `write_*` methods generated for a field `scale`.
+///
+/// The value is below the `bias`, or its encoding doesn't fit into the field.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ScaleError;
+
+impl fmt::Display for ScaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "value is out of the field range")
+    }
+}
+
 impl<T, R> WoWoRegField<T> for R
 where
     T: RegTag,
@@ -257,12 +411,7 @@ where
 
     #[inline]
     fn store_val(&self, val: <Self::Reg as Reg<T>>::Val) {
-        unsafe {
-            write_volatile(
-                Self::Reg::ADDRESS as *mut <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
-                val.bits(),
-            );
-        }
+        store_bits_reset_reserved::<T, Self::Reg>(val.bits());
     }
 
     #[inline]
@@ -430,3 +579,34 @@ where
         });
     }
 }
+
+impl<R> WRwRegFieldUnsync for R
+where
+    R: WWRegField<Urt>,
+    R::Reg: RReg<Urt> + WReg<Urt>,
+{
+    #[inline]
+    fn modify_field<F>(&mut self, f: F)
+    where
+        F: FnOnce(
+            <<Self::Reg as Reg<Urt>>::Val as Bitfield>::Bits,
+        ) -> <<Self::Reg as Reg<Urt>>::Val as Bitfield>::Bits,
+    {
+        unsafe {
+            let offset = <<Self::Reg as Reg<Urt>>::Val as Bitfield>::Bits::from_usize(Self::OFFSET);
+            let width = <<Self::Reg as Reg<Urt>>::Val as Bitfield>::Bits::from_usize(Self::WIDTH);
+            let mut val = Self::Reg::val_from(load_raw(Self::Reg::ADDRESS));
+            let bits = f(val.read_bits(offset, width));
+            val.write_bits(offset, width, bits);
+            store_raw(Self::Reg::ADDRESS, val.bits());
+        }
+    }
+}
+
+fn store_bits_reset_reserved<T: RegTag, R: Reg<T>>(bits: <R::Val as Bitfield>::Bits) {
+    let bits = match R::RESERVED {
+        Some((mask, _)) => bits & !mask | R::RESET & mask,
+        None => bits,
+    };
+    unsafe { store_raw(R::ADDRESS, bits) };
+}
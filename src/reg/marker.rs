@@ -1,9 +1,30 @@
 //! Marker traits representing properties of memory-mapped registers.
+//!
+//! The traits combine common trait bundles, so generic drivers don't need to
+//! repeat them. The prefix of a trait name selects the register token tag:
+//! `U` for [`Urt`], `S` for [`Srt`], `C` for [`Crt`], and `A` for any atomic
+//! tag (see [`RegAtomic`]). Traits without a prefix are generic over all tags.
+//!
+//! # Examples
+//!
+//! ```
+//! use drone_core::reg::marker::{ARwReg, ARwRwRegFieldBit};
+//! use drone_core::reg::prelude::*;
+//!
+//! // Works for both `Srt` and `Crt` tokens.
+//! fn enable<T: RegAtomic, F: ARwRwRegFieldBit<T>>(field: &F) {
+//!     field.set_bit();
+//! }
+//!
+//! fn reset<T: RegAtomic, R: ARwReg<T>>(reg: &R) {
+//!     reg.reset();
+//! }
+//! ```
 
 use crate::reg::field::{
     RRRegFieldBit, RRRegFieldBits, RoRRegField, WWRegFieldBit, WWRegFieldBits, WoWRegField,
 };
-use crate::reg::tag::{Crt, RegTag, Srt, Urt};
+use crate::reg::tag::{Crt, RegAtomic, RegTag, Srt, Urt};
 #[cfg(feature = "atomics")]
 use crate::reg::{
     field::{WRwRegFieldBitAtomic, WRwRegFieldBitsAtomic},
@@ -176,6 +197,100 @@ where
 {
 }
 
+/// Unsynchronized readable register.
+#[marker]
+pub trait URReg
+where
+    Self: RReg<Urt>,
+{
+}
+
+impl<R> URReg for R
+where
+    R: RReg<Urt>,
+{
+}
+
+/// Unsynchronized writable register.
+#[marker]
+pub trait UWReg
+where
+    Self: WReg<Urt>,
+    Self: WRegUnsync,
+{
+}
+
+impl<R> UWReg for R
+where
+    R: WReg<Urt>,
+    R: WRegUnsync,
+{
+}
+
+/// Synchronized readable register.
+#[marker]
+pub trait SRReg
+where
+    Self: RReg<Srt>,
+{
+}
+
+impl<R> SRReg for R
+where
+    R: RReg<Srt>,
+{
+}
+
+/// Synchronized writable register.
+#[marker]
+pub trait SWReg
+where
+    Self: WReg<Srt>,
+    Self: WRegAtomic<Srt>,
+{
+}
+
+impl<R> SWReg for R
+where
+    R: WReg<Srt>,
+    R: WRegAtomic<Srt>,
+{
+}
+
+/// Copyable readable register.
+#[marker]
+pub trait CRReg
+where
+    Self: RReg<Crt>,
+    Self: Copy,
+{
+}
+
+impl<R> CRReg for R
+where
+    R: RReg<Crt>,
+    R: Copy,
+{
+}
+
+/// Copyable writable register.
+#[marker]
+pub trait CWReg
+where
+    Self: WReg<Crt>,
+    Self: WRegAtomic<Crt>,
+    Self: Copy,
+{
+}
+
+impl<R> CWReg for R
+where
+    R: WReg<Crt>,
+    R: WRegAtomic<Crt>,
+    R: Copy,
+{
+}
+
 /// Single-bit read-write field of read-write register.
 #[marker]
 pub trait RwRwRegFieldBit<T: RegTag>
@@ -835,3 +950,319 @@ where
     R::Reg: CRoReg,
 {
 }
+
+/// Unsynchronized single-bit readable field of readable register.
+#[marker]
+pub trait URRegFieldBit
+where
+    Self: RRRegFieldBit<Urt>,
+    Self::Reg: URReg,
+{
+}
+
+impl<R> URRegFieldBit for R
+where
+    R: RRRegFieldBit<Urt>,
+    R::Reg: URReg,
+{
+}
+
+/// Unsynchronized single-bit writable field of writable register.
+#[marker]
+pub trait UWRegFieldBit
+where
+    Self: WWRegFieldBit<Urt>,
+    Self::Reg: UWReg,
+{
+}
+
+impl<R> UWRegFieldBit for R
+where
+    R: WWRegFieldBit<Urt>,
+    R::Reg: UWReg,
+{
+}
+
+/// Unsynchronized multi-bit readable field of readable register.
+#[marker]
+pub trait URRegFieldBits
+where
+    Self: RRRegFieldBits<Urt>,
+    Self::Reg: URReg,
+{
+}
+
+impl<R> URRegFieldBits for R
+where
+    R: RRRegFieldBits<Urt>,
+    R::Reg: URReg,
+{
+}
+
+/// Unsynchronized multi-bit writable field of writable register.
+#[marker]
+pub trait UWRegFieldBits
+where
+    Self: WWRegFieldBits<Urt>,
+    Self::Reg: UWReg,
+{
+}
+
+impl<R> UWRegFieldBits for R
+where
+    R: WWRegFieldBits<Urt>,
+    R::Reg: UWReg,
+{
+}
+
+/// Synchronized single-bit readable field of readable register.
+#[marker]
+pub trait SRRegFieldBit
+where
+    Self: RRRegFieldBit<Srt>,
+    Self::Reg: SRReg,
+{
+}
+
+impl<R> SRRegFieldBit for R
+where
+    R: RRRegFieldBit<Srt>,
+    R::Reg: SRReg,
+{
+}
+
+/// Synchronized single-bit writable field of writable register.
+#[marker]
+pub trait SWRegFieldBit
+where
+    Self: WWRegFieldBit<Srt>,
+    Self::Reg: SWReg,
+{
+}
+
+impl<R> SWRegFieldBit for R
+where
+    R: WWRegFieldBit<Srt>,
+    R::Reg: SWReg,
+{
+}
+
+/// Synchronized multi-bit readable field of readable register.
+#[marker]
+pub trait SRRegFieldBits
+where
+    Self: RRRegFieldBits<Srt>,
+    Self::Reg: SRReg,
+{
+}
+
+impl<R> SRRegFieldBits for R
+where
+    R: RRRegFieldBits<Srt>,
+    R::Reg: SRReg,
+{
+}
+
+/// Synchronized multi-bit writable field of writable register.
+#[marker]
+pub trait SWRegFieldBits
+where
+    Self: WWRegFieldBits<Srt>,
+    Self::Reg: SWReg,
+{
+}
+
+impl<R> SWRegFieldBits for R
+where
+    R: WWRegFieldBits<Srt>,
+    R::Reg: SWReg,
+{
+}
+
+/// Copyable single-bit readable field of readable register.
+#[marker]
+pub trait CRRegFieldBit
+where
+    Self: RRRegFieldBit<Crt>,
+    Self: Copy,
+    Self::Reg: CRReg,
+{
+}
+
+impl<R> CRRegFieldBit for R
+where
+    R: RRRegFieldBit<Crt>,
+    R: Copy,
+    R::Reg: CRReg,
+{
+}
+
+/// Copyable single-bit writable field of writable register.
+#[marker]
+pub trait CWRegFieldBit
+where
+    Self: WWRegFieldBit<Crt>,
+    Self: Copy,
+    Self::Reg: CWReg,
+{
+}
+
+impl<R> CWRegFieldBit for R
+where
+    R: WWRegFieldBit<Crt>,
+    R: Copy,
+    R::Reg: CWReg,
+{
+}
+
+/// Copyable multi-bit readable field of readable register.
+#[marker]
+pub trait CRRegFieldBits
+where
+    Self: RRRegFieldBits<Crt>,
+    Self: Copy,
+    Self::Reg: CRReg,
+{
+}
+
+impl<R> CRRegFieldBits for R
+where
+    R: RRRegFieldBits<Crt>,
+    R: Copy,
+    R::Reg: CRReg,
+{
+}
+
+/// Copyable multi-bit writable field of writable register.
+#[marker]
+pub trait CWRegFieldBits
+where
+    Self: WWRegFieldBits<Crt>,
+    Self: Copy,
+    Self::Reg: CWReg,
+{
+}
+
+impl<R> CWRegFieldBits for R
+where
+    R: WWRegFieldBits<Crt>,
+    R: Copy,
+    R::Reg: CWReg,
+{
+}
+
+/// Atomic read-write register.
+#[marker]
+pub trait ARwReg<T: RegAtomic>
+where
+    Self: RwReg<T>,
+    Self: RwRegAtomic<T>,
+{
+}
+
+impl<R, T: RegAtomic> ARwReg<T> for R
+where
+    R: RwReg<T>,
+    R: RwRegAtomic<T>,
+{
+}
+
+/// Atomic writable register.
+#[marker]
+pub trait AWReg<T: RegAtomic>
+where
+    Self: WReg<T>,
+    Self: WRegAtomic<T>,
+{
+}
+
+impl<R, T: RegAtomic> AWReg<T> for R
+where
+    R: WReg<T>,
+    R: WRegAtomic<T>,
+{
+}
+
+/// Atomic write-only register.
+#[marker]
+pub trait AWoReg<T: RegAtomic>
+where
+    Self: WoReg<T>,
+    Self: WRegAtomic<T>,
+{
+}
+
+impl<R, T: RegAtomic> AWoReg<T> for R
+where
+    R: WoReg<T>,
+    R: WRegAtomic<T>,
+{
+}
+
+/// Atomic single-bit read-write field of read-write register.
+#[marker]
+pub trait ARwRwRegFieldBit<T: RegAtomic>
+where
+    Self: RwRwRegFieldBit<T>,
+    Self: WRwRegFieldBitAtomic<T>,
+    Self::Reg: ARwReg<T>,
+{
+}
+
+impl<R, T: RegAtomic> ARwRwRegFieldBit<T> for R
+where
+    R: RwRwRegFieldBit<T>,
+    R: WRwRegFieldBitAtomic<T>,
+    R::Reg: ARwReg<T>,
+{
+}
+
+/// Atomic multi-bit read-write field of read-write register.
+#[marker]
+pub trait ARwRwRegFieldBits<T: RegAtomic>
+where
+    Self: RwRwRegFieldBits<T>,
+    Self: WRwRegFieldBitsAtomic<T>,
+    Self::Reg: ARwReg<T>,
+{
+}
+
+impl<R, T: RegAtomic> ARwRwRegFieldBits<T> for R
+where
+    R: RwRwRegFieldBits<T>,
+    R: WRwRegFieldBitsAtomic<T>,
+    R::Reg: ARwReg<T>,
+{
+}
+
+/// Atomic single-bit write-only field of write-only register.
+#[marker]
+pub trait AWoWoRegFieldBit<T: RegAtomic>
+where
+    Self: WoWoRegFieldBit<T>,
+    Self::Reg: AWoReg<T>,
+{
+}
+
+impl<R, T: RegAtomic> AWoWoRegFieldBit<T> for R
+where
+    R: WoWoRegFieldBit<T>,
+    R::Reg: AWoReg<T>,
+{
+}
+
+/// Atomic multi-bit write-only field of write-only register.
+#[marker]
+pub trait AWoWoRegFieldBits<T: RegAtomic>
+where
+    Self: WoWoRegFieldBits<T>,
+    Self::Reg: AWoReg<T>,
+{
+}
+
+impl<R, T: RegAtomic> AWoWoRegFieldBits<T> for R
+where
+    R: WoWoRegFieldBits<T>,
+    R::Reg: AWoReg<T>,
+{
+}
@@ -0,0 +1,157 @@
+//! Mock register memory for host-side tests.
+//!
+//! With the `host` feature enabled, register tokens don't access raw
+//! addresses. Instead every load and store goes through an in-memory register
+//! map, which is local to the current thread. This way drivers built on
+//! register tokens can be unit-tested on the build machine: a test preloads
+//! register values, runs the driver code, and inspects the register writes.
+//!
+//! Registers, which weren't preloaded or written, read as zero.
+//!
+//! # Examples
+//!
+//! ```
+//! use drone_core::reg::mock;
+//! use drone_core::reg::prelude::*;
+//! use drone_core::token::Token;
+//!
+//! drone_core::reg! {
+//!     pub GPIOA ODR => {
+//!         address => 0x4001_080C; size => 0x20; reset => 0; traits => { RReg WReg };
+//!         fields => { ODR2 => { offset => 2; width => 1; traits => { RRRegField WWRegField } } };
+//!     };
+//! }
+//!
+//! # fn main() {
+//! mock::reset();
+//! mock::preload(0x4001_080C, 0x0000_0001);
+//! let mut gpioa_odr = unsafe { gpioa_odr::Reg::<Urt>::take() };
+//! gpioa_odr.modify(|r| r.set_odr2());
+//! assert_eq!(mock::take_writes(), [(0x4001_080C, 0x0000_0005)]);
+//! assert_eq!(mock::peek(0x4001_080C), 0x0000_0005);
+//! # }
+//! ```
+//!
+//! Read side effects are scripted with [`on_read`]. For example a status flag,
+//! which is set on the third poll:
+//!
+//! ```
+//! use drone_core::reg::mock;
+//!
+//! mock::reset();
+//! let mut polls = 0;
+//! mock::on_read(0x4002_1000, move |value| {
+//!     polls += 1;
+//!     if polls >= 3 { value | 1 << 17 } else { value }
+//! });
+//! ```
+
+use crate::bitfield::Bits;
+use crate::reg::tag::RegTag;
+use crate::reg::{bits_to_u64, Reg};
+use core::mem::size_of;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+type ReadHook = Box<dyn FnMut(u64) -> u64>;
+
+#[derive(Default)]
+struct Memory {
+    values: HashMap<usize, u64>,
+    writes: Vec<(usize, u64)>,
+    read_hooks: HashMap<usize, ReadHook>,
+}
+
+std::thread_local! {
+    static MEMORY: RefCell<Memory> = RefCell::default();
+}
+
+/// Clears the register map, the write log, and the read hooks of the current
+/// thread.
+pub fn reset() {
+    MEMORY.with(|memory| *memory.borrow_mut() = Memory::default());
+}
+
+/// Sets the register at `address` to `value` without logging a write.
+pub fn preload(address: usize, value: u64) {
+    MEMORY.with(|memory| {
+        memory.borrow_mut().values.insert(address, value);
+    });
+}
+
+/// Sets the register of the token `reg` to its reset value without logging a
+/// write.
+pub fn preload_reset<T: RegTag, R: Reg<T>>(_reg: &R) {
+    preload(R::ADDRESS, bits_to_u64(R::RESET));
+}
+
+/// Returns the value of the register at `address` without triggering read
+/// side effects.
+pub fn peek(address: usize) -> u64 {
+    MEMORY.with(|memory| memory.borrow().values.get(&address).copied().unwrap_or(0))
+}
+
+/// Returns the logged register writes as `(address, value)` pairs in the
+/// order they happened.
+pub fn writes() -> Vec<(usize, u64)> {
+    MEMORY.with(|memory| memory.borrow().writes.clone())
+}
+
+/// Returns the logged register writes, and clears the log.
+///
+/// See also [`writes`].
+pub fn take_writes() -> Vec<(usize, u64)> {
+    MEMORY.with(|memory| memory.borrow_mut().writes.drain(..).collect())
+}
+
+/// Installs a read side effect for the register at `address`.
+///
+/// On each read the `hook` is called with the current register value. The
+/// returned value becomes the new register value and is returned by the read.
+/// A previously installed hook for `address` is replaced. The hook may access
+/// the mock memory, e.g. to update status registers.
+pub fn on_read<F>(address: usize, hook: F)
+where
+    F: FnMut(u64) -> u64 + 'static,
+{
+    MEMORY.with(|memory| {
+        memory.borrow_mut().read_hooks.insert(address, Box::new(hook));
+    });
+}
+
+pub(crate) fn read<B: Bits>(address: usize) -> B {
+    let (value, hook) = MEMORY.with(|memory| {
+        let memory = &mut *memory.borrow_mut();
+        let value = memory.values.get(&address).copied().unwrap_or(0);
+        (value, memory.read_hooks.remove(&address))
+    });
+    let Some(mut hook) = hook else { return from_u64(value) };
+    // The hook is called without the memory borrowed, so it can access other
+    // registers.
+    let value = hook(value);
+    MEMORY.with(|memory| {
+        let memory = &mut *memory.borrow_mut();
+        memory.values.insert(address, value);
+        memory.read_hooks.entry(address).or_insert(hook);
+    });
+    from_u64(value)
+}
+
+pub(crate) fn write<B: Bits>(address: usize, bits: B) {
+    let value = bits_to_u64(bits);
+    MEMORY.with(|memory| {
+        let memory = &mut *memory.borrow_mut();
+        memory.values.insert(address, value);
+        memory.writes.push((address, value));
+    });
+}
+
+fn from_u64<B: Bits>(value: u64) -> B {
+    (0..width::<B>())
+        .filter(|&i| value >> i & 1 != 0)
+        .fold(B::from_usize(0), |bits, i| bits | B::from_usize(1) << B::from_usize(i))
+}
+
+fn width<B: Bits>() -> usize {
+    (size_of::<B>() * 8).min(64)
+}
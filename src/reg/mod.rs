@@ -73,6 +73,10 @@
 //! use drone_core::reg::prelude::*;
 //! ```
 //!
+//! The traits of the newer features, like snapshots, shadowed write-only
+//! registers, field-level modify, and bit-band access, are exported by the
+//! versioned [`reg::prelude::v2`](prelude::v2).
+//!
 //! ## Field Token
 #![doc = "
 
@@ -88,6 +92,8 @@
 | [`store`](field::WoWoRegField::store)               |           | write | write-only |
 | [`read`](field::RRRegFieldBit::read)                | one-bit   | read  | read       |
 | [`read_bit`](field::RRRegFieldBit::read_bit)        | one-bit   | read  | read       |
+| [`wait_for_set`](field::RRRegFieldBit::wait_for_set)     | one-bit   | read  | read       |
+| [`wait_for_clear`](field::RRRegFieldBit::wait_for_clear) | one-bit   | read  | read       |
 | [`set`](field::WWRegFieldBit::set)                  | one-bit   | write | write      |
 | [`clear`](field::WWRegFieldBit::clear)              | one-bit   | write | write      |
 | [`toggle`](field::WWRegFieldBit::toggle)            | one-bit   | write | write      |
@@ -100,6 +106,11 @@
 | [`read_bits`](field::RRRegFieldBits::read_bits)     | multi-bit | read  | read       |
 | [`write`](field::WWRegFieldBits::write)             | multi-bit | write | write      |
 | [`write_bits`](field::WoWoRegFieldBits::write_bits) | multi-bit | write | write-only |
+| [`clear_flag`](field::W1CRegField::clear_flag)      | one-bit   | w1c   | write      |
+| [`clear_flag`](field::W0CRegField::clear_flag)      | one-bit   | w0c   | write      |
+| [`read_bit_band`](field::RegFieldBitBand::read_bit_band)   | one-bit   | read  | read  |
+| [`set_bit_band`](field::RegFieldBitBand::set_bit_band)     | one-bit   | write | write |
+| [`clear_bit_band`](field::RegFieldBitBand::clear_bit_band) | one-bit   | write | write |
 
 "]
 #![cfg_attr(
@@ -109,6 +120,8 @@
 |                                                     | Field Width | Field Mode | Register Mode | Tag |
 |----------------------------------------------------------|-----------|-------|------------|----------|
 | [`modify`](field::WRwRegFieldAtomic::modify)             |           | write | read-write | Srt, Crt |
+| [`modify_field`](field::WRwRegFieldUnsync::modify_field) |           | write | read-write | Urt      |
+| [`modify_field`](field::WRwRegFieldAtomic::modify_field) |           | write | read-write | Srt, Crt |
 | [`set_bit`](field::WRwRegFieldBitAtomic::set_bit)        | one-bit   | write | read-write | Srt, Crt |
 | [`clear_bit`](field::WRwRegFieldBitAtomic::clear_bit)    | one-bit   | write | read-write | Srt, Crt |
 | [`toggle_bit`](field::WRwRegFieldBitAtomic::toggle_bit)  | one-bit   | write | read-write | Srt, Crt |
@@ -124,6 +137,8 @@
 |                                                         | Field Width | Field Mode | Register Mode | Tag |
 |--------------------------------------------------------------|-----------|-------|------------|----------|
 | [`modify`](field::WRwRegFieldSoftAtomic::modify)             |           | write | read-write | Srt, Crt |
+| [`modify_field`](field::WRwRegFieldUnsync::modify_field)     |           | write | read-write | Urt      |
+| [`modify_field`](field::WRwRegFieldSoftAtomic::modify_field) |           | write | read-write | Srt, Crt |
 | [`set_bit`](field::WRwRegFieldBitSoftAtomic::set_bit)        | one-bit   | write | read-write | Srt, Crt |
 | [`clear_bit`](field::WRwRegFieldBitSoftAtomic::clear_bit)    | one-bit   | write | read-write | Srt, Crt |
 | [`toggle_bit`](field::WRwRegFieldBitSoftAtomic::toggle_bit)  | one-bit   | write | read-write | Srt, Crt |
@@ -146,10 +161,18 @@
 | [`zeroed_val`](Reg::zeroed_val)         |            |          |
 | [`zeroed`](Reg::zeroed)                 |            |          |
 | [`hold`](Reg::hold)                     |            |          |
+| [`into_fields`](RegFields::into_fields) |            |          |
+| [`from_fields`](RegFields::from_fields) |            |          |
+| [`fields_into_unsync`](RegFields::fields_into_unsync) |            |          |
+| [`fields_into_sync`](RegFields::fields_into_sync)     |            |          |
+| [`fields_into_copy`](RegFields::fields_into_copy)     |            |          |
 | [`load`](RReg::load)                    | read       |          |
 | [`load_val`](RReg::load_val)            | read       |          |
 | [`load_bits`](RReg::load_bits)          | read       |          |
 | [`as_ptr`](RReg::as_ptr)                | read       |          |
+| [`load_consume`](RRegSideEffect::load_consume)           | read       |          |
+| [`load_consume_val`](RRegSideEffect::load_consume_val)   | read       |          |
+| [`load_consume_bits`](RRegSideEffect::load_consume_bits) | read       |          |
 | [`as_mut_ptr`](WReg::as_mut_ptr)        | write      |          |
 | [`store`](WRegUnsync::store)            | write      | Urt      |
 | [`store`](WRegAtomic::store)            | write      | Srt, Crt |
@@ -163,6 +186,11 @@
 | [`reset`](WRegAtomic::reset)            | write      | Srt, Crt |
 | [`modify`](RwRegUnsync::modify)         | read-write | Urt      |
 | [`modify_reg`](RwRegUnsync::modify_reg) | read-write | Urt      |
+| [`save`](RegSnapshot::save)             | read-write |          |
+| [`restore`](RegSnapshot::restore)       | read-write |          |
+| [`load_shadow`](Shadowed::load_shadow)       | shadowed   |          |
+| [`store_shadowed`](Shadowed::store_shadowed) | shadowed   |          |
+| [`modify`](Shadowed::modify)                 | shadowed   |          |
 
 "]
 #![cfg_attr(
@@ -204,6 +232,409 @@
 | `write_foo(bits)` ([`write`](field::WWRegFieldBits::write)) | multi-bit | write |
 
 "]
+//! ## Field Conversions
+//!
+//! A field definition in [`reg!`](crate::reg!) can attach conversion
+//! functions between the raw field bits and a high-level value:
+//!
+//! ```ignore
+//! PSC => {
+//!     offset => 0;
+//!     width => 16;
+//!     traits => { RRRegField WWRegField };
+//!     // The paths are resolved inside the generated register module.
+//!     convert => { type => u32; read => super::psc_to_hz; write => super::hz_to_psc };
+//! };
+//! ```
+//!
+//! This generates `read_converted`/`write_converted` methods for the field
+//! token, and `psc_converted()`/`write_psc_converted(value)` methods for the
+//! register value.
+//!
+//! ## Field Scaling
+//!
+//! Many fields hold a linearly encoded quantity, e.g. a prescaler holds the
+//! division factor minus one. Instead of writing conversion functions, a
+//! field can declare a linear `scale`, where the quantity is
+//! `(bits + bias) * factor`:
+//!
+//! ```ignore
+//! PSC => {
+//!     offset => 0;
+//!     width => 16;
+//!     traits => { RRRegField WWRegField };
+//!     scale => { name => div; bias => 1; unit => "timer clock cycles" };
+//! };
+//! ```
+//!
+//! This generates `read_div()` and `write_div(value)` methods for the register
+//! value. The `factor` and `bias` keys default to `1` and `0`, and the `unit`
+//! key only affects the generated documentation. `read_div()` saturates on
+//! overflow. `write_div(value)` returns
+//! [`ScaleError`](field::ScaleError) if the value is below the bias or
+//! doesn't fit into the field.
+//!
+//! Only linear quantities are supported. A reciprocal quantity, like the
+//! output frequency of a clock prescaler, depends on the input clock and
+//! should be computed with a `convert` function instead.
+//!
+//! ## Field Values
+//!
+//! A field definition can enumerate the meaningful bit patterns with the
+//! `values` key:
+//!
+//! ```ignore
+//! SW => {
+//!     offset => 0;
+//!     width => 2;
+//!     traits => { RRRegField WWRegField };
+//!     values => { Hsi => 0, Hse => 1, Pll => 2 };
+//! };
+//! ```
+//!
+//! This generates the `SwValue` enum in the register module, which converts
+//! from the field bits with `TryFrom`, and into the field bits with `From`.
+//! The register value methods then use the enum instead of the raw bits:
+//! `write_sw(SwValue::Pll)` writes the field, and `sw()` returns
+//! `Result<SwValue, u32>`, where the error holds a reserved bit pattern. If
+//! the values cover all bit patterns of the field, `sw()` returns `SwValue`
+//! directly. The raw bits are still accessible through the field token.
+//!
+//! ## Clearing Flags
+//!
+//! Status flags are often cleared by writing `1` (or `0`) to the flag bit,
+//! while writing the opposite value has no effect. Clearing such a flag with
+//! `modify` may also clear other flags, which are pending at the moment of the
+//! read. Single-bit fields with the `W1CRegField` or `W0CRegField` trait get
+//! the `clear_flag` method on the field token, and the `clear_<field>` method
+//! on the register token. Both write only the target bit in a single store:
+//!
+//! ```ignore
+//! PR0 => { offset => 0; width => 1; traits => { RRRegField W1CRegField } };
+//! ```
+//!
+//! ```ignore
+//! // Clears only the pending bit 0, without touching other pending bits.
+//! exti_pr.clear_pr0();
+//! ```
+//!
+//! ## Bit-Banding
+//!
+//! Cortex-M3 and Cortex-M4 map each bit of the first megabyte of SRAM and
+//! peripheral memory to a word in the bit-band alias region. A register
+//! definition with the `bitband` key gets the
+//! [`RegFieldBitBand`](field::RegFieldBitBand) trait for all its single-bit
+//! fields, with the alias addresses computed by [`reg!`](crate::reg!):
+//!
+//! ```ignore
+//! CR => {
+//!     address => 0x4002_1000;
+//!     size => 0x20;
+//!     reset => 0x0000_0083;
+//!     bitband => true;
+//!     traits => { RReg WReg };
+//!     fields => { /* HSEON, HSERDY, ... */ };
+//! };
+//! ```
+//!
+//! ```ignore
+//! // A single atomic store to the alias word, without a CAS loop.
+//! rcc_cr.hseon.set_bit_band();
+//! ```
+//!
+//! ## Accessor Names
+//!
+//! The register value methods are named after the field by default. A field
+//! definition can override the name with the `as` key, and add a
+//! documentation alias with the `alias` key, e.g. to keep the name from the
+//! reference manual searchable:
+//!
+//! ```ignore
+//! W5RDY => {
+//!     offset => 5;
+//!     width => 1;
+//!     traits => { RRRegField };
+//!     as => rx_ready;
+//!     alias => "receive buffer ready";
+//! };
+//! ```
+//!
+//! This generates `rx_ready()` instead of `w5rdy()` for the register value.
+//! The field token and the register struct field keep the `w5rdy` name, which
+//! is also added as a documentation alias of the generated methods.
+//!
+//! ## Field Groups
+//!
+//! Fields, which are usually configured together, can be grouped with the
+//! `groups` key of [`reg!`](crate::reg!). A group generates a single method,
+//! which writes all fields of the group:
+//!
+//! ```ignore
+//! CRL => {
+//!     address => 0x4001_0800;
+//!     size => 0x20;
+//!     reset => 0x4444_4444;
+//!     traits => { RReg WReg };
+//!     fields => { /* MODE2 and CNF2 among others */ };
+//!     groups => {
+//!         /// Pin 2 configuration.
+//!         PIN2_CFG => { MODE2 CNF2 };
+//!     };
+//! };
+//! ```
+//!
+//! This generates `write_pin2_cfg(mode2, cnf2)` for the register value. For
+//! the unsynchronized register token, it also generates
+//! `write_pin2_cfg(mode2, cnf2)`, which updates the group in a single
+//! read-modify-write (see [`RwRegUnsync::modify`]). Synchronized tokens can
+//! use the value method inside their atomic `modify`.
+//!
+//! ## Side-Effectful Reads
+//!
+//! Reads of some registers have side effects, e.g. reading a UART data
+//! register pops the receive FIFO. Such register lists the `RRegSideEffect`
+//! trait instead of `RReg`:
+//!
+//! ```ignore
+//! DR => {
+//!     address => 0x4001_3804;
+//!     size => 0x20;
+//!     reset => 0x0000_0000;
+//!     traits => { RRegSideEffect WReg };
+//!     fields => { DR => { offset => 0; width => 9; traits => { RRRegField WWRegField } } };
+//! };
+//! ```
+//!
+//! The register then has no `load` methods, and is read explicitly with
+//! [`load_consume`](RRegSideEffect::load_consume). The readable fields are
+//! accessible only through the consumed value, e.g.
+//! `usart1_dr.load_consume().dr()`, and their field tokens have no read
+//! methods.
+//!
+//! ## Shadowed Registers
+//!
+//! Write-only registers can't be read back, so they have no `modify`
+//! operation. Adding the `Shadowed` trait keeps the last written value in a
+//! static shadow copy:
+//!
+//! ```ignore
+//! CR => {
+//!     address => 0x4000_7400;
+//!     size => 0x20;
+//!     reset => 0x0000_0000;
+//!     traits => { WReg WoReg Shadowed };
+//!     fields => {
+//!         EN1 => { offset => 0; width => 1; traits => { WWRegField WoWRegField } };
+//!         EN2 => { offset => 16; width => 1; traits => { WWRegField WoWRegField } };
+//!     };
+//! };
+//! ```
+//!
+//! Then [`Shadowed::modify`] passes the shadow copy to the closure, and writes
+//! the result to both the register and the shadow copy, e.g.
+//! `dac_cr.modify(|r| r.set_en2())` keeps `EN1` as it was written last time.
+//! Only [`Shadowed::store_shadowed`] and [`Shadowed::modify`] update the
+//! shadow copy. A shadowed register can't have `instances`.
+//!
+//! ## Register Arrays
+//!
+//! Repeated registers can be declared once with the `array` key of
+//! [`reg!`](crate::reg!). The `address` is the address of the first register,
+//! and each next register is `stride` bytes further:
+//!
+//! ```ignore
+//! AFR => {
+//!     address => 0x4002_0020;
+//!     size => 0x20;
+//!     reset => 0x0000_0000;
+//!     array => { count => 2; stride => 0x4; };
+//!     traits => { RReg WReg };
+//!     fields => { /* AFSEL0 to AFSEL7 */ };
+//! };
+//! ```
+//!
+//! This generates usual register modules `gpioa_afr0` and `gpioa_afr1`, and
+//! the `gpioa_afr` module with the array token, which is a tuple of the
+//! register tokens. The array token is listed in
+//! [`tokens!`](crate::reg::tokens!) as a single register. The registers are
+//! accessed by a constant index with [`reg::<I>()`](RegArray), e.g.
+//! `reg.gpioa_afr.reg::<1>()`, or by the tuple field, e.g. `reg.gpioa_afr.1`.
+//! An out of bounds index fails at compile-time.
+//!
+//! Each array is a separate token. Register clusters, i.e. repeated blocks of
+//! different registers, e.g. DMA channel blocks, are out of scope of
+//! [`reg!`](crate::reg!), and the `cluster` key is rejected. Such a block is
+//! declared as several arrays with the same `count` and `stride`, which are
+//! indexed separately.
+//!
+//! ## Peripheral Instances
+//!
+//! Identical peripherals, e.g. USART1 and USART2, share one register definition
+//! with the `instances` key of [`reg!`](crate::reg!). The `address` is then the
+//! offset from the instance base address:
+//!
+//! ```ignore
+//! USART DR => {
+//!     address => 0x04;
+//!     size => 0x20;
+//!     reset => 0x0000_0000;
+//!     instances => { USART1 => 0x4001_3800; USART2 => 0x4000_4400; };
+//!     traits => { RReg WReg };
+//!     fields => { /* DR */ };
+//! };
+//! ```
+//!
+//! This generates the `usart_dr` module with the register and field tokens,
+//! which take the base address as a const generic parameter, so the register
+//! methods are generated once for all instances. The `usart1_dr` and
+//! `usart2_dr` modules are the usual register modules with the base address
+//! applied, which are listed in [`tokens!`](crate::reg::tokens!) and
+//! [`periph!`](crate::periph!) as usual.
+//!
+//! ## Register Variants
+//!
+//! Some registers reinterpret their bits depending on the peripheral mode,
+//! e.g. a timer capture/compare mode register in input and output modes. Such
+//! register lists its layouts with the `variants` key of [`reg!`](crate::reg!).
+//! Fields outside of `variants` are common to all layouts:
+//!
+//! ```ignore
+//! CCMR1 => {
+//!     address => 0x4001_0018;
+//!     size => 0x20;
+//!     reset => 0x0000_0000;
+//!     traits => { RReg WReg };
+//!     fields => { /* CC1S */ };
+//!     variants => {
+//!         Input => { fields => { /* IC1F, IC1PSC */ }; };
+//!         Output => { fields => { /* OC1M, OC1PE */ }; groups => { /* ... */ }; };
+//!     };
+//! };
+//! ```
+//!
+//! This generates a register module for each layout, `tim1_ccmr1_input` and
+//! `tim1_ccmr1_output`, and the `tim1_ccmr1` module, which re-exports the
+//! first layout. The tokens are converted between the layouts with
+//! `into_tim1_ccmr1_output()` and `into_tim1_ccmr1_input()`. Only the
+//! register itself is listed in [`tokens!`](crate::reg::tokens!):
+//!
+//! ```ignore
+//! pub mod TIM1 { CCMR1; }
+//! ```
+//!
+//! ## Overlapping Fields
+//!
+//! [`reg!`](crate::reg!) rejects fields, which don't fit into the register
+//! size, or which overlap other fields. Some registers legitimately interpret
+//! the same bits in different ways, e.g. a multi-bit field and its individual
+//! bits. Such fields should be marked with `overlapping => true`:
+//!
+//! ```ignore
+//! CCMR1 => {
+//!     // ...
+//!     fields => {
+//!         OC1M => { offset => 4; width => 3; traits => { RRRegField WWRegField } };
+//!         OC1M0 => {
+//!             offset => 4;
+//!             width => 1;
+//!             traits => { RRRegField WWRegField };
+//!             overlapping => true;
+//!         };
+//!     };
+//! };
+//! ```
+//!
+//! ## Reserved Bits
+//!
+//! By default store operations write reserved bits as they are in the stored
+//! value, i.e. from the reset value for [`store`](WRegUnsync::store), or as is
+//! for [`store_bits`](WRegUnsync::store_bits). Some peripherals require the
+//! reserved bits to keep their reset value or their current value. The
+//! `reserved` key of [`reg!`](crate::reg!) records the mask of such bits:
+//!
+//! ```ignore
+//! CR => {
+//!     address => 0x4002_3800;
+//!     size => 0x20;
+//!     reset => 0x0000_0083;
+//!     // `preserve` is either `reset` (default) or `read`.
+//!     reserved => { mask => 0xF0F0_0004; preserve => read; };
+//!     traits => { RReg WReg };
+//!     fields => { /* ... */ };
+//! };
+//! ```
+//!
+//! All store operations then replace the reserved bits with the reset value,
+//! or with the value read from the register right before the store. See
+//! [`Reg::RESERVED`]. Read-modify-write operations keep the read value.
+//!
+//! ## Debug Formatting
+//!
+//! With the `debug` key of [`reg!`](crate::reg!) the register value gets a
+//! [`Debug`](core::fmt::Debug) implementation, which prints the raw bits of
+//! each field by name:
+//!
+//! ```ignore
+//! CTRL => {
+//!     address => 0xE000_E010;
+//!     size => 0x20;
+//!     reset => 0x0000_0000;
+//!     debug => true;
+//!     traits => { RReg WReg };
+//!     fields => { /* ENABLE, TICKINT, ... */ };
+//! };
+//! ```
+//!
+//! ```ignore
+//! // Prints `STK_CTRL { ENABLE: 1, TICKINT: 0 }`.
+//! writeln!(stream, "{:?}", stk_ctrl.load())?;
+//! ```
+//!
+//! ## Polling
+//!
+//! [`poll_until`] turns a busy wait on a register field into a future, which is
+//! re-checked on each activation of a thread:
+//!
+//! ```ignore
+//! // Instead of `spin_until!(rcc_cr_hserdy.read_bit())`:
+//! reg::poll_until(rcc_cr_hserdy, thr.rcc, |f| f.read_bit()).await;
+//! ```
+//!
+//! Single-bit fields have the shortcuts
+//! [`wait_for_set`](field::RRRegFieldBit::wait_for_set) and
+//! [`wait_for_clear`](field::RRRegFieldBit::wait_for_clear):
+//!
+//! ```ignore
+//! let rcc_cr_hserdy = rcc_cr_hserdy.wait_for_set(thr.rcc).await;
+//! ```
+//!
+//! ## Batches
+//!
+//! Atomic operations of synchronized tokens protect each register update
+//! separately. [`batch`] performs several register operations with interrupts
+//! disabled exactly once, so the other code never observes a partially applied
+//! configuration:
+//!
+//! ```ignore
+//! reg::batch(|b| {
+//!     b.modify(&rcc_cfgr, |r| r.write_hpre(0b1000).write_ppre1(0b100));
+//!     b.modify(&rcc_cfgr, |r| r.write_sw(0b10));
+//! });
+//! ```
+//!
+//! ## Snapshots
+//!
+//! Low-power modes often lose the peripheral state. Every read-write or
+//! [`Shadowed`] register token can [`save`](RegSnapshot::save) its current
+//! value and [`restore`](RegSnapshot::restore) it later. [`snapshot!`] does
+//! the same for a list of tokens:
+//!
+//! ```ignore
+//! let saved = reg::snapshot!(rcc_cfgr, gpioa_moder, gpioa_afrl);
+//! enter_stop_mode();
+//! reg::snapshot!(restore saved => rcc_cfgr, gpioa_moder, gpioa_afrl);
+//! ```
+//!
 //! # Tags
 //!
 //! Each register or field token can have one of three flavors. They are encoded
@@ -230,6 +661,20 @@
 //!
 //! **Non-affine** means the token could be freely copied.
 //!
+//! Registers with atomic tags can be modified through a shared reference, so
+//! there is no need to convert a shared token to `Urt` first. With the
+//! `atomics` feature the `modify` method comes from `RwRegAtomic`, which
+//! repeats a compare-and-swap loop (`LDREX`/`STREX` on ARM) until the update
+//! is not interrupted, thus the closure may be called more than once. Without
+//! the feature the same method comes from
+//! [`RwRegSoftAtomic`](soft_atomic::RwRegSoftAtomic), which runs the update in
+//! a critical section:
+//!
+//! ```ignore
+//! // `gpioa_crl` is a `Reg<Srt>` shared between threads.
+//! gpioa_crl.modify(|r| r.write_mode2(0b10));
+//! ```
+//!
 //! Tokens of some tags can be converted to the same tokens of other tags using
 //! `.into_unsync()`, `.into_sync()`, `.into_copy()`. Here is the conversion
 //! matrix for *register* tokens:
@@ -252,6 +697,16 @@
 | Crt        | -     | -     | **+** |
 
 "]
+//! A single field token can't change its tag, because the other fields of the
+//! same register could still be used with the old tag. A *complete* set of
+//! field tokens, on the other hand, can be converted the same way as the
+//! register token with [`RegFields`]:
+//!
+//! ```ignore
+//! // `mms` and `ccds` are all fields of `tim2_cr2`, split as `Urt`.
+//! let (mms, ccds) = tim2_cr2::Reg::<Urt>::fields_into_sync((mms, ccds));
+//! ```
+//!
 //! # Mappings
 //!
 //! We define concrete register mappings in platform crates. Usually the user
@@ -274,7 +729,7 @@
 //!     /// SysTick control and status register.
 //!     pub STK CTRL => {
 //!         address => 0xE000_E010; // the register address in memory
-//!         size => 0x20;           // size of the register in bits
+//!         size => 0x20;           // size of the register in bits: 8, 16, 32, or 64
 //!         reset => 0x0000_0000;   // reset value of the register
 //!         // Traits to implement for the register token. The most common sets are:
 //!         //     RReg RoReg - read-only register
@@ -362,29 +817,125 @@
 //!
 //! fn trunk(reg: Regs) {}
 //! ```
+//!
+//! One platform crate can serve several chip variants. Blocks and individual
+//! registers in [`tokens!`](crate::reg::tokens!) can have `#[cfg(...)]`
+//! attributes, which exclude them from the block modules and the index struct:
+//!
+//! ```ignore
+//! reg::tokens! {
+//!     pub macro stm32_reg_tokens;
+//!     crate;
+//!     crate;
+//!
+//!     /// Ethernet MAC.
+//!     #[cfg(stm32_mcu = "stm32f107")]
+//!     pub mod ETH { MACCR; }
+//!
+//!     /// Reset and clock control.
+//!     pub mod RCC {
+//!         CR;
+//!         #[cfg(stm32_mcu = "stm32f107")]
+//!         CFGR2;
+//!     }
+//! }
+//! ```
+//!
+//! The corresponding [`reg!`](crate::reg!) definitions should have the same
+//! attributes.
+//!
+//! ## C Headers
+//!
+//! Projects mixing Drone with C libraries can export the same register map for
+//! C code. The index structure has a `write_c_header` function, which writes
+//! `#define`s of the addresses, the reset values, and the field positions and
+//! masks of all indexed registers into any [`core::fmt::Write`]. It's usually
+//! called on the host, e.g. from a build script or a test with the `host`
+//! feature:
+//!
+//! ```ignore
+//! let mut header = String::new();
+//! Regs::write_c_header(&mut header).unwrap();
+//! std::fs::write("regs.h", header).unwrap();
+//! ```
+//!
+//! ## SVD Ingestion
+//!
+//! Instead of writing [`reg!`](crate::reg!) definitions by hand, a platform
+//! crate can generate them from a CMSIS-SVD file provided by the manufacturer
+//! with [`from_svd!`](crate::reg::from_svd!). The path is relative to the
+//! crate root:
+//!
+//! ```ignore
+//! reg::from_svd! {
+//!     path => "svd/STM32F103.svd";
+//!     // Optional, all peripherals are ingested by default.
+//!     peripherals => { GPIOA RCC };
+//! }
+//! ```
+//!
+//! Each SVD register expands to a `reg!` invocation. The register `access`
+//! selects the register and field traits. The `writeOnce` registers are
+//! mapped to read-write ones with a note in their documentation. The
+//! `oneToClear` and `zeroToClear` single-bit fields get
+//! [`W1CRegField`](field::W1CRegField) and [`W0CRegField`](field::W0CRegField).
+//! Enumerated values become field `values`; duplicate names or values are
+//! compile errors. Fields, which overlap other fields of the same register,
+//! are marked with `overlapping => true`. Derived peripherals, clusters, and
+//! `dim` arrays are expanded into separate registers; a `dimIndex` list must
+//! have exactly `dim` entries. The generated definitions name the register
+//! traits, which should be in scope, e.g. through the [`prelude`].
 
 pub mod field;
 pub mod marker;
+#[cfg(feature = "host")]
+pub mod mock;
 pub mod prelude;
 pub mod soft_atomic;
 pub mod tag;
+#[cfg(feature = "reg-trace")]
+pub mod trace;
 
 #[cfg(feature = "atomics")]
 mod atomic;
+mod batch;
+mod c_header;
+mod poll;
+mod shadow;
+mod snapshot;
 
 #[cfg(feature = "atomics")]
 pub use self::atomic::RwRegAtomic;
+pub use self::batch::{batch, Batch};
+pub use self::c_header::{write_c_defines, RegCDefines};
+pub use self::poll::poll_until;
+pub use self::shadow::{Shadow, Shadowed};
+pub use self::snapshot::RegSnapshot;
+#[doc(hidden)]
+pub use self::snapshot::{
+    restore as snapshot_restore, restore_shadowed as snapshot_restore_shadowed,
+};
 #[cfg(not(feature = "atomics"))]
 pub use self::soft_atomic::RwRegSoftAtomic;
 use self::tag::{Crt, RegAtomic, RegOwned, RegTag, Srt, Urt};
 use crate::bitfield::{Bitfield, Bits};
 use crate::token::Token;
+#[cfg(not(feature = "host"))]
 use core::ptr::{read_volatile, write_volatile};
 /// A macro to define a macro to define a set of register tokens.
 ///
 /// See [the module level documentation](self) for details.
 #[doc(inline)]
 pub use drone_core_macros::reg_tokens as tokens;
+
+#[doc(inline)]
+pub use crate::reg_snapshot as snapshot;
+
+/// A macro to define registers from a CMSIS-SVD file.
+///
+/// See [the module level documentation](self) for details.
+#[doc(inline)]
+pub use drone_core_macros::reg_from_svd as from_svd;
 #[doc(hidden)]
 pub use drone_core_macros::reg_tokens_inner as tokens_inner;
 
@@ -419,6 +970,10 @@ pub trait Reg<T: RegTag>: Token + Sync {
     /// The register default value.
     const RESET: <Self::Val as Bitfield>::Bits;
 
+    /// The mask of reserved bits and the way they are written by store
+    /// operations, or `None` if the register has no special reserved bits.
+    const RESERVED: Option<(<Self::Val as Bitfield>::Bits, Reserved)> = None;
+
     /// Creates a new instance of [`Reg::Val`] from raw `bits`.
     ///
     /// # Safety
@@ -481,6 +1036,24 @@ pub trait Reg<T: RegTag>: Token + Sync {
         unsafe { Self::val_from(<<Self::Val as Bitfield>::Bits as Bits>::from_usize(0)) }
     }
 
+    /// Replaces the reserved bits of `bits` according to [`Reg::RESERVED`].
+    ///
+    /// This method is called by the store operations.
+    #[inline]
+    fn preserve_reserved(
+        &self,
+        bits: <Self::Val as Bitfield>::Bits,
+    ) -> <Self::Val as Bitfield>::Bits {
+        match Self::RESERVED {
+            None => bits,
+            Some((mask, Reserved::Reset)) => bits & !mask | Self::RESET & mask,
+            Some((mask, Reserved::Read)) => {
+                let read = unsafe { load_raw::<<Self::Val as Bitfield>::Bits>(Self::ADDRESS) };
+                bits & !mask | read & mask
+            }
+        }
+    }
+
     /// Creates a new exposed register value, and initializes it with the reset
     /// value.
     ///
@@ -502,6 +1075,18 @@ pub trait Reg<T: RegTag>: Token + Sync {
     }
 }
 
+/// The value written to the reserved bits of a register by store operations.
+///
+/// See [`Reg::RESERVED`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reserved {
+    /// The reserved bits are written with their reset value.
+    Reset,
+    /// The reserved bits are written with the value read from the register
+    /// right before the store.
+    Read,
+}
+
 /// Exposed storage for register values.
 ///
 /// A type implementing this trait should have public getters and setters to
@@ -519,6 +1104,67 @@ where
     fn set_val(&mut self, val: R::Val);
 }
 
+/// Register token, which can be split into the complete set of its field
+/// tokens, and assembled back.
+///
+/// This trait is implemented by [`reg!`](crate::reg!) for all registers with
+/// fields. Because no field of the register is left behind, the whole set of
+/// field tokens can change its tag the same way as the register token.
+pub trait RegFields<T: RegTag>: Reg<T> {
+    /// A tuple of all field tokens of the register.
+    type Fields;
+
+    /// Splits the register token into its field tokens.
+    fn into_fields(self) -> Self::Fields;
+
+    /// Assembles the register token from the complete set of its field tokens.
+    fn from_fields(fields: Self::Fields) -> Self;
+
+    /// Converts the complete set of field tokens into unsynchronized field
+    /// tokens.
+    #[inline]
+    fn fields_into_unsync(fields: Self::Fields) -> <Self::UReg as RegFields<Urt>>::Fields
+    where
+        T: RegOwned,
+        Self::UReg: RegFields<Urt>,
+    {
+        Self::from_fields(fields).into_unsync().into_fields()
+    }
+
+    /// Converts the complete set of field tokens into synchronized field
+    /// tokens.
+    #[inline]
+    fn fields_into_sync(fields: Self::Fields) -> <Self::SReg as RegFields<Srt>>::Fields
+    where
+        T: RegOwned,
+        Self::SReg: RegFields<Srt>,
+    {
+        Self::from_fields(fields).into_sync().into_fields()
+    }
+
+    /// Converts the complete set of field tokens into copyable field tokens.
+    #[inline]
+    fn fields_into_copy(fields: Self::Fields) -> <Self::CReg as RegFields<Crt>>::Fields
+    where
+        Self::CReg: RegFields<Crt>,
+    {
+        Self::from_fields(fields).into_copy().into_fields()
+    }
+}
+
+/// Register array token, which has an element at the index `I`.
+///
+/// This trait is implemented by [`reg!`](crate::reg!) for each index of a
+/// register array. The array token has a `reg::<I>()` method, which returns the
+/// element, and fails to compile if `I` is out of bounds.
+pub trait RegArray<const I: usize> {
+    /// The register token at the index `I`.
+    type Element;
+
+    /// Returns a reference to the register token at the index `I`.
+    fn element(&self) -> &Self::Element;
+}
+
 /// Readable register.
 pub trait RReg<T: RegTag>: Reg<T> {
     /// Reads the value from the register memory to the exposed value type.
@@ -543,7 +1189,7 @@ pub trait RReg<T: RegTag>: Reg<T> {
     /// See also [`load`](RReg::load), [`load_val`](RReg::load_val).
     #[inline]
     fn load_bits(&self) -> <Self::Val as Bitfield>::Bits {
-        unsafe { read_volatile(self.as_ptr()) }
+        unsafe { load_raw(Self::ADDRESS) }
     }
 
     /// Returns a raw pointer to the register memory.
@@ -572,6 +1218,45 @@ pub trait RoReg<T: RegTag>: RReg<T> {}
 /// Write-only register.
 pub trait WoReg<T: RegTag>: WReg<T> {}
 
+/// Register, which reads have side effects.
+///
+/// Reading such register, e.g. the data register of a receive FIFO, pops the
+/// hardware FIFO. The register doesn't implement [`RReg`], so it can't be read
+/// accidentally, e.g. by a generic debug printing helper. The read operations
+/// are exposed under distinct names instead.
+pub trait RRegSideEffect<T: RegTag>: Reg<T> {
+    /// Reads the value from the register memory to the exposed value type,
+    /// triggering the read side effects.
+    ///
+    /// See also [`load_consume_val`](RRegSideEffect::load_consume_val),
+    /// [`load_consume_bits`](RRegSideEffect::load_consume_bits).
+    #[allow(clippy::needless_lifetimes)]
+    #[inline]
+    fn load_consume<'a>(&'a self) -> <Self as Reg<T>>::Hold<'a> {
+        self.hold(self.load_consume_val())
+    }
+
+    /// Reads the value from the register memory to the opaque value type,
+    /// triggering the read side effects.
+    ///
+    /// See also [`load_consume`](RRegSideEffect::load_consume),
+    /// [`load_consume_bits`](RRegSideEffect::load_consume_bits).
+    #[inline]
+    fn load_consume_val(&self) -> Self::Val {
+        unsafe { Self::val_from(self.load_consume_bits()) }
+    }
+
+    /// Reads the value from the register memory to the raw value type,
+    /// triggering the read side effects.
+    ///
+    /// See also [`load_consume`](RRegSideEffect::load_consume),
+    /// [`load_consume_val`](RRegSideEffect::load_consume_val).
+    #[inline]
+    fn load_consume_bits(&self) -> <Self::Val as Bitfield>::Bits {
+        unsafe { load_raw(Self::ADDRESS) }
+    }
+}
+
 /// Non-atomic operations for writable register.
 pub trait WRegUnsync: WReg<Urt> {
     /// Passes the reset value to the closure `f`, then writes the result of the
@@ -690,9 +1375,8 @@ where
             &'b mut <Self as Reg<Urt>>::Hold<'a>,
         ) -> &'b mut <Self as Reg<Urt>>::Hold<'a>,
     {
-        unsafe {
-            write_volatile(self.as_mut_ptr(), f(&mut self.default()).val().bits());
-        }
+        let bits = f(&mut self.default()).val().bits();
+        unsafe { store_raw(Self::ADDRESS, self.preserve_reserved(bits)) };
     }
 
     #[inline]
@@ -712,12 +1396,12 @@ where
 
     #[inline]
     fn store_bits(&mut self, bits: <Self::Val as Bitfield>::Bits) {
-        unsafe { write_volatile(self.as_mut_ptr(), bits) };
+        unsafe { store_raw(Self::ADDRESS, self.preserve_reserved(bits)) };
     }
 
     #[inline]
     fn reset(&mut self) {
-        unsafe { write_volatile(self.as_mut_ptr(), self.default_val().bits()) };
+        unsafe { store_raw(Self::ADDRESS, self.default_val().bits()) };
     }
 }
 
@@ -753,7 +1437,7 @@ where
 
     #[inline]
     fn store_bits(&self, bits: <Self::Val as Bitfield>::Bits) {
-        unsafe { write_volatile(self.as_mut_ptr(), bits) };
+        unsafe { store_raw(Self::ADDRESS, self.preserve_reserved(bits)) };
     }
 
     #[inline]
@@ -773,9 +1457,7 @@ where
             &'b mut <Self as Reg<Urt>>::Hold<'a>,
         ) -> &'b mut <Self as Reg<Urt>>::Hold<'a>,
     {
-        unsafe {
-            write_volatile(self.as_mut_ptr(), f(&mut self.load()).val().bits());
-        }
+        unsafe { store_raw(Self::ADDRESS, f(&mut self.load()).val().bits()) };
     }
 
     #[inline]
@@ -789,6 +1471,51 @@ where
     }
 }
 
+/// Reads the register memory at `address`.
+#[cfg(not(feature = "host"))]
+#[inline]
+pub(crate) unsafe fn load_raw<B: Bits>(address: usize) -> B {
+    let bits = unsafe { read_volatile(address as *const B) };
+    #[cfg(feature = "reg-trace")]
+    trace::emit(address, bits, trace::Access::Read);
+    bits
+}
+
+/// Reads the mock register memory at `address`.
+#[cfg(feature = "host")]
+#[inline]
+pub(crate) unsafe fn load_raw<B: Bits>(address: usize) -> B {
+    let bits = mock::read(address);
+    #[cfg(feature = "reg-trace")]
+    trace::emit(address, bits, trace::Access::Read);
+    bits
+}
+
+/// Writes `bits` into the register memory at `address`.
+#[cfg(not(feature = "host"))]
+#[inline]
+pub(crate) unsafe fn store_raw<B: Bits>(address: usize, bits: B) {
+    #[cfg(feature = "reg-trace")]
+    trace::emit(address, bits, trace::Access::Write);
+    unsafe { write_volatile(address as *mut B, bits) };
+}
+
+/// Writes `bits` into the mock register memory at `address`.
+#[cfg(feature = "host")]
+#[inline]
+pub(crate) unsafe fn store_raw<B: Bits>(address: usize, bits: B) {
+    #[cfg(feature = "reg-trace")]
+    trace::emit(address, bits, trace::Access::Write);
+    mock::write(address, bits);
+}
+
+/// Converts register `bits` to `u64`.
+pub(crate) fn bits_to_u64<B: Bits>(bits: B) -> u64 {
+    (0..(core::mem::size_of::<B>() * 8).min(64))
+        .filter(|&i| !(bits >> B::from_usize(i) & B::from_usize(1)).is_zero())
+        .fold(0, |value, i| value | 1 << i)
+}
+
 mod compile_tests {
     //! ```compile_fail
     //! use drone_core::reg::prelude::*;
@@ -964,4 +1691,57 @@ mod compile_tests {
     //!     reg.foo_bar;
     //! }
     //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub TST TST_OVERLAP_REG => {
+    //!         address => 0xDEAD_BEEF; size => 0x20; reset => 0xBEEF_CACE; traits => { RReg WReg };
+    //!         fields => {
+    //!             TST_BITS => { offset => 0; width => 4; traits => { RRRegField WWRegField } };
+    //!             TST_BIT => { offset => 3; width => 1; traits => { RRRegField WWRegField } };
+    //!         };
+    //!     };
+    //! }
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg::from_svd!(path => "tests/svd/duplicate_values.svd");
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg::from_svd!(path => "tests/svd/dim_index.svd");
+    //! fn main() {}
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::{reg::prelude::*, token::Token};
+    //! drone_core::reg! {
+    //!     pub GPIOA AFR => {
+    //!         address => 0x4002_0020; size => 0x20; reset => 0x0000_0000;
+    //!         array => { count => 2; stride => 0x4; };
+    //!         traits => { RReg WReg };
+    //!     };
+    //! }
+    //! fn main() {
+    //!     let afr = unsafe { gpioa_afr::Reg::<drone_core::reg::tag::Srt>::take() };
+    //!     afr.reg::<2>();
+    //! }
+    //! ```
+    //!
+    //! ```compile_fail
+    //! use drone_core::reg::prelude::*;
+    //! drone_core::reg! {
+    //!     pub DMA1 CCR => {
+    //!         address => 0x4002_0008; size => 0x20; reset => 0x0000_0000;
+    //!         cluster => { count => 7; stride => 0x14; };
+    //!         traits => { RReg WReg };
+    //!     };
+    //! }
+    //! fn main() {}
+    //! ```
 }
@@ -0,0 +1,43 @@
+use crate::fib::{self, FiberFuture, ThrFiberFuture};
+use crate::reg::field::RRRegField;
+use crate::reg::tag::RegTag;
+use crate::reg::RReg;
+use crate::thr::ThrToken;
+
+/// Returns a future, which resolves when `predicate` returns `true` for the
+/// readable register field `field`.
+///
+/// The predicate is re-checked on each activation of the thread `thr`. It is
+/// normally either a periodic timer thread, or the interrupt thread of the
+/// peripheral, which owns the register. The future resolves to `field`, so the
+/// token can be reused afterwards.
+///
+/// This is an awaitable replacement for
+/// [`spin_until!`](crate::spin_until!) busy loops.
+///
+/// # Examples
+///
+/// ```ignore
+/// use drone_core::reg;
+///
+/// // Wait until the HSE oscillator is ready, re-checking on each RCC
+/// // interrupt.
+/// let rcc_cr_hserdy = reg::poll_until(rcc_cr_hserdy, thr.rcc, |f| f.read_bit()).await;
+/// ```
+pub fn poll_until<T, R, H, P>(field: R, thr: H, mut predicate: P) -> FiberFuture<R>
+where
+    T: RegTag,
+    R: RRRegField<T> + Send + 'static,
+    R::Reg: RReg<T>,
+    H: ThrToken,
+    P: FnMut(&R) -> bool + Send + 'static,
+{
+    let mut field = Some(field);
+    thr.add_future(fib::new_fn(move || {
+        if predicate(field.as_ref().unwrap()) {
+            fib::Complete(field.take().unwrap())
+        } else {
+            fib::Yielded(())
+        }
+    }))
+}
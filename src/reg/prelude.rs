@@ -7,6 +7,15 @@
 //! # #![allow(unused_imports)]
 //! use drone_core::reg::prelude::*;
 //! ```
+//!
+//! New traits are added to the versioned [`v2`] prelude, so existing code is
+//! not affected by new names. Platform crates can migrate to it at their own
+//! pace:
+//!
+//! ```
+//! # #![allow(unused_imports)]
+//! use drone_core::reg::prelude::v2::*;
+//! ```
 
 #[doc(no_inline)]
 pub use crate::reg::{
@@ -37,3 +46,26 @@ pub use crate::reg::{
     },
     RwRegSoftAtomic as _,
 };
+
+/// The second version of the Memory-Mapped Registers prelude.
+///
+/// It is a superset of the original prelude, which additionally exports the
+/// write-1-to-clear, side-effect, shadow, snapshot, and bit-band register
+/// traits, and the [`marker`](crate::reg::marker) aliases, so driver bounds can
+/// be written as `R: SRwReg` without extra imports.
+pub mod v2 {
+    #[doc(no_inline)]
+    pub use super::*;
+    #[doc(no_inline)]
+    pub use crate::reg::marker::*;
+    #[doc(no_inline)]
+    pub use crate::reg::{
+        field::{W0CRegField, W1CRegField},
+        RRegSideEffect, RegFields, Shadowed,
+    };
+    #[doc(no_inline)]
+    pub use crate::reg::{
+        field::{RegFieldBitBand as _, WRwRegFieldUnsync as _},
+        RegSnapshot as _,
+    };
+}
@@ -0,0 +1,106 @@
+use crate::bitfield::{Bitfield, Bits};
+use crate::platform::Interrupts;
+use crate::reg::tag::RegTag;
+use crate::reg::{store_raw, Reg, RegHold, Reserved, WoReg};
+use core::cell::UnsafeCell;
+
+/// Storage for the last value written to a [`Shadowed`] register.
+///
+/// Statics of this type are defined by [`reg!`](crate::reg!) for registers
+/// with the `Shadowed` trait.
+pub struct Shadow<B: Bits>(UnsafeCell<B>);
+
+unsafe impl<B: Bits> Sync for Shadow<B> {}
+
+impl<B: Bits> Shadow<B> {
+    /// Creates a new shadow storage holding the register `reset` value.
+    #[inline]
+    pub const fn new(reset: B) -> Self {
+        Self(UnsafeCell::new(reset))
+    }
+
+    #[inline]
+    fn get(&self) -> B {
+        unsafe { *self.0.get() }
+    }
+
+    #[inline]
+    fn set(&self, bits: B) {
+        unsafe { *self.0.get() = bits };
+    }
+}
+
+/// Write-only register, which keeps a shadow copy of the last written value.
+///
+/// The hardware returns nothing useful on reads of write-only registers, so
+/// they can't be modified in place. A shadowed register tracks the last value
+/// written with [`store_shadowed`](Shadowed::store_shadowed) and
+/// [`modify`](Shadowed::modify) in a static, and `modify` rewrites the shadow
+/// copy with the changed fields.
+///
+/// Both operations apply the [`Reg::RESERVED`] policy. Since the hardware
+/// value can't be read, [`Reserved::Read`] takes the reserved bits from the
+/// shadow copy.
+///
+/// Writes through the regular [`WReg`](crate::reg::WReg) operations bypass
+/// the shadow copy.
+pub trait Shadowed<T: RegTag>: WoReg<T> {
+    /// Returns the shadow storage of the register.
+    fn shadow(&self) -> &'static Shadow<<Self::Val as Bitfield>::Bits>;
+
+    /// Returns the last written value.
+    #[allow(clippy::needless_lifetimes)]
+    #[inline]
+    fn load_shadow<'a>(&'a self) -> <Self as Reg<T>>::Hold<'a> {
+        self.hold(unsafe { Self::val_from(self.shadow().get()) })
+    }
+
+    /// Passes the reset value to the closure `f`, then writes the result of the
+    /// closure into the register memory and the shadow copy.
+    ///
+    /// See also [`modify`](Shadowed::modify).
+    #[inline]
+    fn store_shadowed<'a, F>(&'a self, f: F)
+    where
+        F: for<'b> FnOnce(&'b mut <Self as Reg<T>>::Hold<'a>) -> &'b mut <Self as Reg<T>>::Hold<'a>,
+    {
+        let bits = f(&mut self.default()).val().bits();
+        Interrupts::paused(|| {
+            let bits = self.preserve_shadowed(bits);
+            unsafe { store_raw(Self::ADDRESS, bits) };
+            self.shadow().set(bits);
+        });
+    }
+
+    /// Passes the last written value to the closure `f`, then writes the
+    /// result of the closure into the register memory and the shadow copy.
+    ///
+    /// The operation runs with interrupts disabled, therefore it's safe to use
+    /// on shared tokens.
+    ///
+    /// See also [`store_shadowed`](Shadowed::store_shadowed).
+    #[inline]
+    fn modify<'a, F>(&'a self, f: F)
+    where
+        F: for<'b> FnOnce(&'b mut <Self as Reg<T>>::Hold<'a>) -> &'b mut <Self as Reg<T>>::Hold<'a>,
+    {
+        Interrupts::paused(|| {
+            let bits = self.preserve_shadowed(f(&mut self.load_shadow()).val().bits());
+            unsafe { store_raw(Self::ADDRESS, bits) };
+            self.shadow().set(bits);
+        });
+    }
+
+    /// Sets the reserved bits of `bits` according to [`Reg::RESERVED`], taking
+    /// the read value from the shadow copy.
+    #[inline]
+    fn preserve_shadowed(
+        &self,
+        bits: <Self::Val as Bitfield>::Bits,
+    ) -> <Self::Val as Bitfield>::Bits {
+        match Self::RESERVED {
+            Some((mask, Reserved::Read)) => bits & !mask | self.shadow().get() & mask,
+            _ => self.preserve_reserved(bits),
+        }
+    }
+}
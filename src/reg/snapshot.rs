@@ -0,0 +1,108 @@
+use crate::bitfield::Bitfield;
+use crate::reg::tag::RegTag;
+use crate::reg::{store_raw, RReg, RegHold, Shadowed, WReg};
+
+/// Register, which state can be saved and restored later.
+///
+/// This trait is implemented by [`reg!`](crate::reg!) for all registers,
+/// which are both readable and writable, and for [`Shadowed`] write-only
+/// registers. See also [`snapshot!`](crate::reg::snapshot!) for saving and
+/// restoring several registers at once.
+pub trait RegSnapshot<T: RegTag>: WReg<T> {
+    /// Reads the current value of the register, e.g. before entering a
+    /// low-power mode, which loses the peripheral state. For a [`Shadowed`]
+    /// register this is the shadow copy.
+    ///
+    /// See also [`restore`](RegSnapshot::restore).
+    fn save(&self) -> Self::Val;
+
+    /// Writes a value previously returned by [`save`](RegSnapshot::save) back
+    /// into the register memory.
+    ///
+    /// The value is written as a single store, including the reserved bits as
+    /// they were read. Write-1-to-clear and write-0-to-clear fields are
+    /// written with their no-effect values, so the restore doesn't clear the
+    /// flags, which were set at the time of the save. A [`Shadowed`] register
+    /// updates its shadow copy too.
+    fn restore(&self, saved: Self::Val);
+}
+
+/// Implementation of [`RegSnapshot::restore`] for read-write registers.
+///
+/// `w1c` and `w0c` are the masks of write-1-to-clear and write-0-to-clear
+/// fields.
+#[doc(hidden)]
+#[inline]
+pub fn restore<T, R>(
+    _reg: &R,
+    saved: R::Val,
+    w1c: <R::Val as Bitfield>::Bits,
+    w0c: <R::Val as Bitfield>::Bits,
+) where
+    T: RegTag,
+    R: RReg<T> + WReg<T>,
+{
+    unsafe { store_raw(R::ADDRESS, saved.bits() & !w1c | w0c) };
+}
+
+/// Implementation of [`RegSnapshot::restore`] for [`Shadowed`] registers.
+///
+/// `w1c` and `w0c` are the masks of write-1-to-clear and write-0-to-clear
+/// fields.
+#[doc(hidden)]
+#[inline]
+pub fn restore_shadowed<T, R>(
+    reg: &R,
+    saved: R::Val,
+    w1c: <R::Val as Bitfield>::Bits,
+    w0c: <R::Val as Bitfield>::Bits,
+) where
+    T: RegTag,
+    R: Shadowed<T>,
+{
+    let bits = saved.bits() & !w1c | w0c;
+    reg.store_shadowed(|r| {
+        r.set_val(Bitfield::from_bits(bits));
+        r
+    });
+}
+
+/// Saves or restores the values of a list of register tokens.
+///
+/// `snapshot!(a, b, c)` returns the saved values, and
+/// `snapshot!(restore saved => a, b, c)` writes them back. The registers must
+/// be listed in the same order in both invocations.
+///
+/// # Examples
+///
+/// ```ignore
+/// use drone_core::reg;
+///
+/// let saved = reg::snapshot!(rcc_cfgr, gpioa_moder, gpioa_afrl);
+/// enter_stop_mode();
+/// reg::snapshot!(restore saved => rcc_cfgr, gpioa_moder, gpioa_afrl);
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! reg_snapshot {
+    (restore $saved:expr => $($reg:expr),+ $(,)?) => {
+        $crate::reg::snapshot!(@restore $saved; $($reg),+)
+    };
+    (@restore $saved:expr; $reg:expr $(, $rest:expr)*) => {{
+        let (saved, rest) = $saved;
+        $crate::reg::RegSnapshot::restore(&$reg, saved);
+        $crate::reg::snapshot!(@restore rest; $($rest),*)
+    }};
+    (@restore $saved:expr;) => {{
+        let () = $saved;
+    }};
+    (@save $reg:expr $(, $rest:expr)*) => {
+        ($crate::reg::RegSnapshot::save(&$reg), $crate::reg::snapshot!(@save $($rest),*))
+    };
+    (@save) => {
+        ()
+    };
+    ($($reg:expr),+ $(,)?) => {
+        $crate::reg::snapshot!(@save $($reg),+)
+    };
+}
@@ -1,11 +1,10 @@
 //! Software-implemented synchronization for memory-mapped registers.
 
-use crate::bitfield::Bitfield;
+use crate::bitfield::{Bitfield, Bits};
 use crate::platform::Interrupts;
 use crate::reg::field::{RegFieldBit, RegFieldBits, WWRegField, WWRegFieldBit, WWRegFieldBits};
 use crate::reg::tag::RegAtomic;
-use crate::reg::{RReg, Reg, RegHold, WReg, WRegAtomic};
-use core::ptr::{read_volatile, write_volatile};
+use crate::reg::{load_raw, store_raw, RReg, Reg, RegHold, WReg, WRegAtomic};
 
 /// Software-implemented atomic operations for read-write register.
 pub trait RwRegSoftAtomic<T: RegAtomic>: RReg<T> + WRegAtomic<T> {
@@ -44,6 +43,18 @@ where
     fn modify<F>(&self, f: F)
     where
         F: FnOnce(&mut <Self::Reg as Reg<T>>::Val);
+
+    /// Reads the value from the register memory, then passes the field bits to
+    /// the closure `f`, then replaces the field bits with the result of the
+    /// closure and writes the value back to the register memory. Other bits of
+    /// the register are preserved.
+    ///
+    /// This operation is atomic, it temporarily disables interrupts.
+    fn modify_field<F>(&self, f: F)
+    where
+        F: FnOnce(
+            <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+        ) -> <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits;
 }
 
 /// Software-implemented atomic operations for writable single-bit field of
@@ -94,7 +105,7 @@ where
         F: for<'b> FnOnce(&'b mut <Self as Reg<T>>::Hold<'a>) -> &'b mut <Self as Reg<T>>::Hold<'a>,
     {
         Interrupts::paused(|| unsafe {
-            write_volatile(self.as_mut_ptr(), f(&mut self.load()).val().bits());
+            store_raw(Self::ADDRESS, f(&mut self.load()).val().bits());
         });
     }
 
@@ -123,14 +134,24 @@ where
         F: FnOnce(&mut <Self::Reg as Reg<T>>::Val),
     {
         Interrupts::paused(|| unsafe {
-            let mut val = Self::Reg::val_from(read_volatile(
-                Self::Reg::ADDRESS as *const <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
-            ));
+            let mut val = Self::Reg::val_from(load_raw(Self::Reg::ADDRESS));
             f(&mut val);
-            write_volatile(
-                Self::Reg::ADDRESS as *mut <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
-                val.bits(),
-            );
+            store_raw(Self::Reg::ADDRESS, val.bits());
+        });
+    }
+
+    #[inline]
+    fn modify_field<F>(&self, f: F)
+    where
+        F: FnOnce(
+            <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+        ) -> <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits,
+    {
+        let offset = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::OFFSET);
+        let width = <<Self::Reg as Reg<T>>::Val as Bitfield>::Bits::from_usize(Self::WIDTH);
+        self.modify(|val| unsafe {
+            let bits = f(val.read_bits(offset, width));
+            val.write_bits(offset, width, bits);
         });
     }
 }
@@ -29,6 +29,15 @@ pub trait RegOwned: RegTag {}
 #[marker]
 pub trait RegAtomic: RegTag {}
 
+/// A register token tag, which can be instantiated in `const` contexts.
+///
+/// This trait is used by the `take_const` constructors generated by
+/// [`reg!`](crate::reg!).
+pub trait RegTagConst: RegTag {
+    /// The tag instance.
+    const TAG: Self;
+}
+
 /// The unsynchronized register token tag.
 ///
 /// A token tagged with `Urt` cannot be used concurrently and has
@@ -37,6 +46,9 @@ pub trait RegAtomic: RegTag {}
 pub struct Urt;
 
 impl RegTag for Urt {}
+impl RegTagConst for Urt {
+    const TAG: Self = Urt;
+}
 impl RegOwned for Urt {}
 
 /// The synchronized register token tag.
@@ -46,6 +58,9 @@ impl RegOwned for Urt {}
 pub struct Srt;
 
 impl RegTag for Srt {}
+impl RegTagConst for Srt {
+    const TAG: Self = Srt;
+}
 impl RegOwned for Srt {}
 impl RegAtomic for Srt {}
 
@@ -56,4 +71,7 @@ impl RegAtomic for Srt {}
 pub struct Crt;
 
 impl RegTag for Crt {}
+impl RegTagConst for Crt {
+    const TAG: Self = Crt;
+}
 impl RegAtomic for Crt {}
@@ -0,0 +1,110 @@
+//! Register access tracing.
+//!
+//! With the `reg-trace` feature enabled, every register load and store calls
+//! the hook installed with [`set_hook`], passing the register address, the
+//! value, and the access direction. This makes it possible to capture a full
+//! register access log from a live target.
+//!
+//! [`to_stream`] is a ready-made hook, which routes the accesses to a Drone
+//! Stream. Each access is written as a single transaction, which starts with
+//! a tag byte ([`READ_TAG`] or [`WRITE_TAG`]), followed by the big-endian
+//! 32-bit address, and the big-endian 64-bit value.
+//!
+//! # Examples
+//!
+//! ```
+//! use drone_core::reg::trace;
+//!
+//! // Route register accesses to the stream number 3.
+//! trace::set_hook(trace::to_stream::<3>);
+//! ```
+
+use crate::bitfield::Bits;
+use crate::stream::Stream;
+use core::{mem, ptr};
+
+#[cfg(any(feature = "atomics", loom))]
+type AtomicPtr = core::sync::atomic::AtomicPtr<()>;
+#[cfg(not(any(feature = "atomics", loom)))]
+type AtomicPtr = crate::sync::soft_atomic::Atomic<*mut ()>;
+
+/// Tag of a register read event.
+pub const READ_TAG: u8 = 0x10;
+
+/// Tag of a register write event.
+pub const WRITE_TAG: u8 = 0x11;
+
+/// Register access direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    /// The register was read.
+    Read,
+    /// The register was written.
+    Write,
+}
+
+/// A register access tracing hook.
+///
+/// The arguments are the register address, the value read or written, and
+/// the access direction. The hook must not access registers itself.
+pub type Hook = fn(usize, u64, Access);
+
+static HOOK: AtomicPtr = AtomicPtr::new(ptr::null_mut());
+
+/// Installs `hook` to be called on every register access, replacing the
+/// previous hook.
+#[inline]
+pub fn set_hook(hook: Hook) {
+    store_atomic!(HOOK, hook as *mut (), Release);
+}
+
+/// Removes the installed hook.
+#[inline]
+pub fn clear_hook() {
+    store_atomic!(HOOK, ptr::null_mut(), Release);
+}
+
+/// A hook, which writes register accesses to the stream `STREAM`.
+///
+/// See [the module level documentation](self) for the format.
+pub fn to_stream<const STREAM: u8>(address: usize, value: u64, access: Access) {
+    let stream = Stream::new(STREAM);
+    if stream.is_enabled() {
+        stream.write_transaction(&encode(address, value, access));
+    }
+}
+
+#[inline]
+pub(crate) fn emit<B: Bits>(address: usize, bits: B, access: Access) {
+    let hook = load_atomic!(HOOK, Acquire);
+    if !hook.is_null() {
+        let hook = unsafe { mem::transmute::<*mut (), Hook>(hook) };
+        hook(address, super::bits_to_u64(bits), access);
+    }
+}
+
+fn encode(address: usize, value: u64, access: Access) -> [u8; 13] {
+    let mut bytes = [0; 13];
+    bytes[0] = match access {
+        Access::Read => READ_TAG,
+        Access::Write => WRITE_TAG,
+    };
+    bytes[1..5].copy_from_slice(&(address as u32).to_be_bytes());
+    bytes[5..].copy_from_slice(&value.to_be_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_event() {
+        assert_eq!(encode(0x4002_1000, 0x0300_0083, Access::Read), [
+            0x10, 0x40, 0x02, 0x10, 0x00, 0, 0, 0, 0, 0x03, 0x00, 0x00, 0x83
+        ]);
+        assert_eq!(encode(0x4000_0004, 1, Access::Write), [
+            0x11, 0x40, 0x00, 0x00, 0x04, 0, 0, 0, 0, 0, 0, 0, 1
+        ]);
+    }
+}
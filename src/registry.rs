@@ -0,0 +1,259 @@
+//! Runtime driver registry.
+//!
+//! Drivers are resolved at compile-time with [`token`](crate::token)s and
+//! [`inventory`](crate::inventory) states, which leaves no way to enumerate
+//! them at run-time. A [`Registry`] is a static intrusive list of [`Entry`]s,
+//! which drivers add themselves to at initialization. Each entry records the
+//! driver name, its version, and an erased [`Control`] handle. Application or
+//! diagnostic code can then enumerate the registered drivers, e.g. to power
+//! everything down before entering a low-power mode, or to report the device
+//! composition over a stream.
+//!
+//! The registry doesn't allocate, and entries can't be removed.
+//!
+//! # Examples
+//!
+//! ```
+//! use core::sync::atomic::{AtomicBool, Ordering};
+//! use drone_core::registry::{Control, Entry, Registry};
+//!
+//! static DRIVERS: Registry = Registry::new();
+//!
+//! struct UartControl {
+//!     enabled: AtomicBool,
+//! }
+//!
+//! impl Control for UartControl {
+//!     fn power_down(&self) {
+//!         self.enabled.store(false, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! static UART_CONTROL: UartControl = UartControl { enabled: AtomicBool::new(true) };
+//! static UART_ENTRY: Entry = Entry::new("uart1", "0.2.0", &UART_CONTROL);
+//!
+//! // At the driver initialization.
+//! DRIVERS.register(&UART_ENTRY);
+//!
+//! // Before entering a low-power mode.
+//! for entry in DRIVERS.iter() {
+//!     entry.control().power_down();
+//! }
+//! assert!(!UART_CONTROL.enabled.load(Ordering::Relaxed));
+//! ```
+
+use core::fmt;
+use core::ptr;
+
+#[cfg(all(feature = "atomics", not(loom)))]
+type AtomicPtr = core::sync::atomic::AtomicPtr<Entry>;
+#[cfg(all(feature = "atomics", loom))]
+type AtomicPtr = loom::sync::atomic::AtomicPtr<Entry>;
+#[cfg(not(feature = "atomics"))]
+type AtomicPtr = crate::sync::soft_atomic::Atomic<*mut Entry>;
+
+#[cfg(all(feature = "atomics", not(loom)))]
+type AtomicBool = core::sync::atomic::AtomicBool;
+#[cfg(all(feature = "atomics", loom))]
+type AtomicBool = loom::sync::atomic::AtomicBool;
+#[cfg(not(feature = "atomics"))]
+type AtomicBool = crate::sync::soft_atomic::Atomic<bool>;
+
+/// A run-time registry of drivers.
+///
+/// See [the module level documentation](self) for details.
+pub struct Registry {
+    head: AtomicPtr,
+}
+
+/// A registered driver record.
+///
+/// See [the module level documentation](self) for details.
+pub struct Entry {
+    name: &'static str,
+    version: &'static str,
+    control: &'static dyn Control,
+    registered: AtomicBool,
+    next: AtomicPtr,
+}
+
+/// An erased control handle of a registered driver.
+///
+/// All methods have no-op default implementations, a driver overrides only
+/// the supported operations.
+pub trait Control: Sync {
+    /// Powers the driver down.
+    fn power_down(&self) {}
+
+    /// Powers the driver up.
+    fn power_up(&self) {}
+}
+
+/// An iterator over the entries of [`Registry`].
+///
+/// The entries are yielded in the reverse order of registration.
+pub struct Iter<'a> {
+    next: *const Entry,
+    _registry: &'a Registry,
+}
+
+unsafe impl Sync for Registry {}
+unsafe impl Send for Registry {}
+
+unsafe impl Sync for Entry {}
+
+impl Registry {
+    maybe_const_fn! {
+        /// Creates an empty registry.
+        #[inline]
+        pub const fn new() -> Self {
+            Self { head: AtomicPtr::new(ptr::null_mut()) }
+        }
+    }
+
+    /// Adds `entry` to the registry.
+    ///
+    /// Returns `false` if `entry` is already registered in this or another
+    /// registry, in which case the registry is not changed.
+    pub fn register(&self, entry: &'static Entry) -> bool {
+        if swap_atomic!(entry.registered, true, Relaxed) {
+            return false;
+        }
+        let entry_ptr: *const Entry = entry;
+        let entry_ptr = entry_ptr.cast_mut();
+        load_modify_atomic!(self.head, Relaxed, Release, |head| {
+            store_atomic!(entry.next, head, Relaxed);
+            entry_ptr
+        });
+        true
+    }
+
+    /// Returns `true` if there are no registered entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        load_atomic!(self.head, Relaxed).is_null()
+    }
+
+    /// Returns an iterator over the registered entries.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { next: load_atomic!(self.head, Acquire), _registry: self }
+    }
+
+    /// Writes the list of registered entries into the `stream`, one entry per
+    /// line.
+    ///
+    /// The output looks like the following:
+    ///
+    /// ```text
+    /// uart1 0.2.0
+    /// dma1 0.1.0
+    /// ```
+    #[inline(never)]
+    pub fn report(&self, stream: u8) {
+        for entry in self.iter() {
+            crate::stream::write_fmt(stream, format_args!("{entry}\n"));
+        }
+    }
+}
+
+impl Default for Registry {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Entry {
+    maybe_const_fn! {
+        /// Creates a new unregistered entry.
+        #[inline]
+        pub const fn new(
+            name: &'static str,
+            version: &'static str,
+            control: &'static dyn Control,
+        ) -> Self {
+            Self {
+                name,
+                version,
+                control,
+                registered: AtomicBool::new(false),
+                next: AtomicPtr::new(ptr::null_mut()),
+            }
+        }
+    }
+
+    /// Returns the driver name.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the driver version.
+    #[inline]
+    pub fn version(&self) -> &'static str {
+        self.version
+    }
+
+    /// Returns the driver control handle.
+    #[inline]
+    pub fn control(&self) -> &'static dyn Control {
+        self.control
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.version)
+    }
+}
+
+impl fmt::Debug for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Entry").field("name", &self.name).field("version", &self.version).finish()
+    }
+}
+
+impl Iterator for Iter<'_> {
+    type Item = &'static Entry;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = unsafe { self.next.as_ref() }?;
+        self.next = load_atomic!(entry.next, Relaxed);
+        Some(entry)
+    }
+}
+
+impl<'a> IntoIterator for &'a Registry {
+    type IntoIter = Iter<'a>;
+    type Item = &'static Entry;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Noop;
+
+    impl Control for Noop {}
+
+    #[test]
+    fn register_iter() {
+        static FIRST: Entry = Entry::new("first", "1.0.0", &Noop);
+        static SECOND: Entry = Entry::new("second", "0.1.0", &Noop);
+        let registry = Registry::new();
+        assert!(registry.is_empty());
+        assert!(registry.register(&FIRST));
+        assert!(registry.register(&SECOND));
+        assert!(!registry.register(&FIRST));
+        let names = registry.iter().map(Entry::name).collect::<Vec<_>>();
+        assert_eq!(names, ["second", "first"]);
+        assert_eq!(format!("{FIRST}"), "first 1.0.0");
+    }
+}
@@ -2,18 +2,68 @@
 //!
 //! This module implements standard output/error interface, which mimics Rust's
 //! standard library.
+//!
+//! Output written from different threads may be interleaved at the transaction
+//! granularity. To keep multi-line messages coherent, a thread can accumulate
+//! its output in a [`StagingBuffer`] placed into its thread-local storage.
+//!
+//! By default all streams share one ring buffer, which size is configured in
+//! `layout.toml`. A stream can be given a dedicated ring buffer of its own
+//! capacity with the `streams` key of [`stream!`](crate::stream!):
+//!
+//! ```ignore
+//! stream! {
+//!     layout => core0;
+//!     metadata => pub Stream;
+//!     instance => pub STREAM;
+//!     global => true;
+//!     // Stream number => buffer size in bytes.
+//!     streams => {
+//!         1 => 64;
+//!         11 => 4096;
+//!     };
+//! }
+//! ```
+//!
+//! Dedicated buffers require `global => true`. A dedicated buffer has the same
+//! layout as the shared one: a runtime structure, which holds the buffer size,
+//! followed by the ring buffer. A debug probe finds the dedicated buffers
+//! through the `DRONE_STREAM_DEDICATED` symbol, which is a [`DedicatedTable`]:
+//! a `u32` number of entries followed by that many [`DedicatedDescriptor`]s.
+//!
+//! Whether a debug probe is attached can be estimated at run-time with
+//! [`is_probe_connected`]. Before a reset, [`wait_drained`] gives the probe a
+//! chance to read the buffered data.
+//!
+//! Thread scheduling can be traced into a stream with the compact binary
+//! format defined in [`trace`].
+//!
+//! Next to the global runtime structure, the `.stream_rt` section holds the
+//! [heap trace filters](crate::heap::TraceFilter), one per stream. A debug
+//! probe can set them at the bootstrap by appending
+//! [`HEAP_TRACE_FILTERS_SEQUENCE`] followed by the filter bytes after the
+//! global runtime structure, or later through the `stream_heap_trace_filters`
+//! symbol. Without the sequence, e.g. with a probe unaware of the filters, or
+//! if the filters don't fit into the buffer, the filters are zeroed.
 
 #![cfg_attr(feature = "host", allow(unused_imports, dead_code, unreachable_code, unused_variables))]
 
+pub mod trace;
+
 mod macros;
+mod probe;
 mod runtime;
+mod staging;
 
 use self::runtime::{LocalGlobalRuntime, LocalRuntime};
-use crate::platform::stream_rt;
+use crate::heap::TraceFilter;
+use crate::platform::stream_rt_for;
 use core::cell::SyncUnsafeCell;
 use core::fmt::Write;
 use core::mem::size_of;
-use core::{fmt, mem, ptr};
+use core::{fmt, mem, ptr, slice};
+pub use self::probe::{is_probe_connected, wait_drained};
+pub use self::staging::StagingBuffer;
 pub use drone_stream::STREAM_COUNT;
 use drone_stream::{GlobalRuntime, Runtime, BOOTSTRAP_SEQUENCE, BOOTSTRAP_SEQUENCE_LENGTH};
 
@@ -21,6 +71,17 @@ use drone_stream::{GlobalRuntime, Runtime, BOOTSTRAP_SEQUENCE, BOOTSTRAP_SEQUENC
 #[no_mangle]
 static GLOBAL_RT: SyncUnsafeCell<GlobalRuntime> = SyncUnsafeCell::new(GlobalRuntime::zeroed());
 
+/// Filters of the heap trace streams, indexed by the stream number.
+#[link_section = ".stream_rt"]
+#[export_name = "stream_heap_trace_filters"]
+pub(crate) static HEAP_TRACE_FILTERS: SyncUnsafeCell<[TraceFilter; STREAM_COUNT as usize]> =
+    SyncUnsafeCell::new([TraceFilter::ZEROED; STREAM_COUNT as usize]);
+
+/// Sequence marking the heap trace filters in the bootstrap data.
+///
+/// See [the module-level documentation](self) for details.
+pub const HEAP_TRACE_FILTERS_SEQUENCE: [u8; 4] = *b"DHTF";
+
 /// Stream number of the standard output.
 pub const STDOUT_STREAM: u8 = 0;
 
@@ -31,6 +92,67 @@ pub const STDERR_STREAM: u8 = 1;
 #[derive(Clone, Copy)]
 pub struct Stream(u8);
 
+/// Dedicated ring buffer for a single stream.
+///
+/// Created by the `streams` key of [`stream!`](crate::stream!).
+#[doc(hidden)]
+#[repr(C)]
+pub struct DedicatedRuntime<const N: usize> {
+    /// Drone Stream runtime structure.
+    pub runtime: Runtime,
+    buffer: [u8; N],
+}
+
+/// Descriptor of a dedicated stream buffer.
+///
+/// The layout of this structure is a part of the debug probe protocol.
+#[doc(hidden)]
+#[repr(C)]
+pub struct DedicatedDescriptor {
+    /// Stream number.
+    pub stream: u8,
+    /// Size of the ring buffer in bytes.
+    pub buffer_size: u32,
+    /// Pointer to the runtime structure, followed by the ring buffer.
+    pub runtime: *mut Runtime,
+}
+
+/// Table of dedicated stream buffers.
+///
+/// Exported by [`stream!`](crate::stream!) with `global => true` under the
+/// `DRONE_STREAM_DEDICATED` symbol, so that a debug probe can learn the
+/// location and size of each dedicated buffer. The layout of this structure is
+/// a part of the debug probe protocol.
+#[doc(hidden)]
+#[repr(C)]
+pub struct DedicatedTable<const N: usize> {
+    /// Number of descriptors.
+    pub count: u32,
+    /// Descriptors of the dedicated buffers.
+    pub descriptors: [DedicatedDescriptor; N],
+}
+
+unsafe impl Sync for DedicatedDescriptor {}
+
+impl<const N: usize> DedicatedTable<N> {
+    /// Creates a new table from `descriptors`.
+    #[must_use]
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn new(descriptors: [DedicatedDescriptor; N]) -> Self {
+        Self { count: N as u32, descriptors }
+    }
+}
+
+impl<const N: usize> DedicatedRuntime<N> {
+    /// Creates a new zeroed dedicated runtime.
+    #[must_use]
+    #[inline]
+    pub const fn zeroed() -> Self {
+        Self { runtime: Runtime::zeroed(), buffer: [0; N] }
+    }
+}
+
 #[doc(hidden)]
 #[inline(never)]
 pub unsafe fn init(rt: *mut Runtime, buffer_size: u32, init_global: bool) {
@@ -59,12 +181,33 @@ pub unsafe fn init(rt: *mut Runtime, buffer_size: u32, init_global: bool) {
                     GLOBAL_RT.get().cast(),
                     mem::size_of::<GlobalRuntime>(),
                 );
+                buffer = buffer.add(mem::size_of::<GlobalRuntime>());
+                let marker = HEAP_TRACE_FILTERS_SEQUENCE.len();
+                let filters = mem::size_of::<[TraceFilter; STREAM_COUNT as usize]>();
+                let end = buffer.offset_from(rt.add(1).cast::<u8>()) as usize + marker + filters;
+                if end <= buffer_size as usize
+                    && slice::from_raw_parts(buffer, marker) == HEAP_TRACE_FILTERS_SEQUENCE
+                {
+                    ptr::copy_nonoverlapping(
+                        buffer.add(marker),
+                        HEAP_TRACE_FILTERS.get().cast(),
+                        filters,
+                    );
+                } else {
+                    ptr::write_bytes(HEAP_TRACE_FILTERS.get().cast::<u8>(), 0, filters);
+                }
             }
             // Invalidate the bootstrap sequence.
             *rt.add(1).cast::<u8>() = 0;
+            probe::set_bootstrapped();
         } else {
             if init_global {
                 ptr::write_bytes(GLOBAL_RT.get().cast::<u8>(), 0, size_of::<GlobalRuntime>());
+                ptr::write_bytes(
+                    HEAP_TRACE_FILTERS.get().cast::<u8>(),
+                    0,
+                    size_of::<[TraceFilter; STREAM_COUNT as usize]>(),
+                );
             }
             *rt = Runtime { buffer_size, read_cursor: 0, write_cursor: 0 };
         }
@@ -158,7 +301,7 @@ impl Stream {
     #[inline]
     pub fn write_bytes(self, bytes: &[u8]) -> Self {
         let Self(stream) = self;
-        unsafe { (*stream_rt()).write_bytes(stream, bytes.as_ptr(), bytes.len()) };
+        unsafe { (*stream_rt_for(stream)).write_bytes(stream, bytes.as_ptr(), bytes.len()) };
         self
     }
 
@@ -172,7 +315,7 @@ impl Stream {
     pub fn write_transaction(self, bytes: &[u8]) -> Self {
         let Self(stream) = self;
         let length = bytes.len().try_into().expect("maximum transaction length exceeded");
-        unsafe { (*stream_rt()).write_transaction(stream, bytes.as_ptr(), length) };
+        unsafe { (*stream_rt_for(stream)).write_transaction(stream, bytes.as_ptr(), length) };
         self
     }
 
@@ -196,7 +339,7 @@ impl Write for Stream {
 }
 
 mod sealed {
-    use super::{stream_rt, LocalRuntime};
+    use super::{stream_rt_for, LocalRuntime};
 
     pub trait StreamWrite: Copy {
         fn stream_write(stream: u8, value: Self);
@@ -209,7 +352,8 @@ mod sealed {
                 fn stream_write(stream: u8, value: Self) {
                     let bytes = value.to_ne_bytes();
                     unsafe {
-                        (*stream_rt()).write_transaction(stream, bytes.as_ptr(), bytes.len() as u8);
+                        (*stream_rt_for(stream))
+                            .write_transaction(stream, bytes.as_ptr(), bytes.len() as u8);
                     }
                 }
             }
@@ -0,0 +1,82 @@
+#![cfg_attr(feature = "host", allow(unused_imports, dead_code, unreachable_code))]
+
+use super::STREAM_COUNT;
+use crate::platform::{stream_rt, stream_rt_for};
+use core::ptr;
+use drone_stream::Runtime;
+
+#[cfg(feature = "atomics")]
+type AtomicBool = core::sync::atomic::AtomicBool;
+#[cfg(not(feature = "atomics"))]
+type AtomicBool = crate::sync::soft_atomic::Atomic<bool>;
+
+#[cfg(feature = "atomics")]
+type AtomicU32 = core::sync::atomic::AtomicU32;
+#[cfg(not(feature = "atomics"))]
+type AtomicU32 = crate::sync::soft_atomic::Atomic<u32>;
+
+/// Set when a debug probe has completed the bootstrap handshake.
+static BOOTSTRAPPED: AtomicBool = AtomicBool::new(false);
+
+/// The read cursor observed by the previous [`is_probe_connected`] call.
+static LAST_READ_CURSOR: AtomicU32 = AtomicU32::new(0);
+
+pub(super) fn set_bootstrapped() {
+    store_atomic!(BOOTSTRAPPED, true, Relaxed);
+}
+
+/// Returns `true` if a debug probe appears to be reading the streams.
+///
+/// The result is derived from two signals:
+///
+/// * whether the probe has completed the bootstrap handshake at startup;
+/// * whether the read cursor of the shared ring buffer has moved since the
+///   previous call to this function.
+///
+/// If the probe has bootstrapped the streams, it is considered connected
+/// unless there is unread data and the read cursor has stalled. Otherwise it
+/// is considered connected only if the read cursor has moved, which detects a
+/// probe attached after startup.
+///
+/// This is a heuristic, and it shouldn't be called more often than the probe
+/// polls the buffer. Firmware can use it to choose cheaper code paths, or to
+/// keep critical messages buffered until a probe is attached.
+#[inline]
+pub fn is_probe_connected() -> bool {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        let rt = stream_rt();
+        let read_cursor = ptr::addr_of!((*rt).read_cursor).read_volatile();
+        let write_cursor = ptr::addr_of!((*rt).write_cursor).read_volatile();
+        let moved = swap_atomic!(LAST_READ_CURSOR, read_cursor, Relaxed) != read_cursor;
+        if load_atomic!(BOOTSTRAPPED, Relaxed) {
+            moved || read_cursor == write_cursor
+        } else {
+            moved
+        }
+    }
+}
+
+/// Waits until a debug probe reads all the data written into the streams.
+///
+/// Checks the shared ring buffer and all dedicated buffers up to `spins`
+/// times. Returns `true` if all buffers are drained, or `false` if the limit
+/// is reached, e.g. because no probe is attached.
+///
+/// This is useful before a reset or a jump to another firmware, which would
+/// otherwise lose the buffered data.
+pub fn wait_drained(spins: u32) -> bool {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    (0..spins).any(|_| (0..STREAM_COUNT).all(|stream| unsafe { is_drained(stream_rt_for(stream)) }))
+}
+
+unsafe fn is_drained(rt: *mut Runtime) -> bool {
+    unsafe {
+        ptr::addr_of!((*rt).read_cursor).read_volatile()
+            == ptr::addr_of!((*rt).write_cursor).read_volatile()
+    }
+}
@@ -0,0 +1,158 @@
+use super::{Stream, STREAM_COUNT};
+use core::cell::{Cell, UnsafeCell};
+use core::fmt;
+
+/// Maximum number of bytes emitted in a single stream transaction.
+const MAX_TRANSACTION_LENGTH: usize = u8::MAX as usize;
+
+/// Per-thread staging buffer for a stream.
+///
+/// Bytes written to the buffer are accumulated until a newline is encountered,
+/// the buffer is full, or [`StagingBuffer::flush`] is called explicitly. Then
+/// the accumulated bytes are emitted as transactions of up to 255 bytes, so
+/// that lines written from different threads don't get interleaved.
+///
+/// The buffer is not `Sync`, therefore it is intended to be placed into the
+/// thread-local storage of `thr::pool!`:
+///
+/// ```
+/// # fn main() {}
+/// use core::fmt::Write;
+/// use drone_core::stream::{StagingBuffer, STDOUT_STREAM};
+/// use drone_core::thr;
+/// use drone_core::thr::Thread;
+///
+/// thr::pool! {
+///     thread => pub Thr {};
+///     local => pub ThrLocal {
+///         pub stdout: StagingBuffer<128> = StagingBuffer::new(STDOUT_STREAM);
+///     };
+///     index => pub Thrs;
+///     threads => { pub thread1 };
+/// }
+///
+/// fn handler() {
+///     let mut stdout = &Thr::local().stdout;
+///     if stdout.stream().is_enabled() {
+///         writeln!(stdout, "a = {}", 1).ok();
+///         writeln!(stdout, "b = {}", 2).ok();
+///     }
+/// }
+/// ```
+pub struct StagingBuffer<const N: usize> {
+    stream: u8,
+    length: Cell<usize>,
+    buffer: UnsafeCell<[u8; N]>,
+}
+
+impl<const N: usize> StagingBuffer<N> {
+    /// Creates a new empty staging buffer for the stream number `stream`.
+    ///
+    /// # Panics
+    ///
+    /// If `stream` is more than or equal to [`STREAM_COUNT`], or `N` is zero.
+    #[inline]
+    pub const fn new(stream: u8) -> Self {
+        assert!(stream < STREAM_COUNT);
+        assert!(N > 0);
+        Self { stream, length: Cell::new(0), buffer: UnsafeCell::new([0; N]) }
+    }
+
+    /// Returns the stream handle this buffer writes to.
+    #[inline]
+    pub fn stream(&self) -> Stream {
+        Stream(self.stream)
+    }
+
+    /// Returns the number of bytes waiting to be flushed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.length.get()
+    }
+
+    /// Returns `true` if there are no bytes waiting to be flushed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.length.get() == 0
+    }
+
+    /// Appends `bytes` to the buffer.
+    ///
+    /// The buffer is flushed each time a newline is appended or the buffer
+    /// becomes full.
+    #[inline]
+    pub fn write_bytes(&self, bytes: &[u8]) {
+        self.stage(bytes, |chunk| {
+            self.stream().write_transaction(chunk);
+        });
+    }
+
+    /// Emits all accumulated bytes to the stream.
+    #[inline]
+    pub fn flush(&self) {
+        self.drain(|chunk| {
+            self.stream().write_transaction(chunk);
+        });
+    }
+
+    fn stage(&self, mut bytes: &[u8], mut emit: impl FnMut(&[u8])) {
+        while !bytes.is_empty() {
+            let length = self.length.get();
+            let mut count = bytes.len().min(N - length);
+            let newline = bytes[..count].iter().rposition(|&byte| byte == b'\n');
+            if let Some(position) = newline {
+                count = position + 1;
+            }
+            let (head, tail) = bytes.split_at(count);
+            unsafe { (*self.buffer.get())[length..length + count].copy_from_slice(head) };
+            self.length.set(length + count);
+            if newline.is_some() || length + count == N {
+                self.drain(&mut emit);
+            }
+            bytes = tail;
+        }
+    }
+
+    fn drain(&self, mut emit: impl FnMut(&[u8])) {
+        let length = self.length.replace(0);
+        let buffer = unsafe { &(*self.buffer.get())[..length] };
+        for chunk in buffer.chunks(MAX_TRANSACTION_LENGTH) {
+            emit(chunk);
+        }
+    }
+}
+
+impl<const N: usize> fmt::Write for &StagingBuffer<N> {
+    #[inline]
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.write_bytes(string.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage<const N: usize>(buffer: &StagingBuffer<N>, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut chunks = Vec::new();
+        buffer.stage(bytes, |chunk| chunks.push(chunk.to_vec()));
+        chunks
+    }
+
+    #[test]
+    fn flush_on_newline() {
+        let buffer = StagingBuffer::<16>::new(0);
+        assert!(stage(&buffer, b"hello ").is_empty());
+        assert_eq!(buffer.len(), 6);
+        assert_eq!(stage(&buffer, b"world\nfoo"), vec![b"hello world\n".to_vec()]);
+        assert_eq!(buffer.len(), 3);
+    }
+
+    #[test]
+    fn flush_on_full() {
+        let buffer = StagingBuffer::<4>::new(0);
+        assert_eq!(stage(&buffer, b"abcdefghij"), vec![b"abcd".to_vec(), b"efgh".to_vec()]);
+        assert_eq!(buffer.len(), 2);
+    }
+}
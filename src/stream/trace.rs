@@ -0,0 +1,102 @@
+//! Scheduler trace events.
+//!
+//! Each event is written to a stream as a single transaction, which starts
+//! with a tag byte, followed by the big-endian 16-bit thread index, and
+//! optionally by a tag-specific payload:
+//!
+//! | Tag                     | Payload             | Emitted by                     |
+//! |-------------------------|---------------------|--------------------------------|
+//! | [`THREAD_BEGIN_TAG`]    | -                   | [`Thread::call`]               |
+//! | [`THREAD_END_TAG`]      | -                   | [`Thread::call`]               |
+//! | [`FIBERS_RESUME_TAG`]   | -                   | [`Thread::resume`]             |
+//! | [`PEND_TAG`]            | -                   | platform crate                 |
+//! | [`PRIORITY_TAG`]        | 8-bit priority      | platform crate                 |
+//!
+//! The thread events are emitted for a thread pool with
+//! `enable_trace_stream` key in [`thr::pool!`](crate::thr::pool!). The rest of
+//! the events are specific to interrupt controllers, and should be emitted by
+//! platform crates with the functions of this module.
+//!
+//! [`Thread::call`]: crate::thr::Thread::call
+//! [`Thread::resume`]: crate::thr::Thread::resume
+
+use super::Stream;
+
+/// Tag of an event emitted when a thread starts running.
+pub const THREAD_BEGIN_TAG: u8 = 0;
+
+/// Tag of an event emitted when a thread stops running.
+pub const THREAD_END_TAG: u8 = 1;
+
+/// Tag of an event emitted when a thread resumes its fiber chain.
+pub const FIBERS_RESUME_TAG: u8 = 2;
+
+/// Tag of an event emitted when a thread is pended.
+pub const PEND_TAG: u8 = 3;
+
+/// Tag of an event emitted when a thread priority is changed.
+pub const PRIORITY_TAG: u8 = 4;
+
+/// Emits a [`THREAD_BEGIN_TAG`] event.
+#[inline(always)]
+pub fn thread_begin(trace_stream: u8, thr_idx: u16) {
+    emit(trace_stream, THREAD_BEGIN_TAG, thr_idx);
+}
+
+/// Emits a [`THREAD_END_TAG`] event.
+#[inline(always)]
+pub fn thread_end(trace_stream: u8, thr_idx: u16) {
+    emit(trace_stream, THREAD_END_TAG, thr_idx);
+}
+
+/// Emits a [`FIBERS_RESUME_TAG`] event.
+#[inline(always)]
+pub fn fibers_resume(trace_stream: u8, thr_idx: u16) {
+    emit(trace_stream, FIBERS_RESUME_TAG, thr_idx);
+}
+
+/// Emits a [`PEND_TAG`] event.
+#[inline(always)]
+pub fn pend(trace_stream: u8, thr_idx: u16) {
+    emit(trace_stream, PEND_TAG, thr_idx);
+}
+
+/// Emits a [`PRIORITY_TAG`] event.
+#[inline(always)]
+pub fn priority(trace_stream: u8, thr_idx: u16, priority: u8) {
+    #[inline(never)]
+    fn trace(trace_stream: u8, thr_idx: u16, priority: u8) {
+        let [hi, lo] = thr_idx.to_be_bytes();
+        Stream::new(trace_stream).write_transaction(&[PRIORITY_TAG, hi, lo, priority]);
+    }
+    if Stream::new(trace_stream).is_enabled() {
+        trace(trace_stream, thr_idx, priority);
+    }
+}
+
+#[inline(always)]
+fn emit(trace_stream: u8, tag: u8, thr_idx: u16) {
+    #[inline(never)]
+    fn trace(trace_stream: u8, tag: u8, thr_idx: u16) {
+        Stream::new(trace_stream).write_transaction(&encode(tag, thr_idx));
+    }
+    if Stream::new(trace_stream).is_enabled() {
+        trace(trace_stream, tag, thr_idx);
+    }
+}
+
+fn encode(tag: u8, thr_idx: u16) -> [u8; 3] {
+    let [hi, lo] = thr_idx.to_be_bytes();
+    [tag, hi, lo]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_event() {
+        assert_eq!(encode(THREAD_BEGIN_TAG, 0x0102), [0, 1, 2]);
+        assert_eq!(encode(PEND_TAG, 7), [3, 0, 7]);
+    }
+}
@@ -0,0 +1,120 @@
+//! Supervisor calls.
+//!
+//! A supervisor call is a request from unprivileged code to run a *service* in
+//! the privileged mode. The request is delivered through a platform-specific
+//! trap, e.g. the `SVC` instruction on ARM Cortex-M, or the `ECALL`
+//! instruction on RISC-V. This module is platform-agnostic: it provides the
+//! service registration and the typed calls, while a platform crate provides
+//! only the trap mechanism through the following functions:
+//!
+//! ```ignore
+//! extern "C" {
+//!     // Provided by the platform crate: traps into the privileged mode and
+//!     // calls `drone_sv_handler` with the same arguments.
+//!     fn drone_sv_call(number: u8, service: *mut u8);
+//!     // Generated by `sv!`: dispatches the call to the service.
+//!     fn drone_sv_handler(number: u8, service: *mut u8);
+//! }
+//! ```
+//!
+//! # Usage
+//!
+//! ```
+//! # fn main() {}
+//! use drone_core::sv;
+//! use drone_core::sv::{SvCall, SvService};
+//!
+//! /// A service to switch the stack pointer.
+//! pub struct SwitchStackService {
+//!     pub stack_ptr: *mut u8,
+//! }
+//!
+//! unsafe impl Send for SwitchStackService {}
+//!
+//! impl SvService for SwitchStackService {
+//!     unsafe fn handler(&mut self) {
+//!         // Executed in the privileged mode.
+//!     }
+//! }
+//!
+//! sv! {
+//!     /// The supervisor.
+//!     supervisor => pub Sv;
+//!     // The list of services. The position in the list is the service number.
+//!     services => {
+//!         SwitchStackService;
+//!     };
+//! }
+//!
+//! fn switch_stack(stack_ptr: *mut u8) {
+//!     // Executed in the unprivileged mode.
+//!     unsafe { Sv::call(&mut SwitchStackService { stack_ptr }) };
+//! }
+//! ```
+//!
+//! Only one supervisor can be defined in a program, because `sv!` defines the
+//! global `drone_sv_handler` function.
+
+#![cfg_attr(feature = "host", allow(dead_code, unreachable_code, unused_variables))]
+
+extern "C" {
+    fn drone_sv_call(number: u8, service: *mut u8);
+}
+
+/// The supervisor.
+///
+/// Normally this trait is implemented by [`sv!`](crate::sv!) macro.
+pub trait Supervisor: Sized + 'static {
+    /// Runs the service number `number` with `service` as the argument.
+    ///
+    /// This function is called by the platform trap handler in the privileged
+    /// mode.
+    ///
+    /// # Safety
+    ///
+    /// `service` must point to a valid value of the service type registered
+    /// under `number`.
+    ///
+    /// # Panics
+    ///
+    /// If no service is registered under `number`.
+    unsafe fn handle(number: u8, service: *mut u8);
+}
+
+/// A supervisor service.
+pub trait SvService: Sized + Send + 'static {
+    /// Runs the service in the privileged mode.
+    ///
+    /// # Safety
+    ///
+    /// Must be called only by the supervisor.
+    unsafe fn handler(&mut self);
+}
+
+/// A supervisor, which can call the service `T`.
+///
+/// Normally this trait is implemented by [`sv!`](crate::sv!) macro.
+pub trait SvCall<T: SvService>: Supervisor {
+    /// Calls the service `service` in the privileged mode.
+    ///
+    /// # Safety
+    ///
+    /// Safety is defined by the service.
+    unsafe fn call(service: &mut T);
+}
+
+/// Triggers the supervisor trap for the service number `number` with `service`
+/// as the argument.
+///
+/// # Safety
+///
+/// `service` must have the type registered under `number`.
+#[inline]
+pub unsafe fn sv_call<T: SvService>(service: &mut T, number: u8) {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        drone_sv_call(number, (service as *mut T).cast());
+    }
+}
@@ -0,0 +1,209 @@
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+
+#[cfg(all(feature = "atomics", not(loom)))]
+type State = core::sync::atomic::AtomicU8;
+#[cfg(all(feature = "atomics", loom))]
+type State = loom::sync::atomic::AtomicU8;
+#[cfg(not(feature = "atomics"))]
+type State = crate::sync::soft_atomic::Atomic<u8>;
+
+/// The index of the buffer owned by the hardware.
+const FILL_MASK: u8 = 1 << 0;
+/// The buffer not owned by the hardware contains completed data.
+const READY_MASK: u8 = 1 << 1;
+/// The buffer not owned by the hardware is borrowed by the software.
+const BUSY_MASK: u8 = 1 << 2;
+
+/// A double buffer (ping-pong buffer).
+///
+/// One of the buffers is owned by the hardware, e.g. a DMA channel, which fills
+/// it, while the software processes the other one. When the hardware signals
+/// completion, [`DoubleBuffer::complete`] swaps the buffers, and the completed
+/// buffer becomes available to [`DoubleBuffer::take`].
+///
+/// The buffers are never swapped while the software holds a
+/// [`DoubleBufferGuard`]. In this case the hardware should keep filling the
+/// same buffer, and the data of the last transfer is lost.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::sync::DoubleBuffer;
+///
+/// static SAMPLES: DoubleBuffer<[u16; 4]> = DoubleBuffer::new([0; 4], [0; 4]);
+///
+/// // Point the DMA channel to the hardware buffer.
+/// let dma_target = SAMPLES.fill_ptr();
+/// unsafe { *dma_target = [1, 2, 3, 4] };
+///
+/// // In the DMA completion interrupt handler.
+/// assert!(SAMPLES.complete());
+/// // Re-point the DMA channel to the other buffer.
+/// assert_ne!(SAMPLES.fill_ptr(), dma_target);
+///
+/// // In the processing thread.
+/// let samples = SAMPLES.take().unwrap();
+/// assert_eq!(*samples, [1, 2, 3, 4]);
+/// assert!(SAMPLES.take().is_none());
+/// // The buffer is not released yet.
+/// assert!(!SAMPLES.complete());
+/// drop(samples);
+/// assert!(SAMPLES.complete());
+/// ```
+pub struct DoubleBuffer<T> {
+    state: State,
+    buffers: [UnsafeCell<T>; 2],
+}
+
+/// A guard for the completed buffer of [`DoubleBuffer`].
+///
+/// The buffers can't be swapped while the guard is alive.
+#[must_use = "if unused the buffer will be immediately released"]
+pub struct DoubleBufferGuard<'a, T> {
+    double_buffer: &'a DoubleBuffer<T>,
+    buffer: &'a mut T,
+}
+
+unsafe impl<T: Send> Send for DoubleBuffer<T> {}
+unsafe impl<T: Send> Sync for DoubleBuffer<T> {}
+
+impl<T> DoubleBuffer<T> {
+    maybe_const_fn! {
+        /// Creates a new double buffer from the buffers `first` and `second`.
+        ///
+        /// The hardware starts filling the `first` buffer.
+        #[inline]
+        pub const fn new(first: T, second: T) -> Self {
+            Self {
+                state: State::new(0),
+                buffers: [UnsafeCell::new(first), UnsafeCell::new(second)],
+            }
+        }
+    }
+
+    /// Returns a raw pointer to the buffer currently owned by the hardware.
+    ///
+    /// The pointer should be passed to the hardware at the start, and after
+    /// each successful [`DoubleBuffer::complete`].
+    #[inline]
+    pub fn fill_ptr(&self) -> *mut T {
+        self.buffers[usize::from(load_atomic!(self.state, Acquire) & FILL_MASK)].get()
+    }
+
+    /// Signals that the hardware has completed filling its buffer, and swaps
+    /// the buffers.
+    ///
+    /// Returns `false` if the software still holds the other buffer. In this
+    /// case the buffers are not swapped. If the previously completed buffer
+    /// hasn't been taken yet, it is given back to the hardware and its data is
+    /// lost.
+    ///
+    /// This method should be called from the hardware completion interrupt
+    /// handler.
+    pub fn complete(&self) -> bool {
+        load_try_modify_atomic!(self.state, Acquire, AcqRel, |state| {
+            (state & BUSY_MASK == 0).then_some((state ^ FILL_MASK) | READY_MASK)
+        })
+        .is_ok()
+    }
+
+    /// Returns a guarded reference to the completed buffer, or `None` if there
+    /// is no new completed buffer.
+    pub fn take(&self) -> Option<DoubleBufferGuard<'_, T>> {
+        load_try_modify_atomic!(self.state, Acquire, AcqRel, |state| {
+            (state & (READY_MASK | BUSY_MASK) == READY_MASK)
+                .then_some((state & !READY_MASK) | BUSY_MASK)
+        })
+        .ok()
+        .map(|state| DoubleBufferGuard {
+            double_buffer: self,
+            buffer: unsafe { &mut *self.buffers[usize::from(!state & FILL_MASK)].get() },
+        })
+    }
+
+    /// Returns `true` if there is a completed buffer, which hasn't been taken
+    /// yet.
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        load_atomic!(self.state, Relaxed) & READY_MASK != 0
+    }
+
+    /// Consumes the double buffer, returning the underlying buffers.
+    #[inline]
+    pub fn into_inner(self) -> (T, T) {
+        let [first, second] = self.buffers;
+        (first.into_inner(), second.into_inner())
+    }
+}
+
+impl<T> Deref for DoubleBufferGuard<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        self.buffer
+    }
+}
+
+impl<T> DerefMut for DoubleBufferGuard<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        self.buffer
+    }
+}
+
+impl<T> Drop for DoubleBufferGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        fetch_and_atomic!(self.double_buffer.state, !BUSY_MASK, Release);
+    }
+}
+
+impl<T> fmt::Debug for DoubleBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DoubleBuffer").field("ready", &self.is_ready()).finish_non_exhaustive()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoubleBufferGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.buffer, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_take() {
+        let buffer = DoubleBuffer::new(0, 0);
+        assert!(buffer.take().is_none());
+        unsafe { *buffer.fill_ptr() = 1 };
+        assert!(buffer.complete());
+        assert!(buffer.is_ready());
+        unsafe { *buffer.fill_ptr() = 2 };
+        {
+            let mut guard = buffer.take().unwrap();
+            assert_eq!(*guard, 1);
+            *guard = 3;
+            assert!(!buffer.is_ready());
+            assert!(!buffer.complete());
+        }
+        assert!(buffer.complete());
+        assert_eq!(*buffer.take().unwrap(), 2);
+        assert_eq!(buffer.into_inner(), (3, 2));
+    }
+
+    #[test]
+    fn overrun() {
+        let buffer = DoubleBuffer::new(0, 0);
+        unsafe { *buffer.fill_ptr() = 1 };
+        assert!(buffer.complete());
+        unsafe { *buffer.fill_ptr() = 2 };
+        assert!(buffer.complete());
+        assert_eq!(*buffer.take().unwrap(), 2);
+    }
+}
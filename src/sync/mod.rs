@@ -3,8 +3,16 @@
 pub mod linked_list;
 pub mod soft_atomic;
 pub mod spsc;
+pub mod stack;
 
+mod double_buffer;
 mod mutex;
+mod rcu_cell;
+mod seqlock;
 
+pub use self::double_buffer::{DoubleBuffer, DoubleBufferGuard};
 pub use self::linked_list::LinkedList;
 pub use self::mutex::{Mutex, MutexGuard};
+pub use self::rcu_cell::{RcuCell, RcuGuard};
+pub use self::seqlock::SeqLock;
+pub use self::stack::Stack;
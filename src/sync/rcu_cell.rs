@@ -0,0 +1,255 @@
+use crate::sync::linked_list::LinkedList;
+use core::fmt;
+use core::ops::Deref;
+use core::ptr;
+
+#[cfg(all(feature = "atomics", not(loom)))]
+type AtomicPtr<T> = core::sync::atomic::AtomicPtr<T>;
+#[cfg(all(feature = "atomics", loom))]
+type AtomicPtr<T> = loom::sync::atomic::AtomicPtr<T>;
+#[cfg(not(feature = "atomics"))]
+type AtomicPtr<T> = crate::sync::soft_atomic::Atomic<*mut T>;
+
+#[cfg(all(feature = "atomics", not(loom)))]
+type AtomicUsize = core::sync::atomic::AtomicUsize;
+#[cfg(all(feature = "atomics", loom))]
+type AtomicUsize = loom::sync::atomic::AtomicUsize;
+#[cfg(not(feature = "atomics"))]
+type AtomicUsize = crate::sync::soft_atomic::Atomic<usize>;
+
+#[cfg(all(feature = "atomics", not(loom)))]
+type AtomicBool = core::sync::atomic::AtomicBool;
+#[cfg(all(feature = "atomics", loom))]
+type AtomicBool = loom::sync::atomic::AtomicBool;
+#[cfg(not(feature = "atomics"))]
+type AtomicBool = crate::sync::soft_atomic::Atomic<bool>;
+
+/// A read-copy-update cell.
+///
+/// Readers obtain a reference to the current value with a single atomic
+/// increment of the reader counter of the current epoch. Writers never wait
+/// for readers: a new heap-allocated value is swapped in, and the old value is
+/// retired. Retired values are reclaimed by [`RcuCell::reclaim`] after a grace
+/// period, i.e. once all readers that could have seen them are gone.
+///
+/// Each call to [`RcuCell::reclaim`] tries to advance the epoch, which
+/// succeeds when the readers of the previous epoch are gone. The values
+/// retired before an epoch advance are dropped on the next advance. Readers
+/// that start after an advance are counted in the new epoch, so a steady
+/// stream of short readers doesn't block the reclamation. Only a reader that
+/// outlives a whole epoch delays it.
+///
+/// The reclamation should be driven by thread activations: for example a
+/// fiber attached to a low-priority thread can call [`RcuCell::reclaim`] on
+/// each activation until it returns `true`.
+///
+/// The initial value is stored inline, which makes [`RcuCell::new`] usable in
+/// statics. It is dropped together with the cell.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::sync::RcuCell;
+///
+/// static CONFIG: RcuCell<[u32; 4]> = RcuCell::new([0; 4]);
+///
+/// CONFIG.replace([1; 4]);
+/// let guard = CONFIG.read();
+/// CONFIG.replace([2; 4]);
+/// assert_eq!(*guard, [1; 4]);
+/// assert_eq!(*CONFIG.read(), [2; 4]);
+/// // The old value is still referenced by `guard`.
+/// assert!(!CONFIG.reclaim());
+/// assert!(!CONFIG.reclaim());
+/// drop(guard);
+/// assert!(CONFIG.reclaim());
+/// ```
+pub struct RcuCell<T> {
+    initial: T,
+    current: AtomicPtr<T>,
+    epoch: AtomicUsize,
+    readers: [AtomicUsize; 2],
+    reclaiming: AtomicBool,
+    /// Values retired in the current epoch.
+    retired: LinkedList<Retired<T>>,
+    /// Values retired before the current epoch.
+    pending: LinkedList<Retired<T>>,
+}
+
+/// A reference to the value of [`RcuCell`].
+///
+/// The referenced value is not reclaimed while the guard is alive.
+#[must_use = "if unused the value will be immediately released"]
+pub struct RcuGuard<'a, T> {
+    cell: &'a RcuCell<T>,
+    value: &'a T,
+    epoch: usize,
+}
+
+struct Retired<T>(*mut T);
+
+unsafe impl<T: Send + Sync> Send for RcuCell<T> {}
+unsafe impl<T: Send + Sync> Sync for RcuCell<T> {}
+
+impl<T> RcuCell<T> {
+    maybe_const_fn! {
+        /// Creates a new cell initialized with `value`.
+        #[inline]
+        pub const fn new(value: T) -> Self {
+            Self {
+                initial: value,
+                current: AtomicPtr::new(ptr::null_mut()),
+                epoch: AtomicUsize::new(0),
+                readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+                reclaiming: AtomicBool::new(false),
+                retired: LinkedList::new(),
+                pending: LinkedList::new(),
+            }
+        }
+    }
+
+    /// Returns a guarded reference to the current value.
+    pub fn read(&self) -> RcuGuard<'_, T> {
+        let epoch = load_atomic!(self.epoch, SeqCst) & 1;
+        load_modify_atomic!(self.readers[epoch], SeqCst, SeqCst, |readers| readers + 1);
+        // The increment above and this load are both `SeqCst`, which pairs
+        // with the `SeqCst` swap in `replace` and the `SeqCst` loads in
+        // `reclaim`: either `reclaim` sees this reader, or this reader sees
+        // the new value.
+        let value = unsafe { self.value(load_atomic!(self.current, SeqCst)) };
+        RcuGuard { cell: self, value, epoch }
+    }
+
+    /// Replaces the current value with `value`.
+    ///
+    /// The old value is retired, and will be dropped by a future call to
+    /// [`RcuCell::reclaim`].
+    pub fn replace(&self, value: T) {
+        let old = swap_atomic!(self.current, Box::into_raw(Box::new(value)), SeqCst);
+        if !old.is_null() {
+            self.retired.push(Retired(old));
+        }
+    }
+
+    /// Tries to advance the epoch, dropping the values retired before the
+    /// previous advance.
+    ///
+    /// Returns `true` if there are no more retired values. Returns `false` if
+    /// there are readers of the previous epoch, or if another call is in
+    /// progress.
+    pub fn reclaim(&self) -> bool {
+        if swap_atomic!(self.reclaiming, true, Acquire) {
+            return false;
+        }
+        let epoch = load_atomic!(self.epoch, SeqCst);
+        if load_atomic!(self.readers[(epoch ^ 1) & 1], SeqCst) == 0 {
+            // No readers of the previous epoch are left, and the readers of
+            // the current epoch started after the pending values were swapped
+            // out.
+            while let Some(retired) = self.pending.pop() {
+                drop(retired);
+            }
+            unsafe {
+                while let Some(node) = self.retired.pop_raw() {
+                    self.pending.push_raw(node);
+                }
+            }
+            store_atomic!(self.epoch, epoch.wrapping_add(1), SeqCst);
+        }
+        let done = self.retired.is_empty() && self.pending.is_empty();
+        store_atomic!(self.reclaiming, false, Release);
+        done
+    }
+
+    /// Returns a mutable reference to the current value.
+    ///
+    /// Since this call borrows the `RcuCell` mutably, no synchronization needs
+    /// to take place.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        let current = load_atomic!(self.current, Relaxed);
+        if current.is_null() { &mut self.initial } else { unsafe { &mut *current } }
+    }
+
+    unsafe fn value(&self, current: *mut T) -> &T {
+        if current.is_null() { &self.initial } else { unsafe { &*current } }
+    }
+}
+
+impl<T> Drop for RcuCell<T> {
+    fn drop(&mut self) {
+        let current = load_atomic!(self.current, Relaxed);
+        if !current.is_null() {
+            drop(unsafe { Box::from_raw(current) });
+        }
+    }
+}
+
+impl<T> Drop for Retired<T> {
+    fn drop(&mut self) {
+        drop(unsafe { Box::from_raw(self.0) });
+    }
+}
+
+impl<T> Deref for RcuGuard<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T> Drop for RcuGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        load_modify_atomic!(self.cell.readers[self.epoch], SeqCst, SeqCst, |readers| readers - 1);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcuCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RcuCell").field("value", &*self.read()).finish()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcuGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.value, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_replace_reclaim() {
+        let cell = RcuCell::new(0);
+        cell.replace(1);
+        let guard = cell.read();
+        cell.replace(2);
+        assert_eq!(*guard, 1);
+        assert_eq!(*cell.read(), 2);
+        assert!(!cell.reclaim());
+        assert!(!cell.reclaim());
+        drop(guard);
+        assert!(cell.reclaim());
+        assert_eq!(*cell.read(), 2);
+    }
+
+    #[test]
+    fn new_readers_dont_block() {
+        let cell = RcuCell::new(0);
+        cell.replace(1);
+        let old = cell.read();
+        cell.replace(2);
+        // Move the retired value to the pending list.
+        assert!(!cell.reclaim());
+        // A reader started in the new epoch.
+        let new = cell.read();
+        drop(old);
+        assert!(cell.reclaim());
+        assert_eq!(*new, 2);
+    }
+}
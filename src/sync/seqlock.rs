@@ -0,0 +1,180 @@
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+#[cfg(all(feature = "atomics", not(loom)))]
+type Sequence = core::sync::atomic::AtomicUsize;
+#[cfg(all(feature = "atomics", loom))]
+type Sequence = loom::sync::atomic::AtomicUsize;
+#[cfg(not(feature = "atomics"))]
+type Sequence = crate::sync::soft_atomic::Atomic<usize>;
+
+/// A sequence lock.
+///
+/// The lock is optimized for a single writer, for example an interrupt
+/// handler, and many readers. Readers never block the writer: a reader makes a
+/// copy of the data and retries if the data has been modified in the meantime.
+/// Therefore the data type must be [`Copy`].
+///
+/// This is the recommended way to publish multi-word values, like timestamps
+/// or sensor states, which can't be updated with a single atomic operation.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::sync::SeqLock;
+///
+/// static TIMESTAMP: SeqLock<(u32, u32)> = SeqLock::new((0, 0));
+///
+/// // In the interrupt handler, which is the only writer.
+/// unsafe { TIMESTAMP.write((1, 500)) };
+///
+/// // In any thread.
+/// assert_eq!(TIMESTAMP.read(), (1, 500));
+/// ```
+pub struct SeqLock<T: Copy> {
+    sequence: Sequence,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for SeqLock<T> {}
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+impl<T: Copy> SeqLock<T> {
+    maybe_const_fn! {
+        /// Creates a new sequence lock initialized with `data`.
+        #[inline]
+        pub const fn new(data: T) -> Self {
+            Self { sequence: Sequence::new(0), data: UnsafeCell::new(data) }
+        }
+    }
+
+    /// Reads a consistent copy of the data.
+    ///
+    /// Spins while the data is being written.
+    #[inline]
+    pub fn read(&self) -> T {
+        loop {
+            if let Some(data) = self.try_read() {
+                break data;
+            }
+        }
+    }
+
+    /// Attempts to read a consistent copy of the data once.
+    ///
+    /// Returns `None` if the data was being written concurrently.
+    pub fn try_read(&self) -> Option<T> {
+        let sequence = load_atomic!(self.sequence, Acquire);
+        if sequence & 1 != 0 {
+            return None;
+        }
+        // The copy can be torn by a concurrent write, so it is not a valid `T`
+        // until the sequence check passes.
+        let data = unsafe { ptr::read_volatile(self.data.get().cast::<MaybeUninit<T>>()) };
+        fence_acquire();
+        (load_atomic!(self.sequence, Relaxed) == sequence).then(|| unsafe { data.assume_init() })
+    }
+
+    /// Replaces the data with `data`.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called concurrently with another write.
+    #[inline]
+    pub unsafe fn write(&self, data: T) {
+        unsafe { self.modify(|value| *value = data) };
+    }
+
+    /// Modifies the data in place with the closure `f`.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called concurrently with another write.
+    pub unsafe fn modify<F: FnOnce(&mut T)>(&self, f: F) {
+        let sequence = load_atomic!(self.sequence, Relaxed);
+        store_atomic!(self.sequence, sequence.wrapping_add(1), Relaxed);
+        fence_release();
+        // The writer is exclusive, so this copy can't be torn.
+        let mut data = unsafe { *self.data.get() };
+        f(&mut data);
+        unsafe { ptr::write_volatile(self.data.get(), data) };
+        store_atomic!(self.sequence, sequence.wrapping_add(2), Release);
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// Since this call borrows the `SeqLock` mutably, no synchronization needs
+    /// to take place.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Consumes this lock, returning the underlying data.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: Copy + Default> Default for SeqLock<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for SeqLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeqLock").field("data", &self.read()).finish()
+    }
+}
+
+#[inline]
+fn fence_acquire() {
+    #[cfg(all(feature = "atomics", not(loom)))]
+    core::sync::atomic::fence(core::sync::atomic::Ordering::Acquire);
+    #[cfg(all(feature = "atomics", loom))]
+    loom::sync::atomic::fence(core::sync::atomic::Ordering::Acquire);
+    #[cfg(not(feature = "atomics"))]
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::Acquire);
+}
+
+#[inline]
+fn fence_release() {
+    #[cfg(all(feature = "atomics", not(loom)))]
+    core::sync::atomic::fence(core::sync::atomic::Ordering::Release);
+    #[cfg(all(feature = "atomics", loom))]
+    loom::sync::atomic::fence(core::sync::atomic::Ordering::Release);
+    #[cfg(not(feature = "atomics"))]
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::Release);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_write() {
+        let lock = SeqLock::new((0_u32, 0_u32));
+        assert_eq!(lock.read(), (0, 0));
+        unsafe { lock.write((1, 2)) };
+        assert_eq!(lock.try_read(), Some((1, 2)));
+        unsafe { lock.modify(|(a, b)| *a += *b) };
+        assert_eq!(lock.read(), (3, 2));
+        assert_eq!(lock.into_inner(), (3, 2));
+    }
+
+    #[test]
+    fn write_in_progress() {
+        let lock = SeqLock::new(0_u32);
+        unsafe {
+            lock.modify(|_| {
+                assert_eq!(lock.try_read(), None);
+            });
+        }
+        assert_eq!(lock.try_read(), Some(0));
+    }
+}
@@ -1,7 +1,28 @@
 //! Software-implemented atomic types.
 //!
 //! Atomic types from this module don't require harware support of atomics.
-//! They are implemented with [critical sections](Interrupts).
+//! They are implemented with [critical sections](Interrupts). This makes them
+//! usable on targets without atomic read-modify-write instructions, like
+//! ARMv6-M. Plain loads and stores don't need a critical section, because the
+//! type is at most word-sized.
+//!
+//! Unlike [`core::sync::atomic`] types, the operations don't take memory
+//! ordering arguments: a critical section is always sequentially consistent on
+//! a single core.
+//!
+//! # Examples
+//!
+//! ```
+//! use drone_core::sync::soft_atomic::Atomic;
+//!
+//! static COUNTER: Atomic<u32> = Atomic::new(0);
+//!
+//! assert_eq!(COUNTER.fetch_update(|x| x.checked_add(1)), Ok(0));
+//! assert_eq!(COUNTER.compare_exchange(1, 10), Ok(1));
+//! assert_eq!(COUNTER.compare_exchange(1, 20), Err(10));
+//! assert_eq!(COUNTER.swap(5), 10);
+//! assert_eq!(COUNTER.load(), 5);
+//! ```
 
 use crate::platform::Interrupts;
 use core::cell::UnsafeCell;
@@ -28,6 +49,9 @@ mod sealed {
 }
 
 /// Software-implemented generic atomic type.
+///
+/// `T` can be any integer type, `bool`, or a raw pointer, which is at most
+/// word-sized.
 #[derive(Default)]
 #[repr(transparent)]
 pub struct Atomic<T: sealed::AtMostWordSized + Copy> {
@@ -80,6 +104,33 @@ impl<T: sealed::AtMostWordSized + Copy> Atomic<T> {
         Interrupts::paused(|| unsafe { mem::replace(&mut *self.inner.get(), value) })
     }
 
+    /// Stores `new` into the atomic if the current value is the same as
+    /// `current`.
+    ///
+    /// The return value is a result indicating whether the new value was
+    /// written and containing the previous value. On success this value is
+    /// guaranteed to be equal to `current`.
+    #[inline]
+    pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T>
+    where
+        T: PartialEq,
+    {
+        self.try_modify(|value| (value == current).then_some(new))
+    }
+
+    /// Fetches the value, and applies a function to it that returns an
+    /// optional new value.
+    ///
+    /// Returns a `Result` of `Ok(previous_value)` if the function returned
+    /// `Some(_)`, else `Err(previous_value)`. Unlike
+    /// [`core::sync::atomic::AtomicUsize::fetch_update`], the function is
+    /// called exactly once, because nothing can modify the value concurrently
+    /// inside the critical section.
+    #[inline]
+    pub fn fetch_update<F: FnOnce(T) -> Option<T>>(&self, f: F) -> Result<T, T> {
+        self.try_modify(f)
+    }
+
     /// Performs read-modify-write sequence, returning the previus value.
     #[inline]
     pub fn modify<F: FnOnce(T) -> T>(&self, f: F) -> T {
@@ -112,3 +163,24 @@ impl<T: sealed::AtMostWordSized + Copy> fmt::Debug for Atomic<T> {
         f.debug_struct("Atomic").finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_exchange() {
+        let atomic = Atomic::new(1_u8);
+        assert_eq!(atomic.compare_exchange(1, 2), Ok(1));
+        assert_eq!(atomic.compare_exchange(1, 3), Err(2));
+        assert_eq!(atomic.load(), 2);
+    }
+
+    #[test]
+    fn fetch_update() {
+        let atomic = Atomic::new(u8::MAX - 1);
+        assert_eq!(atomic.fetch_update(|x| x.checked_add(1)), Ok(u8::MAX - 1));
+        assert_eq!(atomic.fetch_update(|x| x.checked_add(1)), Err(u8::MAX));
+        assert_eq!(atomic.into_inner(), u8::MAX);
+    }
+}
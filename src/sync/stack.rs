@@ -0,0 +1,160 @@
+//! An intrusive stack with lock-free push.
+
+use crate::platform::Interrupts;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+
+#[cfg(all(feature = "atomics", not(loom)))]
+type AtomicPtr<T> = core::sync::atomic::AtomicPtr<Node<T>>;
+#[cfg(all(feature = "atomics", loom))]
+type AtomicPtr<T> = loom::sync::atomic::AtomicPtr<Node<T>>;
+#[cfg(not(feature = "atomics"))]
+type AtomicPtr<T> = crate::sync::soft_atomic::Atomic<*mut Node<T>>;
+
+/// An intrusive stack with lock-free push, based on the Treiber stack.
+///
+/// The stack doesn't allocate: it links caller-provided [`Node`]s with
+/// `'static` lifetime. A node is moved into the stack by [`Stack::push`] and
+/// moved out by [`Stack::pop`], therefore a stack of pre-allocated nodes can be
+/// used as a free-list for buffers, or as a queue of deferred work, from both
+/// threads and interrupt handlers.
+///
+/// [`Stack::push`] is lock-free. [`Stack::pop`] reads the head and swings it
+/// to the next node inside a short critical section. Otherwise a preempting
+/// thread could pop the head node, pop the next node, and push the head node
+/// back, so the interrupted pop would corrupt the stack (the ABA problem).
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::sync::stack::{Node, Stack};
+///
+/// static FREE: Stack<[u8; 64]> = Stack::new();
+///
+/// for _ in 0..2 {
+///     FREE.push(Box::leak(Box::new(Node::new([0; 64]))));
+/// }
+/// let buffer = FREE.pop().unwrap();
+/// buffer[0] = 1;
+/// FREE.push(buffer);
+/// ```
+pub struct Stack<T> {
+    head: AtomicPtr<T>,
+}
+
+/// A node of [`Stack`].
+#[repr(C)]
+pub struct Node<T> {
+    next: *mut Node<T>,
+    /// The value attached to this node.
+    pub value: T,
+}
+
+unsafe impl<T: Send> Send for Stack<T> {}
+unsafe impl<T: Send> Sync for Stack<T> {}
+
+unsafe impl<T: Send> Send for Node<T> {}
+unsafe impl<T: Sync> Sync for Node<T> {}
+
+impl<T> Stack<T> {
+    maybe_const_fn! {
+        /// Creates an empty [`Stack`].
+        #[inline]
+        pub const fn new() -> Self {
+            Self { head: AtomicPtr::new(ptr::null_mut()) }
+        }
+    }
+
+    /// Returns `true` if the [`Stack`] is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        load_atomic!(self.head, Relaxed).is_null()
+    }
+
+    /// Pushes `node` on top of the stack.
+    ///
+    /// This operation should compute in *O*(1) time.
+    #[inline]
+    pub fn push(&self, node: &'static mut Node<T>) {
+        let node: *mut Node<T> = node;
+        load_modify_atomic!(self.head, Relaxed, Release, |curr| unsafe {
+            (*node).next = curr;
+            node
+        });
+    }
+
+    /// Pops the top node from the stack, or returns `None` if the stack is
+    /// empty.
+    ///
+    /// This operation is not lock-free, it runs in a short critical section.
+    /// It should compute in *O*(1) time.
+    #[inline]
+    pub fn pop(&self) -> Option<&'static mut Node<T>> {
+        Interrupts::paused(|| {
+            load_try_modify_atomic!(self.head, Acquire, Acquire, |curr| unsafe {
+                (!curr.is_null()).then(|| (*curr).next)
+            })
+            .ok()
+        })
+        .map(|node| unsafe {
+            (*node).next = ptr::null_mut();
+            &mut *node
+        })
+    }
+}
+
+impl<T> Default for Stack<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Node<T> {
+    /// Creates a new unlinked node with `value`.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self { next: ptr::null_mut(), value }
+    }
+}
+
+impl<T> From<T> for Node<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Deref for Node<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Node<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_pop() {
+        let stack = Stack::new();
+        assert!(stack.is_empty());
+        stack.push(Box::leak(Box::new(Node::new(1))));
+        stack.push(Box::leak(Box::new(Node::new(2))));
+        assert!(!stack.is_empty());
+        assert_eq!(stack.pop().map(|node| node.value), Some(2));
+        assert_eq!(stack.pop().map(|node| node.value), Some(1));
+        assert!(stack.pop().is_none());
+        assert!(stack.is_empty());
+    }
+}
@@ -0,0 +1,335 @@
+//! Property-based testing helpers.
+//!
+//! This module provides round-trip checks for [`Bitfield`] accessors and
+//! register fields. Each check generates random values, writes a field, reads
+//! it back, and verifies that the bits outside the field are left untouched.
+//! This way layout bugs in hand-written or generated register definitions,
+//! like a wrong offset or overlapping fields, are caught mechanically.
+//!
+//! The module is available only with the `host` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use drone_core::bitfield::Bitfield;
+//! use drone_core::testing;
+//!
+//! #[derive(Bitfield, Copy, Clone)]
+//! #[bitfield(
+//!     en(rw, 0, 1, "Enable bit."),
+//!     mode(rw, 1, 3, "Mode bits."),
+//!     prio(rw, 4, 4, "Priority bits.")
+//! )]
+//! pub struct Ctrl(u8);
+//!
+//! testing::check_bit::<Ctrl, _, _>(0, Ctrl::en, |ctrl, bit| {
+//!     ctrl.write_en(bit);
+//! });
+//! testing::check_bits::<Ctrl, _, _>(1, 3, Ctrl::mode, |ctrl, bits| {
+//!     ctrl.write_mode(bits);
+//! });
+//! testing::check_bits::<Ctrl, _, _>(4, 4, Ctrl::prio, |ctrl, bits| {
+//!     ctrl.write_prio(bits);
+//! });
+//! testing::check_layout::<u8>(&[(0, 1), (1, 3), (4, 4)]);
+//! ```
+
+use crate::bitfield::{Bitfield, Bits};
+use crate::reg::field::{RRRegFieldBit, RRRegFieldBits, WWRegFieldBit, WWRegFieldBits};
+use crate::reg::tag::RegTag;
+use crate::reg::{RReg, Reg, RegHold, WReg};
+use core::mem::size_of;
+
+/// The number of random values tried by each check.
+pub const ITERATIONS: usize = 256;
+
+/// The seed used by the checks of this module.
+pub const SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// A pseudo-random number generator for the checks.
+///
+/// This is a xorshift generator: it is fast and deterministic, therefore a
+/// failing check is reproducible.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a new generator from `seed`.
+    ///
+    /// A zero seed is replaced by [`SEED`].
+    #[inline]
+    pub const fn new(seed: u64) -> Self {
+        Self { state: if seed == 0 { SEED } else { seed } }
+    }
+
+    /// Returns the next random 64-bit number.
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Returns a random integer of the type `B`, with all bits random.
+    pub fn bits<B: Bits>(&mut self) -> B {
+        let mut bits = B::from_usize(self.next_u64() as u8 as usize);
+        for _ in 1..size_of::<B>() {
+            bits = bits << B::from_usize(8) | B::from_usize(self.next_u64() as u8 as usize);
+        }
+        bits
+    }
+
+    /// Returns a random value of the bitfield `T`.
+    #[inline]
+    pub fn bitfield<T: Bitfield>(&mut self) -> T {
+        T::from_bits(self.bits())
+    }
+
+    /// Returns a random boolean.
+    #[inline]
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 != 0
+    }
+}
+
+impl Default for Rng {
+    #[inline]
+    fn default() -> Self {
+        Self::new(SEED)
+    }
+}
+
+/// Checks a single-bit accessor pair of the bitfield `T`.
+///
+/// `read` and `write` should access the bit at `offset`.
+///
+/// # Panics
+///
+/// If the bit read back differs from the bit written, or if `write` changes
+/// other bits.
+pub fn check_bit<T, R, W>(offset: usize, read: R, write: W)
+where
+    T: Bitfield,
+    R: Fn(&T) -> bool,
+    W: Fn(&mut T, bool),
+{
+    check_bits::<T, _, _>(
+        offset,
+        1,
+        |val| T::Bits::from_usize(usize::from(read(val))),
+        |val, bits| write(val, !bits.is_zero()),
+    );
+}
+
+/// Checks a multiple-bit accessor pair of the bitfield `T`.
+///
+/// `read` and `write` should access `width` bits at `offset`.
+///
+/// # Panics
+///
+/// If the bits read back differ from the bits written, or if `write` changes
+/// bits outside the field.
+pub fn check_bits<T, R, W>(offset: usize, width: usize, read: R, write: W)
+where
+    T: Bitfield,
+    R: Fn(&T) -> T::Bits,
+    W: Fn(&mut T, T::Bits),
+{
+    assert_field::<T::Bits>(offset, width);
+    let field_mask = mask::<T::Bits>(width) << T::Bits::from_usize(offset);
+    let mut rng = Rng::default();
+    for iteration in 0..ITERATIONS {
+        let mut val = rng.bitfield::<T>();
+        let old = val.bits();
+        let bits = rng.bits::<T::Bits>() & mask(width);
+        write(&mut val, bits);
+        assert_round_trip(iteration, offset, width, old, val.bits(), field_mask);
+        let read_bits = read(&val);
+        assert!(
+            read_bits == bits,
+            "iteration {iteration}: field at offset {offset} width {width}: wrote {bits:?}, read \
+             {read_bits:?}"
+        );
+    }
+}
+
+/// Checks a single-bit field of a readable and writable register.
+///
+/// # Panics
+///
+/// If the bit read back differs from the bit written, or if the field write
+/// changes other bits.
+pub fn check_field_bit<T, F>(field: &F)
+where
+    T: RegTag,
+    F: RRRegFieldBit<T> + WWRegFieldBit<T>,
+    F::Reg: RReg<T> + WReg<T>,
+{
+    check_bit::<<F::Reg as Reg<T>>::Val, _, _>(
+        F::OFFSET,
+        |val| field.read(val),
+        |val, bit| field.write(val, bit),
+    );
+    assert_eq!(F::WIDTH, 1, "single-bit field at offset {} has width {}", F::OFFSET, F::WIDTH);
+}
+
+/// Checks a multiple-bit field of a readable and writable register.
+///
+/// # Panics
+///
+/// If the bits read back differ from the bits written, or if the field write
+/// changes bits outside the field.
+pub fn check_field_bits<T, F>(field: &F)
+where
+    T: RegTag,
+    F: RRRegFieldBits<T> + WWRegFieldBits<T>,
+    F::Reg: RReg<T> + WReg<T>,
+{
+    check_bits::<<F::Reg as Reg<T>>::Val, _, _>(
+        F::OFFSET,
+        F::WIDTH,
+        |val| field.read(val),
+        |val, bits| field.write(val, bits),
+    );
+}
+
+/// Checks a multiple-bit accessor pair of the register hold.
+///
+/// `read` and `write` should call the generated hold methods of the field with
+/// `width` bits at `offset`, e.g. `Hold::psc` and `Hold::write_psc`.
+///
+/// # Panics
+///
+/// If the bits read back differ from the bits written, or if the field write
+/// changes bits outside the field.
+pub fn check_hold_bits<'a, T, R, FR, FW>(
+    reg: &'a R,
+    offset: usize,
+    width: usize,
+    read: FR,
+    write: FW,
+) where
+    T: RegTag,
+    R: Reg<T>,
+    FR: Fn(&R::Hold<'a>) -> <R::Val as Bitfield>::Bits,
+    FW: Fn(&mut R::Hold<'a>, <R::Val as Bitfield>::Bits),
+{
+    check_bits::<R::Val, _, _>(
+        offset,
+        width,
+        |val| read(&reg.hold(*val)),
+        |val, bits| {
+            let mut hold = reg.hold(*val);
+            write(&mut hold, bits);
+            *val = hold.val();
+        },
+    );
+}
+
+/// Checks a single-bit accessor pair of the register hold.
+///
+/// `read` and `write` should call the generated hold methods of the field at
+/// `offset`, e.g. `Hold::cen` and `Hold::write_cen`.
+///
+/// # Panics
+///
+/// If the bit read back differs from the bit written, or if the field write
+/// changes other bits.
+pub fn check_hold_bit<'a, T, R, FR, FW>(reg: &'a R, offset: usize, read: FR, write: FW)
+where
+    T: RegTag,
+    R: Reg<T>,
+    FR: Fn(&R::Hold<'a>) -> bool,
+    FW: Fn(&mut R::Hold<'a>, bool),
+{
+    check_bit::<R::Val, _, _>(offset, |val| read(&reg.hold(*val)), |val, bit| {
+        let mut hold = reg.hold(*val);
+        write(&mut hold, bit);
+        *val = hold.val();
+    });
+}
+
+/// Checks that the fields given as `(offset, width)` pairs fit into the
+/// integer type `B` and don't overlap.
+///
+/// # Panics
+///
+/// If a field is empty, exceeds the integer width, or overlaps another field.
+pub fn check_layout<B: Bits>(fields: &[(usize, usize)]) {
+    for (i, &(offset, width)) in fields.iter().enumerate() {
+        assert_field::<B>(offset, width);
+        for &(other_offset, other_width) in &fields[..i] {
+            assert!(
+                offset + width <= other_offset || other_offset + other_width <= offset,
+                "field at offset {offset} width {width} overlaps field at offset {other_offset} \
+                 width {other_width}"
+            );
+        }
+    }
+}
+
+fn assert_field<B: Bits>(offset: usize, width: usize) {
+    let total = size_of::<B>() * 8;
+    assert!(width > 0, "field at offset {offset} has zero width");
+    assert!(
+        offset + width <= total,
+        "field at offset {offset} width {width} exceeds {total}-bit integer"
+    );
+}
+
+fn assert_round_trip<B: Bits>(
+    iteration: usize,
+    offset: usize,
+    width: usize,
+    old: B,
+    new: B,
+    field_mask: B,
+) {
+    let changed = (old ^ new) & !field_mask;
+    assert!(
+        changed.is_zero(),
+        "iteration {iteration}: field at offset {offset} width {width}: write changed bits \
+         {changed:?} outside the field"
+    );
+}
+
+fn mask<B: Bits>(width: usize) -> B {
+    if width >= size_of::<B>() * 8 {
+        !B::from_usize(0)
+    } else {
+        (B::from_usize(1) << B::from_usize(width)) - B::from_usize(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_bits() {
+        let mut rng = Rng::default();
+        assert!((0..16).map(|_| rng.bits::<u128>()).any(|bits| bits >> 64 != 0));
+        assert!((0..16).map(|_| rng.bits::<u8>()).any(|bits| bits != 0));
+    }
+
+    #[test]
+    fn layout() {
+        check_layout::<u8>(&[(0, 1), (1, 3), (4, 4)]);
+        check_layout::<u32>(&[(0, 32)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps")]
+    fn layout_overlap() {
+        check_layout::<u8>(&[(0, 2), (1, 3)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds")]
+    fn layout_exceeds() {
+        check_layout::<u8>(&[(4, 5)]);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::thr::ThrExec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::task::Waker;
+
+/// A type-erased thread token.
+///
+/// `DynThr` is a thread index paired with a table of functions of the original
+/// token type. Unlike concrete thread tokens, handles of different threads and
+/// even of different thread pools have the same type. Therefore they can be
+/// stored in arrays and passed to non-generic code, e.g. a routing table
+/// mapping event sources to threads.
+///
+/// # Examples
+///
+/// ```
+/// use drone_core::thr;
+/// use drone_core::thr::{DynThr, ThrExec};
+/// use drone_core::token::Token;
+///
+/// thr::soft! {
+///     thread => pub Thr {};
+///     local => pub ThrLocal {};
+///     index => pub Thrs;
+///     threads => { uart; spi; };
+/// }
+///
+/// let thr = unsafe { Thrs::take() };
+/// // Event source number => handling thread.
+/// let routes = [DynThr::new(thr.uart), DynThr::new(thr.spi)];
+/// routes[1].wakeup();
+/// assert_eq!(routes[1].thr_idx(), 1);
+/// ```
+#[derive(Clone, Copy)]
+pub struct DynThr {
+    thr_idx: u16,
+    vtable: &'static VTable,
+}
+
+struct VTable {
+    wakeup: fn(),
+    waker: fn() -> Waker,
+    is_empty: fn() -> bool,
+}
+
+struct VTableFor<T: ThrExec>(PhantomData<T>);
+
+impl<T: ThrExec> VTableFor<T> {
+    const VTABLE: VTable =
+        VTable { wakeup: wakeup::<T>, waker: waker::<T>, is_empty: is_empty::<T> };
+}
+
+impl DynThr {
+    /// Erases the type of the thread token `thr`.
+    #[inline]
+    pub fn new<T: ThrExec>(_thr: T) -> Self {
+        Self { thr_idx: T::THR_IDX, vtable: &VTableFor::<T>::VTABLE }
+    }
+
+    /// Returns the position of the thread within its thread pool.
+    #[inline]
+    pub fn thr_idx(self) -> u16 {
+        self.thr_idx
+    }
+
+    /// Wakes up the thread.
+    #[inline]
+    pub fn wakeup(self) {
+        (self.vtable.wakeup)();
+    }
+
+    /// Returns a handle for waking up the thread.
+    #[inline]
+    pub fn waker(self) -> Waker {
+        (self.vtable.waker)()
+    }
+
+    /// Returns `true` if the fiber chain of the thread is empty.
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        (self.vtable.is_empty)()
+    }
+}
+
+impl fmt::Debug for DynThr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynThr").field("thr_idx", &self.thr_idx).finish_non_exhaustive()
+    }
+}
+
+fn wakeup<T: ThrExec>() {
+    unsafe { T::take() }.wakeup();
+}
+
+fn waker<T: ThrExec>() -> Waker {
+    unsafe { T::take() }.waker()
+}
+
+fn is_empty<T: ThrExec>() -> bool {
+    unsafe { T::take() }.is_empty()
+}
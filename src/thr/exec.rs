@@ -1,4 +1,5 @@
 use crate::fib;
+use crate::fib::FiberFuture;
 use crate::thr::prelude::*;
 use core::fmt::Display;
 use core::future::Future;
@@ -60,11 +61,6 @@ pub trait ThrExec: ThrToken {
         F: Future<Output = O> + 'static,
         O: ExecOutput,
     {
-        fn poll<T: ThrExec, F: Future>(thr: T, fut: Pin<&mut F>) -> Poll<F::Output> {
-            let waker = thr.waker();
-            let mut cx = Context::from_waker(&waker);
-            fut.poll(&mut cx)
-        }
         self.add_fn_factory(move || {
             let mut fut = factory();
             move || match poll(self, unsafe { Pin::new_unchecked(&mut fut) }) {
@@ -76,10 +72,82 @@ pub trait ThrExec: ThrToken {
             }
         });
     }
+
+    /// Adds an executor for the future `fut` to the fiber chain and returns a
+    /// future, which resolves to the output of `fut`.
+    ///
+    /// The future `fut` will start polling on the next thread wake-up.
+    /// Dropping or closing the returned future removes the executor without
+    /// polling `fut` again.
+    #[inline]
+    fn add_exec_future<F, T>(self, fut: F) -> FiberFuture<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.add_exec_future_factory(|| fut)
+    }
+
+    /// Adds an executor for the future returned by `factory` to the fiber
+    /// chain and returns a future, which resolves to the output of the
+    /// executed future.
+    ///
+    /// This method is useful for non-`Send` futures, e.g. the ones owning
+    /// thread-local peripherals. Only the output needs to be `Send` to be
+    /// awaited from another thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use drone_core::token::Token;
+    /// # drone_core::thr::soft! {
+    /// #     thread => Thr {};
+    /// #     local => ThrLocal {};
+    /// #     index => Thrs;
+    /// #     threads => { thr_0; };
+    /// # }
+    /// # fn main() {
+    /// #     let Thrs { thr_0 } = unsafe { Thrs::take() };
+    /// use drone_core::thr::prelude::*;
+    /// use futures::FutureExt;
+    /// use std::rc::Rc;
+    ///
+    /// let output = thr_0.add_exec_future_factory(|| {
+    ///     let local = Rc::new(21); // `Rc` is not `Send`
+    ///     async move { *local * 2 }
+    /// });
+    /// thr_0.wakeup();
+    /// assert_eq!(output.now_or_never(), Some(42));
+    /// # }
+    /// ```
+    #[inline]
+    fn add_exec_future_factory<C, F, T>(self, factory: C) -> FiberFuture<T>
+    where
+        C: FnOnce() -> F + Send + 'static,
+        F: Future<Output = T> + 'static,
+        T: Send + 'static,
+    {
+        self.add_future_factory(move || {
+            let mut fut = factory();
+            fib::new_fn(move || match poll(self, unsafe { Pin::new_unchecked(&mut fut) }) {
+                Poll::Pending => fib::Yielded(()),
+                Poll::Ready(output) => fib::Complete(output),
+            })
+        })
+    }
+}
+
+fn poll<T: ThrExec, F: Future>(thr: T, fut: Pin<&mut F>) -> Poll<F::Output> {
+    let waker = thr.waker();
+    let mut cx = Context::from_waker(&waker);
+    fut.poll(&mut cx)
 }
 
 /// A trait for implementing arbitrary output types for futures passed to
 /// [`ThrExec::exec`] and [`ThrExec::add_exec`].
+///
+/// Futures passed to [`ThrExec::add_exec_future`] don't need this trait, as
+/// their output is delivered to the returned [`FiberFuture`].
 pub trait ExecOutput: Sized + Send {
     /// The return type of [`ExecOutput::terminate`]. Should be either `()` or
     /// `!`.
@@ -46,19 +46,39 @@
 //!         /// Example thread 2.
 //!         pub thread2;
 //!     };
+//!
+//!     // Uncomment the following line to enable scheduler tracing feature:
+//!     // enable_trace_stream => 30;
+//!
+//!     // Uncomment the following line to call a function each time a thread
+//!     // preempts another thread of the pool:
+//!     // on_preempt => on_preempt;
 //! }
 //! ```
+//!
+//! The thread token set should be obtained once at the program start, after
+//! [`mem::init`](crate::mem::init). `Thrs::take_checked()` does the same as
+//! `Thrs::take()`, but in debug builds it also panics with a clear message if
+//! the initialization order is wrong or the token set is taken twice.
 
 pub mod prelude;
 
+mod dyn_thr;
 mod exec;
+#[cfg(feature = "fib-priority")]
+mod prioritized;
 mod soft;
 
+pub use self::dyn_thr::DynThr;
 pub use self::exec::{ExecOutput, ThrExec};
+#[cfg(feature = "fib-priority")]
+pub use self::prioritized::Prioritized;
 pub use self::soft::{
-    pending_size, PendingState, PriorityState, SoftThrToken, SoftThread, PRIORITY_LEVELS,
+    dump_state, is_pool_paused, pause_pool, pending_size, resume_pool, try_pause_pool,
+    PendingState, PriorityState, SoftThrToken, SoftThread, StateDump, PRIORITY_LEVELS,
 };
-use crate::fib::{Chain, RootFiber};
+use crate::fib;
+use crate::fib::{Chain, FiberWeak, RootFiber};
 use crate::token::Token;
 /// Defines a thread pool.
 ///
@@ -78,13 +98,31 @@ pub type CurrentState = crate::sync::soft_atomic::Atomic<u16>;
 #[doc(hidden)]
 pub type CurrentState = core::sync::atomic::AtomicU16;
 
+#[cfg(not(feature = "atomics"))]
+#[doc(hidden)]
+pub type TakenState = crate::sync::soft_atomic::Atomic<bool>;
+#[cfg(feature = "atomics")]
+#[doc(hidden)]
+pub type TakenState = core::sync::atomic::AtomicBool;
+
+#[doc(hidden)]
+#[track_caller]
+pub fn check_take(taken: &TakenState, index: &str) {
+    assert!(
+        crate::mem::is_initialized(),
+        "`{index}::take_checked` called before `drone_core::mem::init`"
+    );
+    assert!(!swap_atomic!(taken, true, Relaxed), "`{index}::take_checked` called more than once");
+}
+
 /// Basic thread.
 ///
 /// # Safety
 ///
 /// * [`Thread::pool`] must point to an array with [`Thread::COUNT`] number of
 ///   elements.
-/// * [`Thread::current`] value must be zero-initialized.
+/// * [`Thread::current`] and [`Thread::depth`] values must be
+///   zero-initialized.
 pub unsafe trait Thread: Sized + Sync + 'static {
     /// The thread-local storage type.
     type Local: Sized + 'static;
@@ -92,6 +130,10 @@ pub unsafe trait Thread: Sized + Sync + 'static {
     /// Number of threads in the pool.
     const COUNT: u16;
 
+    /// The stream number for [scheduler trace events](crate::stream::trace),
+    /// or `None` if tracing is disabled.
+    const TRACE_STREAM: Option<u8> = None;
+
     /// Returns a raw pointer to the thread pool.
     ///
     /// To obtain a safe reference to a thread object, use [`ThrToken::to_thr`]
@@ -101,6 +143,11 @@ pub unsafe trait Thread: Sized + Sync + 'static {
     /// Returns a raw pointer to the current thread index storage.
     fn current() -> *const CurrentState;
 
+    /// Returns a raw pointer to the preemption depth storage of the pool.
+    ///
+    /// See [`preemption_depth`].
+    fn depth() -> *const CurrentState;
+
     /// Returns a reference to the fiber chain.
     fn fib_chain(&self) -> &Chain;
 
@@ -145,9 +192,23 @@ pub unsafe trait Thread: Sized + Sync + 'static {
     /// The method is not reentrant.
     #[inline]
     unsafe fn resume(&self) {
+        if let Some(trace_stream) = Self::TRACE_STREAM {
+            if let Some(thr_idx) = current_idx::<Self>() {
+                crate::stream::trace::fibers_resume(trace_stream, thr_idx);
+            }
+        }
         unsafe { self.fib_chain().drain().for_each(drop) };
     }
 
+    /// Called by [`Thread::call`] when the thread preempts another thread,
+    /// of the same pool, i.e. when [`preemption_depth`] is greater than one.
+    ///
+    /// The hook runs inside the preempting thread, before `f`. It can be set
+    /// with the `on_preempt` key of [`thr::pool!`](crate::thr::pool!), e.g. to
+    /// flush the blocks cached by the preempted thread back to the heap.
+    #[inline]
+    fn on_preempt() {}
+
     /// Runs the function `f` inside the thread number `thr_idx`.
     ///
     /// # Safety
@@ -158,13 +219,51 @@ pub unsafe trait Thread: Sized + Sync + 'static {
     unsafe fn call(thr_idx: u16, f: unsafe fn(&'static Self)) {
         unsafe {
             let preempted = load_atomic!(*Self::current(), Relaxed);
+            // Threads of a pool run on a single core and preempt each other
+            // in a nested fashion, so the depth is restored before the
+            // preempted thread observes it again.
+            let depth = load_atomic!(*Self::depth(), Relaxed);
             store_atomic!(*Self::current(), thr_idx + 1, Relaxed);
+            store_atomic!(*Self::depth(), depth + 1, Relaxed);
+            if let Some(trace_stream) = Self::TRACE_STREAM {
+                crate::stream::trace::thread_begin(trace_stream, thr_idx);
+            }
+            if depth > 0 {
+                Self::on_preempt();
+            }
             f(&*Self::pool().add(usize::from(thr_idx)));
+            if let Some(trace_stream) = Self::TRACE_STREAM {
+                crate::stream::trace::thread_end(trace_stream, thr_idx);
+            }
+            store_atomic!(*Self::depth(), depth, Relaxed);
             store_atomic!(*Self::current(), preempted, Relaxed);
         }
     }
 }
 
+/// Returns the index of the currently running thread of the pool `T`.
+///
+/// Returns `None` if called outside of the thread pool `T`, e.g. from the
+/// program entry point.
+#[inline]
+pub fn current_idx<T: Thread>() -> Option<u16> {
+    unsafe { load_atomic!(*T::current(), Relaxed).checked_sub(1) }
+}
+
+/// Returns the number of nested thread activations of the pool `T`.
+///
+/// Each [`Thread::call`] increments the depth of its pool for the duration of
+/// the call. Zero means that the code runs outside of the thread pool `T`,
+/// e.g. from the program entry point. A value greater than one means that the
+/// current thread has preempted another thread of the same pool.
+///
+/// The depth is kept per pool, so pools running on different cores don't
+/// affect each other. Activations of other pools are not counted.
+#[inline]
+pub fn preemption_depth<T: Thread>() -> u16 {
+    unsafe { load_atomic!(*T::depth(), Relaxed) }
+}
+
 /// Token for a thread in a thread pool.
 ///
 /// # Safety
@@ -200,6 +299,33 @@ where
         self.to_thr().fib_chain().add(fib);
     }
 
+    /// Returns a token, which adds fibers with the priority `P`.
+    ///
+    /// Fibers with higher priorities are resumed first. The fibers added
+    /// through the original token have the lowest priority `0`. A priority
+    /// not less than [`Chain::PRIORITIES`] fails to compile.
+    ///
+    /// Requires the `fib-priority` feature.
+    #[cfg(feature = "fib-priority")]
+    #[inline]
+    fn with_priority<const P: u8>(self) -> Prioritized<Self, P> {
+        Prioritized::new(self)
+    }
+
+    /// Adds the fiber `fib` to the fiber chain and returns a weak handle to it.
+    ///
+    /// The handle can be used to check whether the fiber is still alive, for
+    /// example by supervisory code watching long-lived worker fibers.
+    #[inline]
+    fn add_fib_weak<F>(self, fib: F) -> FiberWeak
+    where
+        F: RootFiber + Send,
+    {
+        let (fib, weak) = fib::track(fib);
+        self.to_thr().fib_chain().add(fib);
+        weak
+    }
+
     /// Adds the fiber returned by `factory` to the fiber chain.
     ///
     /// This method is useful for non-`Send` fibers.
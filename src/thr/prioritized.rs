@@ -0,0 +1,71 @@
+use crate::fib;
+use crate::fib::{Chain, FiberWeak, RootFiber};
+use crate::thr::ThrToken;
+use crate::token::Token;
+
+/// A thread token, which adds fibers with the priority `P`.
+///
+/// Created by [`ThrToken::with_priority`]. All fiber extension methods called
+/// on this token, like `add_fn`, `add_future`, or `add_exec`, add their fibers
+/// with the priority `P`. Fibers with higher priorities are resumed first. The
+/// fibers added through the original token have the lowest priority `0`.
+#[derive(Clone, Copy)]
+pub struct Prioritized<T: ThrToken, const P: u8>(T);
+
+impl<T: ThrToken, const P: u8> Prioritized<T, P> {
+    const PRIORITY: u8 = {
+        assert!(P < Chain::PRIORITIES, "fiber priority out of range");
+        P
+    };
+
+    pub(crate) fn new(token: T) -> Self {
+        let _ = Self::PRIORITY;
+        Self(token)
+    }
+
+    /// Returns the original thread token.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+unsafe impl<T: ThrToken, const P: u8> Token for Prioritized<T, P> {
+    #[inline]
+    unsafe fn take() -> Self {
+        Self::new(unsafe { T::take() })
+    }
+}
+
+unsafe impl<T: ThrToken, const P: u8> ThrToken for Prioritized<T, P> {
+    type Thread = T::Thread;
+
+    const THR_IDX: u16 = T::THR_IDX;
+
+    #[inline]
+    fn add_fib<F>(self, fib: F)
+    where
+        F: RootFiber + Send,
+    {
+        self.to_thr().fib_chain().add_with_priority(fib, Self::PRIORITY);
+    }
+
+    #[inline]
+    fn add_fib_weak<F>(self, fib: F) -> FiberWeak
+    where
+        F: RootFiber + Send,
+    {
+        let (fib, weak) = fib::track(fib);
+        self.to_thr().fib_chain().add_with_priority(fib, Self::PRIORITY);
+        weak
+    }
+
+    #[inline]
+    fn add_fib_factory<C, F>(self, factory: C)
+    where
+        C: FnOnce() -> F + Send + 'static,
+        F: RootFiber,
+    {
+        self.to_thr().fib_chain().add_with_priority(factory(), Self::PRIORITY);
+    }
+}
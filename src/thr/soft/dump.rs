@@ -0,0 +1,84 @@
+use super::{cell_idx, pending_bit, SoftThread, PRIORITY_LEVELS};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A snapshot of the software-managed thread pool scheduler state.
+///
+/// The [`Display`](fmt::Display) implementation prints the currently running
+/// priority, the pending priority levels from the header, and the pending
+/// threads at each priority level. The state is read without
+/// synchronization, therefore it may be inconsistent if the scheduler runs
+/// concurrently.
+///
+/// See also [`dump_state`].
+pub struct StateDump<T: SoftThread> {
+    _thr: PhantomData<T>,
+}
+
+/// Writes the scheduler state of the software-managed thread pool `T` into
+/// the `stream`.
+///
+/// The output looks like the following:
+///
+/// ```text
+/// running priority: 1
+/// pending priorities: 3 0
+/// priority 3: 2 5
+/// priority 0: 0
+/// ```
+///
+/// See also [`StateDump`].
+#[inline(never)]
+pub fn dump_state<T: SoftThread>(stream: u8) {
+    crate::stream::write_fmt(stream, format_args!("{}", StateDump::<T>::new()));
+}
+
+impl<T: SoftThread> StateDump<T> {
+    /// Creates a new scheduler state snapshot.
+    #[inline]
+    pub fn new() -> Self {
+        Self { _thr: PhantomData }
+    }
+}
+
+impl<T: SoftThread> Default for StateDump<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: SoftThread> fmt::Display for StateDump<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pending = T::pending();
+        let header = load_atomic!(unsafe { &*pending }, Relaxed);
+        match header >> PRIORITY_LEVELS {
+            0 => writeln!(f, "running priority: none")?,
+            running => writeln!(f, "running priority: {}", running - 1)?,
+        }
+        write!(f, "pending priorities:")?;
+        for priority in (0..PRIORITY_LEVELS).rev() {
+            if header & 1 << priority != 0 {
+                write!(f, " {priority}")?;
+            }
+        }
+        writeln!(f)?;
+        for priority in (0..PRIORITY_LEVELS).rev() {
+            let mut empty = true;
+            for thr_idx in 0..T::COUNT {
+                let cell = unsafe { &*pending.add(cell_idx::<T>(thr_idx, priority)) };
+                if load_atomic!(cell, Relaxed) & pending_bit(thr_idx) != 0 {
+                    if empty {
+                        write!(f, "priority {priority}:")?;
+                        empty = false;
+                    }
+                    write!(f, " {thr_idx}")?;
+                }
+            }
+            if !empty {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
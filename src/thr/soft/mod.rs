@@ -1,6 +1,9 @@
+mod dump;
 mod wake;
 
+pub use self::dump::{dump_state, StateDump};
 use self::wake::SoftWaker;
+use crate::platform::Interrupts;
 use crate::thr::{ThrExec, ThrToken, Thread};
 use core::task::Waker;
 
@@ -9,7 +12,65 @@ pub const PRIORITY_LEVELS: u8 = 27;
 
 /// Returns the number of elements in [`SoftThread::pending`] array.
 pub const fn pending_size<T: SoftThread>() -> usize {
-    1 + row_size::<T>() * PRIORITY_LEVELS as usize
+    2 + row_size::<T>() * PRIORITY_LEVELS as usize
+}
+
+/// Pauses the software-managed thread pool `T`, and waits until its running
+/// threads finish their current activations.
+///
+/// After this function returns, no thread of the pool is running, and no
+/// thread is resumed until [`resume_pool`] is called. The threads can still be
+/// set pending, and the pending state is recorded.
+///
+/// This is useful to get a clean stop point for firmware update or low-power
+/// entry sequences, without masking all interrupts.
+///
+/// This function must not be called from a thread of the pool, or from a
+/// context, which preempted a thread of the pool: it would wait for itself
+/// forever. Use [`try_pause_pool`] in such contexts.
+#[inline]
+pub fn pause_pool<T: SoftThread>() {
+    while !try_pause_pool::<T>() {
+        core::hint::spin_loop();
+    }
+}
+
+/// Pauses the software-managed thread pool `T` without waiting, and returns
+/// `true` if no thread of the pool is running.
+///
+/// If this function returns `false`, the threads, which are already running,
+/// i.e. the caller's thread and the threads it preempted, are not interrupted
+/// and finish their current activations normally. No other thread of the pool
+/// is resumed. The function can be called again to poll for the completion.
+#[inline]
+pub fn try_pause_pool<T: SoftThread>() -> bool {
+    store_atomic!(unsafe { &*paused::<T>() }, 1, SeqCst);
+    // Pairs with the `SeqCst` header update in `row_start` and the `SeqCst`
+    // load in `is_paused`: either this load sees the running thread, or the
+    // scheduler sees the pause before resuming a thread.
+    load_atomic!(unsafe { &*T::pending() }, SeqCst) >> PRIORITY_LEVELS == 0
+}
+
+/// Resumes the software-managed thread pool `T`, which was paused by
+/// [`pause_pool`].
+///
+/// The threads, which were set pending while the pool was paused, are run
+/// immediately if their priorities are higher than the current priority.
+#[inline]
+pub fn resume_pool<T: SoftThread>() {
+    store_atomic!(unsafe { &*paused::<T>() }, 0, Release);
+    T::preempt();
+}
+
+/// Returns `true` if the software-managed thread pool `T` is paused by
+/// [`pause_pool`].
+#[inline]
+pub fn is_pool_paused<T: SoftThread>() -> bool {
+    unsafe { is_paused(paused::<T>()) }
+}
+
+fn paused<T: SoftThread>() -> *const PendingState {
+    unsafe { T::pending().add(pending_size::<T>() - 1) }
 }
 
 const fn row_size<T: SoftThread>() -> usize {
@@ -46,6 +107,112 @@ pub type PriorityState = loom::sync::atomic::AtomicU8;
 #[doc(hidden)]
 pub type PriorityState = crate::sync::soft_atomic::Atomic<u8>;
 
+// The following macros update the scheduler state. Without hardware atomics,
+// they use the critical sections of `SoftThread::critical_section`.
+
+macro_rules! masked_modify {
+    (
+        $thr:ty,
+        $atomic:expr,
+        $ordering_read:ident,
+        $ordering_cas:ident,
+        | $old:ident | $new:expr
+    ) => {{
+        #[cfg(not(any(feature = "atomics", loom)))]
+        {
+            let atomic = $atomic;
+            <$thr>::critical_section(|| {
+                let $old = atomic.load();
+                atomic.store($new);
+                $old
+            })
+        }
+        #[cfg(any(feature = "atomics", loom))]
+        {
+            modify_atomic!($atomic, $ordering_read, $ordering_cas, |$old| $new)
+        }
+    }};
+}
+
+macro_rules! masked_load_modify {
+    (
+        $thr:ty,
+        $atomic:expr,
+        $ordering_read:ident,
+        $ordering_cas:ident,
+        | $old:ident | $new:expr
+    ) => {{
+        #[cfg(not(any(feature = "atomics", loom)))]
+        {
+            let atomic = $atomic;
+            <$thr>::critical_section(|| {
+                let $old = atomic.load();
+                atomic.store($new);
+                $old
+            })
+        }
+        #[cfg(any(feature = "atomics", loom))]
+        {
+            load_modify_atomic!($atomic, $ordering_read, $ordering_cas, |$old| $new)
+        }
+    }};
+}
+
+macro_rules! masked_load_try_modify {
+    (
+        $thr:ty,
+        $atomic:expr,
+        $ordering_read:ident,
+        $ordering_cas:ident,
+        | $old:ident | $new:expr
+    ) => {{
+        #[cfg(not(any(feature = "atomics", loom)))]
+        {
+            let atomic = $atomic;
+            <$thr>::critical_section(|| {
+                let $old = atomic.load();
+                match $new {
+                    Some(next) => {
+                        atomic.store(next);
+                        Ok($old)
+                    }
+                    None => Err($old),
+                }
+            })
+        }
+        #[cfg(any(feature = "atomics", loom))]
+        {
+            load_try_modify_atomic!($atomic, $ordering_read, $ordering_cas, |$old| $new)
+        }
+    }};
+}
+
+macro_rules! masked_fetch_or {
+    ($thr:ty, $atomic:expr, $value:expr, $ordering:ident) => {{
+        #[cfg(not(any(feature = "atomics", loom)))]
+        {
+            masked_modify!($thr, $atomic, $ordering, $ordering, |old| old | $value)
+        }
+        #[cfg(any(feature = "atomics", loom))]
+        {
+            fetch_or_atomic!($atomic, $value, $ordering)
+        }
+    }};
+}
+
+macro_rules! masked_fetch_and {
+    ($thr:ty, $atomic:expr, $value:expr, $ordering:ident) => {{
+        #[cfg(not(any(feature = "atomics", loom)))]
+        {
+            masked_modify!($thr, $atomic, $ordering, $ordering, |old| old & $value)
+        }
+        #[cfg(any(feature = "atomics", loom))]
+        {
+            fetch_and_atomic!($atomic, $value, $ordering)
+        }
+    }};
+}
+
 /// Software-managed thread.
 ///
 /// # Pending state structure
@@ -53,12 +220,13 @@ pub type PriorityState = crate::sync::soft_atomic::Atomic<u8>;
 /// [`SoftThread::pending`] function returns a static array of `u32` with the
 /// following structure:
 ///
-/// `[H, C<L>, C<L>, ..., C<L-1>, C<L-1>, ..., C<0>, C<0>]`, where
+/// `[H, C<L>, C<L>, ..., C<L-1>, C<L-1>, ..., C<0>, C<0>, S]`, where
 ///
 /// * `H` - header
 /// * `L` - maximum priority number
 /// * `C<n>` - a set of pending status bits for each defined thread at the
 ///   priority level `n`
+/// * `S` - non-zero if the thread pool is paused (see [`pause_pool`])
 ///
 /// The header has the following bit structure:
 ///
@@ -73,6 +241,33 @@ pub type PriorityState = crate::sync::soft_atomic::Atomic<u8>;
 /// [`SoftThread::pending`] must point to a static array with [`pending_size`]
 /// number of elements.
 pub unsafe trait SoftThread: Thread {
+    /// The priority for the scheduler critical sections.
+    ///
+    /// Without hardware atomics, the scheduler state is updated inside
+    /// critical sections, which by default block all interrupts. If this is
+    /// `Some(priority)`, the critical sections block only interrupts with
+    /// priorities lower than `priority` (see
+    /// [`Interrupts::mask_below`](crate::platform::Interrupts::mask_below)),
+    /// which shortens the worst-case latency of higher-priority interrupts. In
+    /// this case threads of the pool must be resumed, woken up, and accessed
+    /// only from interrupts with priorities lower than `priority`.
+    ///
+    /// The critical sections themselves are entered through
+    /// [`SoftThread::critical_section`], which must agree with this value.
+    const CRITICAL_PRIORITY: Option<u8> = None;
+
+    /// Runs `f` inside a scheduler critical section.
+    ///
+    /// Used only without hardware atomics. The default implementation blocks
+    /// all interrupts. [`thr::soft!`](crate::thr::soft!) with the
+    /// `critical_priority` key overrides it to block only interrupts with
+    /// priorities lower than [`SoftThread::CRITICAL_PRIORITY`], so the
+    /// priority-masking platform hooks are referenced only by such pools.
+    #[inline]
+    fn critical_section<R, F: FnOnce() -> R>(f: F) -> R {
+        Interrupts::paused(f)
+    }
+
     /// Returns a raw pointer to the pending state storage.
     fn pending() -> *const PendingState;
 
@@ -107,7 +302,7 @@ pub unsafe trait SoftThread: Thread {
         unsafe {
             let thr = Self::pool().add(usize::from(thr_idx));
             let priority = load_atomic!(*(*thr).priority(), Relaxed);
-            set_pending(
+            set_pending::<Self>(
                 Self::pending(),
                 cell_idx::<Self>(thr_idx, priority),
                 pending_bit(thr_idx),
@@ -123,12 +318,24 @@ pub unsafe trait SoftThread: Thread {
             unsafe { T::call(thr_idx, T::resume) };
         }
         let pending = Self::pending();
+        let paused = paused::<Self>();
         let row_size = row_size::<Self>();
         unsafe {
-            if let Some((mut ptr, mut priority, prev_priority)) = row_start(pending, row_size) {
+            if let Some((mut ptr, mut priority, prev_priority)) =
+                row_start::<Self>(pending, paused, row_size)
+            {
                 loop {
-                    row_run(&mut ptr, Self::COUNT, resume::<Self>);
-                    if !row_next(pending, &mut ptr, &mut priority, prev_priority, row_size) {
+                    if !row_run::<Self>(&mut ptr, Self::COUNT, resume::<Self>, paused) {
+                        row_abort::<Self>(pending, priority, prev_priority);
+                        break;
+                    }
+                    if !row_next::<Self>(
+                        pending,
+                        &mut ptr,
+                        &mut priority,
+                        prev_priority,
+                        row_size,
+                    ) {
                         break;
                     }
                 }
@@ -158,7 +365,7 @@ pub trait SoftThrToken: ThrToken {
     #[inline]
     fn clear_pending(self) {
         unsafe {
-            clear_pending(
+            clear_pending::<Self::SoftThread>(
                 Self::SoftThread::pending(),
                 cell_idx::<Self::SoftThread>(Self::THR_IDX, self.priority()),
                 pending_bit(Self::THR_IDX),
@@ -212,13 +419,17 @@ impl<T: SoftThrToken> ThrExec for T {
     }
 }
 
-unsafe fn row_start(
+unsafe fn row_start<T: SoftThread>(
     header: *const PendingState,
+    paused: *const PendingState,
     row_size: usize,
 ) -> Option<(*const PendingState, u8, u8)> {
+    if unsafe { is_paused(paused) } {
+        return None;
+    }
     #[cfg_attr(any(feature = "atomics", loom), allow(unused_assignments))]
     let (mut priority, mut prev_priority) = (0, 0);
-    load_try_modify_atomic!(unsafe { &*header }, Relaxed, Acquire, |cursor| {
+    masked_load_try_modify!(T, unsafe { &*header }, Relaxed, SeqCst, |cursor| {
         priority = PRIORITY_LEVELS;
         prev_priority = (cursor >> priority) as u8;
         cursor_find_priority(cursor, &mut priority, prev_priority)
@@ -228,27 +439,35 @@ unsafe fn row_start(
     Some((ptr, priority, prev_priority))
 }
 
-unsafe fn row_run(ptr: &mut *const PendingState, thr_count: u16, resume: unsafe fn(u16)) {
+unsafe fn row_run<T: SoftThread>(
+    ptr: &mut *const PendingState,
+    thr_count: u16,
+    resume: unsafe fn(u16),
+    paused: *const PendingState,
+) -> bool {
     let mut thr_idx = 0;
     loop {
         let mut cell = load_atomic!(unsafe { &**ptr }, Relaxed);
         if cell == 0 {
             thr_idx += 1 << COL_BITS;
             if thr_idx >= thr_count {
-                break;
+                break true;
             }
         } else {
             let mut pending_bit = 1;
             loop {
                 if cell & pending_bit != 0 {
-                    cell = modify_atomic!(unsafe { &**ptr }, Relaxed, Acquire, |cell| {
+                    if unsafe { is_paused(paused) } {
+                        return false;
+                    }
+                    cell = masked_modify!(T, unsafe { &**ptr }, Relaxed, Acquire, |cell| {
                         cell & !pending_bit
                     });
                     unsafe { resume(thr_idx) };
                 }
                 thr_idx += 1;
                 if thr_idx == thr_count {
-                    return;
+                    return true;
                 }
                 pending_bit <<= 1;
                 if pending_bit == 0 {
@@ -260,7 +479,7 @@ unsafe fn row_run(ptr: &mut *const PendingState, thr_count: u16, resume: unsafe
     }
 }
 
-unsafe fn row_next(
+unsafe fn row_next<T: SoftThread>(
     header: *const PendingState,
     ptr: &mut *const PendingState,
     priority: &mut u8,
@@ -269,7 +488,7 @@ unsafe fn row_next(
 ) -> bool {
     #[cfg_attr(any(feature = "atomics", loom), allow(unused_assignments))]
     let mut next_priority = 0;
-    load_modify_atomic!(unsafe { &*header }, Relaxed, Release, |cursor| {
+    masked_load_modify!(T, unsafe { &*header }, Relaxed, Release, |cursor| {
         next_priority = *priority - 1;
         cursor_find_priority(cursor, &mut next_priority, prev_priority)
             .unwrap_or_else(|| cursor_set_priority(cursor, prev_priority))
@@ -284,19 +503,39 @@ unsafe fn row_next(
     true
 }
 
-unsafe fn set_pending(
+unsafe fn row_abort<T: SoftThread>(
+    header: *const PendingState,
+    priority: u8,
+    prev_priority: u8,
+) {
+    masked_load_modify!(T, unsafe { &*header }, Relaxed, Release, |cursor| {
+        cursor_set_priority(cursor | 1 << priority - 1, prev_priority)
+    });
+}
+
+unsafe fn set_pending<T: SoftThread>(
     pending: *const PendingState,
     cell_idx: usize,
     pending_bit: u32,
     priority: u8,
 ) -> bool {
-    fetch_or_atomic!(unsafe { &*pending.add(cell_idx) }, pending_bit, Release) & pending_bit == 0
-        && fetch_or_atomic!(unsafe { &*pending }, 1 << priority, Release) >> PRIORITY_LEVELS
+    let cell = unsafe { &*pending.add(cell_idx) };
+    let header = unsafe { &*pending };
+    masked_fetch_or!(T, cell, pending_bit, Release) & pending_bit == 0
+        && masked_fetch_or!(T, header, 1 << priority, Release) >> PRIORITY_LEVELS
             < u32::from(priority + 1)
 }
 
-unsafe fn clear_pending(pending: *const PendingState, cell_idx: usize, pending_bit: u32) {
-    fetch_and_atomic!(unsafe { &*pending.add(cell_idx) }, !pending_bit, Release);
+unsafe fn clear_pending<T: SoftThread>(
+    pending: *const PendingState,
+    cell_idx: usize,
+    pending_bit: u32,
+) {
+    masked_fetch_and!(T, unsafe { &*pending.add(cell_idx) }, !pending_bit, Release);
+}
+
+unsafe fn is_paused(paused: *const PendingState) -> bool {
+    load_atomic!(unsafe { &*paused }, SeqCst) != 0
 }
 
 unsafe fn is_pending(pending: *const PendingState, cell_idx: usize, pending_bit: u32) -> bool {
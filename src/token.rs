@@ -138,6 +138,33 @@
 //!     *foo += 1;
 //! }
 //! ```
+//!
+//! # Const Construction
+//!
+//! [`Token::take`] can't be called in `const` contexts. Therefore tokens
+//! generated by [`simple_token!`], [`unsafe_static_tokens!`],
+//! [`thr::pool!`](crate::thr::pool!), and [`reg!`](crate::reg!) also provide
+//! an inherent `const unsafe fn take_const()` constructor with the same safety
+//! contract. It allows embedding tokens directly into statics without wrapping
+//! them into [`Option`] and initializing at run-time:
+//!
+//! ```
+//! use drone_core::token::simple_token;
+//!
+//! simple_token! {
+//!     /// The token for the LED pin.
+//!     pub struct LedPin;
+//! }
+//!
+//! /// A statically allocated driver.
+//! pub struct Led {
+//!     pin: LedPin,
+//! }
+//!
+//! // Here is `unsafe`, we need to ensure that `LedPin` is not instantiated
+//! // anywhere else.
+//! static LED: Led = Led { pin: unsafe { LedPin::take_const() } };
+//! ```
 
 /// Defines a new simple [`Token`].
 ///
@@ -172,7 +199,8 @@ pub use drone_core_macros::unsafe_static_tokens;
 /// keep the following invariants:
 ///
 /// 1. The type must not implement [`Clone`].
-/// 2. The type must be instantiated only inside [`Token::take`] method.
+/// 2. The type must be instantiated only inside [`Token::take`] method, or
+///    an equivalent `const` constructor.
 /// 3. The type must be zero-sized.
 pub unsafe trait Token: Sized + Send + 'static {
     /// Creates the token instance.
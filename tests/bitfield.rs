@@ -1,7 +1,11 @@
 #![no_implicit_prelude]
 
-use ::drone_core::bitfield::Bitfield;
+use ::drone_core::bitfield::{Bitfield, FieldOverflow, InvalidBits};
 use ::std::assert_eq;
+use ::std::convert::{From, Into};
+use ::std::iter::{ExactSizeIterator, Iterator};
+use ::std::option::Option::{None, Some};
+use ::std::result::Result::{Err, Ok};
 
 #[derive(Bitfield, Copy, Clone)]
 #[bitfield(
@@ -130,3 +134,269 @@ fn write_bits() {
     unsafe { x.write_bits(0, 8, 0b1111_1111) };
     assert_eq!(x.bits(), 0b1111_1111);
 }
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(lo(rw, 0, 4), hi(rw, 4, 4))]
+#[bitfield_conv(Byte)]
+pub struct Nibbles(u8);
+
+#[test]
+fn conv() {
+    let mut x = Nibbles(0);
+    x.write_lo(0b1010).write_hi(0b0101);
+    let y: Byte = x.into();
+    assert_eq!(y.bits(), 0b0101_1010);
+    let z = Nibbles::from(y);
+    assert_eq!(z.lo(), 0b1010);
+    assert_eq!(z.hi(), 0b0101);
+    assert_eq!(Byte::from_bits(0b1000_0001).bits(), 0b1000_0001);
+}
+
+#[derive(Bitfield, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Input = 0b00,
+    Output = 0b01,
+    Analog = 0b11,
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(mode(rw, 2, 2, Mode, "Test enum field."), status(r, 4, 2, Mode))]
+pub struct Port(u16);
+
+#[test]
+fn enum_field() {
+    let mut x = Port(0);
+    assert_eq!(x.mode(), Ok(Mode::Input));
+    x.write_mode(Mode::Analog);
+    assert_eq!(x.bits(), 0b1100);
+    x.write_mode(Mode::Output);
+    assert_eq!(x.bits(), 0b0100);
+    let x = Port(0b10_0000);
+    assert_eq!(x.status(), Err(InvalidBits(0b10)));
+}
+
+#[test]
+fn iter_ones() {
+    let x = Byte(0b1010_0110);
+    let mut ones = x.iter_ones();
+    assert_eq!(ones.len(), 4);
+    assert_eq!(ones.next(), Some(1));
+    assert_eq!(ones.next(), Some(2));
+    assert_eq!(ones.next(), Some(5));
+    assert_eq!(ones.next(), Some(7));
+    assert_eq!(ones.next(), None);
+    assert_eq!(Byte(0).iter_ones().next(), None);
+    assert_eq!(Byte(0b1000_0000).iter_ones().next(), Some(7));
+}
+
+#[test]
+fn iter_zeros() {
+    let x = Byte(0b1111_0101);
+    let mut zeros = x.iter_zeros();
+    assert_eq!(zeros.len(), 2);
+    assert_eq!(zeros.next(), Some(1));
+    assert_eq!(zeros.next(), Some(3));
+    assert_eq!(zeros.next(), None);
+    assert_eq!(Byte(0xFF).iter_zeros().next(), None);
+}
+
+#[test]
+fn count_ones() {
+    assert_eq!(Byte(0b1010_0110).count_ones(), 4);
+    assert_eq!(Byte(0b1010_0110).count_zeros(), 4);
+    assert_eq!(Byte(0xFF).count_ones(), 8);
+    assert_eq!(Byte(0).count_zeros(), 8);
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(lo(rw, 0), mid(rw, 30, 4), hi(rw, 95), mode(rw, 62, 2, Mode))]
+pub struct Wide([u32; 3]);
+
+#[test]
+fn words() {
+    let mut x = Wide([0; 3]);
+    x.set_lo().set_hi();
+    x.write_mid(::drone_core::bitfield::Words([0b1011, 0, 0]));
+    x.write_mode(Mode::Analog);
+    assert_eq!(x.0, [0xC000_0001, 0xC000_0002, 0x8000_0000]);
+    assert_eq!(x.mid().0, [0b1011, 0, 0]);
+    assert_eq!(x.mode(), Ok(Mode::Analog));
+    assert_eq!(x.iter_ones().count(), 7);
+    x.toggle_hi();
+    assert!(!x.hi());
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(offset(rw_i, 1, 4), gain(r_i, 5, 3))]
+pub struct Trim(u8);
+
+#[test]
+fn signed_field() {
+    let mut x = Trim(0);
+    x.write_offset(-8);
+    assert_eq!(x.bits(), 0b0001_0000);
+    assert_eq!(x.offset(), -8);
+    x.write_offset(7);
+    assert_eq!(x.offset(), 7);
+    x.write_offset(-1);
+    assert_eq!(x.bits(), 0b0001_1110);
+    assert_eq!(Trim(0b1010_0000).gain(), -3);
+    assert_eq!(Trim(0b0110_0000).gain(), 3);
+}
+
+#[test]
+#[should_panic]
+fn signed_field_out_of_range() {
+    Trim(0).write_offset(8);
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(ch(rw, 0, 2, 4), en(rw, 8, 1, 4, 2), lvl(r_i, 16, 4, 2))]
+pub struct Channels(u32);
+
+#[test]
+fn repeated_field() {
+    let mut x = Channels(0);
+    x.write_ch(0, 0b01).write_ch(3, 0b11).set_en(2).set_en(3).toggle_en(3);
+    assert_eq!(x.bits(), 0b0001_0000_1100_0001);
+    assert_eq!(x.ch(0), 0b01);
+    assert_eq!(x.ch(1), 0b00);
+    assert_eq!(x.ch(3), 0b11);
+    assert!(x.en(2));
+    assert!(!x.en(3));
+    let x = Channels(0x0071_0000);
+    assert_eq!(x.lvl(0), 1);
+    assert_eq!(x.lvl(1), 7);
+}
+
+#[test]
+#[should_panic]
+fn repeated_field_out_of_bounds() {
+    Channels(0).ch(4);
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(strict, lo(rw, 0, 4), ch(rw, 4, 2, 2))]
+pub struct Strict(u8);
+
+#[test]
+fn checked_setters() {
+    let mut x = Strict(0);
+    assert_eq!(x.try_write_lo(0b1111), Ok(()));
+    assert_eq!(x.try_write_lo(0b1_0000), Err(FieldOverflow));
+    assert_eq!(x.try_write_ch(1, 0b11), Ok(()));
+    assert_eq!(x.try_write_ch(0, 0b100), Err(FieldOverflow));
+    assert_eq!(x.bits(), 0b1100_1111);
+    let mut x = Byte(0);
+    assert_eq!(x.try_write_baz(0b1000), Err(FieldOverflow));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn strict_setter_overflow() {
+    Strict(0).write_lo(0b1_0000);
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(
+    kind(rw, 0, 3) = 5,
+    trim(rw_i, 3, 4) = -2,
+    pin(rw, 8, 2, 3) = 0b01,
+    hi(rw, 63) = 1
+)]
+pub struct Config(u64);
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(en(rw, 0, 1, 3, 32) = 1)]
+pub struct WideConfig([u32; 3]);
+
+#[test]
+fn default_values() {
+    use ::std::default::Default;
+    assert_eq!(Config::DEFAULT.bits(), 0x8000_0000_0000_1575);
+    let x = Config::default();
+    assert_eq!(x.kind(), 5);
+    assert_eq!(x.trim(), -2);
+    assert_eq!(x.pin(2), 0b01);
+    assert!(x.hi());
+    assert_eq!(WideConfig::default().0, [1, 1, 1]);
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(debug, ready(r, 7), code(rw, 0, 3), wide(rw, 35, 2))]
+pub struct Status(u64);
+
+#[test]
+fn debug() {
+    assert_eq!(
+        ::std::format!("{:?}", Status(0x18_0000_0085)),
+        "Status { ready: true, code: 5, wide: 3 }"
+    );
+}
+
+#[test]
+fn fields() {
+    use ::drone_core::bitfield::{FieldInfo, FieldMode};
+    assert_eq!(Byte::FIELDS.len(), 3);
+    assert_eq!(Byte::FIELDS[2], FieldInfo {
+        name: "baz",
+        offset: 3,
+        width: 3,
+        mode: FieldMode::Write
+    });
+    let names = Channels::FIELDS.iter().map(|field| field.name).collect::<::std::vec::Vec<_>>();
+    assert_eq!(names, [
+        "ch[0]", "ch[1]", "ch[2]", "ch[3]", "en[0]", "en[1]", "en[2]", "en[3]", "lvl[0]", "lvl[1]"
+    ]);
+    assert_eq!(Channels::FIELDS[5].offset, 10);
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(foo(w, 7), bar(w, 4, 2), baz(w, 0, 3))]
+pub struct ByteSet(u32);
+
+#[test]
+fn copy_fields_from() {
+    let mut x = ByteSet(0xFFFF_0000);
+    x.copy_fields_from(&Byte(0b1010_1111));
+    assert_eq!(x.bits(), 0xFFFF_00B5);
+    let mut y = Byte(0);
+    y.copy_fields_from(&x);
+    assert_eq!(y.bits(), 0b0010_1111);
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(const_builder, en(rw, 0), mode(w, 1, 2, Mode), gain(rw_i, 4, 4), ch(rw, 8, 2, 4))]
+pub struct Preset(u16);
+
+const PRESET: Preset =
+    Preset(0).with_en(true).with_mode(Mode::Analog).with_gain(-3).with_ch(2, 0b10);
+const PRESET_GAIN: i16 = PRESET.gain();
+
+#[test]
+fn const_builder() {
+    assert_eq!(PRESET.bits(), 0x20D7);
+    assert_eq!(PRESET_GAIN, -3);
+    assert_eq!(PRESET.ch(2), 0b10);
+    let x = PRESET.with_en(false).with_gain(7).with_ch(2, 0b01);
+    assert_eq!(x.bits(), 0x1076);
+}
+
+#[derive(Bitfield, Copy, Clone)]
+#[bitfield(
+    lo(rw, 3..=0),
+    mid(rw, 11..=4, "Test range field."),
+    top(r, 31..=31),
+    ch(rw, 17..=16, 4)
+)]
+pub struct Ranges(u32);
+
+#[test]
+fn bit_ranges() {
+    let mut x = Ranges(0x8000_0000);
+    assert!(x.top());
+    x.write_lo(0xF).write_mid(0xAB).write_ch(3, 0b10);
+    assert_eq!(x.bits(), 0x8080_0ABF);
+    assert_eq!(x.mid(), 0xAB);
+}
@@ -0,0 +1,37 @@
+#![no_implicit_prelude]
+
+use ::drone_core::drv::{Driver, Resource};
+use ::drone_core::token::{simple_token, Token};
+use ::std::assert_eq;
+
+simple_token! {
+    pub struct FooToken;
+}
+
+#[derive(Resource)]
+pub struct FooRes {
+    pub foo: FooToken,
+    pub count: u32,
+}
+
+#[derive(Driver)]
+pub struct FooDrv {
+    state: u32,
+    #[resource]
+    res: FooRes,
+}
+
+#[derive(Driver)]
+pub struct TupleDrv(FooRes);
+
+#[test]
+fn new_free() {
+    let res = FooRes { foo: unsafe { FooToken::take() }, count: 3 };
+    let mut drv = FooDrv::new(res);
+    assert_eq!(drv.state, 0);
+    drv.state = drv.res.count;
+    let FooRes { foo, count } = drv.free();
+    assert_eq!(count, 3);
+    let drv = TupleDrv::new(FooRes { foo, count: 4 });
+    assert_eq!(drv.free().count, 4);
+}
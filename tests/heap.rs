@@ -3,13 +3,15 @@
 #![feature(slice_ptr_get)]
 #![no_implicit_prelude]
 
+use ::drone_core::heap::PoolFit;
 use ::drone_core::{heap, override_layout};
-use ::std::assert_eq;
+use ::std::{assert, assert_eq};
 use ::std::mem::size_of;
 
 override_layout! { r#"
 [ram]
 main = { origin = 0x20000000, size = "20K" }
+dtcm = { origin = 0x10000000, size = "8K" }
 
 [data]
 ram = "main"
@@ -24,12 +26,34 @@ pools = [
 ]
 
 [heap.secondary]
-ram = "main"
+ram = "dtcm"
 size = "6K"
 pools = [
     { block = "4", count = "896" },
     { block = "32", count = "80" },
 ]
+
+[heap.runtime]
+ram = "main"
+size = "384"
+pools = [
+    { block = "8", count = "16" },
+    { block = "64", count = "4" },
+]
+
+[heap.chained]
+ram = "main"
+size = "64"
+pools = [
+    { block = "16", count = "4" },
+]
+
+[heap.spare]
+ram = "main"
+size = "64"
+pools = [
+    { block = "32", count = "2" },
+]
 "# }
 
 heap! {
@@ -41,6 +65,7 @@ heap! {
     #[cfg_attr(not(feature = "host"), global_allocator)]
     #[doc = "test attribute"]
     instance => pub HEAP_PRIMARY;
+    fallback => HEAP_SECONDARY;
 }
 
 heap! {
@@ -48,13 +73,154 @@ heap! {
     metadata => pub HeapSecondary;
     instance => pub HEAP_SECONDARY;
     enable_trace_stream => 5;
+    unsafe dma => true;
+    paint => 0xA5;
+    zeroize => true;
+}
+
+heap! {
+    layout => runtime;
+    metadata => pub HeapRuntime;
+    instance => pub HEAP_RUNTIME;
+}
+
+heap! {
+    layout => chained;
+    metadata => pub HeapChained;
+    instance => pub HEAP_CHAINED;
+    fallback => HEAP_SPARE;
+}
+
+heap! {
+    layout => spare;
+    metadata => pub HeapSpare;
+    instance => pub HEAP_SPARE;
 }
 
 fn assert_global_alloc<T: ::core::alloc::GlobalAlloc>() {}
 
+fn assert_dma_alloc<T: heap::DmaAllocator>() {}
+
 #[test]
 fn size() {
     assert_global_alloc::<HeapPrimary>();
     assert_eq!(size_of::<HeapPrimary>(), size_of::<heap::Pool>() * 3 + size_of::<usize>());
     assert_eq!(size_of::<HeapSecondary>(), size_of::<heap::Pool>() * 2 + size_of::<usize>());
 }
+
+#[test]
+fn checkpoint() {
+    let checkpoint = HEAP_SECONDARY.checkpoint();
+    assert_eq!(checkpoint.sizes().len(), 2);
+    assert!(heap::diff(&checkpoint, &HEAP_SECONDARY.checkpoint()).is_empty());
+}
+
+#[test]
+fn fit_capacity() {
+    // Pool parameters are set by drone-ld, so they are zero on the host.
+    assert_eq!(HEAP_PRIMARY.fit_capacity::<u32>(3), 3);
+}
+
+#[test]
+fn stats() {
+    let primary = HEAP_PRIMARY.stats();
+    let secondary = HEAP_SECONDARY.stats();
+    assert_eq!(primary.pools().len(), 3);
+    assert_eq!(secondary.pools().len(), 2);
+    assert_eq!(secondary.allocated_bytes(), 0);
+}
+
+#[test]
+fn dma() {
+    assert_dma_alloc::<HeapSecondary>();
+    let handle: heap::DmaHeap = HEAP_SECONDARY.dma();
+    // Pool parameters are set by drone-ld, so the pools are empty on the host.
+    let layout = ::core::alloc::Layout::new::<u64>();
+    assert!(::core::alloc::Allocator::allocate(&handle, layout).is_err());
+}
+
+#[test]
+fn fallback() {
+    #[repr(align(64))]
+    struct Memory([u8; 64]);
+    static mut CHAINED: Memory = Memory([0; 64]);
+    static mut SPARE: Memory = Memory([0; 64]);
+    unsafe {
+        HeapChained::init_at(::core::ptr::addr_of_mut!(CHAINED).cast()).unwrap();
+        HeapSpare::init_at(::core::ptr::addr_of_mut!(SPARE).cast()).unwrap();
+    }
+    let layout = ::core::alloc::Layout::from_size_align(16, 4).unwrap();
+    let blocks = [(); 4].map(|()| {
+        ::core::alloc::Allocator::allocate(&HEAP_CHAINED, layout).unwrap()
+    });
+    assert_eq!(HEAP_CHAINED.stats().pools()[0].allocated(), 4);
+    assert_eq!(HEAP_SPARE.stats().pools()[0].allocated(), 0);
+    // The primary heap is exhausted, so the block comes from the fallback.
+    let spare = ::core::alloc::Allocator::allocate(&HEAP_CHAINED, layout).unwrap();
+    assert_eq!(HEAP_CHAINED.stats().pools()[0].allocated(), 4);
+    assert_eq!(HEAP_SPARE.stats().pools()[0].allocated(), 1);
+    // No primary pool fits the new size, so the block moves to the fallback.
+    let new_layout = ::core::alloc::Layout::from_size_align(32, 4).unwrap();
+    let grown = unsafe {
+        ::core::alloc::Allocator::grow(
+            &HEAP_CHAINED,
+            blocks[0].as_non_null_ptr(),
+            layout,
+            new_layout,
+        )
+        .unwrap()
+    };
+    assert_eq!(HEAP_CHAINED.stats().pools()[0].allocated(), 3);
+    assert_eq!(HEAP_SPARE.stats().pools()[0].allocated(), 2);
+    assert!(::core::alloc::Allocator::allocate(&HEAP_CHAINED, new_layout).is_err());
+    unsafe {
+        ::core::alloc::Allocator::deallocate(&HEAP_CHAINED, spare.as_non_null_ptr(), layout);
+        ::core::alloc::Allocator::deallocate(&HEAP_CHAINED, grown.as_non_null_ptr(), new_layout);
+        for block in &blocks[1..] {
+            ::core::alloc::Allocator::deallocate(&HEAP_CHAINED, block.as_non_null_ptr(), layout);
+        }
+    }
+    assert_eq!(HEAP_CHAINED.stats().pools()[0].allocated(), 0);
+    assert_eq!(HEAP_SPARE.stats().pools()[0].allocated(), 0);
+}
+
+#[test]
+fn init_runtime() {
+    let pools = [heap::PoolConfig { block: 0, count: 1 }; 2];
+    assert_eq!(
+        unsafe { HeapSecondary::init_runtime(&pools) },
+        ::core::result::Result::Err(heap::PoolConfigError::InvalidBlock(0))
+    );
+}
+
+#[test]
+fn init_runtime_override() {
+    #[repr(align(64))]
+    struct Memory([u8; 384]);
+    static mut MEMORY: Memory = Memory([0; 384]);
+    let layout = ::core::alloc::Layout::from_size_align(12, 4).unwrap();
+    unsafe { HeapRuntime::init_at(::core::ptr::addr_of_mut!(MEMORY).cast()).unwrap() };
+    let block = ::core::alloc::Allocator::allocate(&HEAP_RUNTIME, layout).unwrap();
+    assert_eq!(HEAP_RUNTIME.stats().pools()[1].allocated(), 1);
+    let pools = [
+        heap::PoolConfig { block: 16, count: 8 },
+        heap::PoolConfig { block: 64, count: 4 },
+    ];
+    assert_eq!(
+        unsafe { HeapRuntime::init_runtime(&pools) },
+        ::core::result::Result::Err(heap::PoolConfigError::InUse)
+    );
+    unsafe { ::core::alloc::Allocator::deallocate(&HEAP_RUNTIME, block.as_non_null_ptr(), layout) };
+    assert_eq!(unsafe { HeapRuntime::init_runtime(&pools) }, ::core::result::Result::Ok(()));
+    let block = ::core::alloc::Allocator::allocate(&HEAP_RUNTIME, layout).unwrap();
+    let stats = HEAP_RUNTIME.stats();
+    assert_eq!(stats.pools()[0].block_size(), 16);
+    assert_eq!(stats.pools()[0].allocated(), 1);
+    assert_eq!(stats.pools()[1].allocated(), 0);
+    unsafe { ::core::alloc::Allocator::deallocate(&HEAP_RUNTIME, block.as_non_null_ptr(), layout) };
+}
+
+#[test]
+fn high_water() {
+    assert_eq!(HEAP_SECONDARY.high_water(), [0, 0]);
+}
@@ -202,3 +202,26 @@ fn loom_deallocate_deallocate() {
     });
     statemap_check_exhaustive(states);
 }
+
+#[test]
+fn loom_allocate_aba() {
+    loom::model(|| {
+        let (_addr, pool) = make_pool(2, 2);
+        let x = loom::thread::spawn(move || pool.allocate());
+        let y = loom::thread::spawn(move || {
+            let a = pool.allocate();
+            let b = pool.allocate();
+            if let Some(a) = a {
+                unsafe { pool.deallocate(a) };
+            }
+            b
+        });
+        let (x, y) = join_allocate!(x, y);
+        let z = pool.allocate().map_or(-1, |ptr| ptr.as_ptr() as isize);
+        assert!(pool.allocate().is_none());
+        let mut allocated = [x, y, z].into_iter().filter(|&ptr| ptr != -1).collect::<Vec<_>>();
+        assert_eq!(allocated.len(), 2);
+        allocated.dedup();
+        assert_eq!(allocated.len(), 2);
+    });
+}
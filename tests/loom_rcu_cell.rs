@@ -0,0 +1,28 @@
+#![cfg(loom)]
+
+#[macro_use]
+mod loom_helpers;
+
+use self::loom_helpers::*;
+use drone_core::sync::RcuCell;
+
+#[test]
+fn loom_read_replace() {
+    let states = statemap![0 => [1, 2], 1 => [0, 1]];
+    loom::model(|| {
+        let cell: &'static _ = Box::leak(Box::new(RcuCell::new(0_usize)));
+        cell.replace(1);
+        let writer = loom::thread::spawn(move || {
+            cell.replace(2);
+            cell.reclaim();
+            cell.reclaim()
+        });
+        let reader = loom::thread::spawn(move || *cell.read());
+        let reclaimed = writer.join().unwrap();
+        statemap_put(states, 0, reader.join().unwrap());
+        statemap_put(states, 1, usize::from(reclaimed));
+        assert!(cell.reclaim());
+        assert_eq!(*cell.read(), 2);
+    });
+    statemap_check_exhaustive(states);
+}
@@ -0,0 +1,27 @@
+#![cfg(loom)]
+
+#[macro_use]
+mod loom_helpers;
+
+use self::loom_helpers::*;
+use drone_core::sync::SeqLock;
+
+#[test]
+fn loom_read_write() {
+    let states = statemap![0 => [0, 1, 2]];
+    loom::model(|| {
+        let lock: &'static _ = Box::leak(Box::new(SeqLock::new((0_usize, 0_usize))));
+        let writer = loom::thread::spawn(move || unsafe { lock.write((1, 1)) });
+        let reader = loom::thread::spawn(move || lock.try_read());
+        writer.join().unwrap();
+        let value = reader.join().unwrap();
+        statemap_put(states, 0, match value {
+            None => 0,
+            Some((0, 0)) => 1,
+            Some((1, 1)) => 2,
+            _ => 3,
+        });
+        assert_eq!(lock.read(), (1, 1));
+    });
+    statemap_check_exhaustive(states);
+}
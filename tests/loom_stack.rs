@@ -0,0 +1,29 @@
+#![cfg(loom)]
+
+#[macro_use]
+mod loom_helpers;
+
+use self::loom_helpers::*;
+use drone_core::sync::stack::{Node, Stack};
+
+#[test]
+fn loom_push_pop() {
+    let states = statemap![0 => [0, 1]];
+    loom::model(|| {
+        let stack: &'static _ = Box::leak(Box::new(Stack::new()));
+        stack.push(Box::leak(Box::new(Node::new(0_usize))));
+        let pusher = loom::thread::spawn(move || stack.push(Box::leak(Box::new(Node::new(1)))));
+        let popper = loom::thread::spawn(move || stack.pop().map(|node| node.value));
+        pusher.join().unwrap();
+        let popped = popper.join().unwrap();
+        statemap_put(states, 0, popped.unwrap());
+        let mut rest = Vec::new();
+        while let Some(node) = stack.pop() {
+            rest.push(node.value);
+        }
+        rest.extend(popped);
+        rest.sort_unstable();
+        assert_eq!(rest, [0, 1]);
+    });
+    statemap_check_exhaustive(states);
+}
@@ -3,10 +3,20 @@
 
 use ::drone_core::bitfield::Bitfield;
 use ::drone_core::reg;
-use ::drone_core::reg::prelude::*;
+use ::drone_core::reg::marker::{SRReg, SWReg, URRegFieldBits, UWRegFieldBits};
+use ::drone_core::reg::prelude::v2::*;
+use ::drone_core::testing;
+use ::drone_core::thr;
+use ::drone_core::thr::prelude::*;
+use ::drone_core::thr::Thread;
 use ::drone_core::token::Token;
-use ::std::assert_eq;
+use ::futures::FutureExt;
+use ::std::{assert, assert_eq};
+use ::std::convert::From;
+use ::std::format;
 use ::std::mem::{size_of, size_of_val};
+use ::std::result::Result::{Err, Ok};
+use ::std::string::String;
 
 reg! {
     /// Provides identification information for the processor.
@@ -117,9 +127,215 @@ reg! {
                 traits => { RRRegField WWRegField };
             };
         };
+        groups => {
+            /// Output Compare 1 configuration.
+            OC1 => { OC1M OC1PE };
+        };
+    };
+}
+
+reg! {
+    /// Prescaler.
+    pub TIM2 PSC => {
+        address => 0x4000_0028;
+        size => 0x20;
+        reset => 0x0000_0000;
+        traits => { RReg WReg };
+        fields => {
+            /// Prescaler value.
+            PSC => {
+                offset => 0;
+                width => 16;
+                traits => { RRRegField WWRegField };
+                convert => {
+                    type => u32;
+                    read => super::psc_to_hz;
+                    write => super::hz_to_psc;
+                };
+            };
+        };
+    };
+}
+
+reg! {
+    /// Control register 2.
+    pub TIM2 CR2 => {
+        address => 0x4000_0004;
+        size => 0x20;
+        reset => 0x0000_0800;
+        reserved => { mask => 0xFFFF_FF00; preserve => reset; };
+        traits => { RReg WReg };
+        fields => {
+            /// Master mode selection.
+            MMS => {
+                offset => 4;
+                width => 3;
+                traits => { RRRegField WWRegField };
+            };
+        };
+    };
+}
+
+reg! {
+    /// Status register.
+    pub TIM2 SR => {
+        address => 0x4000_0010;
+        size => 0x20;
+        reset => 0x0000_0000;
+        debug => true;
+        bitband => true;
+        traits => { RReg WReg };
+        fields => {
+            /// Update interrupt flag.
+            UIF => {
+                offset => 0;
+                width => 1;
+                traits => { RRRegField WWRegField W0CRegField };
+                as => update_pending;
+                alias => "update interrupt";
+            };
+            /// Capture/Compare 1 overcapture flag.
+            CC1OF => {
+                offset => 9;
+                width => 1;
+                traits => { RRRegField WWRegField };
+                as => val_overcapture;
+            };
+        };
+    };
+}
+
+reg! {
+    /// Alternate function registers.
+    pub GPIOA AFR => {
+        address => 0x4002_0020;
+        size => 0x20;
+        reset => 0x0000_0000;
+        array => { count => 2; stride => 0x4; };
+        traits => { RReg WReg };
+        fields => {
+            /// Alternate function selection for the first pin.
+            AFSEL0 => {
+                offset => 0;
+                width => 4;
+                traits => { RRRegField WWRegField };
+            };
+        };
+    };
+}
+
+reg! {
+    /// Data register.
+    pub SPI1 DR => {
+        address => 0x4001_300C;
+        size => 0x10;
+        reset => 0x0000;
+        traits => { RReg WReg };
+        fields => {
+            /// Data register.
+            DR => {
+                offset => 0;
+                width => 16;
+                traits => { RRRegField WWRegField };
+            };
+        };
+    };
+}
+
+reg! {
+    /// Timestamp register.
+    pub ETH PTPTS => {
+        address => 0x4002_8708;
+        size => 0x40;
+        reset => 0x0000_0000_0000_0000;
+        traits => { RReg WReg };
+        fields => {
+            /// Subseconds.
+            STSS => {
+                offset => 0;
+                width => 31;
+                traits => { RRRegField WWRegField };
+            };
+            /// Seconds.
+            STS => {
+                offset => 32;
+                width => 32;
+                traits => { RRRegField WWRegField };
+            };
+        };
     };
 }
 
+reg! {
+    /// Clock configuration register.
+    pub RCC CFGR => {
+        address => 0x4002_1004;
+        size => 0x20;
+        reset => 0x0000_0000;
+        traits => { RReg WReg };
+        fields => {
+            /// System clock switch.
+            SW => {
+                offset => 0;
+                width => 2;
+                traits => { RRRegField WWRegField };
+                values => {
+                    /// HSI selected as system clock.
+                    Hsi => 0,
+                    /// HSE selected as system clock.
+                    Hse => 1,
+                    /// PLL selected as system clock.
+                    Pll => 2,
+                };
+            };
+            /// PLL entry clock source.
+            PLLSRC => {
+                offset => 16;
+                width => 1;
+                traits => { RRRegField WWRegField };
+                values => {
+                    /// HSI oscillator clock divided by 2.
+                    HsiDiv2 => 0,
+                    /// HSE oscillator clock.
+                    Hse => 1,
+                };
+            };
+        };
+    };
+}
+
+reg! {
+    /// Pending register.
+    pub EXTI PR => {
+        address => 0x4001_0414;
+        size => 0x20;
+        reset => 0x0000_0000;
+        traits => { RReg WReg };
+        fields => {
+            /// Pending bit 0.
+            PR0 => {
+                offset => 0;
+                width => 1;
+                traits => { RRRegField W1CRegField };
+            };
+            /// Pending bit 1.
+            PR1 => {
+                offset => 1;
+                width => 1;
+                traits => { RRRegField W1CRegField };
+            };
+        };
+    };
+}
+
+const fn psc_to_hz(psc: u32) -> u32 {
+    8_000_000 / (psc + 1)
+}
+
+const fn hz_to_psc(hz: u32) -> u32 {
+    8_000_000 / hz - 1
+}
+
 reg::tokens! {
     /// Intermediate register tokens macro.
     pub macro reg_tokens_intermediate;
@@ -132,6 +348,164 @@ reg::tokens! {
     }
 }
 
+reg! {
+    /// Capture/Compare mode register 1.
+    pub TIM3 CCMR1 => {
+        address => 0x4000_0418;
+        size => 0x20;
+        reset => 0x0000_0000;
+        traits => { RReg WReg };
+        fields => {
+            /// Capture/Compare 1 selection.
+            CC1S => {
+                offset => 0;
+                width => 2;
+                traits => { RRRegField WWRegField };
+            };
+        };
+        variants => {
+            /// Input mode.
+            Input => {
+                fields => {
+                    /// Input Capture 1 filter.
+                    IC1F => {
+                        offset => 4;
+                        width => 4;
+                        traits => { RRRegField WWRegField };
+                    };
+                };
+            };
+            /// Output mode.
+            Output => {
+                fields => {
+                    /// Output Compare 1 mode.
+                    OC1M => {
+                        offset => 4;
+                        width => 3;
+                        traits => { RRRegField WWRegField };
+                    };
+                };
+            };
+        };
+    };
+}
+
+reg! {
+    /// Data register.
+    pub USART DR => {
+        address => 0x04;
+        size => 0x20;
+        reset => 0x0000_0000;
+        instances => {
+            /// USART1 instance.
+            USART1 => 0x4001_3800;
+            /// USART2 instance.
+            USART2 => 0x4000_4400;
+        };
+        traits => { RReg WReg };
+        fields => {
+            /// Data value.
+            DR => {
+                offset => 0;
+                width => 9;
+                traits => { RRRegField WWRegField };
+            };
+        };
+    };
+}
+
+reg! {
+    /// Receive data register.
+    pub UART4 RDR => {
+        address => 0x4000_4C24;
+        size => 0x20;
+        reset => 0x0000_0000;
+        traits => { RRegSideEffect };
+        fields => {
+            /// Receive data value.
+            RDR => {
+                offset => 0;
+                width => 9;
+                traits => { RRRegField };
+            };
+        };
+    };
+}
+
+reg! {
+    /// Prescaler.
+    pub TIM3 PSC => {
+        address => 0x4000_0428;
+        size => 0x20;
+        reset => 0x0000_0000;
+        traits => { RReg WReg };
+        fields => {
+            /// Prescaler value.
+            PSC => {
+                offset => 0;
+                width => 16;
+                traits => { RRRegField WWRegField };
+                scale => { name => div; bias => 1; };
+            };
+        };
+    };
+}
+
+reg! {
+    /// Auto-reload register.
+    pub TIM2 ARR => {
+        address => 0x4000_002C;
+        size => 0x20;
+        reset => 0x0000_FFFF;
+        traits => { RReg WReg };
+        fields => {
+            /// Auto-reload value.
+            ARR => {
+                offset => 0;
+                width => 16;
+                traits => { RRRegField WWRegField };
+                scale => { name => period_ns; factor => 1000; unit => "ns" };
+            };
+        };
+    };
+}
+
+reg! {
+    /// DAC control register.
+    pub DAC CR => {
+        address => 0x4000_7400;
+        size => 0x20;
+        reset => 0x0000_0000;
+        reserved => { mask => 0xFF00_0000; preserve => reset; };
+        traits => { WReg WoReg Shadowed };
+        fields => {
+            /// DAC channel1 enable.
+            EN1 => {
+                offset => 0;
+                width => 1;
+                traits => { WWRegField WoWRegField };
+            };
+            /// DAC channel2 enable.
+            EN2 => {
+                offset => 16;
+                width => 1;
+                traits => { WWRegField WoWRegField };
+            };
+        };
+    };
+}
+
+reg! {
+    /// Capture/Compare mode register 3. (absent)
+    #[cfg(any())]
+    pub TIM1 CCMR3 => {
+        address => 0x4001_0054;
+        size => 0x20;
+        reset => 0x0000_0000;
+        traits => { RReg WReg };
+    };
+}
+
 reg::tokens! {
     /// Register tokens macro.
     pub macro reg_tokens;
@@ -143,6 +517,24 @@ reg::tokens! {
     pub mod TIM1 {
         CCMR1_Input;
         !CCMR1_Output;
+        /// Absent register.
+        #[cfg(any())]
+        CCMR3;
+    }
+
+    /// General-purpose I/O.
+    pub mod GPIOA {
+        AFR;
+    }
+
+    /// General-purpose timer.
+    pub mod TIM3 {
+        CCMR1;
+    }
+
+    /// Universal synchronous asynchronous receiver transmitter.
+    pub mod USART1 {
+        DR;
     }
 }
 
@@ -151,6 +543,16 @@ reg_tokens! {
     index => pub Regs;
 }
 
+thr::pool! {
+    thread => Thr {};
+    local => ThrLocal {};
+    index => Thrs;
+    threads => {
+        thr0;
+        thr1;
+    }
+}
+
 #[test]
 fn default_val() {
     assert_eq!(unsafe { scb::Cpuid::<Srt>::take() }.default_val().bits(), 0x410F_C241);
@@ -170,6 +572,42 @@ fn tokens() {
     let reg = unsafe { Regs::take() };
     assert_eq!(size_of_val(&reg.scb_cpuid), 0);
     assert_eq!(size_of_val(&reg.tim1_ccmr1_input), 0);
+    assert_eq!(size_of_val(&reg.gpioa_afr), 0);
+}
+
+#[test]
+fn register_sizes() {
+    assert_eq!(size_of::<spi1_dr::Val>(), 2);
+    assert_eq!(size_of::<eth_ptpts::Val>(), 8);
+    let spi1_dr = unsafe { spi1_dr::Reg::<Urt>::take() };
+    let mut val = spi1_dr.default_val();
+    spi1_dr.dr.write(&mut val, 0xBEEF);
+    assert_eq!(val.bits(), 0xBEEF_u16);
+    let eth_ptpts = unsafe { eth_ptpts::Reg::<Urt>::take() };
+    let mut val = eth_ptpts.default_val();
+    eth_ptpts.sts.write(&mut val, 0x1234_5678);
+    eth_ptpts.stss.write(&mut val, 0x0000_0042);
+    assert_eq!(val.bits(), 0x1234_5678_0000_0042_u64);
+    assert_eq!(eth_ptpts.hold(val).sts(), 0x1234_5678);
+    reg::mock::reset();
+    reg::mock::preload(0x4002_8708, 0x0000_0001_0000_0000);
+    let eth_ptpts = unsafe { eth_ptpts::Reg::<Srt>::take() };
+    eth_ptpts.modify(|r| r.write_stss(5));
+    assert_eq!(reg::mock::peek(0x4002_8708), 0x0000_0001_0000_0005);
+}
+
+#[test]
+fn arrays() {
+    let reg = unsafe { Regs::take() };
+    assert_eq!(gpioa_afr::LEN, 2);
+    assert_eq!(<gpioa_afr0::Reg<Srt> as reg::Reg<Srt>>::ADDRESS, 0x4002_0020);
+    assert_eq!(<gpioa_afr1::Reg<Srt> as reg::Reg<Srt>>::ADDRESS, 0x4002_0024);
+    let mut val = reg.gpioa_afr.1.default_val();
+    reg.gpioa_afr.1.afsel0.write(&mut val, 0b0111);
+    assert_eq!(val.bits(), 0x0000_0007);
+    let afr1 = reg.gpioa_afr.reg::<1>();
+    assert_eq!(afr1.as_ptr() as usize, 0x4002_0024);
+    assert_eq!(reg.gpioa_afr.reg::<0>().as_ptr() as usize, 0x4002_0020);
 }
 
 #[test]
@@ -178,3 +616,428 @@ fn variants() {
     let output: tim1::Ccmr1Output<Srt> = input.into_tim1_ccmr1_output();
     let _input: tim1::Ccmr1Input<Srt> = output.into_tim1_ccmr1_input();
 }
+
+#[test]
+fn variant_views() {
+    let reg = unsafe { Regs::take() };
+    let input: tim3_ccmr1_input::Reg<Srt> = reg.tim3_ccmr1;
+    let mut val = input.default_val();
+    input.ic1f.write(&mut val, 0b1010);
+    input.cc1s.write(&mut val, 0b01);
+    assert_eq!(val.bits(), 0x0000_00A1);
+    let output = input.into_tim3_ccmr1_output();
+    let mut val = output.default_val();
+    output.oc1m.write(&mut val, 0b110);
+    assert_eq!(val.bits(), 0x0000_0060);
+    let _input: tim3::Ccmr1<Srt> = output.into_tim3_ccmr1_input();
+}
+
+#[test]
+fn convert() {
+    let psc = unsafe { tim2_psc::Reg::<Urt>::take() };
+    let mut val = psc.default_val();
+    psc.psc.write_converted(&mut val, 1_000_000);
+    assert_eq!(val.bits(), 7);
+    assert_eq!(psc.psc.read_converted(&val), 1_000_000);
+    assert_eq!(psc.hold(val).write_psc_converted(2_000_000).psc_converted(), 2_000_000);
+}
+
+#[test]
+fn reserved() {
+    let cr2 = unsafe { tim2_cr2::Reg::<Urt>::take() };
+    assert_eq!(cr2.preserve_reserved(0xFFFF_FFFF), 0x0000_08FF);
+    assert_eq!(cr2.preserve_reserved(0x0000_0070), 0x0000_0870);
+    let psc = unsafe { tim2_psc::Reg::<Urt>::take() };
+    assert_eq!(psc.preserve_reserved(0xFFFF_FFFF), 0xFFFF_FFFF);
+}
+
+#[test]
+fn take_const() {
+    static REGS: Regs = unsafe { Regs::take_const() };
+    static CPUID: scb::Cpuid<Crt> = unsafe { scb::Cpuid::<Crt>::take_const() };
+    assert_eq!(size_of_val(&REGS.scb_cpuid), 0);
+    assert_eq!(CPUID.default_val().bits(), 0x410F_C241);
+}
+
+#[test]
+fn markers() {
+    fn readable<R: SRReg>(_reg: &R) {}
+    fn writable<R: SWReg>(_reg: &R) {}
+    fn field<F: URRegFieldBits + UWRegFieldBits>(_field: &F) {}
+    let cpuid = unsafe { scb::Cpuid::<Srt>::take() };
+    let psc = unsafe { tim2_psc::Reg::<Srt>::take() };
+    readable(&cpuid);
+    readable(&psc);
+    writable(&psc);
+    field(&unsafe { tim2_psc::Reg::<Urt>::take() }.psc);
+}
+
+#[test]
+fn layout_checks() {
+    let output = unsafe { tim1_ccmr1_output::Reg::<Urt>::take() };
+    testing::check_field_bit(&output.oc1ce);
+    testing::check_field_bits(&output.oc1m);
+    testing::check_field_bit(&output.oc1pe);
+    testing::check_field_bit(&output.oc1fe);
+    testing::check_field_bits(&output.cc1s);
+    testing::check_hold_bit::<Urt, _, _, _>(&output, 15, |hold| hold.oc1ce(), |hold, bit| {
+        hold.write_oc1ce(bit);
+    });
+    testing::check_hold_bits::<Urt, _, _, _>(&output, 12, 3, |hold| hold.oc1m(), |hold, bits| {
+        hold.write_oc1m(bits);
+    });
+    testing::check_layout::<u32>(&[(15, 1), (12, 3), (11, 1), (10, 1), (8, 2)]);
+}
+
+#[test]
+fn accessor_names() {
+    let sr = unsafe { tim2_sr::Reg::<Urt>::take() };
+    let mut hold = sr.default();
+    hold.set_update_pending().write_val_overcapture(true);
+    assert!(hold.update_pending());
+    assert!(hold.val_overcapture());
+    assert_eq!(hold.val().bits(), 0x0000_0201);
+    assert!(sr.uif.read(&hold.val()));
+}
+
+#[test]
+fn groups() {
+    let output = unsafe { tim1_ccmr1_output::Reg::<Urt>::take() };
+    let mut hold = output.default();
+    hold.write_oc1(0b110, true);
+    assert_eq!(hold.oc1m(), 0b110);
+    assert!(hold.oc1pe());
+    assert_eq!(hold.val().bits(), 0x0000_6800);
+}
+
+#[test]
+fn mock_memory() {
+    reg::mock::reset();
+    let mut cr2 = unsafe { tim2_cr2::Reg::<Urt>::take() };
+    reg::mock::preload_reset(&cr2);
+    cr2.modify(|r| r.write_mms(0b010));
+    assert_eq!(cr2.load().mms(), 0b010);
+    assert_eq!(reg::mock::take_writes(), [(0x4000_0004, 0x0000_0820)]);
+    let sr = unsafe { tim2_sr::Reg::<Srt>::take() };
+    let mut polls = 0;
+    reg::mock::on_read(0x4000_0010, move |value| {
+        polls += 1;
+        if polls == 2 { value | 1 } else { value }
+    });
+    assert!(!sr.uif.read_bit());
+    assert!(sr.uif.read_bit());
+    sr.uif.clear_bit();
+    assert_eq!(reg::mock::peek(0x4000_0010), 0);
+    assert_eq!(reg::mock::writes(), [(0x4000_0010, 0x0000_0000)]);
+}
+
+#[test]
+fn wait_for_bit() {
+    reg::mock::reset();
+    let thr = unsafe { Thr0::take() };
+    let sr = unsafe { tim2_sr::Reg::<Srt>::take() };
+    let (uif, cc1of) = sr.into_fields();
+    reg::mock::preload(0x4000_0010, 0x0000_0001);
+    let mut set = uif.wait_for_set(thr);
+    let clear = cc1of.wait_for_clear(thr);
+    assert!((&mut set).now_or_never().is_none());
+    unsafe { thr.to_thr().fib_chain().drain() };
+    let uif = set.now_or_never().unwrap();
+    let cc1of = clear.now_or_never().unwrap();
+    reg::mock::preload(0x4000_0010, 0x0000_0000);
+    let mut set = uif.wait_for_set(thr);
+    let clear = cc1of.wait_for_clear(thr);
+    for _ in 0..3 {
+        unsafe { thr.to_thr().fib_chain().drain() };
+        assert!((&mut set).now_or_never().is_none());
+    }
+    assert!(clear.now_or_never().is_some());
+    reg::mock::preload(0x4000_0010, 0x0000_0201);
+    unsafe { thr.to_thr().fib_chain().drain() };
+    assert!((&mut set).now_or_never().is_some());
+    unsafe { thr.to_thr().fib_chain().drain() };
+    assert!(thr.to_thr().fib_chain().is_empty());
+}
+
+#[test]
+fn poll_until() {
+    reg::mock::reset();
+    let thr = unsafe { Thr1::take() };
+    let cr2 = unsafe { tim2_cr2::Reg::<Srt>::take() };
+    let mut ready = reg::poll_until(cr2.mms, thr, |mms| mms.read_bits() == 0b010);
+    reg::mock::preload(0x4000_0004, 0x0000_0010);
+    for _ in 0..2 {
+        unsafe { thr.to_thr().fib_chain().drain() };
+        assert!((&mut ready).now_or_never().is_none());
+    }
+    reg::mock::preload(0x4000_0004, 0x0000_0020);
+    unsafe { thr.to_thr().fib_chain().drain() };
+    let mms = ready.now_or_never().unwrap();
+    // A timeout is a part of the predicate, which gives up after a number of
+    // activations.
+    let mut polls = 0;
+    let mut timeout = reg::poll_until(mms, thr, move |mms| {
+        polls += 1;
+        mms.read_bits() == 0b111 || polls == 3
+    });
+    for _ in 0..2 {
+        unsafe { thr.to_thr().fib_chain().drain() };
+        assert!((&mut timeout).now_or_never().is_none());
+    }
+    unsafe { thr.to_thr().fib_chain().drain() };
+    let mms = timeout.now_or_never().unwrap();
+    assert_eq!(mms.read_bits(), 0b010);
+    assert!(thr.to_thr().fib_chain().is_empty());
+}
+
+#[test]
+fn values() {
+    let cfgr = unsafe { rcc_cfgr::Reg::<Urt>::take() };
+    let mut hold = cfgr.default();
+    assert_eq!(hold.sw(), Ok(rcc_cfgr::SwValue::Hsi));
+    assert_eq!(hold.pllsrc(), rcc_cfgr::PllsrcValue::HsiDiv2);
+    hold.write_sw(rcc_cfgr::SwValue::Pll).write_pllsrc(rcc_cfgr::PllsrcValue::Hse);
+    assert_eq!(hold.val().bits(), 0x0001_0002);
+    assert_eq!(hold.sw(), Ok(rcc_cfgr::SwValue::Pll));
+    assert_eq!(hold.pllsrc(), rcc_cfgr::PllsrcValue::Hse);
+    let mut val = hold.val();
+    cfgr.sw.write(&mut val, 0b11);
+    assert_eq!(cfgr.hold(val).sw(), Err(0b11));
+    assert_eq!(u32::from(rcc_cfgr::SwValue::Hse), 1);
+}
+
+#[test]
+fn clear_flags() {
+    reg::mock::reset();
+    reg::mock::preload(0x4001_0414, 0b11);
+    let pr = unsafe { exti_pr::Reg::<Srt>::take() };
+    pr.clear_pr1();
+    pr.pr0.clear_flag();
+    let sr = unsafe { tim2_sr::Reg::<Srt>::take() };
+    sr.clear_update_pending();
+    assert_eq!(reg::mock::take_writes(), [
+        (0x4001_0414, 0x0000_0002),
+        (0x4001_0414, 0x0000_0001),
+        (0x4000_0010, 0xFFFF_FFFE),
+    ]);
+}
+
+#[cfg(feature = "reg-trace")]
+#[test]
+fn trace_hook() {
+    use ::drone_core::reg::trace::{self, Access};
+    use ::std::cell::RefCell;
+    use ::std::vec::Vec;
+    ::std::thread_local! {
+        static EVENTS: RefCell<Vec<(usize, u64, Access)>> = RefCell::new(Vec::new());
+    }
+    fn hook(address: usize, value: u64, access: Access) {
+        EVENTS.with(|events| events.borrow_mut().push((address, value, access)));
+    }
+    reg::mock::reset();
+    reg::mock::preload(0x4000_0004, 0x0000_0800);
+    let cr2 = unsafe { tim2_cr2::Reg::<Srt>::take() };
+    trace::set_hook(hook);
+    assert_eq!(cr2.load().mms(), 0);
+    cr2.store(|r| r.write_mms(0b001));
+    cr2.modify(|r| r.write_mms(0b010));
+    trace::clear_hook();
+    EVENTS.with(|events| {
+        let events = events.borrow();
+        assert_eq!(events[..2], [
+            (0x4000_0004, 0x0000_0800, Access::Read),
+            (0x4000_0004, 0x0000_0810, Access::Write),
+        ]);
+        // The modification reads the register at least once, and ends with a
+        // write.
+        assert!(events[2..].contains(&(0x4000_0004, 0x0000_0810, Access::Read)));
+        let last = events.last().copied();
+        assert_eq!(last, ::std::option::Option::Some((0x4000_0004, 0x0000_0820, Access::Write)));
+    });
+}
+
+#[test]
+fn modify_field() {
+    reg::mock::reset();
+    reg::mock::preload(0x4000_0004, 0x0000_08A5);
+    let mut cr2 = unsafe { tim2_cr2::Reg::<Urt>::take() };
+    cr2.mms.modify_field(|mms| mms + 1);
+    assert_eq!(reg::mock::peek(0x4000_0004), 0x0000_08B5);
+    reg::mock::preload(0x4000_0010, 0x0000_0200);
+    let sr = unsafe { tim2_sr::Reg::<Srt>::take() };
+    sr.uif.modify_field(|uif| uif | 1);
+    assert_eq!(reg::mock::take_writes(), [(0x4000_0004, 0x0000_08B5), (0x4000_0010, 0x0000_0201)]);
+}
+
+#[test]
+fn debug() {
+    let sr = unsafe { tim2_sr::Reg::<Urt>::take() };
+    let mut hold = sr.default();
+    hold.set_update_pending();
+    assert_eq!(format!("{hold:?}"), "TIM2_SR { UIF: 1, CC1OF: 0 }");
+}
+
+#[test]
+fn instances() {
+    let reg = unsafe { Regs::take() };
+    assert_eq!(size_of_val(&reg.usart1_dr), 0);
+    assert_eq!(<usart1_dr::Reg<Srt> as reg::Reg<Srt>>::ADDRESS, 0x4001_3804);
+    assert_eq!(<usart2_dr::Reg<Srt> as reg::Reg<Srt>>::ADDRESS, 0x4000_4404);
+    reg::mock::reset();
+    let mut dr = unsafe { usart2_dr::Reg::<Urt>::take() };
+    dr.store(|r| r.write_dr(0x41));
+    assert_eq!(reg::mock::take_writes(), [(0x4000_4404, 0x0000_0041)]);
+}
+
+#[test]
+fn batch() {
+    reg::mock::reset();
+    let cr2 = unsafe { tim2_cr2::Reg::<Srt>::take() };
+    let sr = unsafe { tim2_sr::Reg::<Srt>::take() };
+    reg::batch(|b| {
+        b.modify(&cr2, |r| r.write_mms(0b011));
+        b.store(&sr, |r| r.set_update_pending());
+    });
+    assert_eq!(reg::mock::take_writes(), [(0x4000_0004, 0x0000_0030), (0x4000_0010, 0x0000_0001)]);
+}
+
+#[test]
+fn side_effect() {
+    reg::mock::reset();
+    let mut fifo = 0x40;
+    reg::mock::on_read(0x4000_4C24, move |_| {
+        fifo += 1;
+        fifo
+    });
+    let rdr = unsafe { uart4_rdr::Reg::<Srt>::take() };
+    assert_eq!(rdr.load_consume().rdr(), 0x41);
+    assert_eq!(rdr.load_consume_bits(), 0x42);
+    assert_eq!(reg::mock::peek(0x4000_4C24), 0x42);
+}
+
+#[test]
+fn side_effect_on_other_register() {
+    reg::mock::reset();
+    reg::mock::preload(0x4000_4C1C, 0x0000_0020);
+    reg::mock::on_read(0x4000_4C24, |value| {
+        reg::mock::preload(0x4000_4C1C, reg::mock::peek(0x4000_4C1C) & !0x20);
+        value
+    });
+    let rdr = unsafe { uart4_rdr::Reg::<Srt>::take() };
+    rdr.load_consume_bits();
+    assert_eq!(reg::mock::peek(0x4000_4C1C), 0);
+}
+
+#[test]
+fn bit_band() {
+    reg::mock::reset();
+    let sr = unsafe { tim2_sr::Reg::<Srt>::take() };
+    let address = <tim2_sr::Uif<Srt> as reg::field::RegFieldBitBand<Srt>>::BIT_BAND_ADDRESS;
+    assert_eq!(address, 0x4200_0200);
+    sr.cc1of.set_bit_band();
+    sr.uif.clear_bit_band();
+    assert_eq!(reg::mock::take_writes(), [(0x4200_0224, 1), (0x4200_0200, 0)]);
+    reg::mock::preload(0x4200_0224, 1);
+    assert!(sr.cc1of.read_bit_band());
+}
+
+#[test]
+fn shadowed() {
+    reg::mock::reset();
+    let cr = unsafe { dac_cr::Reg::<Srt>::take() };
+    cr.store_shadowed(|r| r.set_en1());
+    cr.modify(|r| r.set_en2());
+    cr.modify(|r| r.clear_en1());
+    assert_eq!(
+        reg::mock::take_writes(),
+        [(0x4000_7400, 0x0000_0001), (0x4000_7400, 0x0001_0001), (0x4000_7400, 0x0001_0000)]
+    );
+    assert_eq!(cr.load_shadow().val().bits(), 0x0001_0000);
+    cr.modify(|r| {
+        r.set_val(Bitfield::from_bits(0xFF01_0001));
+        r
+    });
+    assert_eq!(reg::mock::take_writes(), [(0x4000_7400, 0x0001_0001)]);
+    assert_eq!(cr.load_shadow().val().bits(), 0x0001_0001);
+    let saved = cr.save();
+    cr.store_shadowed(|r| r.set_en2());
+    cr.restore(saved);
+    assert_eq!(
+        reg::mock::take_writes(),
+        [(0x4000_7400, 0x0001_0000), (0x4000_7400, 0x0001_0001)]
+    );
+    assert_eq!(cr.load_shadow().val().bits(), 0x0001_0001);
+}
+
+#[test]
+fn scale() {
+    let psc = unsafe { tim3_psc::Reg::<Urt>::take() };
+    let mut hold = psc.default();
+    assert_eq!(hold.read_div(), 1);
+    hold.write_div(72).unwrap();
+    assert_eq!(hold.psc(), 71);
+    assert_eq!(hold.write_div(0).err(), ::std::option::Option::Some(reg::field::ScaleError));
+    assert_eq!(hold.write_div(0x1_0001).err(), ::std::option::Option::Some(reg::field::ScaleError));
+    assert_eq!(hold.psc(), 71);
+    hold.write_div(0x1_0000).unwrap();
+    assert_eq!(hold.read_div(), 0x1_0000);
+    let arr = unsafe { tim2_arr::Reg::<Urt>::take() };
+    let mut hold = arr.default();
+    assert_eq!(hold.read_period_ns(), 65_535_000);
+    hold.write_period_ns(20_000).unwrap();
+    assert_eq!(hold.val().bits(), 20);
+    assert!(hold.write_period_ns(65_536_000).is_err());
+}
+
+#[test]
+fn c_header() {
+    let mut header = String::new();
+    Regs::write_c_header(&mut header).unwrap();
+    assert!(header.contains("#define SCB_CPUID_ADDR 0xE000ED00UL\n"));
+    assert!(header.contains("#define SCB_CPUID_RESET 0x410FC241UL\n"));
+    assert!(header.contains("#define SCB_CPUID_IMPLEMENTER_Pos 24U\n"));
+    assert!(header.contains("#define SCB_CPUID_IMPLEMENTER_Msk 0xFF000000UL\n"));
+}
+
+#[test]
+fn snapshot() {
+    reg::mock::reset();
+    reg::mock::preload(0x4000_0004, 0x0000_0820);
+    reg::mock::preload(0x4000_002C, 0x0000_1234);
+    let cr2 = unsafe { tim2_cr2::Reg::<Srt>::take() };
+    let arr = unsafe { tim2_arr::Reg::<Srt>::take() };
+    let saved = reg::snapshot!(cr2, arr);
+    reg::mock::preload(0x4000_0004, 0);
+    reg::mock::preload(0x4000_002C, 0xFFFF);
+    reg::snapshot!(restore saved => cr2, arr);
+    assert_eq!(
+        reg::mock::take_writes(),
+        [(0x4000_0004, 0x0000_0820), (0x4000_002C, 0x0000_1234)]
+    );
+    let saved = cr2.save();
+    cr2.reset();
+    cr2.restore(saved);
+    assert_eq!(reg::mock::peek(0x4000_0004), 0x0000_0820);
+}
+
+#[test]
+fn snapshot_clear_on_write_fields() {
+    reg::mock::reset();
+    reg::mock::preload(0x4000_0010, 0x0000_0200);
+    let sr = unsafe { tim2_sr::Reg::<Srt>::take() };
+    let saved = sr.save();
+    sr.restore(saved);
+    assert_eq!(reg::mock::take_writes(), [(0x4000_0010, 0x0000_0201)]);
+}
+
+#[test]
+fn fields_into_sync() {
+    reg::mock::reset();
+    let sr = unsafe { tim2_sr::Reg::<Urt>::take() };
+    let (uif, cc1of) = sr.into_fields();
+    let (uif, cc1of) = tim2_sr::Reg::<Urt>::fields_into_sync((uif, cc1of));
+    cc1of.set_bit();
+    assert!(cc1of.read_bit());
+    let sr = tim2_sr::Reg::<Srt>::from_fields((uif, cc1of));
+    assert_eq!(sr.load_bits(), 0x0000_0200);
+}
@@ -0,0 +1,48 @@
+#![feature(proc_macro_hygiene)]
+#![no_implicit_prelude]
+
+use ::drone_core::bitfield::Bitfield;
+use ::drone_core::reg;
+use ::drone_core::reg::prelude::*;
+use ::drone_core::token::Token;
+use ::std::{assert, assert_eq};
+use ::std::result::Result::Ok;
+
+reg::from_svd! {
+    path => "tests/svd/device.svd";
+}
+
+#[test]
+fn addresses() {
+    assert_eq!(<wdg_kr::Reg<Srt> as reg::Reg<Srt>>::ADDRESS, 0x4000_3000);
+    assert_eq!(<wdg_sr::Reg<Srt> as reg::Reg<Srt>>::ADDRESS, 0x4000_3008);
+    assert_eq!(<wdg_cnt0::Reg<Srt> as reg::Reg<Srt>>::ADDRESS, 0x4000_3010);
+    assert_eq!(<wdg_cnt1::Reg<Srt> as reg::Reg<Srt>>::ADDRESS, 0x4000_3014);
+    assert_eq!(<wdg2_sr::Reg<Srt> as reg::Reg<Srt>>::ADDRESS, 0x4000_3408);
+    assert_eq!(<wdg_sr::Reg<Srt> as reg::Reg<Srt>>::RESET, 0x0000_0001);
+}
+
+#[test]
+fn fields() {
+    reg::mock::reset();
+    let kr = unsafe { wdg_kr::Reg::<Srt>::take() };
+    kr.store(|r| r.write_key(0xCCCC));
+    let sr = unsafe { wdg_sr::Reg::<Srt>::take() };
+    sr.clear_ewif();
+    assert_eq!(reg::mock::take_writes(), [(0x4000_3000, 0x0000_CCCC), (0x4000_3008, 0x0000_0002)]);
+    let mut hold = sr.default();
+    assert!(hold.pvu());
+    assert_eq!(hold.mode(), Ok(wdg_sr::ModeValue::Off));
+    hold.write_mode(wdg_sr::ModeValue::Window);
+    assert_eq!(hold.val().bits(), 0x0000_0021);
+    assert!(!hold.mode0());
+}
+
+#[test]
+fn write_once() {
+    reg::mock::reset();
+    let lock = unsafe { wdg_lock::Reg::<Srt>::take() };
+    lock.store(|r| r.set_lock());
+    assert!(lock.load().lock());
+    assert_eq!(reg::mock::take_writes(), [(0x4000_3018, 0x0000_0001)]);
+}
@@ -2,10 +2,19 @@
 #![no_implicit_prelude]
 
 use ::drone_core::thr;
-use ::drone_core::thr::{pending_size, SoftThrToken, SoftThread, ThrExec, PRIORITY_LEVELS};
+use ::drone_core::thr::{
+    is_pool_paused, pause_pool, pending_size, resume_pool, try_pause_pool, DynThr, SoftThrToken,
+    SoftThread, StateDump, ThrExec, Thread, PRIORITY_LEVELS,
+};
 use ::drone_core::token::Token;
 use ::std::assert_eq;
 use ::std::clone::Clone;
+use ::futures::FutureExt;
+use ::std::format;
+use ::std::option::Option::Some;
+use ::std::rc::Rc;
+use ::std::sync::atomic::AtomicBool;
+use ::std::sync::atomic::Ordering::SeqCst;
 use ::std::sync::{Arc, Mutex};
 use ::std::vec::Vec;
 
@@ -68,9 +77,9 @@ fn test_pending_size() {
             b28; b29; b30; b31; b32;
         };
     }
-    assert_eq!(pending_size::<Thr0>(), 1 + 0 * PRIORITY_LEVELS as usize);
-    assert_eq!(pending_size::<Thr32>(), 1 + 1 * PRIORITY_LEVELS as usize);
-    assert_eq!(pending_size::<Thr33>(), 1 + 2 * PRIORITY_LEVELS as usize);
+    assert_eq!(pending_size::<Thr0>(), 2 + 0 * PRIORITY_LEVELS as usize);
+    assert_eq!(pending_size::<Thr32>(), 2 + 1 * PRIORITY_LEVELS as usize);
+    assert_eq!(pending_size::<Thr33>(), 2 + 2 * PRIORITY_LEVELS as usize);
 }
 
 #[test]
@@ -113,3 +122,199 @@ fn test_priorities() {
         assert_eq!(cell, 0);
     }
 }
+
+#[test]
+fn test_dyn_thr() {
+    thr::soft! {
+        thread => Thr {};
+        local => ThrLocal {};
+        index => Thrs;
+        threads => { thr_0; thr_1; };
+    }
+    let Thrs { thr_0, thr_1 } = unsafe { Thrs::take() };
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let log_0 = Arc::clone(&log);
+    let log_1 = Arc::clone(&log);
+    thr_0.add_exec(async move {
+        log_0.lock().unwrap().push(0);
+    });
+    thr_1.add_exec(async move {
+        log_1.lock().unwrap().push(1);
+    });
+    let routes = [DynThr::new(thr_1), DynThr::new(thr_0)];
+    assert_eq!(routes[0].thr_idx(), 1);
+    assert!(!routes[0].is_empty());
+    routes[0].wakeup();
+    routes[1].waker().wake();
+    assert_eq!(*log.lock().unwrap(), &[1, 0]);
+    assert!(routes[0].is_empty());
+    assert!(routes[1].is_empty());
+}
+
+#[test]
+fn test_state_dump() {
+    thr::soft! {
+        thread => Thr {};
+        local => ThrLocal {};
+        index => Thrs;
+        threads => { thr_0; thr_1; thr_2; };
+    }
+    let Thrs { thr_0, thr_1, thr_2 } = unsafe { Thrs::take() };
+    thr_0.set_priority(0);
+    thr_1.set_priority(2);
+    thr_2.set_priority(2);
+    assert_eq!(
+        format!("{}", StateDump::<Thr>::new()),
+        "running priority: none\npending priorities:\n"
+    );
+    unsafe {
+        Thr::will_preempt(0);
+        Thr::will_preempt(2);
+    }
+    assert_eq!(
+        format!("{}", StateDump::<Thr>::new()),
+        "running priority: none\npending priorities: 2 0\npriority 2: 2\npriority 0: 0\n"
+    );
+    Thr::preempt();
+    assert_eq!(
+        format!("{}", StateDump::<Thr>::new()),
+        "running priority: none\npending priorities:\n"
+    );
+}
+
+#[test]
+fn test_critical_priority() {
+    thr::soft! {
+        thread => Thr {};
+        local => ThrLocal {};
+        index => Thrs;
+        threads => { thr_0; };
+        critical_priority => 2;
+    }
+    assert_eq!(Thr::CRITICAL_PRIORITY, ::std::option::Option::Some(2));
+    assert_eq!(Thr::critical_section(|| 1), 1);
+    let Thrs { thr_0 } = unsafe { Thrs::take() };
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let log_0 = Arc::clone(&log);
+    thr_0.add_exec(async move {
+        log_0.lock().unwrap().push(0);
+    });
+    thr_0.wakeup();
+    assert_eq!(*log.lock().unwrap(), &[0]);
+}
+
+#[test]
+fn test_exec_future_factory() {
+    thr::soft! {
+        thread => Thr {};
+        local => ThrLocal {};
+        index => Thrs;
+        threads => { thr_0; };
+    }
+    let Thrs { thr_0 } = unsafe { Thrs::take() };
+    let output = thr_0.add_exec_future_factory(|| {
+        let local = Rc::new(21);
+        async move { *local * 2 }
+    });
+    thr_0.wakeup();
+    assert_eq!(output.now_or_never(), Some(42));
+}
+
+#[test]
+fn test_pause_pool() {
+    thr::soft! {
+        thread => Thr {};
+        local => ThrLocal {};
+        index => Thrs;
+        threads => { thr_0; thr_1; };
+    }
+    let Thrs { thr_0, thr_1 } = unsafe { Thrs::take() };
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let log_0 = Arc::clone(&log);
+    let log_1 = Arc::clone(&log);
+    thr_0.add_exec(async move {
+        log_0.lock().unwrap().push(0);
+    });
+    thr_1.add_exec(async move {
+        log_1.lock().unwrap().push(1);
+    });
+    pause_pool::<Thr>();
+    assert!(is_pool_paused::<Thr>());
+    thr_0.wakeup();
+    thr_1.wakeup();
+    assert!(log.lock().unwrap().is_empty());
+    assert!(thr_0.is_pending());
+    assert!(thr_1.is_pending());
+    resume_pool::<Thr>();
+    assert!(!is_pool_paused::<Thr>());
+    assert_eq!(*log.lock().unwrap(), &[0, 1]);
+}
+
+#[test]
+fn test_pause_pool_running() {
+    thr::soft! {
+        thread => Thr {};
+        local => ThrLocal {};
+        index => Thrs;
+        threads => { thr_0; thr_1; };
+    }
+    let Thrs { thr_0, thr_1 } = unsafe { Thrs::take() };
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let log_0 = Arc::clone(&log);
+    let log_1 = Arc::clone(&log);
+    let (started_tx, started_rx) = ::std::sync::mpsc::channel();
+    let (release_tx, release_rx) = ::std::sync::mpsc::channel::<()>();
+    thr_0.add_exec(async move {
+        started_tx.send(()).unwrap();
+        release_rx.recv().unwrap();
+        log_0.lock().unwrap().push(0);
+    });
+    thr_1.add_exec(async move {
+        log_1.lock().unwrap().push(1);
+    });
+    let running = ::std::thread::spawn(move || thr_0.wakeup());
+    started_rx.recv().unwrap();
+    // `thr_0` is in the middle of its activation.
+    assert!(!try_pause_pool::<Thr>());
+    let paused = Arc::new(AtomicBool::new(false));
+    let pauser = ::std::thread::spawn({
+        let paused = Arc::clone(&paused);
+        move || {
+            pause_pool::<Thr>();
+            paused.store(true, SeqCst);
+        }
+    });
+    ::std::thread::sleep(::std::time::Duration::from_millis(10));
+    assert!(!paused.load(SeqCst));
+    thr_1.wakeup();
+    release_tx.send(()).unwrap();
+    running.join().unwrap();
+    pauser.join().unwrap();
+    assert!(paused.load(SeqCst));
+    assert!(try_pause_pool::<Thr>());
+    assert_eq!(*log.lock().unwrap(), &[0]);
+    assert!(thr_1.is_pending());
+    resume_pool::<Thr>();
+    assert_eq!(*log.lock().unwrap(), &[0, 1]);
+}
+
+#[test]
+fn test_on_preempt() {
+    thr::soft! {
+        thread => Thr {};
+        local => ThrLocal {};
+        index => Thrs;
+        threads => { thr_0; thr_1; };
+        on_preempt => on_preempt;
+    }
+    static PREEMPTIONS: Mutex<Vec<::std::option::Option<u16>>> = Mutex::new(Vec::new());
+    fn on_preempt() {
+        PREEMPTIONS.lock().unwrap().push(thr::current_idx::<Thr>());
+    }
+    unsafe fn outer(_thr: &'static Thr) {
+        unsafe { Thr::call(1, inner) };
+    }
+    unsafe fn inner(_thr: &'static Thr) {}
+    unsafe { Thr::call(0, outer) };
+    assert_eq!(PREEMPTIONS.lock().unwrap().last(), Some(&Some(1)));
+}
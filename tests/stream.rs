@@ -33,6 +33,10 @@ stream! {
     #[doc = "test attribute"]
     instance => pub STREAM0;
     global => true;
+    streams => {
+        11 => 64;
+        12 => 4096;
+    };
 }
 
 stream! {
@@ -40,3 +44,24 @@ stream! {
     metadata => pub Stream1;
     instance => pub STREAM1;
 }
+
+#[test]
+fn dedicated_buffers() {
+    use ::core::mem::{size_of, size_of_val};
+    use ::core::ptr::addr_of_mut;
+    use ::drone_core::_rt::drone_stream::Runtime;
+    use ::std::{assert, assert_eq};
+
+    assert_eq!(size_of_val(&STREAM0_STREAM_11), size_of::<Runtime>() + 64);
+    assert_eq!(size_of_val(&STREAM0_STREAM_12), size_of::<Runtime>() + 4096);
+    // The runtime structure is followed by the buffer, same as the shared one.
+    let runtime = unsafe { addr_of_mut!((*STREAM0_STREAM_12.get()).runtime) };
+    assert_eq!(STREAM0_STREAM_12.get().cast::<Runtime>(), runtime);
+    assert_eq!(drone_stream_dedicated_runtime(12), runtime);
+    assert!(drone_stream_dedicated_runtime(13).is_null());
+    assert_eq!(DRONE_STREAM_DEDICATED.count, 2);
+    let descriptor = &DRONE_STREAM_DEDICATED.descriptors[1];
+    assert_eq!(descriptor.stream, 12);
+    assert_eq!(descriptor.buffer_size, 4096);
+    assert_eq!(descriptor.runtime, runtime);
+}
@@ -0,0 +1,46 @@
+#![no_implicit_prelude]
+
+use ::drone_core::sv;
+use ::drone_core::sv::{Supervisor, SvService};
+use ::std::assert_eq;
+
+pub struct AddService {
+    pub value: u32,
+}
+
+pub struct MulService {
+    pub value: u32,
+}
+
+impl SvService for AddService {
+    unsafe fn handler(&mut self) {
+        self.value += 1;
+    }
+}
+
+impl SvService for MulService {
+    unsafe fn handler(&mut self) {
+        self.value *= 3;
+    }
+}
+
+sv! {
+    /// Test supervisor.
+    supervisor => pub Sv;
+    services => {
+        AddService;
+        MulService;
+    };
+}
+
+#[test]
+fn handle() {
+    let mut add = AddService { value: 1 };
+    let mut mul = MulService { value: 2 };
+    unsafe {
+        Sv::handle(0, (&mut add as *mut AddService).cast());
+        Sv::handle(1, (&mut mul as *mut MulService).cast());
+    }
+    assert_eq!(add.value, 2);
+    assert_eq!(mul.value, 6);
+}
@@ -8,12 +8,17 @@
 mod t {
     use ::drone_core::thr::prelude::*;
     use ::drone_core::thr::Thread;
+    use ::drone_core::sync::spsc::oneshot::Canceled;
     use ::drone_core::token::Token;
+    use ::drone_core::fib::IntervalPolicy;
     use ::drone_core::{fib, thr};
+    use ::std::assert;
     use ::std::assert_eq;
     use ::std::clone::Clone;
     use ::std::ops::Drop;
-    use ::std::sync::atomic::AtomicI8;
+    use ::std::option::Option::{None, Some};
+    use ::std::result::Result::{Err, Ok};
+    use ::std::sync::atomic::{AtomicI8, AtomicU32, AtomicUsize};
     use ::std::sync::atomic::Ordering::*;
     use ::std::sync::Arc;
 
@@ -40,6 +45,13 @@ mod t {
             thr0;
             thr1;
             thr2;
+            thr3;
+            thr4;
+            thr5;
+            thr6;
+            thr7;
+            thr8;
+            thr9;
         }
     }
 
@@ -111,4 +123,213 @@ mod t {
             assert_eq!(counter.load(Relaxed), -2);
         }
     }
+
+    #[test]
+    fn fiber_every() {
+        let counter = Arc::new(AtomicI8::new(0));
+        let inner = Counter(Arc::clone(&counter));
+        unsafe {
+            let thr = Thr3::take();
+            thr.add_every(3, move || {
+                if inner.0.fetch_add(1, Relaxed) < 1 { fib::Yielded(()) } else { fib::Complete(()) }
+            });
+            for _ in 0..2 {
+                thr.to_thr().fib_chain().drain();
+            }
+            assert_eq!(counter.load(Relaxed), 0);
+            thr.to_thr().fib_chain().drain();
+            assert_eq!(counter.load(Relaxed), 1);
+            for _ in 0..2 {
+                thr.to_thr().fib_chain().drain();
+            }
+            assert_eq!(counter.load(Relaxed), 1);
+            thr.to_thr().fib_chain().drain();
+            assert_eq!(counter.load(Relaxed), -3);
+        }
+    }
+
+    #[test]
+    fn fiber_every_ticks() {
+        static TICKS: AtomicU32 = AtomicU32::new(u32::MAX - 1);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let inner = Arc::clone(&calls);
+        unsafe {
+            let thr = Thr8::take();
+            thr.add_every_ticks(
+                4,
+                IntervalPolicy::CatchUp,
+                || TICKS.load(Relaxed),
+                move || {
+                    if inner.fetch_add(1, Relaxed) < 3 {
+                        fib::Yielded(())
+                    } else {
+                        fib::Complete(())
+                    }
+                },
+            );
+            TICKS.fetch_add(3, Relaxed);
+            thr.to_thr().fib_chain().drain();
+            assert_eq!(calls.load(Relaxed), 0);
+            // Two periods elapsed across the counter wrap-around.
+            TICKS.fetch_add(6, Relaxed);
+            thr.to_thr().fib_chain().drain();
+            assert_eq!(calls.load(Relaxed), 2);
+            thr.to_thr().fib_chain().drain();
+            assert_eq!(calls.load(Relaxed), 2);
+            TICKS.fetch_add(9, Relaxed);
+            thr.to_thr().fib_chain().drain();
+            assert_eq!(calls.load(Relaxed), 4);
+            assert!(thr.is_empty());
+        }
+    }
+
+    #[test]
+    fn fiber_weak() {
+        let counter = Arc::new(AtomicI8::new(0));
+        let inner = Counter(Arc::clone(&counter));
+        unsafe {
+            let thr = Thr4::take();
+            let mut weak = thr.add_fib_weak(fib::new_fn(move || {
+                if inner.0.fetch_add(1, Relaxed) < 1 { fib::Yielded(()) } else { fib::Complete(()) }
+            }));
+            assert!(weak.is_alive());
+            thr.to_thr().fib_chain().drain();
+            assert!(weak.is_alive());
+            thr.to_thr().fib_chain().drain();
+            assert!(!weak.is_alive());
+            assert_eq!(counter.load(Relaxed), -3);
+        }
+    }
+
+    #[test]
+    fn fiber_handle() {
+        let counter = Arc::new(AtomicI8::new(0));
+        let inner = Counter(Arc::clone(&counter));
+        unsafe {
+            let thr = Thr5::take();
+            let canceled = thr.add_fn_cancelable(move || {
+                inner.0.fetch_add(1, Relaxed);
+                fib::Yielded::<(), ()>(())
+            });
+            let complete = thr.add_once_cancelable(|| {});
+            assert!(!complete.is_complete());
+            thr.to_thr().fib_chain().drain();
+            assert!(complete.is_complete());
+            assert_eq!(counter.load(Relaxed), 1);
+            canceled.cancel();
+            thr.to_thr().fib_chain().drain();
+            assert_eq!(counter.load(Relaxed), -2);
+            assert!(thr.is_empty());
+        }
+    }
+
+    #[cfg(feature = "fib-priority")]
+    #[test]
+    fn fiber_priority() {
+        let order = Arc::new(AtomicUsize::new(0));
+        unsafe {
+            let thr = Thr6::take();
+            let push = |id| {
+                let order = Arc::clone(&order);
+                move || {
+                    order.fetch_update(Relaxed, Relaxed, |x| Some(x * 10 + id)).unwrap();
+                }
+            };
+            thr.add_once(push(1));
+            thr.with_priority::<2>().add_once(push(2));
+            thr.with_priority::<1>().add_fib(fib::new_once(push(3)));
+            thr.with_priority::<2>().add_once(push(4));
+            thr.to_thr().fib_chain().drain();
+            assert_eq!(order.load(Relaxed), 4231);
+            assert!(thr.is_empty());
+        }
+    }
+
+    #[test]
+    fn fiber_try() {
+        let counter = Arc::new(AtomicI8::new(0));
+        let inner = Counter(Arc::clone(&counter));
+        unsafe {
+            let thr = Thr7::take();
+            let mut failed = thr.add_try_fn(move || {
+                if inner.0.fetch_add(1, Relaxed) < 1 {
+                    Ok(fib::Yielded::<(), ()>(()))
+                } else {
+                    Err(7)
+                }
+            });
+            let mut succeeded = thr.add_try_once(|| Ok::<(), i32>(()));
+            thr.to_thr().fib_chain().drain();
+            assert_eq!(succeeded.try_recv(), Err(Canceled));
+            assert_eq!(failed.try_recv(), Ok(None));
+            thr.to_thr().fib_chain().drain();
+            assert_eq!(failed.try_recv(), Ok(Some(7)));
+            assert_eq!(counter.load(Relaxed), -3);
+            assert!(thr.is_empty());
+        }
+    }
+
+    #[test]
+    fn current_idx() {
+        unsafe fn outer(_thr: &'static Thr) {
+            assert_eq!(thr::current_idx::<Thr>(), Some(2));
+            assert_eq!(thr::preemption_depth::<Thr>(), 1);
+            unsafe { Thr::call(5, inner) };
+            assert_eq!(thr::current_idx::<Thr>(), Some(2));
+            assert_eq!(thr::preemption_depth::<Thr>(), 1);
+        }
+        unsafe fn inner(_thr: &'static Thr) {
+            assert_eq!(thr::current_idx::<Thr>(), Some(5));
+            assert_eq!(thr::preemption_depth::<Thr>(), 2);
+        }
+        assert_eq!(thr::current_idx::<Thr>(), None);
+        assert_eq!(thr::preemption_depth::<Thr>(), 0);
+        unsafe { Thr::call(2, outer) };
+        assert_eq!(thr::current_idx::<Thr>(), None);
+        assert_eq!(thr::preemption_depth::<Thr>(), 0);
+    }
+
+    #[test]
+    fn take_checked() {
+        let Thrs { .. } = unsafe { Thrs::take_checked() };
+        let again = ::std::panic::catch_unwind(|| unsafe { Thrs::take_checked() });
+        assert_eq!(again.is_err(), ::std::cfg!(debug_assertions));
+    }
+
+    #[cfg(feature = "fib-budget")]
+    ::std::thread_local! {
+        static CYCLES: ::std::cell::Cell<u32> = ::std::cell::Cell::new(0);
+    }
+
+    #[cfg(feature = "fib-budget")]
+    #[no_mangle]
+    extern "C" fn drone_cycle_counter() -> u32 {
+        CYCLES.with(::std::cell::Cell::get)
+    }
+
+    #[cfg(feature = "fib-budget")]
+    #[test]
+    fn fiber_budget() {
+        fn spend(cycles: u32) {
+            CYCLES.with(|counter| counter.set(counter.get().wrapping_add(cycles)));
+        }
+        unsafe {
+            let thr = Thr9::take();
+            thr.add(|| {
+                spend(100);
+                yield;
+                spend(300);
+                yield;
+            });
+            thr.to_thr().fib_chain().drain();
+            thr.to_thr().fib_chain().drain();
+            let budgets = thr.to_thr().fib_chain().budgets().collect::<::std::vec::Vec<_>>();
+            assert_eq!(budgets, [fib::FiberBudget {
+                priority: 0,
+                resumes: 2,
+                cycles: 400,
+                max_cycles: 300,
+            }]);
+        }
+    }
 }